@@ -3,14 +3,40 @@ use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
 use crate::supabase::SupabaseClient;
+use crate::vault::encryption::VaultEncryption;
+use crate::vault::storage::VaultStorage;
+
+/// Secret names under which a workspace stores its Vault token or AppRole
+/// secret ID in the credential store, for the provider that uses Vault.
+pub const VAULT_TOKEN_CREDENTIAL: &str = "kms:vault_token";
+pub const VAULT_SECRET_ID_CREDENTIAL: &str = "kms:vault_secret_id";
+
+/// Config keys that would put Vault credentials, or the name of an API
+/// process environment variable holding them, in `config_jsonb`.
+const VAULT_CREDENTIAL_KEYS: [&str; 4] = [
+    "vault_token",
+    "vault_secret_id",
+    "vault_token_env",
+    "vault_secret_id_env",
+];
+
+/// How the API authenticates to a workspace's Vault.
+pub enum VaultAuth {
+    Token(String),
+    AppRole { role_id: String, secret_id: String },
+}
 
 pub struct KmsHandler {
     client: SupabaseClient,
+    credentials: VaultStorage,
 }
 
 impl KmsHandler {
-    pub fn new(client: SupabaseClient) -> Self {
-        Self { client }
+    pub fn new(client: SupabaseClient, encryption: VaultEncryption) -> Self {
+        Self {
+            credentials: VaultStorage::new(client.clone(), encryption),
+            client,
+        }
     }
 
     async fn get_provider_config(&self, workspace_id: Uuid, provider: &str) -> Result<JsonValue> {
@@ -52,6 +78,10 @@ impl KmsHandler {
                     .await
             }
             "azure" => self.resolve_from_azure_keyvault(&config, secret_name).await,
+            "vault" => {
+                let auth = self.vault_auth(workspace_id, provider, &config).await?;
+                Self::resolve_from_vault(&config, &auth, secret_name).await
+            }
             _ => anyhow::bail!("Unsupported KMS provider: {}", kms_provider),
         }
     }
@@ -127,4 +157,159 @@ impl KmsHandler {
 
         anyhow::bail!("Azure Key Vault integration requires managed identity or service principal")
     }
+
+    /// Loads the workspace's Vault credentials from the credential store: the
+    /// AppRole secret ID when `vault_role_id` is set, the token otherwise.
+    async fn vault_auth(
+        &self,
+        workspace_id: Uuid,
+        provider: &str,
+        config: &JsonValue,
+    ) -> Result<VaultAuth> {
+        check_vault_config(config)?;
+
+        let (name, what) = match config["vault_role_id"].as_str() {
+            Some(_) => (VAULT_SECRET_ID_CREDENTIAL, "AppRole secret ID"),
+            None => (VAULT_TOKEN_CREDENTIAL, "token"),
+        };
+        let credential = self
+            .credentials
+            .get_credential(workspace_id, provider, name)
+            .await?
+            .with_context(|| {
+                format!(
+                    "No Vault {} stored; store it as credential '{}' of provider '{}'",
+                    what, name, provider
+                )
+            })?;
+
+        Ok(match config["vault_role_id"].as_str() {
+            Some(role_id) => VaultAuth::AppRole {
+                role_id: role_id.to_string(),
+                secret_id: credential,
+            },
+            None => VaultAuth::Token(credential),
+        })
+    }
+
+    /// Reads a field of a Vault KV v2 secret. `secrets.<name>` maps the
+    /// secret to a path, either `"path#field"` or
+    /// `{"path": ..., "field": ..., "version": N}`; without a mapping,
+    /// `vault_path` is used with `{name}` replaced by the secret name.
+    ///
+    /// `auth` comes from the workspace's credential store; the config, which
+    /// the workspace controls, may not carry credentials.
+    pub async fn resolve_from_vault(
+        config: &JsonValue,
+        auth: &VaultAuth,
+        secret_name: &str,
+    ) -> Result<String> {
+        check_vault_config(config)?;
+
+        let address = config["vault_address"]
+            .as_str()
+            .context("Missing vault_address in config")?
+            .trim_end_matches('/');
+        let mount = config["vault_mount"]
+            .as_str()
+            .unwrap_or("secret")
+            .trim_matches('/');
+
+        let (path, field, version) = match config["secrets"].get(secret_name) {
+            Some(JsonValue::String(mapping)) => match mapping.split_once('#') {
+                Some((path, field)) => (path.to_string(), field.to_string(), None),
+                None => (mapping.clone(), "value".to_string(), None),
+            },
+            Some(mapping) => (
+                mapping["path"]
+                    .as_str()
+                    .context("Missing path in Vault secret mapping")?
+                    .to_string(),
+                mapping["field"].as_str().unwrap_or("value").to_string(),
+                mapping["version"].as_u64(),
+            ),
+            None => (
+                config["vault_path"]
+                    .as_str()
+                    .context("Missing secret mapping for this secret")?
+                    .replace("{name}", secret_name),
+                config["vault_field"]
+                    .as_str()
+                    .unwrap_or("value")
+                    .replace("{name}", secret_name),
+                None,
+            ),
+        };
+
+        let client = reqwest::Client::new();
+        let namespace = config["vault_namespace"].as_str();
+        let with_namespace = |request: reqwest::RequestBuilder| match namespace {
+            Some(namespace) => request.header("X-Vault-Namespace", namespace),
+            None => request,
+        };
+
+        let token = match auth {
+            VaultAuth::Token(token) => token.clone(),
+            VaultAuth::AppRole { role_id, secret_id } => {
+                let approle_mount = config["vault_approle_mount"].as_str().unwrap_or("approle");
+
+                let response = with_namespace(
+                    client.post(format!("{}/v1/auth/{}/login", address, approle_mount)),
+                )
+                .json(&serde_json::json!({ "role_id": role_id, "secret_id": secret_id }))
+                .send()
+                .await
+                .context("Failed to reach Vault")?;
+
+                if !response.status().is_success() {
+                    anyhow::bail!("Vault AppRole login failed ({})", response.status());
+                }
+
+                let login: JsonValue = response.json().await?;
+                login["auth"]["client_token"]
+                    .as_str()
+                    .context("Vault AppRole login returned no token")?
+                    .to_string()
+            }
+        };
+
+        let mut url = format!("{}/v1/{}/data/{}", address, mount, path.trim_matches('/'));
+        if let Some(version) = version {
+            url.push_str(&format!("?version={}", version));
+        }
+
+        let response = with_namespace(client.get(&url))
+            .header("X-Vault-Token", token)
+            .send()
+            .await
+            .context("Failed to reach Vault")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Failed to read Vault path {} ({})", path, response.status());
+        }
+
+        let secret: JsonValue = response.json().await?;
+        match &secret["data"]["data"][&field] {
+            JsonValue::String(value) => Ok(value.clone()),
+            JsonValue::Null => anyhow::bail!("Vault path {} has no field '{}'", path, field),
+            other => Ok(other.to_string()),
+        }
+    }
+}
+
+fn check_vault_config(config: &JsonValue) -> Result<()> {
+    if let Some(key) = VAULT_CREDENTIAL_KEYS
+        .iter()
+        .find(|key| config.get(**key).is_some())
+    {
+        anyhow::bail!(
+            "{} is not allowed in the provider config; store the Vault token as credential '{}' \
+             or the AppRole secret ID as '{}' instead",
+            key,
+            VAULT_TOKEN_CREDENTIAL,
+            VAULT_SECRET_ID_CREDENTIAL
+        );
+    }
+
+    Ok(())
 }
@@ -32,7 +32,10 @@ impl CredentialResolver {
     pub fn new(client: SupabaseClient, vault: VaultStorage, cache: CredentialCache) -> Self {
         let encryption = VaultEncryption::new().expect("Failed to initialize encryption");
         let oauth_handler = OAuthHandler::new(client.clone(), encryption);
-        let kms_handler = KmsHandler::new(client.clone());
+        let kms_handler = KmsHandler::new(
+            client.clone(),
+            VaultEncryption::new().expect("Failed to initialize encryption"),
+        );
         let api_key_handler = ApiKeyHandler::new(client.clone());
         let circuit_breaker = CircuitBreaker::default();
         let health_monitor = HealthMonitor::new(client.clone());
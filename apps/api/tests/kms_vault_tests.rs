@@ -0,0 +1,197 @@
+use birch_api::credentials::kms::{KmsHandler, VaultAuth};
+use mockito::Matcher;
+
+/// Runs against a Vault dev server:
+/// `vault server -dev -dev-root-token-id=root`, then
+/// `VAULT_ADDR=http://127.0.0.1:8200 VAULT_TOKEN=root cargo test -- --ignored`.
+#[tokio::test]
+#[ignore]
+async fn test_resolve_from_vault_dev_server() {
+    let address = std::env::var("VAULT_ADDR").expect("VAULT_ADDR not set");
+    let token = std::env::var("VAULT_TOKEN").expect("VAULT_TOKEN not set");
+    let client = reqwest::Client::new();
+
+    for value in ["first-value", "second-value"] {
+        let response = client
+            .post(format!("{}/v1/secret/data/birch-test/provider", address))
+            .header("X-Vault-Token", &token)
+            .json(&serde_json::json!({ "data": { "token": value } }))
+            .send()
+            .await
+            .expect("Failed to write to Vault");
+        assert!(response.status().is_success());
+    }
+
+    let config = serde_json::json!({
+        "kms_provider": "vault",
+        "vault_address": address,
+        "vault_path": "birch-test/{name}",
+        "secrets": {
+            "LATEST": "birch-test/provider#token",
+            "FIRST": { "path": "birch-test/provider", "field": "token", "version": 1 },
+        },
+    });
+
+    let auth = VaultAuth::Token(token);
+
+    let latest = KmsHandler::resolve_from_vault(&config, &auth, "LATEST")
+        .await
+        .expect("Failed to resolve latest version");
+    assert_eq!(latest, "second-value");
+
+    let first = KmsHandler::resolve_from_vault(&config, &auth, "FIRST")
+        .await
+        .expect("Failed to resolve pinned version");
+    assert_eq!(first, "first-value");
+
+    let missing = KmsHandler::resolve_from_vault(&config, &auth, "provider").await;
+    assert!(missing.is_err(), "Default field 'value' does not exist");
+}
+
+#[tokio::test]
+async fn test_resolve_from_vault_with_token() {
+    let mut server = mockito::Server::new_async().await;
+
+    let latest = server
+        .mock("GET", "/v1/kv/data/apps/provider")
+        .match_query(Matcher::Missing)
+        .match_header("X-Vault-Token", "s.token")
+        .match_header("X-Vault-Namespace", "team")
+        .with_body(r#"{"data": {"data": {"token": "second-value", "port": 5432}}}"#)
+        .create_async()
+        .await;
+    let pinned = server
+        .mock("GET", "/v1/kv/data/apps/provider")
+        .match_query(Matcher::UrlEncoded("version".into(), "1".into()))
+        .match_header("X-Vault-Token", "s.token")
+        .with_body(r#"{"data": {"data": {"token": "first-value"}}}"#)
+        .create_async()
+        .await;
+
+    let config = serde_json::json!({
+        "kms_provider": "vault",
+        "vault_address": format!("{}/", server.url()),
+        "vault_mount": "kv",
+        "vault_namespace": "team",
+        "secrets": {
+            "LATEST": "apps/provider#token",
+            "FIRST": { "path": "apps/provider", "field": "token", "version": 1 },
+            "PORT": "apps/provider#port",
+            "MISSING": "apps/provider#password",
+        },
+    });
+
+    let auth = VaultAuth::Token("s.token".to_string());
+
+    let value = KmsHandler::resolve_from_vault(&config, &auth, "LATEST")
+        .await
+        .expect("Failed to resolve latest version");
+    assert_eq!(value, "second-value");
+
+    let value = KmsHandler::resolve_from_vault(&config, &auth, "FIRST")
+        .await
+        .expect("Failed to resolve pinned version");
+    assert_eq!(value, "first-value");
+
+    let value = KmsHandler::resolve_from_vault(&config, &auth, "PORT")
+        .await
+        .expect("Failed to resolve non-string field");
+    assert_eq!(value, "5432");
+
+    let missing = KmsHandler::resolve_from_vault(&config, &auth, "MISSING").await;
+    assert!(missing.is_err(), "Field 'password' does not exist");
+
+    latest.expect(3).assert_async().await;
+    pinned.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_resolve_from_vault_with_approle() {
+    let mut server = mockito::Server::new_async().await;
+
+    let login = server
+        .mock("POST", "/v1/auth/ci/login")
+        .match_body(Matcher::Json(serde_json::json!({
+            "role_id": "role-id",
+            "secret_id": "secret-id",
+        })))
+        .with_body(r#"{"auth": {"client_token": "s.approle"}}"#)
+        .create_async()
+        .await;
+    let read = server
+        .mock("GET", "/v1/secret/data/birch/api_key")
+        .match_header("X-Vault-Token", "s.approle")
+        .with_body(r#"{"data": {"data": {"value": "from-approle"}}}"#)
+        .create_async()
+        .await;
+
+    let config = serde_json::json!({
+        "kms_provider": "vault",
+        "vault_address": server.url(),
+        "vault_role_id": "role-id",
+        "vault_approle_mount": "ci",
+        "vault_path": "birch/{name}",
+    });
+
+    let auth = VaultAuth::AppRole {
+        role_id: "role-id".to_string(),
+        secret_id: "secret-id".to_string(),
+    };
+
+    let value = KmsHandler::resolve_from_vault(&config, &auth, "api_key")
+        .await
+        .expect("Failed to resolve through AppRole");
+    assert_eq!(value, "from-approle");
+
+    login.assert_async().await;
+    read.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_resolve_from_vault_rejects_credentials_in_config() {
+    let mut server = mockito::Server::new_async().await;
+    let never_read = server
+        .mock("GET", Matcher::Any)
+        .expect(0)
+        .create_async()
+        .await;
+    let never_login = server
+        .mock("POST", Matcher::Any)
+        .expect(0)
+        .create_async()
+        .await;
+    std::env::set_var("JWT_SECRET", "api-signing-secret");
+
+    // Naming an API environment variable must not send its value to a
+    // server the workspace controls.
+    for (key, value) in [
+        ("vault_token_env", "JWT_SECRET"),
+        ("vault_secret_id_env", "JWT_SECRET"),
+        ("vault_token", "plaintext"),
+        ("vault_secret_id", "plaintext"),
+    ] {
+        let mut config = serde_json::json!({
+            "kms_provider": "vault",
+            "vault_address": server.url(),
+            "vault_role_id": "role-id",
+            "vault_path": "birch/{name}",
+        });
+        config[key] = serde_json::json!(value);
+
+        for auth in [
+            VaultAuth::Token("s.token".to_string()),
+            VaultAuth::AppRole {
+                role_id: "role-id".to_string(),
+                secret_id: "secret-id".to_string(),
+            },
+        ] {
+            let err = KmsHandler::resolve_from_vault(&config, &auth, "api_key")
+                .await
+                .expect_err("credential settings in config should be rejected");
+            assert!(err.to_string().contains(key), "{}", err);
+        }
+    }
+
+    never_read.assert_async().await;
+    never_login.assert_async().await;
+}
@@ -0,0 +1,335 @@
+use anyhow::Result;
+use dialoguer::Confirm;
+use serde::Serialize;
+use std::io::IsTerminal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ApprovalMode {
+    #[default]
+    Interactive,
+    AssumeYes,
+    Policy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Rotate,
+    Rollback,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum Decision {
+    Approved,
+    Denied { reason: String },
+}
+
+impl ApprovalMode {
+    pub fn from_flag(yes: bool) -> Self {
+        if yes {
+            ApprovalMode::AssumeYes
+        } else {
+            ApprovalMode::Interactive
+        }
+    }
+
+    /// Asks to go ahead with a step that `evaluate` has already approved for
+    /// policy runs. Decisions `evaluate` does not cover need their own method.
+    pub fn confirm(&self, prompt: &str) -> Result<bool> {
+        match self {
+            ApprovalMode::Interactive => prompt_user(prompt),
+            ApprovalMode::AssumeYes | ApprovalMode::Policy => Ok(true),
+        }
+    }
+
+    pub fn confirm_override(&self, prompt: &str) -> Result<bool> {
+        match self {
+            ApprovalMode::Interactive => prompt_user(prompt),
+            ApprovalMode::AssumeYes => Ok(true),
+            ApprovalMode::Policy => Ok(false),
+        }
+    }
+
+    /// Asks to continue outside every maintenance window. A policy run only
+    /// continues when the `auto_apply` rule of each `(env, secret)` pair sets
+    /// `ignore_maintenance_windows`.
+    pub fn confirm_outside_window<'a>(
+        &self,
+        config: &crate::config::Config,
+        scope: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<bool> {
+        match self {
+            ApprovalMode::Interactive => prompt_user("Continue anyway?"),
+            ApprovalMode::AssumeYes => Ok(true),
+            ApprovalMode::Policy => Ok(scope.into_iter().all(|(env, secret_name)| {
+                matching_rule(config, env, secret_name)
+                    .is_some_and(|rule| rule.ignore_maintenance_windows)
+            })),
+        }
+    }
+}
+
+fn prompt_user(prompt: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Confirmation required ('{}') but no terminal is attached; re-run with --yes or set BIRCH_ASSUME_YES=1",
+            prompt
+        );
+    }
+
+    Ok(Confirm::new()
+        .with_prompt(prompt)
+        .default(false)
+        .interact()?)
+}
+
+pub fn evaluate(
+    config: &crate::config::Config,
+    operation: Operation,
+    env: &str,
+    secret_name: &str,
+) -> Decision {
+    if env == "dev" {
+        return Decision::Approved;
+    }
+
+    let Some(rule) = matching_rule(config, env, secret_name) else {
+        return Decision::Denied {
+            reason: format!(
+                "No auto_apply rule allows '{}' in env '{}'",
+                secret_name, env
+            ),
+        };
+    };
+
+    if operation == Operation::Rollback && !rule.allow_rollback {
+        return Decision::Denied {
+            reason: format!(
+                "auto_apply rule for env '{}' does not allow rollbacks",
+                rule.env
+            ),
+        };
+    }
+
+    if !rule.ignore_maintenance_windows
+        && !crate::prod::check_maintenance_window(config).unwrap_or(false)
+    {
+        return Decision::Denied {
+            reason: "Outside maintenance window".to_string(),
+        };
+    }
+
+    Decision::Approved
+}
+
+/// The first `auto_apply` rule covering `secret_name` in `env`.
+fn matching_rule<'a>(
+    config: &'a crate::config::Config,
+    env: &str,
+    secret_name: &str,
+) -> Option<&'a crate::config::AutoApplyRule> {
+    config.auto_apply.iter().find(|rule| {
+        matches_pattern(&rule.env, env)
+            && rule
+                .secrets
+                .iter()
+                .any(|pattern| matches_pattern(pattern, secret_name))
+    })
+}
+
+pub fn matches_pattern(pattern: &str, value: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == value,
+        Some((prefix, rest)) => {
+            let Some(remaining) = value.strip_prefix(prefix) else {
+                return false;
+            };
+            if rest.is_empty() {
+                return true;
+            }
+            (0..=remaining.len())
+                .filter(|i| remaining.is_char_boundary(*i))
+                .any(|i| matches_pattern(rest, &remaining[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AutoApplyRule, Config, MaintenanceWindow};
+
+    const ALL_DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+    fn rule(env: &str, secrets: &[&str]) -> AutoApplyRule {
+        AutoApplyRule {
+            env: env.to_string(),
+            secrets: secrets.iter().map(|s| s.to_string()).collect(),
+            allow_rollback: false,
+            ignore_maintenance_windows: false,
+        }
+    }
+
+    /// A window that is open at every hour of every day, or at none.
+    fn window(open: bool) -> MaintenanceWindow {
+        MaintenanceWindow {
+            start_hour: 0,
+            end_hour: 24,
+            days: if open {
+                ALL_DAYS.iter().map(|d| d.to_string()).collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    fn config(rules: Vec<AutoApplyRule>, windows: Vec<MaintenanceWindow>) -> Config {
+        Config {
+            auto_apply: rules,
+            maintenance_windows: windows,
+            ..Config::default()
+        }
+    }
+
+    fn denied(decision: Decision) -> String {
+        match decision {
+            Decision::Denied { reason } => reason,
+            Decision::Approved => panic!("expected a denial"),
+        }
+    }
+
+    #[test]
+    fn pattern_without_wildcard_matches_exactly() {
+        assert!(matches_pattern("prod", "prod"));
+        assert!(!matches_pattern("prod", "production"));
+        assert!(!matches_pattern("prod", ""));
+    }
+
+    #[test]
+    fn pattern_wildcards() {
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("*", "anything"));
+        assert!(matches_pattern("STRIPE_*", "STRIPE_KEY"));
+        assert!(matches_pattern("STRIPE_*", "STRIPE_"));
+        assert!(!matches_pattern("STRIPE_*", "OPENAI_KEY"));
+        assert!(matches_pattern("*_KEY", "OPENAI_KEY"));
+        assert!(!matches_pattern("*_KEY", "OPENAI_KEY_2"));
+        assert!(matches_pattern("A*B*C", "AxxBxxC"));
+        assert!(matches_pattern("A*B*C", "ABC"));
+        assert!(!matches_pattern("A*B*C", "AxxCxxB"));
+        assert!(matches_pattern("*é*", "café au lait"));
+    }
+
+    #[test]
+    fn dev_is_always_approved() {
+        let config = config(Vec::new(), vec![window(false)]);
+        assert_eq!(
+            evaluate(&config, Operation::Rollback, "dev", "ANY"),
+            Decision::Approved
+        );
+    }
+
+    #[test]
+    fn denies_without_matching_rule() {
+        let config = config(vec![rule("staging", &["*"])], Vec::new());
+        let reason = denied(evaluate(&config, Operation::Rotate, "prod", "API_KEY"));
+        assert!(reason.contains("No auto_apply rule"), "{}", reason);
+
+        let config = self::config(vec![rule("prod", &["STRIPE_*"])], Vec::new());
+        denied(evaluate(&config, Operation::Rotate, "prod", "API_KEY"));
+    }
+
+    #[test]
+    fn approves_matching_rotation_inside_window() {
+        let config = config(vec![rule("prod", &["API_*"])], vec![window(true)]);
+        assert_eq!(
+            evaluate(&config, Operation::Rotate, "prod", "API_KEY"),
+            Decision::Approved
+        );
+    }
+
+    #[test]
+    fn rollback_requires_allow_rollback() {
+        let mut allowed = rule("prod", &["*"]);
+        let config = self::config(vec![allowed.clone()], Vec::new());
+        let reason = denied(evaluate(&config, Operation::Rollback, "prod", "API_KEY"));
+        assert!(reason.contains("rollbacks"), "{}", reason);
+
+        allowed.allow_rollback = true;
+        let config = self::config(vec![allowed], Vec::new());
+        assert_eq!(
+            evaluate(&config, Operation::Rollback, "prod", "API_KEY"),
+            Decision::Approved
+        );
+    }
+
+    #[test]
+    fn denies_outside_window_unless_rule_ignores_it() {
+        let mut rule = rule("prod", &["*"]);
+        let config = self::config(vec![rule.clone()], vec![window(false)]);
+        let reason = denied(evaluate(&config, Operation::Rotate, "prod", "API_KEY"));
+        assert!(reason.contains("maintenance window"), "{}", reason);
+
+        rule.ignore_maintenance_windows = true;
+        let config = self::config(vec![rule], vec![window(false)]);
+        assert_eq!(
+            evaluate(&config, Operation::Rotate, "prod", "API_KEY"),
+            Decision::Approved
+        );
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let mut specific = rule("prod", &["API_KEY"]);
+        specific.allow_rollback = false;
+        let mut general = rule("*", &["*"]);
+        general.allow_rollback = true;
+        let config = config(vec![specific, general], Vec::new());
+
+        denied(evaluate(&config, Operation::Rollback, "prod", "API_KEY"));
+        assert_eq!(
+            evaluate(&config, Operation::Rollback, "prod", "OTHER"),
+            Decision::Approved
+        );
+    }
+
+    #[test]
+    fn policy_continues_outside_window_only_when_every_rule_allows_it() {
+        let mut ignoring = rule("prod", &["API_*"]);
+        ignoring.ignore_maintenance_windows = true;
+        let config = config(vec![ignoring, rule("prod", &["*"])], vec![window(false)]);
+        let policy = ApprovalMode::Policy;
+
+        assert!(policy
+            .confirm_outside_window(&config, [("prod", "API_KEY")])
+            .unwrap());
+        assert!(!policy
+            .confirm_outside_window(&config, [("prod", "DB_PASSWORD")])
+            .unwrap());
+        assert!(!policy
+            .confirm_outside_window(&config, [("prod", "API_KEY"), ("prod", "DB_PASSWORD")])
+            .unwrap());
+        assert!(!policy
+            .confirm_outside_window(&config, [("staging", "API_KEY")])
+            .unwrap());
+    }
+
+    #[test]
+    fn assume_yes_and_policy_confirmations() {
+        let config = config(Vec::new(), vec![window(false)]);
+
+        assert!(ApprovalMode::AssumeYes.confirm("Apply?").unwrap());
+        assert!(ApprovalMode::AssumeYes
+            .confirm_override("Continue?")
+            .unwrap());
+        assert!(ApprovalMode::AssumeYes
+            .confirm_outside_window(&config, [("prod", "API_KEY")])
+            .unwrap());
+
+        assert!(ApprovalMode::Policy.confirm("Apply?").unwrap());
+        assert!(!ApprovalMode::Policy.confirm_override("Continue?").unwrap());
+        assert!(!ApprovalMode::Policy
+            .confirm_outside_window(&config, [("prod", "API_KEY")])
+            .unwrap());
+    }
+}
@@ -34,6 +34,10 @@ pub enum AuditAction {
     Rotate,
     Rollback,
     Signal,
+    Revoke,
+    Update,
+    Restore,
+    Retire,
 }
 
 #[derive(Debug, Clone)]
@@ -122,9 +126,7 @@ impl AuditLogger {
     }
 
     pub fn log_with_value(&self, params: LogParams) -> Result<()> {
-        let actor = std::env::var("USER")
-            .or_else(|_| std::env::var("USERNAME"))
-            .unwrap_or_else(|_| "unknown".to_string());
+        let actor = current_actor();
 
         let encrypted_secret_value = if let Some(value) = &params.secret_value {
             Some(self.encrypt_secret(value)?)
@@ -226,7 +228,7 @@ impl AuditLogger {
             }
         }
 
-        entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
 
         if let Some(n) = last {
             entries.truncate(n);
@@ -235,7 +237,7 @@ impl AuditLogger {
         Ok(entries)
     }
 
-    fn encrypt_secret(&self, secret: &str) -> Result<String> {
+    pub fn encrypt_secret(&self, secret: &str) -> Result<String> {
         let mut nonce_bytes = [0u8; 12];
         OsRng.fill_bytes(&mut nonce_bytes);
         let nonce = Nonce::from_slice(&nonce_bytes);
@@ -272,6 +274,12 @@ impl AuditLogger {
     }
 }
 
+pub fn current_actor() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
 pub async fn show_audit(
     secret_name: Option<String>,
     env: Option<String>,
@@ -0,0 +1,63 @@
+//! Secrets made of several values that are rotated together, such as an AWS
+//! access key ID and its secret access key. The combined value is what version
+//! history, staged rotations and the audit log hold, as `NAME=value` lines;
+//! targets receive each part under its own name.
+
+use anyhow::Result;
+
+/// Names of the parts of `secret_name`, if its value is a bundle.
+pub fn parts(secret_name: &str) -> Result<Option<Vec<String>>> {
+    let config = crate::config::Config::load()?;
+
+    Ok(config
+        .secrets
+        .get(secret_name)
+        .and_then(|s| s.minter.as_ref())
+        .and_then(|m| m.bundle_parts()))
+}
+
+pub fn join(values: &[(String, String)]) -> String {
+    values
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits a bundle into `(name, value)` pairs, in the order of `parts`.
+pub fn split(parts: &[String], value: &str) -> Result<Vec<(String, String)>> {
+    parts
+        .iter()
+        .map(|part| {
+            value
+                .lines()
+                .find_map(|line| line.strip_prefix(part.as_str())?.strip_prefix('='))
+                .map(|v| (part.clone(), v.to_string()))
+                .ok_or_else(|| anyhow::anyhow!("Value has no {} part", part))
+        })
+        .collect()
+}
+
+/// Combines the parts read from one place. A place holding none of them does
+/// not hold the secret; one holding only some of them is an error.
+pub fn combine(values: Vec<(String, Option<String>)>) -> Result<Option<String>> {
+    let missing: Vec<&str> = values
+        .iter()
+        .filter(|(_, value)| value.is_none())
+        .map(|(name, _)| name.as_str())
+        .collect();
+
+    if missing.len() == values.len() {
+        return Ok(None);
+    }
+    if !missing.is_empty() {
+        anyhow::bail!("incomplete: {} not set", missing.join(", "));
+    }
+
+    Ok(Some(join(
+        &values
+            .into_iter()
+            .map(|(name, value)| (name, value.unwrap_or_default()))
+            .collect::<Vec<_>>(),
+    )))
+}
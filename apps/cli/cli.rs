@@ -16,6 +16,16 @@ pub struct Cli {
         help = "Dry-run mode: show what would change without making changes"
     )]
     pub dry_run: bool,
+
+    #[arg(
+        long,
+        short = 'y',
+        global = true,
+        env = "BIRCH_ASSUME_YES",
+        value_parser = clap::builder::BoolishValueParser::new(),
+        help = "Answer yes to all confirmation prompts (non-interactive/CI mode)"
+    )]
+    pub yes: bool,
 }
 
 #[derive(Subcommand)]
@@ -26,7 +36,10 @@ pub enum Commands {
         #[arg(long, help = "Environment (dev/staging/prod)")]
         env: Option<String>,
 
-        #[arg(long, help = "Service name")]
+        #[arg(
+            long,
+            help = "Service or profile (vercel.marketing), or a comma-separated list; defaults to the profiles bound to --env"
+        )]
         service: Option<String>,
 
         #[arg(long, help = "Trigger rotation from app signal")]
@@ -38,8 +51,34 @@ pub enum Commands {
         #[arg(long, help = "New secret value (if not provided, will be generated)")]
         value: Option<String>,
 
-        #[arg(long, help = "Path to .env file (dev mode only)")]
+        #[arg(
+            long,
+            help = "Path to a local .env, JSON, YAML or TOML file (dev mode only)"
+        )]
         env_file: Option<String>,
+
+        #[arg(
+            long = "path",
+            help = "Key path inside the file, e.g. database.password (dev mode only)"
+        )]
+        key_path: Option<String>,
+
+        #[arg(long, help = "Stage and verify the new value before promoting it")]
+        staged: bool,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["abort", "staged", "value"],
+            help = "Resume an in-progress staged rotation"
+        )]
+        resume: bool,
+
+        #[arg(
+            long,
+            conflicts_with_all = ["staged", "value"],
+            help = "Abort an in-progress staged rotation"
+        )]
+        abort: bool,
     },
 
     Rollback {
@@ -48,13 +87,55 @@ pub enum Commands {
         #[arg(long, help = "Environment (dev/staging/prod)")]
         env: String,
 
-        #[arg(long, help = "Service name")]
+        #[arg(
+            long,
+            help = "Service or profile (vercel.marketing), or a comma-separated list; defaults to the profiles bound to --env"
+        )]
         service: Option<String>,
 
+        #[arg(long, help = "Version to restore (defaults to the previous version)")]
+        to: Option<u32>,
+
+        #[arg(
+            long,
+            help = "Path to a local .env, JSON, YAML or TOML file (dev mode only)"
+        )]
+        env_file: Option<String>,
+
+        #[arg(
+            long = "path",
+            help = "Key path inside the file, e.g. database.password (dev mode only)"
+        )]
+        key_path: Option<String>,
+
         #[arg(long, help = "Trigger redeploy after rollback (prod only)")]
         redeploy: bool,
     },
 
+    History {
+        secret_name: String,
+
+        #[arg(long, help = "Environment (dev/staging/prod)")]
+        env: String,
+    },
+
+    Revoke {
+        secret_name: String,
+
+        #[arg(long, help = "Environment (dev/staging/prod)")]
+        env: String,
+    },
+
+    Plan {
+        #[arg(long, default_value = crate::manifest::DEFAULT_MANIFEST_PATH, help = "Path to the secrets manifest")]
+        manifest: String,
+    },
+
+    Apply {
+        #[arg(long, default_value = crate::manifest::DEFAULT_MANIFEST_PATH, help = "Path to the secrets manifest")]
+        manifest: String,
+    },
+
     Daemon {
         #[command(subcommand)]
         action: DaemonAction,
@@ -97,10 +178,37 @@ pub enum Commands {
         action: ProviderAction,
     },
 
+    #[command(about = "List built-in connectors and installed connector plugins")]
+    Connectors,
+
+    #[command(about = "Edit and share encrypted secrets files kept in the repository")]
+    Secrets {
+        #[command(subcommand)]
+        action: SecretsAction,
+    },
+
+    #[command(about = "List secrets held by an environment's services, without reading values")]
+    Inventory {
+        #[arg(help = "Show metadata and remote versions of one secret")]
+        secret_name: Option<String>,
+
+        #[arg(long, help = "Environment (staging/prod)")]
+        env: String,
+
+        #[arg(
+            long,
+            help = "Service or profile, or a comma-separated list; defaults to the profiles bound to --env"
+        )]
+        service: Option<String>,
+    },
+
     #[command(hide = true)]
     DaemonInternalRun {
         #[arg(long, default_value = "127.0.0.1:9123")]
         bind: String,
+
+        #[arg(long)]
+        manifest: Option<std::path::PathBuf>,
     },
 }
 
@@ -109,6 +217,12 @@ pub enum DaemonAction {
     Start {
         #[arg(long, default_value = "127.0.0.1:9123")]
         bind: String,
+
+        #[arg(
+            long,
+            help = "Secrets manifest with schedules (defaults to manifest_path in config)"
+        )]
+        manifest: Option<String>,
     },
     Stop,
     Status,
@@ -169,8 +283,46 @@ pub enum PoolAction {
     },
 }
 
+#[derive(Subcommand)]
+pub enum SecretsAction {
+    #[command(about = "Open a secrets file decrypted in $EDITOR, creating it if needed")]
+    Edit {
+        file: String,
+        #[arg(
+            long = "recipient",
+            help = "Public key to encrypt to, in addition to yours"
+        )]
+        recipients: Vec<String>,
+    },
+    #[command(about = "Print the decrypted contents of a secrets file")]
+    Decrypt {
+        file: String,
+        #[arg(long, help = "Write to this file instead of stdout")]
+        output: Option<String>,
+    },
+    Recipients {
+        #[command(subcommand)]
+        action: RecipientsAction,
+    },
+    #[command(about = "Print your public key, creating your identity if needed")]
+    PublicKey,
+}
+
+#[derive(Subcommand)]
+pub enum RecipientsAction {
+    Add {
+        file: String,
+        #[arg(required = true)]
+        public_keys: Vec<String>,
+    },
+    List {
+        file: String,
+    },
+}
+
 pub async fn run() -> Result<()> {
     let cli = Cli::parse();
+    let approval = crate::approval::ApprovalMode::from_flag(cli.yes);
 
     match cli.command {
         Commands::Rotate {
@@ -181,27 +333,60 @@ pub async fn run() -> Result<()> {
             redeploy,
             value,
             env_file,
-        } => {
-            crate::rotation::rotate(
-                secret_name,
-                env,
-                service,
-                from_signal,
-                redeploy,
-                value,
-                env_file,
-                cli.dry_run,
-            )
-            .await
-        }
+            key_path,
+            staged,
+            resume,
+            abort,
+        } => crate::rotation::rotate(crate::rotation::RotateParams {
+            secret_name,
+            env,
+            service,
+            from_signal,
+            redeploy,
+            value,
+            env_file,
+            key_path,
+            dry_run: cli.dry_run,
+            approval,
+            staged,
+            resume,
+            abort,
+        })
+        .await
+        .map(|_| ()),
         Commands::Rollback {
             secret_name,
             env,
             service,
+            to,
+            env_file,
+            key_path,
+            redeploy,
+        } => crate::rollback::rollback(crate::rollback::RollbackParams {
+            secret_name,
+            env,
+            service,
+            to,
+            env_file,
+            key_path,
             redeploy,
-        } => crate::rollback::rollback(secret_name, env, service, redeploy, cli.dry_run).await,
+            dry_run: cli.dry_run,
+            approval,
+        })
+        .await
+        .map(|_| ()),
+        Commands::History { secret_name, env } => crate::versions::show_history(&secret_name, &env),
+        Commands::Revoke { secret_name, env } => {
+            crate::revocation::revoke(secret_name, env, cli.dry_run).await
+        }
+        Commands::Plan { manifest } => crate::manifest::plan(manifest).await,
+        Commands::Apply { manifest } => {
+            crate::manifest::apply(manifest, cli.dry_run, approval).await
+        }
         Commands::Daemon { action } => match action {
-            DaemonAction::Start { bind } => crate::daemon::start(&bind).await,
+            DaemonAction::Start { bind, manifest } => {
+                crate::daemon::start(&bind, manifest.as_deref()).await
+            }
             DaemonAction::Stop => crate::daemon::stop().await,
             DaemonAction::Status => crate::daemon::status().await,
         },
@@ -245,6 +430,51 @@ pub async fn run() -> Result<()> {
             }
             ProviderAction::List => crate::saas::provider_list().await,
         },
-        Commands::DaemonInternalRun { bind } => crate::daemon::run_daemon(bind).await,
+        Commands::Connectors => crate::connectors::list_connectors(),
+        Commands::Secrets { action } => match action {
+            SecretsAction::Edit { file, recipients } => {
+                crate::secrets_file::edit(file, recipients).await
+            }
+            SecretsAction::Decrypt { file, output } => {
+                crate::secrets_file::decrypt_file(file, output).await
+            }
+            SecretsAction::Recipients { action } => match action {
+                RecipientsAction::Add { file, public_keys } => {
+                    crate::secrets_file::recipients_add(file, public_keys).await
+                }
+                RecipientsAction::List { file } => crate::secrets_file::recipients_list(file).await,
+            },
+            SecretsAction::PublicKey => crate::secrets_file::public_key().await,
+        },
+        Commands::Inventory {
+            secret_name,
+            env,
+            service,
+        } => crate::inventory::show_inventory(secret_name, env, service).await,
+        Commands::DaemonInternalRun { bind, manifest } => {
+            crate::daemon::run_daemon(bind, manifest).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assume_yes_env_accepts_boolish_values() {
+        let parse = || Cli::try_parse_from(["birch", "history", "X", "--env", "dev"]);
+
+        std::env::set_var("BIRCH_ASSUME_YES", "1");
+        assert!(parse().unwrap().yes);
+        std::env::set_var("BIRCH_ASSUME_YES", "0");
+        assert!(!parse().unwrap().yes);
+        std::env::remove_var("BIRCH_ASSUME_YES");
+        assert!(!parse().unwrap().yes);
+        assert!(
+            Cli::try_parse_from(["birch", "-y", "history", "X", "--env", "dev"])
+                .unwrap()
+                .yes
+        );
     }
 }
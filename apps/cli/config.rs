@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -14,18 +15,38 @@ pub struct Config {
     #[serde(default = "default_rollback_window_seconds")]
     pub rollback_window_seconds: u64,
 
+    #[serde(default = "default_rotation_grace_period_seconds")]
+    pub rotation_grace_period_seconds: u64,
+
     #[serde(default = "default_daemon_bind")]
     pub daemon_bind: String,
 
     #[serde(default = "default_pool_low_threshold")]
     pub pool_low_threshold: u64,
 
+    #[serde(default)]
+    pub manifest_path: Option<PathBuf>,
+
     #[serde(default)]
     pub maintenance_windows: Vec<MaintenanceWindow>,
 
+    #[serde(default)]
+    pub auto_apply: Vec<AutoApplyRule>,
+
+    #[serde(default)]
+    pub secrets: BTreeMap<String, SecretConfig>,
+
     #[serde(default)]
     pub connector_auth: ConnectorAuth,
 
+    /// Named connector profiles, keyed by provider then profile name
+    /// (`[profiles.vercel.marketing]`).
+    #[serde(default)]
+    pub profiles: BTreeMap<String, BTreeMap<String, ConnectorProfile>>,
+
+    #[serde(default)]
+    pub environments: BTreeMap<String, EnvironmentConfig>,
+
     #[serde(default = "default_mode")]
     pub mode: String,
 
@@ -59,6 +80,32 @@ pub struct ConnectorAuth {
     pub azure_client_id: Option<String>,
     pub azure_client_secret: Option<String>,
     pub azure_tenant_id: Option<String>,
+    pub vault_token: Option<String>,
+    pub github_token: Option<String>,
+    pub gitlab_token: Option<String>,
+    pub postgres_admin_url: Option<String>,
+    pub mysql_admin_url: Option<String>,
+    pub redis_admin_url: Option<String>,
+    pub openai_admin_key: Option<String>,
+    pub sendgrid_admin_api_key: Option<String>,
+    pub datadog_api_key: Option<String>,
+    pub datadog_app_key: Option<String>,
+    pub heroku_api_key: Option<String>,
+    pub railway_api_token: Option<String>,
+    pub digitalocean_access_token: Option<String>,
+}
+
+/// Credentials and target identifiers for one account or project of a
+/// provider. Keys override `connector_auth` and the provider's environment
+/// variables, e.g. `token`, `project_id`, `site_id`.
+pub type ConnectorProfile = BTreeMap<String, String>;
+
+/// Connector profiles (`vercel.app`, `aws.prod`, ...) an environment writes to
+/// when `--service` is not given.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct EnvironmentConfig {
+    #[serde(default)]
+    pub profiles: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +115,175 @@ pub struct MaintenanceWindow {
     pub days: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoApplyRule {
+    pub env: String,
+    #[serde(default = "default_auto_apply_secrets")]
+    pub secrets: Vec<String>,
+    #[serde(default)]
+    pub allow_rollback: bool,
+    #[serde(default)]
+    pub ignore_maintenance_windows: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SecretConfig {
+    #[serde(default)]
+    pub staged: bool,
+    pub verify_command: Option<String>,
+    pub grace_period_seconds: Option<u64>,
+    pub minter: Option<MinterConfig>,
+    pub generate: Option<GenerateConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateConfig {
+    #[serde(default)]
+    pub format: SecretFormat,
+    pub length: Option<usize>,
+    #[serde(default = "default_charset")]
+    pub charset: Vec<CharClass>,
+    #[serde(default)]
+    pub require: Vec<CharClass>,
+    pub symbols: Option<String>,
+    pub prefix: Option<String>,
+    pub separator: Option<String>,
+    pub public_key_file: Option<String>,
+}
+
+impl Default for GenerateConfig {
+    fn default() -> Self {
+        Self {
+            format: SecretFormat::default(),
+            length: None,
+            charset: default_charset(),
+            require: Vec::new(),
+            symbols: None,
+            prefix: None,
+            separator: None,
+            public_key_file: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretFormat {
+    #[default]
+    Charset,
+    Hex,
+    Base64,
+    Base64url,
+    Uuid,
+    Passphrase,
+    Ed25519,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digits,
+    Symbols,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MinterConfig {
+    Openai {
+        project_id: String,
+    },
+    Sendgrid {
+        #[serde(default)]
+        scopes: Vec<String>,
+    },
+    Datadog {
+        #[serde(default = "default_datadog_site")]
+        site: String,
+    },
+    #[serde(rename = "aws_iam")]
+    AwsIam {
+        user: String,
+        #[serde(default = "default_access_key_id_name")]
+        access_key_id_name: String,
+        #[serde(default = "default_secret_access_key_name")]
+        secret_access_key_name: String,
+    },
+    Http(HttpMinterConfig),
+}
+
+impl MinterConfig {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MinterConfig::Openai { .. } => "openai",
+            MinterConfig::Sendgrid { .. } => "sendgrid",
+            MinterConfig::Datadog { .. } => "datadog",
+            MinterConfig::AwsIam { .. } => "aws_iam",
+            MinterConfig::Http(_) => "http",
+        }
+    }
+
+    /// Names the parts of a minted value are written under, for minters that
+    /// issue several values at once (see `crate::bundle`).
+    pub fn bundle_parts(&self) -> Option<Vec<String>> {
+        match self {
+            MinterConfig::AwsIam {
+                access_key_id_name,
+                secret_access_key_name,
+                ..
+            } => Some(vec![
+                access_key_id_name.clone(),
+                secret_access_key_name.clone(),
+            ]),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpMinterConfig {
+    pub url: String,
+    #[serde(default = "default_http_method")]
+    pub method: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<String>,
+    pub value_pointer: String,
+    pub id_pointer: Option<String>,
+    pub revoke_url: Option<String>,
+    #[serde(default = "default_revoke_method")]
+    pub revoke_method: String,
+}
+
+fn default_datadog_site() -> String {
+    "datadoghq.com".to_string()
+}
+
+fn default_access_key_id_name() -> String {
+    "AWS_ACCESS_KEY_ID".to_string()
+}
+
+fn default_secret_access_key_name() -> String {
+    "AWS_SECRET_ACCESS_KEY".to_string()
+}
+
+fn default_http_method() -> String {
+    "POST".to_string()
+}
+
+fn default_revoke_method() -> String {
+    "DELETE".to_string()
+}
+
+fn default_charset() -> Vec<CharClass> {
+    vec![CharClass::Upper, CharClass::Lower, CharClass::Digits]
+}
+
+fn default_auto_apply_secrets() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
 fn default_audit_log_path() -> PathBuf {
     dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -83,6 +299,10 @@ fn default_rollback_window_seconds() -> u64 {
     3600
 }
 
+fn default_rotation_grace_period_seconds() -> u64 {
+    86400
+}
+
 fn default_daemon_bind() -> String {
     "127.0.0.1:9123".to_string()
 }
@@ -101,10 +321,16 @@ impl Default for Config {
             audit_log_path: default_audit_log_path(),
             cooldown_seconds: default_cooldown_seconds(),
             rollback_window_seconds: default_rollback_window_seconds(),
+            rotation_grace_period_seconds: default_rotation_grace_period_seconds(),
             daemon_bind: default_daemon_bind(),
             pool_low_threshold: default_pool_low_threshold(),
+            manifest_path: None,
             maintenance_windows: Vec::new(),
+            auto_apply: Vec::new(),
+            secrets: BTreeMap::new(),
             connector_auth: ConnectorAuth::default(),
+            profiles: BTreeMap::new(),
+            environments: BTreeMap::new(),
             mode: default_mode(),
             saas_api_url: None,
             saas_api_key: None,
@@ -179,6 +405,12 @@ impl Config {
             }
         }
 
+        if let Ok(val) = std::env::var("BIRCH_ROTATION_GRACE_PERIOD_SECONDS") {
+            if let Ok(seconds) = val.parse() {
+                self.rotation_grace_period_seconds = seconds;
+            }
+        }
+
         if let Ok(val) = std::env::var("BIRCH_DAEMON_BIND") {
             self.daemon_bind = val;
         }
@@ -189,6 +421,10 @@ impl Config {
             }
         }
 
+        if let Ok(path) = std::env::var("BIRCH_MANIFEST_PATH") {
+            self.manifest_path = Some(PathBuf::from(path));
+        }
+
         if let Ok(val) = std::env::var("VERCEL_TOKEN") {
             self.connector_auth.vercel_token = Some(val);
         }
@@ -237,6 +473,58 @@ impl Config {
             self.connector_auth.azure_tenant_id = Some(val);
         }
 
+        if let Ok(val) = std::env::var("VAULT_TOKEN") {
+            self.connector_auth.vault_token = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("GITHUB_TOKEN") {
+            self.connector_auth.github_token = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("GITLAB_TOKEN") {
+            self.connector_auth.gitlab_token = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("POSTGRES_ADMIN_URL") {
+            self.connector_auth.postgres_admin_url = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("MYSQL_ADMIN_URL") {
+            self.connector_auth.mysql_admin_url = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("REDIS_ADMIN_URL") {
+            self.connector_auth.redis_admin_url = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("OPENAI_ADMIN_KEY") {
+            self.connector_auth.openai_admin_key = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("SENDGRID_ADMIN_API_KEY") {
+            self.connector_auth.sendgrid_admin_api_key = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("DATADOG_API_KEY") {
+            self.connector_auth.datadog_api_key = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("DATADOG_APP_KEY") {
+            self.connector_auth.datadog_app_key = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("HEROKU_API_KEY") {
+            self.connector_auth.heroku_api_key = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("RAILWAY_API_TOKEN") {
+            self.connector_auth.railway_api_token = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("DIGITALOCEAN_ACCESS_TOKEN") {
+            self.connector_auth.digitalocean_access_token = Some(val);
+        }
+
         if let Ok(val) = std::env::var("BIRCH_MODE") {
             self.mode = val;
         }
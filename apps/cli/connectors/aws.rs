@@ -1,30 +1,89 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use aws_sdk_secretsmanager::Client as SecretsManagerClient;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::connectors::aws_refresh::RefreshActions;
+use crate::connectors::aws_ssm::ParameterStore;
 
 pub struct AwsConnector {
     client: SecretsManagerClient,
+    /// Set when `store = "ssm"`; secrets then live in Parameter Store
+    /// instead of Secrets Manager.
+    parameter_store: Option<ParameterStore>,
+    refresh: RefreshActions,
+    /// Shared by the Parameter Store and refresh clients.
+    sdk_config: aws_config::SdkConfig,
 }
 
 impl AwsConnector {
-    pub async fn new_async(_config: &crate::config::Config) -> Result<Self> {
-        let aws_config = aws_config::load_from_env().await;
+    pub async fn new_async(
+        _config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let mut loader = aws_config::from_env();
+        if let Some(region) = settings.get("region", "AWS_REGION") {
+            loader = loader.region(aws_config::Region::new(region));
+        }
+        // A profile may carry its own access key; otherwise the default
+        // credential chain is used.
+        if let (Some(access_key_id), Some(secret_access_key)) = (
+            settings.0.get("access_key_id"),
+            settings.0.get("secret_access_key"),
+        ) {
+            loader = loader.credentials_provider(aws_sdk_secretsmanager::config::Credentials::new(
+                access_key_id,
+                secret_access_key,
+                settings.0.get("session_token").cloned(),
+                None,
+                "birch-profile",
+            ));
+        }
+        let aws_config = loader.load().await;
         let client = SecretsManagerClient::new(&aws_config);
 
-        Ok(Self { client })
+        let parameter_store = match settings
+            .get("store", "AWS_SECRET_STORE")
+            .as_deref()
+            .unwrap_or("secretsmanager")
+        {
+            "secretsmanager" => None,
+            "ssm" => Some(ParameterStore::new(&aws_config, settings)),
+            other => anyhow::bail!(
+                "Unknown AWS secret store '{}': expected secretsmanager or ssm",
+                other
+            ),
+        };
+
+        Ok(Self {
+            client,
+            parameter_store,
+            refresh: RefreshActions::new(settings)?,
+            sdk_config: aws_config,
+        })
     }
 
-    pub fn new(config: &crate::config::Config) -> Result<Self> {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
         let rt = tokio::runtime::Handle::try_current()
             .map_err(|_| anyhow::anyhow!("No tokio runtime available"))?;
 
-        rt.block_on(Self::new_async(config))
+        // Connectors are created from async code, where blocking the runtime
+        // thread directly panics.
+        tokio::task::block_in_place(|| rt.block_on(Self::new_async(config, settings)))
     }
 }
 
 #[async_trait]
 impl crate::connectors::Connector for AwsConnector {
     async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        if let Some(ssm) = &self.parameter_store {
+            return ssm.put(name, value).await;
+        }
+
         match self.client.describe_secret().secret_id(name).send().await {
             Ok(_) => {
                 self.client
@@ -50,13 +109,22 @@ impl crate::connectors::Connector for AwsConnector {
     }
 
     async fn get_secret(&self, name: &str) -> Result<String> {
+        if let Some(ssm) = &self.parameter_store {
+            return ssm.get(name).await;
+        }
+
         let response = self
             .client
             .get_secret_value()
             .secret_id(name)
             .send()
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to get secret from AWS: {}", e))?;
+            .map_err(|e| match e.as_service_error() {
+                Some(service) if service.is_resource_not_found_exception() => {
+                    crate::connectors::not_found(format!("Secret '{}' not found in AWS", name))
+                }
+                _ => anyhow::anyhow!("Failed to get secret from AWS: {}", e),
+            })?;
 
         response
             .secret_string()
@@ -65,14 +133,229 @@ impl crate::connectors::Connector for AwsConnector {
     }
 
     async fn trigger_refresh(&self, service: Option<&str>) -> Result<()> {
+        if !self.refresh.is_empty() {
+            return self.refresh.run(&self.sdk_config).await;
+        }
+
         if let Some(svc) = service {
             println!(
                 "Note: Automatic refresh not implemented for AWS service: {}",
                 svc
             );
-            println!("Manually restart your service (e.g., ECS task restart, Lambda update)");
+            println!(
+                "Configure ecs_services, lambda_functions or ssm_command, or restart it manually"
+            );
         }
 
         Ok(())
     }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            versions: true,
+            describe: true,
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        if let Some(ssm) = &self.parameter_store {
+            return ssm.list().await;
+        }
+
+        let mut secrets = Vec::new();
+        let mut next_token = None;
+
+        loop {
+            let response = self
+                .client
+                .list_secrets()
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to list secrets in AWS: {}", e))?;
+
+            for entry in response.secret_list() {
+                let Some(name) = entry.name() else {
+                    continue;
+                };
+                secrets.push(crate::connectors::SecretMetadata {
+                    name: name.to_string(),
+                    updated_at: entry.last_changed_date().and_then(to_chrono),
+                    version: entry.secret_versions_to_stages().and_then(current_version),
+                });
+            }
+
+            match response.next_token() {
+                Some(token) => next_token = Some(token.to_string()),
+                None => break,
+            }
+        }
+
+        Ok(secrets)
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        if let Some(ssm) = &self.parameter_store {
+            return ssm.delete(name).await;
+        }
+
+        // Uses the default recovery window, so the secret can still be
+        // restored from the AWS console.
+        self.client
+            .delete_secret()
+            .secret_id(name)
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to delete secret in AWS: {}", e))?;
+
+        Ok(())
+    }
+
+    async fn list_versions(&self, name: &str) -> Result<Vec<crate::connectors::RemoteVersion>> {
+        if let Some(ssm) = &self.parameter_store {
+            return ssm.versions(name).await;
+        }
+
+        let mut versions = Vec::new();
+        let mut next_token = None;
+
+        loop {
+            let response = self
+                .client
+                .list_secret_version_ids()
+                .secret_id(name)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to list secret versions in AWS: {}", e))?;
+
+            for entry in response.versions() {
+                let Some(id) = entry.version_id() else {
+                    continue;
+                };
+                versions.push(crate::connectors::RemoteVersion {
+                    id: id.to_string(),
+                    created_at: entry.created_date().and_then(to_chrono),
+                    // Versions without a staging label are deprecated.
+                    enabled: !entry.version_stages().is_empty(),
+                });
+            }
+
+            match response.next_token() {
+                Some(token) => next_token = Some(token.to_string()),
+                None => break,
+            }
+        }
+
+        versions.sort_by_key(|v| v.created_at);
+        Ok(versions)
+    }
+
+    async fn describe(&self, name: &str) -> Result<crate::connectors::SecretMetadata> {
+        if let Some(ssm) = &self.parameter_store {
+            return ssm.describe(name).await;
+        }
+
+        let response = self
+            .client
+            .describe_secret()
+            .secret_id(name)
+            .send()
+            .await
+            .map_err(|e| match e.as_service_error() {
+                Some(service) if service.is_resource_not_found_exception() => {
+                    crate::connectors::not_found(format!("Secret '{}' not found in AWS", name))
+                }
+                _ => anyhow::anyhow!("Failed to describe secret in AWS: {}", e),
+            })?;
+
+        Ok(crate::connectors::SecretMetadata {
+            name: name.to_string(),
+            updated_at: response.last_changed_date().and_then(to_chrono),
+            version: response.version_ids_to_stages().and_then(current_version),
+        })
+    }
+}
+
+pub(crate) fn to_chrono(
+    date: &aws_sdk_secretsmanager::primitives::DateTime,
+) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(date.secs(), date.subsec_nanos())
+}
+
+fn current_version(stages: &HashMap<String, Vec<String>>) -> Option<String> {
+    stages
+        .iter()
+        .find(|(_, labels)| labels.iter().any(|l| l == "AWSCURRENT"))
+        .map(|(id, _)| id.clone())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::connectors::Connector;
+    use aws_sdk_secretsmanager::config::{Credentials, SharedCredentialsProvider};
+
+    /// SDK config that sends every AWS call to `server`.
+    pub fn sdk_config(server: &mockito::Server) -> aws_config::SdkConfig {
+        aws_config::SdkConfig::builder()
+            .behavior_version(aws_config::BehaviorVersion::latest())
+            .region(aws_config::Region::new("us-east-1"))
+            .endpoint_url(server.url())
+            .credentials_provider(SharedCredentialsProvider::new(Credentials::new(
+                "AKIDTEST", "secret", None, None, "test",
+            )))
+            .retry_config(aws_config::retry::RetryConfig::disabled())
+            .build()
+    }
+
+    fn connector(server: &mockito::Server) -> AwsConnector {
+        let sdk_config = sdk_config(server);
+        AwsConnector {
+            client: SecretsManagerClient::new(&sdk_config),
+            parameter_store: None,
+            refresh: RefreshActions::new(&Default::default()).unwrap(),
+            sdk_config,
+        }
+    }
+
+    fn get_secret_value(server: &mut mockito::Server) -> mockito::Mock {
+        server
+            .mock("POST", "/")
+            .match_header("x-amz-target", "secretsmanager.GetSecretValue")
+            .with_header("content-type", "application/x-amz-json-1.1")
+    }
+
+    #[tokio::test]
+    async fn missing_secret_reads_as_none() {
+        let mut server = mockito::Server::new_async().await;
+        get_secret_value(&mut server)
+            .with_status(400)
+            .with_body(r#"{"__type":"ResourceNotFoundException","message":"not here"}"#)
+            .create_async()
+            .await;
+
+        let value = crate::connectors::read_secret(&connector(&server), "API_KEY")
+            .await
+            .unwrap();
+
+        assert_eq!(value, None);
+    }
+
+    #[tokio::test]
+    async fn other_read_errors_are_not_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        get_secret_value(&mut server)
+            .with_status(400)
+            .with_body(r#"{"__type":"AccessDeniedException","message":"denied"}"#)
+            .create_async()
+            .await;
+
+        let err = connector(&server).get_secret("API_KEY").await.unwrap_err();
+
+        assert!(!crate::connectors::is_not_found(&err));
+    }
 }
@@ -0,0 +1,728 @@
+use anyhow::{Context, Result};
+use aws_sdk_ecs::types::DeploymentRolloutState;
+use aws_sdk_lambda::types::{Environment, LastUpdateStatus};
+use aws_sdk_ssm::error::DisplayErrorContext;
+use aws_sdk_ssm::types::{CommandStatus, Target};
+use std::time::Duration;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What the AWS connector does after a rotation so workloads load the new
+/// value. Configured per target or profile:
+///
+/// - `ecs_services = "cluster/service,..."`: force a new deployment
+/// - `lambda_functions = "name,..."`: set `BIRCH_ROTATED_AT` in the function's
+///   environment. Any configuration change retires warm execution
+///   environments, so new invocations read the new value. The variable stays
+///   on the function and is overwritten by the next rotation.
+/// - `ssm_command` and `ssm_targets`: run a command through SSM Run Command
+pub struct RefreshActions {
+    actions: Vec<RefreshAction>,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+struct Clients {
+    ecs: aws_sdk_ecs::Client,
+    lambda: aws_sdk_lambda::Client,
+    ssm: aws_sdk_ssm::Client,
+}
+
+enum RefreshAction {
+    EcsService {
+        cluster: String,
+        service: String,
+    },
+    Lambda {
+        function: String,
+    },
+    RunCommand {
+        document: String,
+        command: String,
+        targets: Vec<Target>,
+    },
+}
+
+impl std::fmt::Display for RefreshAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RefreshAction::EcsService { cluster, service } => {
+                write!(f, "ECS service {}/{}", cluster, service)
+            }
+            RefreshAction::Lambda { function } => write!(f, "Lambda function {}", function),
+            RefreshAction::RunCommand { document, .. } => write!(f, "SSM command ({})", document),
+        }
+    }
+}
+
+impl RefreshActions {
+    pub fn new(settings: &crate::connectors::ConnectorSettings) -> Result<Self> {
+        let list = |key: &str| -> Vec<String> {
+            settings
+                .0
+                .get(key)
+                .map(|v| {
+                    v.split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut actions = Vec::new();
+
+        for entry in list("ecs_services") {
+            let (cluster, service) = entry.split_once('/').ok_or_else(|| {
+                anyhow::anyhow!("Invalid ECS service '{}': expected cluster/service", entry)
+            })?;
+            actions.push(RefreshAction::EcsService {
+                cluster: cluster.to_string(),
+                service: service.to_string(),
+            });
+        }
+
+        for function in list("lambda_functions") {
+            actions.push(RefreshAction::Lambda { function });
+        }
+
+        if let Some(command) = settings.0.get("ssm_command") {
+            let targets = list("ssm_targets");
+            if targets.is_empty() {
+                anyhow::bail!("ssm_command needs ssm_targets (instance IDs or tag:Key=Value)");
+            }
+            actions.push(RefreshAction::RunCommand {
+                document: settings
+                    .0
+                    .get("ssm_document")
+                    .cloned()
+                    .unwrap_or_else(|| "AWS-RunShellScript".to_string()),
+                command: command.clone(),
+                targets: run_command_targets(&targets)?,
+            });
+        }
+
+        let timeout = match settings.0.get("refresh_timeout") {
+            Some(timeout) => humantime::parse_duration(timeout)
+                .with_context(|| format!("Invalid refresh_timeout '{}'", timeout))?,
+            None => DEFAULT_TIMEOUT,
+        };
+
+        Ok(Self {
+            actions,
+            timeout,
+            poll_interval: POLL_INTERVAL,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Starts every action, then waits for each to finish.
+    pub async fn run(&self, sdk_config: &aws_config::SdkConfig) -> Result<()> {
+        let clients = Clients {
+            ecs: aws_sdk_ecs::Client::new(sdk_config),
+            lambda: aws_sdk_lambda::Client::new(sdk_config),
+            ssm: aws_sdk_ssm::Client::new(sdk_config),
+        };
+        let mut pending = Vec::new();
+
+        for action in &self.actions {
+            let handle = start(&clients, action).await?;
+            println!("🔄 Refreshing {}", action);
+            pending.push((action, handle));
+        }
+
+        for (action, handle) in pending {
+            self.wait(&clients, action, handle.as_deref()).await?;
+            println!("   ✓ {} refreshed", action);
+        }
+
+        Ok(())
+    }
+
+    async fn wait(
+        &self,
+        clients: &Clients,
+        action: &RefreshAction,
+        handle: Option<&str>,
+    ) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + self.timeout;
+
+        loop {
+            if poll(clients, action, handle).await? {
+                return Ok(());
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                anyhow::bail!(
+                    "{} did not finish refreshing within {}",
+                    action,
+                    humantime::format_duration(self.timeout)
+                );
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// `i-...` entries become instance IDs, `tag:Key=Value` entries tag targets.
+fn run_command_targets(targets: &[String]) -> Result<Vec<Target>> {
+    let mut instance_ids = Vec::new();
+    let mut tags = Vec::new();
+
+    for target in targets {
+        match target.strip_prefix("tag:") {
+            Some(tag) => {
+                let (key, value) = tag.split_once('=').ok_or_else(|| {
+                    anyhow::anyhow!("Invalid SSM target '{}': expected tag:Key=Value", target)
+                })?;
+                tags.push(
+                    Target::builder()
+                        .key(format!("tag:{}", key))
+                        .values(value)
+                        .build(),
+                );
+            }
+            None => instance_ids.push(target.clone()),
+        }
+    }
+
+    if !instance_ids.is_empty() {
+        tags.push(
+            Target::builder()
+                .key("InstanceIds")
+                .set_values(Some(instance_ids))
+                .build(),
+        );
+    }
+
+    Ok(tags)
+}
+
+/// Starts an action and returns what `wait` needs to track it.
+async fn start(clients: &Clients, action: &RefreshAction) -> Result<Option<String>> {
+    match action {
+        RefreshAction::EcsService { cluster, service } => {
+            clients
+                .ecs
+                .update_service()
+                .cluster(cluster)
+                .service(service)
+                .force_new_deployment(true)
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to redeploy {}: {}", action, DisplayErrorContext(e))
+                })?;
+            Ok(None)
+        }
+        RefreshAction::Lambda { function } => {
+            let configuration = clients
+                .lambda
+                .get_function_configuration()
+                .function_name(function)
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to read {}: {}", action, DisplayErrorContext(e))
+                })?;
+
+            let mut variables = configuration
+                .environment()
+                .and_then(|e| e.variables())
+                .cloned()
+                .unwrap_or_default();
+            variables.insert(
+                "BIRCH_ROTATED_AT".to_string(),
+                chrono::Utc::now().to_rfc3339(),
+            );
+
+            clients
+                .lambda
+                .update_function_configuration()
+                .function_name(function)
+                .environment(
+                    Environment::builder()
+                        .set_variables(Some(variables))
+                        .build(),
+                )
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to update {}: {}", action, DisplayErrorContext(e))
+                })?;
+            Ok(None)
+        }
+        RefreshAction::RunCommand {
+            document,
+            command,
+            targets,
+        } => {
+            let response = clients
+                .ssm
+                .send_command()
+                .document_name(document)
+                .set_targets(Some(targets.clone()))
+                .parameters("commands", vec![command.clone()])
+                .comment("birch secret refresh")
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to send {}: {}", action, DisplayErrorContext(e))
+                })?;
+
+            response
+                .command()
+                .and_then(|c| c.command_id())
+                .map(|id| Some(id.to_string()))
+                .ok_or_else(|| anyhow::anyhow!("SSM SendCommand returned no command ID"))
+        }
+    }
+}
+
+/// Whether the action has completed; failures are errors.
+async fn poll(clients: &Clients, action: &RefreshAction, handle: Option<&str>) -> Result<bool> {
+    match action {
+        RefreshAction::EcsService { cluster, service } => {
+            let response = clients
+                .ecs
+                .describe_services()
+                .cluster(cluster)
+                .services(service)
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to describe {}: {}", action, DisplayErrorContext(e))
+                })?;
+
+            let deployments = response
+                .services()
+                .first()
+                .map(|s| s.deployments())
+                .unwrap_or_default();
+            let primary = deployments
+                .iter()
+                .find(|d| d.status() == Some("PRIMARY"))
+                .ok_or_else(|| anyhow::anyhow!("{} has no primary deployment", action))?;
+
+            match primary.rollout_state() {
+                Some(DeploymentRolloutState::Failed) => anyhow::bail!(
+                    "{} deployment failed: {}",
+                    action,
+                    primary.rollout_state_reason().unwrap_or("unknown reason")
+                ),
+                Some(DeploymentRolloutState::Completed) => Ok(true),
+                Some(_) => Ok(false),
+                // Services without the deployment circuit breaker report no
+                // rollout state; the old deployments draining is the signal.
+                None => Ok(
+                    deployments.len() == 1 && primary.running_count() == primary.desired_count()
+                ),
+            }
+        }
+        RefreshAction::Lambda { function } => {
+            let configuration = clients
+                .lambda
+                .get_function_configuration()
+                .function_name(function)
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to read {}: {}", action, DisplayErrorContext(e))
+                })?;
+
+            match configuration.last_update_status() {
+                Some(LastUpdateStatus::Failed) => anyhow::bail!(
+                    "{} update failed: {}",
+                    action,
+                    configuration
+                        .last_update_status_reason()
+                        .unwrap_or("unknown reason")
+                ),
+                Some(LastUpdateStatus::InProgress) => Ok(false),
+                _ => Ok(true),
+            }
+        }
+        RefreshAction::RunCommand { .. } => {
+            let command_id = handle.unwrap_or_default();
+            let response = clients
+                .ssm
+                .list_commands()
+                .command_id(command_id)
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to check {}: {}", action, DisplayErrorContext(e))
+                })?;
+            let Some(command) = response.commands().first() else {
+                return Ok(false);
+            };
+
+            match command.status() {
+                Some(CommandStatus::Success) => Ok(true),
+                Some(CommandStatus::Pending)
+                | Some(CommandStatus::InProgress)
+                | Some(CommandStatus::Cancelling)
+                | None => Ok(false),
+                Some(status) => anyhow::bail!(
+                    "{} {} ({}): {} of {} targets failed",
+                    action,
+                    status.as_str().to_lowercase(),
+                    command_id,
+                    command.error_count(),
+                    command.target_count()
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::aws::tests::sdk_config;
+    use crate::connectors::ConnectorSettings;
+    use mockito::Matcher;
+
+    fn actions(settings: &[(&str, &str)]) -> RefreshActions {
+        let settings = ConnectorSettings(
+            settings
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        );
+        RefreshActions {
+            poll_interval: Duration::ZERO,
+            ..RefreshActions::new(&settings).unwrap()
+        }
+    }
+
+    fn json_api(server: &mut mockito::Server, target: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/")
+            .match_header("x-amz-target", target)
+            .with_header("content-type", "application/x-amz-json-1.1")
+    }
+
+    fn ecs_deployments(rollout_state: &str) -> String {
+        serde_json::json!({
+            "services": [{
+                "deployments": [
+                    { "status": "PRIMARY", "rolloutState": rollout_state,
+                      "rolloutStateReason": "tasks failed to start",
+                      "runningCount": 2, "desiredCount": 2 },
+                    { "status": "ACTIVE", "runningCount": 2, "desiredCount": 2 }
+                ]
+            }]
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn ecs_forces_a_deployment_and_waits_for_the_rollout() {
+        let mut server = mockito::Server::new_async().await;
+        let update = json_api(
+            &mut server,
+            "AmazonEC2ContainerServiceV20141113.UpdateService",
+        )
+        .match_body(Matcher::PartialJson(serde_json::json!({
+            "cluster": "prod",
+            "service": "api",
+            "forceNewDeployment": true,
+        })))
+        .with_body(r#"{"service":{}}"#)
+        .create_async()
+        .await;
+        let describe = "AmazonEC2ContainerServiceV20141113.DescribeServices";
+        let in_progress = json_api(&mut server, describe)
+            .with_body(ecs_deployments("IN_PROGRESS"))
+            .expect(2)
+            .create_async()
+            .await;
+        let completed = json_api(&mut server, describe)
+            .with_body(ecs_deployments("COMPLETED"))
+            .create_async()
+            .await;
+
+        actions(&[("ecs_services", "prod/api")])
+            .run(&sdk_config(&server))
+            .await
+            .unwrap();
+
+        update.assert_async().await;
+        in_progress.assert_async().await;
+        completed.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn ecs_failed_rollout_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        json_api(
+            &mut server,
+            "AmazonEC2ContainerServiceV20141113.UpdateService",
+        )
+        .with_body(r#"{"service":{}}"#)
+        .create_async()
+        .await;
+        json_api(
+            &mut server,
+            "AmazonEC2ContainerServiceV20141113.DescribeServices",
+        )
+        .with_body(ecs_deployments("FAILED"))
+        .create_async()
+        .await;
+
+        let err = actions(&[("ecs_services", "prod/api")])
+            .run(&sdk_config(&server))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("tasks failed to start"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn ecs_without_rollout_state_waits_for_old_deployments_to_drain() {
+        let mut server = mockito::Server::new_async().await;
+        json_api(
+            &mut server,
+            "AmazonEC2ContainerServiceV20141113.UpdateService",
+        )
+        .with_body(r#"{"service":{}}"#)
+        .create_async()
+        .await;
+        let describe = "AmazonEC2ContainerServiceV20141113.DescribeServices";
+        let draining = json_api(&mut server, describe)
+            .with_body(
+                r#"{"services":[{"deployments":[
+                    {"status":"PRIMARY","runningCount":2,"desiredCount":2},
+                    {"status":"ACTIVE","runningCount":1,"desiredCount":0}
+                ]}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let drained = json_api(&mut server, describe)
+            .with_body(
+                r#"{"services":[{"deployments":[
+                    {"status":"PRIMARY","runningCount":2,"desiredCount":2}
+                ]}]}"#,
+            )
+            .create_async()
+            .await;
+
+        actions(&[("ecs_services", "prod/api")])
+            .run(&sdk_config(&server))
+            .await
+            .unwrap();
+
+        draining.assert_async().await;
+        drained.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn lambda_bumps_the_configuration_and_keeps_existing_variables() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/2015-03-31/functions/worker/configuration";
+        let read = server
+            .mock("GET", path)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"FunctionName":"worker","Environment":{"Variables":{"MODE":"prod"}},
+                    "LastUpdateStatus":"Successful"}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PUT", path)
+            .match_body(Matcher::AllOf(vec![
+                Matcher::PartialJson(serde_json::json!({
+                    "Environment": { "Variables": { "MODE": "prod" } }
+                })),
+                Matcher::Regex(r#""BIRCH_ROTATED_AT":"\d{4}-"#.to_string()),
+            ]))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"FunctionName":"worker","LastUpdateStatus":"InProgress"}"#)
+            .create_async()
+            .await;
+
+        actions(&[("lambda_functions", "worker")])
+            .run(&sdk_config(&server))
+            .await
+            .unwrap();
+
+        read.assert_async().await;
+        update.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn lambda_failed_update_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/2015-03-31/functions/worker/configuration";
+        server
+            .mock("GET", path)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"FunctionName":"worker","LastUpdateStatus":"Successful"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("PUT", path)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"FunctionName":"worker"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", path)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"FunctionName":"worker","LastUpdateStatus":"Failed",
+                    "LastUpdateStatusReason":"KMS key is disabled"}"#,
+            )
+            .create_async()
+            .await;
+
+        let err = actions(&[("lambda_functions", "worker")])
+            .run(&sdk_config(&server))
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("KMS key is disabled"), "{}", err);
+    }
+
+    fn command(status: &str) -> String {
+        serde_json::json!({
+            "Commands": [{
+                "CommandId": "cmd-1", "Status": status,
+                "TargetCount": 3, "ErrorCount": 1
+            }]
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn run_command_polls_until_the_command_succeeds() {
+        let mut server = mockito::Server::new_async().await;
+        let send = json_api(&mut server, "AmazonSSM.SendCommand")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "DocumentName": "AWS-RunShellScript",
+                "Parameters": { "commands": ["systemctl restart api"] },
+                "Targets": [
+                    { "Key": "tag:Role", "Values": ["api"] },
+                    { "Key": "InstanceIds", "Values": ["i-123", "i-456"] }
+                ]
+            })))
+            .with_body(r#"{"Command":{"CommandId":"cmd-1"}}"#)
+            .create_async()
+            .await;
+        let list = Matcher::PartialJson(serde_json::json!({ "CommandId": "cmd-1" }));
+        let pending = json_api(&mut server, "AmazonSSM.ListCommands")
+            .match_body(list.clone())
+            .with_body(command("Pending"))
+            .expect(1)
+            .create_async()
+            .await;
+        let in_progress = json_api(&mut server, "AmazonSSM.ListCommands")
+            .match_body(list.clone())
+            .with_body(command("InProgress"))
+            .expect(1)
+            .create_async()
+            .await;
+        let success = json_api(&mut server, "AmazonSSM.ListCommands")
+            .match_body(list)
+            .with_body(command("Success"))
+            .create_async()
+            .await;
+
+        actions(&[
+            ("ssm_command", "systemctl restart api"),
+            ("ssm_targets", "i-123, tag:Role=api, i-456"),
+        ])
+        .run(&sdk_config(&server))
+        .await
+        .unwrap();
+
+        send.assert_async().await;
+        pending.assert_async().await;
+        in_progress.assert_async().await;
+        success.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn run_command_failure_reports_failed_targets() {
+        let mut server = mockito::Server::new_async().await;
+        json_api(&mut server, "AmazonSSM.SendCommand")
+            .with_body(r#"{"Command":{"CommandId":"cmd-1"}}"#)
+            .create_async()
+            .await;
+        json_api(&mut server, "AmazonSSM.ListCommands")
+            .with_body(command("Failed"))
+            .create_async()
+            .await;
+
+        let err = actions(&[("ssm_command", "reload"), ("ssm_targets", "i-123")])
+            .run(&sdk_config(&server))
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("failed (cmd-1): 1 of 3 targets failed"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_timeout() {
+        let mut server = mockito::Server::new_async().await;
+        json_api(&mut server, "AmazonSSM.SendCommand")
+            .with_body(r#"{"Command":{"CommandId":"cmd-1"}}"#)
+            .create_async()
+            .await;
+        json_api(&mut server, "AmazonSSM.ListCommands")
+            .with_body(command("InProgress"))
+            .create_async()
+            .await;
+
+        let err = actions(&[
+            ("ssm_command", "reload"),
+            ("ssm_targets", "i-123"),
+            ("refresh_timeout", "0s"),
+        ])
+        .run(&sdk_config(&server))
+        .await
+        .unwrap_err();
+
+        assert!(
+            err.to_string().contains("did not finish refreshing"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        let settings = |pairs: &[(&str, &str)]| {
+            ConnectorSettings(
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            )
+        };
+
+        assert!(RefreshActions::new(&settings(&[("ecs_services", "api")])).is_err());
+        assert!(RefreshActions::new(&settings(&[("ssm_command", "reload")])).is_err());
+        assert!(RefreshActions::new(&settings(&[
+            ("ssm_command", "reload"),
+            ("ssm_targets", "tag:Role")
+        ]))
+        .is_err());
+    }
+}
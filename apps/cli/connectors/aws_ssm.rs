@@ -0,0 +1,363 @@
+use crate::connectors::aws::to_chrono;
+use crate::connectors::{RemoteVersion, SecretMetadata};
+use anyhow::Result;
+use aws_sdk_ssm::error::DisplayErrorContext;
+use aws_sdk_ssm::types::{ParameterStringFilter, ParameterType};
+use aws_sdk_ssm::Client as SsmClient;
+
+/// SecureString parameters in SSM Parameter Store, used by the AWS connector
+/// when `store = "ssm"`.
+pub struct ParameterStore {
+    client: SsmClient,
+    /// Hierarchy the parameters live under, e.g. `/prod/api`.
+    path_prefix: Option<String>,
+    /// KMS key for new values; SSM uses `alias/aws/ssm` when unset.
+    kms_key_id: Option<String>,
+}
+
+impl ParameterStore {
+    pub fn new(
+        sdk_config: &aws_config::SdkConfig,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Self {
+        Self {
+            client: SsmClient::new(sdk_config),
+            path_prefix: settings
+                .get("path_prefix", "AWS_SSM_PATH_PREFIX")
+                .map(|p| format!("/{}", p.trim_matches('/'))),
+            kms_key_id: settings.get("kms_key_id", "AWS_SSM_KMS_KEY_ID"),
+        }
+    }
+
+    fn parameter_name(&self, name: &str) -> String {
+        match &self.path_prefix {
+            Some(prefix) => format!("{}/{}", prefix, name),
+            None => name.to_string(),
+        }
+    }
+
+    pub async fn put(&self, name: &str, value: &str) -> Result<()> {
+        self.client
+            .put_parameter()
+            .name(self.parameter_name(name))
+            .value(value)
+            .r#type(ParameterType::SecureString)
+            .overwrite(true)
+            .set_key_id(self.kms_key_id.clone())
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to update parameter in SSM: {}",
+                    DisplayErrorContext(e)
+                )
+            })?;
+
+        Ok(())
+    }
+
+    pub async fn get(&self, name: &str) -> Result<String> {
+        let response = self
+            .client
+            .get_parameter()
+            .name(self.parameter_name(name))
+            .with_decryption(true)
+            .send()
+            .await
+            .map_err(|e| match e.as_service_error() {
+                Some(service) if service.is_parameter_not_found() => not_found(name),
+                _ => anyhow::anyhow!(
+                    "Failed to get parameter from SSM: {}",
+                    DisplayErrorContext(e)
+                ),
+            })?;
+
+        response
+            .parameter()
+            .and_then(|p| p.value())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("SSM returned no value for '{}'", name))
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        self.client
+            .delete_parameter()
+            .name(self.parameter_name(name))
+            .send()
+            .await
+            .map_err(|e| match e.as_service_error() {
+                Some(service) if service.is_parameter_not_found() => not_found(name),
+                _ => anyhow::anyhow!(
+                    "Failed to delete parameter in SSM: {}",
+                    DisplayErrorContext(e)
+                ),
+            })?;
+
+        Ok(())
+    }
+
+    /// Parameters under `path_prefix` (or all parameters), without values.
+    pub async fn list(&self) -> Result<Vec<SecretMetadata>> {
+        let filters = match &self.path_prefix {
+            Some(prefix) => vec![ParameterStringFilter::builder()
+                .key("Path")
+                .option("Recursive")
+                .values(prefix)
+                .build()?],
+            None => Vec::new(),
+        };
+
+        let mut secrets = Vec::new();
+        let mut next_token = None;
+
+        loop {
+            let response = self
+                .client
+                .describe_parameters()
+                .set_parameter_filters(Some(filters.clone()))
+                .max_results(50)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to list parameters in SSM: {}",
+                        DisplayErrorContext(e)
+                    )
+                })?;
+
+            for parameter in response.parameters() {
+                let Some(name) = parameter.name() else {
+                    continue;
+                };
+                let name = match &self.path_prefix {
+                    Some(prefix) => name
+                        .strip_prefix(prefix.as_str())
+                        .map(|n| n.trim_start_matches('/'))
+                        .unwrap_or(name),
+                    None => name,
+                };
+                secrets.push(SecretMetadata {
+                    name: name.to_string(),
+                    updated_at: parameter.last_modified_date().and_then(to_chrono),
+                    version: Some(parameter.version().to_string()),
+                });
+            }
+
+            match response.next_token() {
+                Some(token) => next_token = Some(token.to_string()),
+                None => break,
+            }
+        }
+
+        Ok(secrets)
+    }
+
+    pub async fn describe(&self, name: &str) -> Result<SecretMetadata> {
+        let response = self
+            .client
+            .describe_parameters()
+            .parameter_filters(
+                ParameterStringFilter::builder()
+                    .key("Name")
+                    .option("Equals")
+                    .values(self.parameter_name(name))
+                    .build()?,
+            )
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to describe parameter in SSM: {}",
+                    DisplayErrorContext(e)
+                )
+            })?;
+
+        response
+            .parameters()
+            .first()
+            .map(|parameter| SecretMetadata {
+                name: name.to_string(),
+                updated_at: parameter.last_modified_date().and_then(to_chrono),
+                version: Some(parameter.version().to_string()),
+            })
+            .ok_or_else(|| not_found(name))
+    }
+
+    pub async fn versions(&self, name: &str) -> Result<Vec<RemoteVersion>> {
+        let mut versions = Vec::new();
+        let mut next_token = None;
+
+        loop {
+            let response = self
+                .client
+                .get_parameter_history()
+                .name(self.parameter_name(name))
+                .with_decryption(false)
+                .max_results(50)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| match e.as_service_error() {
+                    Some(service) if service.is_parameter_not_found() => not_found(name),
+                    _ => anyhow::anyhow!(
+                        "Failed to list parameter versions in SSM: {}",
+                        DisplayErrorContext(e)
+                    ),
+                })?;
+
+            for parameter in response.parameters() {
+                versions.push(RemoteVersion {
+                    id: parameter.version().to_string(),
+                    created_at: parameter.last_modified_date().and_then(to_chrono),
+                    enabled: true,
+                });
+            }
+
+            match response.next_token() {
+                Some(token) => next_token = Some(token.to_string()),
+                None => break,
+            }
+        }
+
+        versions.sort_by_key(|v| v.created_at);
+        Ok(versions)
+    }
+}
+
+fn not_found(name: &str) -> anyhow::Error {
+    crate::connectors::not_found(format!(
+        "Secret '{}' not found in SSM Parameter Store",
+        name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::aws::tests::sdk_config;
+    use crate::connectors::ConnectorSettings;
+    use mockito::Matcher;
+
+    fn store(server: &mockito::Server, prefix: Option<&str>) -> ParameterStore {
+        let mut settings = ConnectorSettings::default();
+        if let Some(prefix) = prefix {
+            settings
+                .0
+                .insert("path_prefix".to_string(), prefix.to_string());
+        }
+        ParameterStore::new(&sdk_config(server), &settings)
+    }
+
+    fn ssm(server: &mut mockito::Server, action: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/")
+            .match_header("x-amz-target", format!("AmazonSSM.{}", action).as_str())
+            .with_header("content-type", "application/x-amz-json-1.1")
+    }
+
+    #[tokio::test]
+    async fn missing_parameter_is_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = ssm(&mut server, "GetParameter")
+            .with_status(400)
+            .with_body(r#"{"__type":"ParameterNotFound","message":"not here"}"#)
+            .create_async()
+            .await;
+
+        let err = store(&server, Some("prod"))
+            .get("API_KEY")
+            .await
+            .unwrap_err();
+
+        mock.assert_async().await;
+        assert!(crate::connectors::is_not_found(&err));
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        ssm(&mut server, "GetParameter")
+            .with_status(400)
+            .with_body(r#"{"__type":"AccessDeniedException","message":"denied"}"#)
+            .create_async()
+            .await;
+
+        let err = store(&server, None).get("API_KEY").await.unwrap_err();
+
+        assert!(!crate::connectors::is_not_found(&err));
+        assert!(err.to_string().contains("denied"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn reads_and_writes_under_the_path_prefix() {
+        let mut server = mockito::Server::new_async().await;
+        let put = ssm(&mut server, "PutParameter")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "Name": "/prod/api/API_KEY",
+                "Value": "secret",
+                "Type": "SecureString",
+                "Overwrite": true,
+            })))
+            .with_body(r#"{"Version":2}"#)
+            .create_async()
+            .await;
+        let get = ssm(&mut server, "GetParameter")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "Name": "/prod/api/API_KEY",
+                "WithDecryption": true,
+            })))
+            .with_body(r#"{"Parameter":{"Name":"/prod/api/API_KEY","Value":"secret","Version":2}}"#)
+            .create_async()
+            .await;
+
+        let store = store(&server, Some("/prod/api/"));
+        store.put("API_KEY", "secret").await.unwrap();
+        assert_eq!(store.get("API_KEY").await.unwrap(), "secret");
+
+        put.assert_async().await;
+        get.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn lists_names_relative_to_the_prefix() {
+        let mut server = mockito::Server::new_async().await;
+        ssm(&mut server, "DescribeParameters")
+            .match_body(Matcher::PartialJson(serde_json::json!({
+                "ParameterFilters": [
+                    { "Key": "Path", "Option": "Recursive", "Values": ["/prod"] }
+                ]
+            })))
+            .with_body(
+                r#"{"Parameters":[
+                    {"Name":"/prod/API_KEY","Version":3,"LastModifiedDate":1700000000.5},
+                    {"Name":"/prod/db/PASSWORD","Version":1}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let secrets = store(&server, Some("prod")).list().await.unwrap();
+
+        let names: Vec<_> = secrets.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["API_KEY", "db/PASSWORD"]);
+        assert_eq!(secrets[0].version.as_deref(), Some("3"));
+        assert_eq!(
+            secrets[0].updated_at.map(|t| t.timestamp_millis()),
+            Some(1_700_000_000_500)
+        );
+    }
+
+    #[tokio::test]
+    async fn describe_without_a_match_is_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        ssm(&mut server, "DescribeParameters")
+            .with_body(r#"{"Parameters":[]}"#)
+            .create_async()
+            .await;
+
+        let err = store(&server, None).describe("API_KEY").await.unwrap_err();
+
+        assert!(crate::connectors::is_not_found(&err));
+    }
+}
@@ -3,35 +3,100 @@ use async_trait::async_trait;
 use azure_core::auth::TokenCredential;
 use azure_identity::ClientSecretCredential;
 use azure_security_keyvault::prelude::*;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
 use std::sync::Arc;
+use std::time::Duration;
+
+use crate::connectors::RetireMode;
+
+const MANAGEMENT_URL: &str = "https://management.azure.com";
+const WEB_API_VERSION: &str = "2023-12-01";
+const DEFAULT_REFRESH_TIMEOUT: Duration = Duration::from_secs(600);
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 pub struct AzureConnector {
     credential: Arc<dyn TokenCredential>,
     vault_url: String,
+    subscription_id: Option<String>,
+    /// App Service apps (or deployment slots) restarted on refresh.
+    app_services: Vec<AppService>,
+    refresh_timeout: Duration,
+    poll_interval: Duration,
+    retire: Option<RetireMode>,
+    client: reqwest::Client,
+    management_url: String,
+}
+
+struct AppService {
+    resource_group: String,
+    name: String,
+    slot: Option<String>,
+}
+
+impl std::fmt::Display for AppService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.slot {
+            Some(slot) => write!(f, "{}/{}", self.name, slot),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl AppService {
+    /// Parses `resource-group/app` or `resource-group/app/slot`.
+    fn parse(value: &str) -> Result<Self> {
+        let parts: Vec<&str> = value.split('/').collect();
+        match parts.as_slice() {
+            [resource_group, name] => Ok(Self {
+                resource_group: resource_group.to_string(),
+                name: name.to_string(),
+                slot: None,
+            }),
+            [resource_group, name, slot] => Ok(Self {
+                resource_group: resource_group.to_string(),
+                name: name.to_string(),
+                slot: Some(slot.to_string()),
+            }),
+            _ => anyhow::bail!(
+                "Invalid App Service '{}': expected resource-group/app or resource-group/app/slot",
+                value
+            ),
+        }
+    }
+
+    fn path(&self, subscription_id: &str) -> String {
+        let site = format!(
+            "subscriptions/{}/resourceGroups/{}/providers/Microsoft.Web/sites/{}",
+            subscription_id, self.resource_group, self.name
+        );
+        match &self.slot {
+            Some(slot) => format!("{}/slots/{}", site, slot),
+            None => site,
+        }
+    }
 }
 
 impl AzureConnector {
-    pub fn new(config: &crate::config::Config) -> Result<Self> {
-        let client_id = config
-            .connector_auth
-            .azure_client_id
-            .as_ref()
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let client_id = settings
+            .credential("client_id", &config.connector_auth.azure_client_id)
             .ok_or_else(|| anyhow::anyhow!("AZURE_CLIENT_ID not configured"))?;
 
-        let client_secret = config
-            .connector_auth
-            .azure_client_secret
-            .as_ref()
+        let client_secret = settings
+            .credential("client_secret", &config.connector_auth.azure_client_secret)
             .ok_or_else(|| anyhow::anyhow!("AZURE_CLIENT_SECRET not configured"))?;
 
-        let tenant_id = config
-            .connector_auth
-            .azure_tenant_id
-            .as_ref()
+        let tenant_id = settings
+            .credential("tenant_id", &config.connector_auth.azure_tenant_id)
             .ok_or_else(|| anyhow::anyhow!("AZURE_TENANT_ID not configured"))?;
 
-        let vault_name = std::env::var("AZURE_VAULT_NAME")
-            .map_err(|_| anyhow::anyhow!("AZURE_VAULT_NAME environment variable not set"))?;
+        let vault_name = settings
+            .get("vault_name", "AZURE_VAULT_NAME")
+            .ok_or_else(|| anyhow::anyhow!("AZURE_VAULT_NAME environment variable not set"))?;
 
         let vault_url = format!("https://{}.vault.azure.net", vault_name);
 
@@ -41,16 +106,125 @@ impl AzureConnector {
         let credential: Arc<dyn TokenCredential> = Arc::new(ClientSecretCredential::new(
             http_client,
             authority_host.parse().unwrap(),
-            tenant_id.clone(),
-            client_id.clone(),
-            client_secret.clone(),
+            tenant_id,
+            client_id,
+            client_secret,
         ));
 
+        let app_services = settings
+            .0
+            .get("app_services")
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(AppService::parse)
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let subscription_id = settings.get("subscription_id", "AZURE_SUBSCRIPTION_ID");
+        if !app_services.is_empty() && subscription_id.is_none() {
+            anyhow::bail!("AZURE_SUBSCRIPTION_ID not set (needed to restart App Services)");
+        }
+
+        let refresh_timeout = match settings.0.get("refresh_timeout") {
+            Some(timeout) => humantime::parse_duration(timeout)
+                .map_err(|e| anyhow::anyhow!("Invalid refresh_timeout '{}': {}", timeout, e))?,
+            None => DEFAULT_REFRESH_TIMEOUT,
+        };
+
+        let retire = RetireMode::from_settings(settings)?;
+        if retire == Some(RetireMode::Destroy) {
+            anyhow::bail!(
+                "Azure Key Vault cannot destroy single secret versions; use retire_versions = \"disable\""
+            );
+        }
+
         Ok(Self {
             credential,
             vault_url,
+            subscription_id,
+            app_services,
+            refresh_timeout,
+            poll_interval: POLL_INTERVAL,
+            retire,
+            client: reqwest::Client::new(),
+            management_url: MANAGEMENT_URL.to_string(),
         })
     }
+
+    fn client(&self) -> Result<SecretClient> {
+        SecretClient::new(&self.vault_url, self.credential.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to create Azure Key Vault client: {}", e))
+    }
+
+    async fn management(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<serde_json::Value> {
+        let token = self
+            .credential
+            .get_token(&[&format!("{}/.default", MANAGEMENT_URL)])
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get Azure management token: {}", e))?;
+
+        let response = self
+            .client
+            .request(method, format!("{}/{}", self.management_url, path))
+            .query(&[("api-version", WEB_API_VERSION)])
+            .query(query)
+            .bearer_auth(token.token.secret())
+            // The restart endpoint rejects POSTs without a length.
+            .header(reqwest::header::CONTENT_LENGTH, 0)
+            .send()
+            .await?;
+        let status = response.status();
+        let json: serde_json::Value = response.json().await.unwrap_or_default();
+
+        if !status.is_success() {
+            anyhow::bail!(
+                "Azure management API error ({}): {}",
+                status,
+                json["error"]["message"].as_str().unwrap_or_default()
+            );
+        }
+
+        Ok(json)
+    }
+
+    /// Restarts the app and waits until it reports `Running` again.
+    async fn restart_app_service(&self, app: &AppService, subscription_id: &str) -> Result<()> {
+        let path = app.path(subscription_id);
+
+        self.management(
+            reqwest::Method::POST,
+            &format!("{}/restart", path),
+            &[("softRestart", "false"), ("synchronous", "true")],
+        )
+        .await?;
+
+        let deadline = tokio::time::Instant::now() + self.refresh_timeout;
+        loop {
+            let site = self.management(reqwest::Method::GET, &path, &[]).await?;
+            if site["properties"]["state"].as_str() == Some("Running") {
+                return Ok(());
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                anyhow::bail!(
+                    "App Service {} is not running {} after the restart",
+                    app,
+                    humantime::format_duration(self.refresh_timeout)
+                );
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
 }
 
 #[async_trait]
@@ -71,23 +245,437 @@ impl crate::connectors::Connector for AzureConnector {
         let client = SecretClient::new(&self.vault_url, self.credential.clone())
             .map_err(|e| anyhow::anyhow!("Failed to create Azure Key Vault client: {}", e))?;
 
-        let secret = client
-            .get(name)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to get secret from Azure Key Vault: {}", e))?;
+        let secret = client.get(name).await.map_err(|e| {
+            if is_not_found(&e) {
+                crate::connectors::not_found(format!(
+                    "Secret '{}' not found in Azure Key Vault",
+                    name
+                ))
+            } else {
+                anyhow::anyhow!("Failed to get secret from Azure Key Vault: {}", e)
+            }
+        })?;
 
         Ok(secret.value.to_string())
     }
 
     async fn trigger_refresh(&self, service: Option<&str>) -> Result<()> {
+        if let Some(subscription_id) = self.subscription_id.as_deref() {
+            if !self.app_services.is_empty() {
+                for app in &self.app_services {
+                    println!("🔄 Restarting App Service {}", app);
+                    self.restart_app_service(app, subscription_id).await?;
+                    println!("   ✓ {} running", app);
+                }
+                return Ok(());
+            }
+        }
+
         if let Some(svc) = service {
             println!(
                 "Note: Automatic refresh not implemented for Azure service: {}",
                 svc
             );
-            println!("Manually restart your service (e.g., App Service restart, Container Apps revision)");
+            println!("Configure app_services, or restart it manually");
+        }
+
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            versions: true,
+            describe: true,
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let client = self.client()?;
+        let mut pages = client.list_secrets().into_stream();
+        let mut secrets = Vec::new();
+
+        while let Some(page) = pages.next().await {
+            let page =
+                page.map_err(|e| anyhow::anyhow!("Failed to list Azure Key Vault secrets: {}", e))?;
+
+            for secret in page.value {
+                secrets.push(crate::connectors::SecretMetadata {
+                    name: last_segment(&secret.id),
+                    updated_at: DateTime::<Utc>::from_timestamp(
+                        secret.attributes.updated.unix_timestamp(),
+                        secret.attributes.updated.nanosecond(),
+                    ),
+                    version: None,
+                });
+            }
         }
 
+        Ok(secrets)
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        // Vaults with soft-delete keep the secret recoverable.
+        self.client()?
+            .delete(name)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to delete secret in Azure Key Vault: {}", e))?;
+
         Ok(())
     }
+
+    async fn list_versions(&self, name: &str) -> Result<Vec<crate::connectors::RemoteVersion>> {
+        let client = self.client()?;
+        let mut pages = client.get_versions(name).into_stream();
+        let mut versions = Vec::new();
+
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| {
+                if is_not_found(&e) {
+                    crate::connectors::not_found(format!(
+                        "Secret '{}' not found in Azure Key Vault",
+                        name
+                    ))
+                } else {
+                    anyhow::anyhow!("Failed to list Azure Key Vault secret versions: {}", e)
+                }
+            })?;
+
+            for version in page.value {
+                versions.push(crate::connectors::RemoteVersion {
+                    id: last_segment(&version.id),
+                    created_at: DateTime::<Utc>::from_timestamp(
+                        version.attributes.created.unix_timestamp(),
+                        version.attributes.created.nanosecond(),
+                    ),
+                    enabled: version.attributes.enabled,
+                });
+            }
+        }
+
+        versions.sort_by_key(|v| v.created_at);
+        Ok(versions)
+    }
+
+    async fn describe(&self, name: &str) -> Result<crate::connectors::SecretMetadata> {
+        let latest = self
+            .list_versions(name)
+            .await?
+            .into_iter()
+            .rfind(|v| v.enabled)
+            .ok_or_else(|| {
+                crate::connectors::not_found(format!(
+                    "Secret '{}' not found in Azure Key Vault",
+                    name
+                ))
+            })?;
+
+        Ok(crate::connectors::SecretMetadata {
+            name: name.to_string(),
+            updated_at: latest.created_at,
+            version: Some(latest.id),
+        })
+    }
+
+    async fn retire_versions(
+        &self,
+        name: &str,
+        superseded_before: DateTime<Utc>,
+    ) -> Result<Vec<String>> {
+        if self.retire.is_none() {
+            return Ok(Vec::new());
+        }
+
+        let versions = self.list_versions(name).await?;
+        let client = self.client()?;
+        let mut retired = Vec::new();
+
+        for version in crate::connectors::superseded_before(&versions, superseded_before) {
+            if !version.enabled {
+                continue;
+            }
+
+            client
+                .update(name)
+                .version(version.id.clone())
+                .enabled(false)
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!("Failed to disable Azure Key Vault secret version: {}", e)
+                })?;
+            retired.push(version.id.clone());
+        }
+
+        Ok(retired)
+    }
+}
+
+fn is_not_found(error: &azure_core::Error) -> bool {
+    matches!(
+        error.kind(),
+        azure_core::error::ErrorKind::HttpResponse {
+            status: azure_core::StatusCode::NotFound,
+            ..
+        }
+    )
+}
+
+fn last_segment(id: &str) -> String {
+    id.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(id)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::Connector;
+    use azure_core::auth::AccessToken;
+    use mockito::Matcher;
+
+    /// Hands out a fixed token instead of calling Entra ID.
+    #[derive(Debug)]
+    struct StaticToken;
+
+    #[async_trait]
+    impl TokenCredential for StaticToken {
+        async fn get_token(&self, _scopes: &[&str]) -> azure_core::Result<AccessToken> {
+            Ok(AccessToken::new(
+                "test-token",
+                azure_core::date::parse_rfc3339("2099-01-01T00:00:00Z")?,
+            ))
+        }
+
+        async fn clear_cache(&self) -> azure_core::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn connector(server: &mockito::Server) -> AzureConnector {
+        AzureConnector {
+            credential: Arc::new(StaticToken),
+            vault_url: server.url(),
+            subscription_id: None,
+            app_services: Vec::new(),
+            refresh_timeout: DEFAULT_REFRESH_TIMEOUT,
+            poll_interval: Duration::ZERO,
+            retire: None,
+            client: reqwest::Client::new(),
+            management_url: server.url(),
+        }
+    }
+
+    fn secret_not_found() -> &'static str {
+        r#"{"error":{"code":"SecretNotFound","message":"A secret with (name/id) API_KEY was not found in this key vault."}}"#
+    }
+
+    #[tokio::test]
+    async fn missing_secret_reads_as_none() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/secrets/API_KEY/")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(secret_not_found())
+            .create_async()
+            .await;
+
+        let value = crate::connectors::read_secret(&connector(&server), "API_KEY")
+            .await
+            .unwrap();
+
+        assert_eq!(value, None);
+    }
+
+    #[tokio::test]
+    async fn describe_missing_secret_is_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/secrets/API_KEY/versions")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(secret_not_found())
+            .create_async()
+            .await;
+
+        let err = connector(&server).describe("API_KEY").await.unwrap_err();
+
+        assert!(crate::connectors::is_not_found(&err));
+    }
+
+    #[tokio::test]
+    async fn forbidden_is_not_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/secrets/API_KEY/")
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error":{"code":"Forbidden","message":"denied"}}"#)
+            .create_async()
+            .await;
+
+        let err = connector(&server).get_secret("API_KEY").await.unwrap_err();
+
+        assert!(!crate::connectors::is_not_found(&err));
+    }
+
+    const SITE: &str = "/subscriptions/sub/resourceGroups/rg/providers/Microsoft.Web/sites/api";
+
+    fn site(state: &str) -> String {
+        serde_json::json!({ "name": "api", "properties": { "state": state } }).to_string()
+    }
+
+    fn app_connector(server: &mockito::Server) -> AzureConnector {
+        AzureConnector {
+            subscription_id: Some("sub".to_string()),
+            app_services: vec![AppService::parse("rg/api").unwrap()],
+            ..connector(server)
+        }
+    }
+
+    #[tokio::test]
+    async fn app_service_restart_waits_until_running() {
+        let mut server = mockito::Server::new_async().await;
+        let restart = server
+            .mock("POST", format!("{}/restart", SITE).as_str())
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("api-version".into(), WEB_API_VERSION.into()),
+                Matcher::UrlEncoded("softRestart".into(), "false".into()),
+            ]))
+            .match_header("authorization", "Bearer test-token")
+            .create_async()
+            .await;
+        let starting = server
+            .mock("GET", SITE)
+            .match_query(Matcher::Any)
+            .with_body(site("Stopped"))
+            .expect(2)
+            .create_async()
+            .await;
+        let running = server
+            .mock("GET", SITE)
+            .match_query(Matcher::Any)
+            .with_body(site("Running"))
+            .create_async()
+            .await;
+
+        app_connector(&server).trigger_refresh(None).await.unwrap();
+
+        restart.assert_async().await;
+        starting.assert_async().await;
+        running.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn app_service_not_running_after_the_timeout_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", format!("{}/restart", SITE).as_str())
+            .match_query(Matcher::Any)
+            .create_async()
+            .await;
+        server
+            .mock("GET", SITE)
+            .match_query(Matcher::Any)
+            .with_body(site("Stopped"))
+            .create_async()
+            .await;
+
+        let err = AzureConnector {
+            refresh_timeout: Duration::ZERO,
+            ..app_connector(&server)
+        }
+        .trigger_refresh(None)
+        .await
+        .unwrap_err();
+
+        assert!(err.to_string().contains("is not running"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn failed_restart_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", format!("{}/restart", SITE).as_str())
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .with_body(
+                r#"{"error":{"code":"AuthorizationFailed","message":"no restart permission"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let err = app_connector(&server)
+            .trigger_refresh(None)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("no restart permission"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn disables_only_superseded_enabled_versions() {
+        let mut server = mockito::Server::new_async().await;
+        let version = |id: &str, created: i64, enabled: bool| {
+            serde_json::json!({
+                "id": format!("{}/secrets/API_KEY/{}", server.url(), id),
+                "attributes": { "enabled": enabled, "created": created, "updated": created }
+            })
+        };
+        let versions = serde_json::json!({
+            "value": [
+                version("v3", 1_709_251_200, true),
+                version("v1", 1_704_067_200, false),
+                version("v2", 1_706_745_600, true),
+                version("v0", 1_701_388_800, true),
+            ],
+            "nextLink": null
+        });
+        server
+            .mock("GET", "/secrets/API_KEY/versions")
+            .match_query(Matcher::Any)
+            .with_header("content-type", "application/json")
+            .with_body(versions.to_string())
+            .create_async()
+            .await;
+        let disable_v0 = server
+            .mock("PATCH", "/secrets/API_KEY/v0")
+            .match_query(Matcher::Any)
+            .match_body(Matcher::PartialJson(
+                serde_json::json!({ "attributes": { "enabled": false } }),
+            ))
+            .with_body("{}")
+            .create_async()
+            .await;
+        let others = server
+            .mock(
+                "PATCH",
+                Matcher::Regex(r"^/secrets/API_KEY/v[123]$".to_string()),
+            )
+            .match_query(Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        // v2 was superseded by v3 only after the cutoff; v1 is already disabled.
+        let cutoff = "2024-02-15T00:00:00Z".parse().unwrap();
+        let retired = AzureConnector {
+            retire: Some(RetireMode::Disable),
+            ..connector(&server)
+        }
+        .retire_versions("API_KEY", cutoff)
+        .await
+        .unwrap();
+
+        assert_eq!(retired, ["v0"]);
+        disable_v0.assert_async().await;
+        others.assert_async().await;
+    }
 }
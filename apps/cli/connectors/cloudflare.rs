@@ -17,16 +17,16 @@ struct UpdateSecretRequest {
 }
 
 impl CloudflareConnector {
-    pub fn new(config: &crate::config::Config) -> Result<Self> {
-        let api_token = config
-            .connector_auth
-            .cloudflare_api_token
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("CLOUDFLARE_API_TOKEN not configured"))?
-            .clone();
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let api_token = settings
+            .credential("api_token", &config.connector_auth.cloudflare_api_token)
+            .ok_or_else(|| anyhow::anyhow!("CLOUDFLARE_API_TOKEN not configured"))?;
 
-        let account_id = std::env::var("CLOUDFLARE_ACCOUNT_ID").ok();
-        let worker_name = std::env::var("CLOUDFLARE_WORKER_NAME").ok();
+        let account_id = settings.get("account_id", "CLOUDFLARE_ACCOUNT_ID");
+        let worker_name = settings.get("worker_name", "CLOUDFLARE_WORKER_NAME");
 
         Ok(Self {
             api_token,
@@ -35,11 +35,8 @@ impl CloudflareConnector {
             client: reqwest::Client::new(),
         })
     }
-}
 
-#[async_trait]
-impl crate::connectors::Connector for CloudflareConnector {
-    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+    fn secrets_url(&self) -> Result<String> {
         let account_id = self
             .account_id
             .as_ref()
@@ -50,10 +47,17 @@ impl crate::connectors::Connector for CloudflareConnector {
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("CLOUDFLARE_WORKER_NAME not set"))?;
 
-        let url = format!(
+        Ok(format!(
             "https://api.cloudflare.com/client/v4/accounts/{}/workers/scripts/{}/secrets",
             account_id, worker_name
-        );
+        ))
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for CloudflareConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        let url = self.secrets_url()?;
 
         let req = UpdateSecretRequest {
             name: name.to_string(),
@@ -88,4 +92,71 @@ impl crate::connectors::Connector for CloudflareConnector {
         println!("ℹ️  Cloudflare Workers automatically use updated secrets on next invocation");
         Ok(())
     }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let url = self.secrets_url()?;
+
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(&self.api_token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Cloudflare API error ({}): {}", status, text);
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        let secrets = json["result"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Invalid response format from Cloudflare API"))?;
+
+        Ok(secrets
+            .iter()
+            .filter_map(|secret| {
+                Some(crate::connectors::SecretMetadata {
+                    name: secret["name"].as_str()?.to_string(),
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let url = format!("{}/{}", self.secrets_url()?, name);
+
+        let response = self
+            .client
+            .delete(&url)
+            .bearer_auth(&self.api_token)
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(crate::connectors::not_found(format!(
+                "Secret '{}' not found in Cloudflare worker",
+                name
+            )));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Cloudflare API error ({}): {}", status, text);
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,288 @@
+use anyhow::Result;
+
+/// Which database user a secret belongs to and how the value is laid out,
+/// shared by the database connectors.
+///
+/// With `user` set, every rotation changes that user's password. With
+/// `users = "app_blue,app_green"`, rotations alternate between the users: the
+/// new password goes to the user that is not in use, so the password
+/// consumers are running with stays valid until they have switched over.
+///
+/// `value_format` is what gets written to the secret, e.g.
+/// `postgres://{user}:{password}@db:5432/app` (default `{password}`). It
+/// must contain `{user}` when alternating, so consumers know which user the
+/// password belongs to.
+pub struct DatabaseUsers {
+    users: Vec<String>,
+    format: ValueFormat,
+}
+
+impl DatabaseUsers {
+    pub fn new(settings: &crate::connectors::ConnectorSettings) -> Result<Self> {
+        let users: Vec<String> = match (settings.0.get("users"), settings.0.get("user")) {
+            (Some(users), _) => users
+                .split(',')
+                .map(str::trim)
+                .filter(|u| !u.is_empty())
+                .map(str::to_string)
+                .collect(),
+            (None, Some(user)) => vec![user.clone()],
+            (None, None) => anyhow::bail!("Database user not configured (set user or users)"),
+        };
+        if users.is_empty() {
+            anyhow::bail!("Database user not configured (set user or users)");
+        }
+
+        let format = ValueFormat::new(
+            settings
+                .0
+                .get("value_format")
+                .map(String::as_str)
+                .unwrap_or("{password}"),
+        )?;
+        if users.len() > 1 && !format.has_user() {
+            anyhow::bail!("value_format must contain {{user}} when alternating between users");
+        }
+
+        Ok(Self { users, format })
+    }
+
+    /// Turns a generated password into the secret value for the user that is
+    /// not in use. Values that already name a configured user (rollbacks,
+    /// `--value`) are kept as they are.
+    pub fn prepare(&self, value: &str, current: Option<&str>) -> Result<String> {
+        if self.format.parse(value).is_some() {
+            // Fails for values naming a user that is not configured.
+            self.parse(value)?;
+            return Ok(value.to_string());
+        }
+
+        let active = current
+            .and_then(|c| self.parse(c).ok())
+            .map(|(user, _)| user);
+        let user = match active.and_then(|a| self.users.iter().position(|u| *u == a)) {
+            Some(i) => &self.users[(i + 1) % self.users.len()],
+            None => &self.users[0],
+        };
+
+        Ok(self.format.render(user, value))
+    }
+
+    /// Splits a secret value into the user and password to set.
+    pub fn parse(&self, value: &str) -> Result<(String, String)> {
+        let (user, password) = self
+            .format
+            .parse(value)
+            .ok_or_else(|| anyhow::anyhow!("Secret value does not match value_format"))?;
+        let user = user.unwrap_or_else(|| self.users[0].clone());
+
+        if !self.users.contains(&user) {
+            anyhow::bail!(
+                "Secret value is for user '{}', which is not one of the configured users ({})",
+                user,
+                self.users.join(", ")
+            );
+        }
+
+        Ok((user, password))
+    }
+}
+
+/// A `value_format` template, split into literals and placeholders.
+struct ValueFormat {
+    prefix: String,
+    parts: Vec<(Placeholder, String)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Placeholder {
+    User,
+    Password,
+}
+
+impl ValueFormat {
+    fn new(template: &str) -> Result<Self> {
+        let mut rest = template;
+        let mut prefix = None;
+        let mut parts: Vec<(Placeholder, String)> = Vec::new();
+        let mut literal = String::new();
+
+        while !rest.is_empty() {
+            let placeholder = if let Some(r) = rest.strip_prefix("{user}") {
+                rest = r;
+                Some(Placeholder::User)
+            } else if let Some(r) = rest.strip_prefix("{password}") {
+                rest = r;
+                Some(Placeholder::Password)
+            } else {
+                None
+            };
+
+            match placeholder {
+                Some(placeholder) => {
+                    if parts.iter().any(|(p, _)| *p == placeholder) {
+                        anyhow::bail!("value_format may contain each placeholder only once");
+                    }
+                    match parts.last_mut() {
+                        Some((_, last)) => {
+                            if literal.is_empty() {
+                                anyhow::bail!(
+                                    "value_format placeholders must be separated by text"
+                                );
+                            }
+                            *last = std::mem::take(&mut literal);
+                        }
+                        None => prefix = Some(std::mem::take(&mut literal)),
+                    }
+                    parts.push((placeholder, String::new()));
+                }
+                None => {
+                    let c = rest.chars().next().unwrap_or_default();
+                    literal.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+
+        if !parts.iter().any(|(p, _)| *p == Placeholder::Password) {
+            anyhow::bail!("value_format must contain {{password}}");
+        }
+        if let Some((_, last)) = parts.last_mut() {
+            *last = literal;
+        }
+
+        Ok(Self {
+            prefix: prefix.unwrap_or_default(),
+            parts,
+        })
+    }
+
+    fn has_user(&self) -> bool {
+        self.parts.iter().any(|(p, _)| *p == Placeholder::User)
+    }
+
+    fn render(&self, user: &str, password: &str) -> String {
+        let mut value = self.prefix.clone();
+        for (placeholder, literal) in &self.parts {
+            value.push_str(match placeholder {
+                Placeholder::User => user,
+                Placeholder::Password => password,
+            });
+            value.push_str(literal);
+        }
+        value
+    }
+
+    /// User names cannot contain the text that follows them, so the user ends
+    /// at its first occurrence; passwords can, so they end at the last.
+    fn parse(&self, value: &str) -> Option<(Option<String>, String)> {
+        let mut rest = value.strip_prefix(self.prefix.as_str())?;
+        let mut user = None;
+        let mut password = None;
+
+        for (i, (placeholder, literal)) in self.parts.iter().enumerate() {
+            let end = if i == self.parts.len() - 1 {
+                rest.strip_suffix(literal.as_str())?.len()
+            } else if *placeholder == Placeholder::User {
+                rest.find(literal.as_str())?
+            } else {
+                rest.rfind(literal.as_str())?
+            };
+
+            let field = rest[..end].to_string();
+            match placeholder {
+                Placeholder::User => user = Some(field),
+                Placeholder::Password => password = Some(field),
+            }
+            rest = &rest[end + literal.len()..];
+        }
+
+        Some((user, password?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::ConnectorSettings;
+
+    fn users(pairs: &[(&str, &str)]) -> Result<DatabaseUsers> {
+        DatabaseUsers::new(&ConnectorSettings(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        ))
+    }
+
+    const URL: &str = "postgres://{user}:{password}@db:5432/app";
+
+    #[test]
+    fn alternates_between_users() {
+        let users = users(&[("users", "app_blue, app_green"), ("value_format", URL)]).unwrap();
+
+        let first = users.prepare("pw1", None).unwrap();
+        assert_eq!(first, "postgres://app_blue:pw1@db:5432/app");
+
+        let second = users.prepare("pw2", Some(&first)).unwrap();
+        assert_eq!(second, "postgres://app_green:pw2@db:5432/app");
+
+        let third = users.prepare("pw3", Some(&second)).unwrap();
+        assert_eq!(third, "postgres://app_blue:pw3@db:5432/app");
+
+        // A current value that does not parse starts over at the first user.
+        let reset = users.prepare("pw4", Some("garbage")).unwrap();
+        assert_eq!(reset, "postgres://app_blue:pw4@db:5432/app");
+    }
+
+    #[test]
+    fn keeps_values_that_name_a_user() {
+        let users = users(&[("users", "app_blue,app_green"), ("value_format", URL)]).unwrap();
+        let rollback = "postgres://app_green:old@db:5432/app";
+
+        assert_eq!(
+            users
+                .prepare(rollback, Some("postgres://app_green:new@db:5432/app"))
+                .unwrap(),
+            rollback
+        );
+        assert!(users
+            .prepare("postgres://admin:pw@db:5432/app", None)
+            .is_err());
+    }
+
+    #[test]
+    fn single_user_defaults_to_password_only() {
+        let users = users(&[("user", "app")]).unwrap();
+
+        assert_eq!(users.prepare("pw", Some("old")).unwrap(), "pw");
+        assert_eq!(
+            users.parse("pw").unwrap(),
+            ("app".to_string(), "pw".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_passwords_containing_the_separators() {
+        let users = users(&[("users", "app_blue,app_green"), ("value_format", URL)]).unwrap();
+
+        assert_eq!(
+            users
+                .parse("postgres://app_green:p:w@db@db:5432/app")
+                .unwrap(),
+            ("app_green".to_string(), "p:w@db".to_string())
+        );
+        assert!(users.parse("mysql://app_green:pw@db:5432/app").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        assert!(users(&[]).is_err());
+        assert!(users(&[("users", " , ")]).is_err());
+        assert!(users(&[("users", "a,b")]).is_err());
+        assert!(users(&[("user", "app"), ("value_format", "{user}")]).is_err());
+        assert!(users(&[("user", "app"), ("value_format", "{user}{password}")]).is_err());
+        assert!(users(&[("user", "app"), ("value_format", "{password}:{password}")]).is_err());
+        assert!(users(&[("user", "app"), ("value_format", "secret={password};")]).is_ok());
+    }
+}
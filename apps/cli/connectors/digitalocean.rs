@@ -0,0 +1,455 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::time::Duration;
+
+const API_URL: &str = "https://api.digitalocean.com/v2";
+const DEFAULT_REFRESH_TIMEOUT: Duration = Duration::from_secs(900);
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Component lists of an app spec that carry their own `envs`.
+const COMPONENT_KINDS: &[&str] = &["services", "workers", "jobs", "static_sites", "functions"];
+
+/// Environment variables of a DigitalOcean App Platform app, either
+/// app-wide or of one component. Variables are edited in the app spec;
+/// App Platform deploys every spec change, so a refresh waits for that
+/// deployment to go live, starting one if none is running.
+pub struct DigitalOceanConnector {
+    token: String,
+    app_id: Option<String>,
+    component: Option<String>,
+    /// Store values as `SECRET` (encrypted, not readable back) or `GENERAL`.
+    env_type: String,
+    refresh_timeout: Duration,
+    poll_interval: Duration,
+    client: reqwest::Client,
+    api_url: String,
+}
+
+impl DigitalOceanConnector {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let token = settings
+            .credential("token", &config.connector_auth.digitalocean_access_token)
+            .ok_or_else(|| anyhow::anyhow!("DIGITALOCEAN_ACCESS_TOKEN not configured"))?;
+
+        let env_type = match settings.0.get("type") {
+            Some(t) if t.eq_ignore_ascii_case("secret") => "SECRET".to_string(),
+            Some(t) if t.eq_ignore_ascii_case("general") => "GENERAL".to_string(),
+            Some(t) => anyhow::bail!(
+                "Invalid DigitalOcean env type '{}': expected secret or general",
+                t
+            ),
+            None => "SECRET".to_string(),
+        };
+
+        let refresh_timeout = match settings.0.get("refresh_timeout") {
+            Some(timeout) => humantime::parse_duration(timeout)
+                .with_context(|| format!("Invalid refresh_timeout '{}'", timeout))?,
+            None => DEFAULT_REFRESH_TIMEOUT,
+        };
+
+        Ok(Self {
+            token,
+            app_id: settings.get("app_id", "DIGITALOCEAN_APP_ID"),
+            component: settings.0.get("component").cloned(),
+            env_type,
+            refresh_timeout,
+            poll_interval: POLL_INTERVAL,
+            client: reqwest::Client::new(),
+            api_url: API_URL.to_string(),
+        })
+    }
+
+    fn app_id(&self) -> Result<&str> {
+        self.app_id
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("DIGITALOCEAN_APP_ID not set"))
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let url = format!("{}/apps/{}{}", self.api_url, self.app_id()?, path);
+        let mut request = self.client.request(method, &url).bearer_auth(&self.token);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("DigitalOcean API error ({}): {}", status, text);
+        }
+
+        Ok(response.json().await?)
+    }
+
+    async fn app(&self) -> Result<serde_json::Value> {
+        let mut json = self.send(reqwest::Method::GET, "", None).await?;
+        Ok(json["app"].take())
+    }
+
+    /// The `envs` list the connector manages within `spec`.
+    fn envs<'a>(&self, spec: &'a mut serde_json::Value) -> Result<&'a mut Vec<serde_json::Value>> {
+        let owner = match &self.component {
+            None => spec,
+            Some(component) => spec
+                .as_object_mut()
+                .into_iter()
+                .flat_map(|spec| spec.iter_mut())
+                .filter(|(kind, _)| COMPONENT_KINDS.contains(&kind.as_str()))
+                .filter_map(|(_, components)| components.as_array_mut())
+                .flatten()
+                .find(|c| c["name"].as_str() == Some(component))
+                .ok_or_else(|| anyhow::anyhow!("App has no component named '{}'", component))?,
+        };
+
+        if !owner["envs"].is_array() {
+            owner["envs"] = serde_json::Value::Array(Vec::new());
+        }
+        Ok(owner["envs"].as_array_mut().expect("envs is an array"))
+    }
+
+    async fn spec(&self) -> Result<serde_json::Value> {
+        let mut app = self.app().await?;
+        Ok(app["spec"].take())
+    }
+
+    async fn update_spec(&self, spec: &serde_json::Value) -> Result<()> {
+        self.send(
+            reqwest::Method::PUT,
+            "",
+            Some(&serde_json::json!({ "spec": spec })),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// The deployment App Platform is building or about to build, if any.
+    async fn running_deployment(&self) -> Result<Option<String>> {
+        let app = self.app().await?;
+        Ok(["in_progress_deployment", "pending_deployment"]
+            .iter()
+            .find_map(|key| app[*key]["id"].as_str().map(str::to_string)))
+    }
+
+    fn not_found(&self, name: &str) -> anyhow::Error {
+        crate::connectors::not_found(format!("Secret '{}' not found in DigitalOcean app", name))
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for DigitalOceanConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        let mut spec = self.spec().await?;
+        let envs = self.envs(&mut spec)?;
+
+        match envs.iter_mut().find(|e| e["key"].as_str() == Some(name)) {
+            Some(env) => {
+                env["value"] = serde_json::Value::String(value.to_string());
+                env["type"] = serde_json::Value::String(self.env_type.clone());
+            }
+            None => envs.push(serde_json::json!({
+                "key": name,
+                "value": value,
+                "type": self.env_type,
+                "scope": "RUN_AND_BUILD_TIME",
+            })),
+        }
+
+        self.update_spec(&spec).await
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<String> {
+        let mut spec = self.spec().await?;
+        let env = self
+            .envs(&mut spec)?
+            .iter()
+            .find(|e| e["key"].as_str() == Some(name))
+            .ok_or_else(|| self.not_found(name))?;
+
+        if env["type"].as_str() == Some("SECRET") {
+            anyhow::bail!("DigitalOcean does not expose values of encrypted env vars");
+        }
+        Ok(env["value"].as_str().unwrap_or_default().to_string())
+    }
+
+    /// Waits for the deployment started by the spec change to become
+    /// active, creating a deployment first if none is pending.
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        let mut id = match self.running_deployment().await? {
+            Some(id) => id,
+            None => {
+                let created = self
+                    .send(
+                        reqwest::Method::POST,
+                        "/deployments",
+                        Some(&serde_json::json!({ "force_build": false })),
+                    )
+                    .await?;
+                created["deployment"]["id"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("DigitalOcean returned no deployment"))?
+                    .to_string()
+            }
+        };
+        let deadline = tokio::time::Instant::now() + self.refresh_timeout;
+
+        loop {
+            let deployment = self
+                .send(reqwest::Method::GET, &format!("/deployments/{}", id), None)
+                .await?;
+
+            match deployment["deployment"]["phase"]
+                .as_str()
+                .unwrap_or_default()
+            {
+                "ACTIVE" => break,
+                // A newer deployment replaced it; it carries the change too.
+                "SUPERSEDED" => match self.running_deployment().await? {
+                    Some(newer) => id = newer,
+                    None => break,
+                },
+                phase @ ("ERROR" | "CANCELED") => {
+                    anyhow::bail!("DigitalOcean deployment {} {}", id, phase.to_lowercase())
+                }
+                _ => {}
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                anyhow::bail!(
+                    "DigitalOcean app {} did not finish deploying within {}",
+                    self.app_id()?,
+                    humantime::format_duration(self.refresh_timeout)
+                );
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+
+        println!("🔄 DigitalOcean deployment {} is live", id);
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: self.env_type == "GENERAL",
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let mut spec = self.spec().await?;
+        Ok(self
+            .envs(&mut spec)?
+            .iter()
+            .filter_map(|e| {
+                Some(crate::connectors::SecretMetadata {
+                    name: e["key"].as_str()?.to_string(),
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let mut spec = self.spec().await?;
+        let envs = self.envs(&mut spec)?;
+
+        let before = envs.len();
+        envs.retain(|e| e["key"].as_str() != Some(name));
+        if envs.len() == before {
+            return Err(self.not_found(name));
+        }
+
+        self.update_spec(&spec).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::Connector;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn connector(server: &mockito::Server, component: Option<&str>) -> DigitalOceanConnector {
+        DigitalOceanConnector {
+            token: "token".to_string(),
+            app_id: Some("app-1".to_string()),
+            component: component.map(str::to_string),
+            env_type: "SECRET".to_string(),
+            refresh_timeout: DEFAULT_REFRESH_TIMEOUT,
+            poll_interval: Duration::ZERO,
+            client: reqwest::Client::new(),
+            api_url: format!("{}/v2", server.url()),
+        }
+    }
+
+    fn app(deployments: serde_json::Value) -> String {
+        let mut app = json!({
+            "id": "app-1",
+            "spec": {
+                "name": "shop",
+                "envs": [{ "key": "LOG_LEVEL", "value": "info", "type": "GENERAL" }],
+                "services": [{
+                    "name": "api",
+                    "envs": [{ "key": "API_KEY", "value": "EV[1:old]", "type": "SECRET" }]
+                }]
+            }
+        });
+        if let Some(deployments) = deployments.as_object() {
+            app.as_object_mut().unwrap().extend(deployments.clone());
+        }
+        json!({ "app": app }).to_string()
+    }
+
+    fn deployment(id: &str, phase: &str) -> String {
+        json!({ "deployment": { "id": id, "phase": phase } }).to_string()
+    }
+
+    #[tokio::test]
+    async fn updates_an_existing_component_var() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v2/apps/app-1")
+            .with_body(app(json!({})))
+            .create_async()
+            .await;
+        let put = server
+            .mock("PUT", "/v2/apps/app-1")
+            .match_header("authorization", "Bearer token")
+            .match_body(Matcher::PartialJson(json!({ "spec": {
+                "envs": [{ "key": "LOG_LEVEL", "value": "info" }],
+                "services": [{
+                    "name": "api",
+                    "envs": [{ "key": "API_KEY", "value": "new", "type": "SECRET" }]
+                }]
+            } })))
+            .with_body(app(json!({})))
+            .create_async()
+            .await;
+
+        connector(&server, Some("api"))
+            .update_secret("API_KEY", "new")
+            .await
+            .unwrap();
+
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn creates_a_missing_app_wide_var() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v2/apps/app-1")
+            .with_body(app(json!({})))
+            .create_async()
+            .await;
+        let put = server
+            .mock("PUT", "/v2/apps/app-1")
+            .match_body(Matcher::PartialJson(json!({ "spec": { "envs": [
+                { "key": "LOG_LEVEL", "value": "info", "type": "GENERAL" },
+                { "key": "API_KEY", "value": "new", "type": "SECRET", "scope": "RUN_AND_BUILD_TIME" }
+            ] } })))
+            .with_body(app(json!({})))
+            .create_async()
+            .await;
+
+        connector(&server, None)
+            .update_secret("API_KEY", "new")
+            .await
+            .unwrap();
+
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn unknown_component_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v2/apps/app-1")
+            .with_body(app(json!({})))
+            .create_async()
+            .await;
+
+        let err = connector(&server, Some("worker"))
+            .update_secret("API_KEY", "new")
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "App has no component named 'worker'");
+    }
+
+    #[tokio::test]
+    async fn refresh_creates_a_deployment_and_waits_until_active() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v2/apps/app-1")
+            .with_body(app(json!({})))
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/v2/apps/app-1/deployments")
+            .match_body(Matcher::Json(json!({ "force_build": false })))
+            .with_body(deployment("dep-2", "PENDING_BUILD"))
+            .create_async()
+            .await;
+        let building = server
+            .mock("GET", "/v2/apps/app-1/deployments/dep-2")
+            .with_body(deployment("dep-2", "BUILDING"))
+            .expect(2)
+            .create_async()
+            .await;
+        let active = server
+            .mock("GET", "/v2/apps/app-1/deployments/dep-2")
+            .with_body(deployment("dep-2", "ACTIVE"))
+            .create_async()
+            .await;
+
+        connector(&server, None)
+            .trigger_refresh(None)
+            .await
+            .unwrap();
+
+        create.assert_async().await;
+        building.assert_async().await;
+        active.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn refresh_follows_the_running_deployment_and_reports_errors() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v2/apps/app-1")
+            .with_body(app(json!({ "in_progress_deployment": { "id": "dep-3" } })))
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/v2/apps/app-1/deployments")
+            .expect(0)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v2/apps/app-1/deployments/dep-3")
+            .with_body(deployment("dep-3", "ERROR"))
+            .create_async()
+            .await;
+
+        let err = connector(&server, None)
+            .trigger_refresh(None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "DigitalOcean deployment dep-3 error");
+        create.assert_async().await;
+    }
+}
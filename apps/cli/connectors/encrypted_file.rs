@@ -0,0 +1,136 @@
+use crate::secrets_file::{Identity, SecretsFile};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+/// Secrets kept in an encrypted `.env` or YAML file in the repository (see
+/// `birch secrets`). Each secret is a top-level entry; writing one only
+/// changes its line, and publishing the change is up to the commit that
+/// follows.
+pub struct EncryptedFileConnector {
+    path: PathBuf,
+    /// Public keys a new file is encrypted to, besides the local identity.
+    recipients: Vec<String>,
+}
+
+impl EncryptedFileConnector {
+    pub fn new(
+        _config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let path =
+            settings.0.get("path").map(PathBuf::from).ok_or_else(|| {
+                anyhow::anyhow!("Encrypted file connector requires a path setting")
+            })?;
+
+        Ok(Self {
+            path,
+            recipients: settings
+                .0
+                .get("recipients")
+                .map(|r| {
+                    r.split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    fn open(&self) -> Result<Option<SecretsFile>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        SecretsFile::open(&self.path, &Identity::load()?).map(Some)
+    }
+
+    fn is_yaml(&self) -> bool {
+        self.path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("yaml") || e.eq_ignore_ascii_case("yml"))
+    }
+
+    /// Secret names address nested YAML values with dots, as listed.
+    fn key_path(&self, name: &str) -> Result<Vec<String>> {
+        if self.is_yaml() {
+            crate::local_files::parse_key_path(name)
+        } else {
+            Ok(vec![name.to_string()])
+        }
+    }
+
+    fn not_found(&self, name: &str) -> anyhow::Error {
+        crate::connectors::not_found(format!(
+            "Secret '{}' not found in {}",
+            name,
+            self.path.display()
+        ))
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for EncryptedFileConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        let mut file = match self.open()? {
+            Some(file) => file,
+            None => {
+                let mut recipients = vec![Identity::load()?.public_key()];
+                recipients.extend(self.recipients.iter().cloned());
+                println!("📄 Creating encrypted secrets file {}", self.path.display());
+                SecretsFile::create(&self.path, &recipients)?
+            }
+        };
+
+        file.set(&self.key_path(name)?, value)?;
+        file.save()
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<String> {
+        let key_path = self.key_path(name)?;
+        self.open()?
+            .and_then(|file| file.get(&key_path).map(str::to_string))
+            .ok_or_else(|| self.not_found(name))
+    }
+
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        println!(
+            "Note: Commit {} so deployments pick up the new value",
+            self.path.display()
+        );
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: !self.is_yaml(),
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let Some(file) = self.open()? else {
+            return Ok(Vec::new());
+        };
+
+        Ok(file
+            .keys()
+            .map(|key_path| crate::connectors::SecretMetadata {
+                name: key_path.join("."),
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let mut file = self.open()?.ok_or_else(|| self.not_found(name))?;
+        if !file.remove(name)? {
+            return Err(self.not_found(name));
+        }
+        file.save()
+    }
+}
@@ -0,0 +1,450 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const DEFAULT_TEMPLATE: &str = "{{value}}";
+const DEFAULT_MODE: u32 = 0o600;
+const DEFAULT_REFRESH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Writes secrets into files on the local host, for services running on VMs
+/// and bare metal. Each secret gets its own file, rendered from a template
+/// and replaced atomically (written to a temporary file next to it, then
+/// renamed). After a rotation, a refresh can run a command such as
+/// `systemctl reload app` and/or signal the process in a pidfile.
+pub struct FileConnector {
+    /// Path of the file, with `{{name}}` expanding to the secret name.
+    path: String,
+    template: String,
+    mode: u32,
+    owner: Option<String>,
+    refresh_command: Option<String>,
+    pidfile: Option<PathBuf>,
+    signal: String,
+    refresh_timeout: Duration,
+}
+
+impl FileConnector {
+    pub fn new(
+        _config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let path = settings
+            .0
+            .get("path")
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("File connector requires a path setting"))?;
+
+        let template = match (settings.0.get("template"), settings.0.get("template_file")) {
+            (Some(_), Some(_)) => anyhow::bail!("Set either template or template_file, not both"),
+            (Some(template), None) => template.clone(),
+            (None, Some(file)) => std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read template file {}", file))?,
+            (None, None) => DEFAULT_TEMPLATE.to_string(),
+        };
+        if template.matches("{{value}}").count() != 1 {
+            anyhow::bail!("File template must contain {{{{value}}}} exactly once");
+        }
+
+        let mode = match settings.0.get("mode") {
+            Some(mode) => u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+                .ok()
+                .filter(|m| *m <= 0o7777)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Invalid file mode '{}': expected octal, e.g. 0640", mode)
+                })?,
+            None => DEFAULT_MODE,
+        };
+
+        let refresh_timeout = match settings.0.get("refresh_timeout") {
+            Some(timeout) => humantime::parse_duration(timeout)
+                .with_context(|| format!("Invalid refresh_timeout '{}'", timeout))?,
+            None => DEFAULT_REFRESH_TIMEOUT,
+        };
+
+        Ok(Self {
+            path,
+            template,
+            mode,
+            owner: settings.0.get("owner").cloned(),
+            refresh_command: settings.0.get("refresh_command").cloned(),
+            pidfile: settings.0.get("pidfile").map(PathBuf::from),
+            signal: settings
+                .0
+                .get("signal")
+                .map(|s| s.trim_start_matches("SIG").to_uppercase())
+                .unwrap_or_else(|| "HUP".to_string()),
+            refresh_timeout,
+        })
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        PathBuf::from(self.path.replace("{{name}}", name))
+    }
+
+    /// The template around `{{value}}`, with `{{name}}` expanded.
+    fn frame(&self, name: &str) -> (String, String) {
+        let template = self.template.replace("{{name}}", name);
+        let (prefix, suffix) = template
+            .split_once("{{value}}")
+            .expect("template contains {{value}}");
+        (prefix.to_string(), suffix.to_string())
+    }
+
+    /// Sets the owner of `path` with `chown`, which resolves user and group
+    /// names the same way the host does.
+    async fn chown(&self, path: &std::path::Path) -> Result<()> {
+        let Some(owner) = &self.owner else {
+            return Ok(());
+        };
+
+        let output = tokio::process::Command::new("chown")
+            .arg(owner)
+            .arg(path)
+            .output()
+            .await
+            .context("Failed to run chown")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "chown {} failed: {}",
+                owner,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    async fn run_command(&self, command: &str) -> Result<()> {
+        let child = shell(command)
+            .kill_on_drop(true)
+            .stdout(std::process::Stdio::inherit())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run refresh command '{}'", command))?;
+
+        let output = tokio::time::timeout(self.refresh_timeout, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Refresh command '{}' did not finish within {}",
+                    command,
+                    humantime::format_duration(self.refresh_timeout)
+                )
+            })??;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "Refresh command '{}' failed ({}): {}",
+                command,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+
+    async fn signal_process(&self, pidfile: &std::path::Path) -> Result<()> {
+        let pid = std::fs::read_to_string(pidfile)
+            .with_context(|| format!("Failed to read pidfile {}", pidfile.display()))?;
+        let pid: u32 = pid
+            .trim()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid PID in {}", pidfile.display()))?;
+
+        let output = tokio::process::Command::new("kill")
+            .args(["-s", &self.signal, &pid.to_string()])
+            .output()
+            .await
+            .context("Failed to run kill")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Failed to send SIG{} to PID {}: {}",
+                self.signal,
+                pid,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// Creates `path` for writing, readable only as `mode` allows from the start.
+fn create(path: &std::path::Path, mode: u32) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        let file = options.mode(mode).open(path)?;
+        // The umask applies to the mode given at creation.
+        file.set_permissions(std::fs::Permissions::from_mode(mode))?;
+        Ok(file)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = mode;
+        options.open(path)
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for FileConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        let path = self.path(name);
+        let (prefix, suffix) = self.frame(name);
+        let dir = path
+            .parent()
+            .filter(|d| !d.as_os_str().is_empty())
+            .unwrap_or(std::path::Path::new("."));
+        if !dir.is_dir() {
+            anyhow::bail!("Directory {} does not exist", dir.display());
+        }
+
+        let file_name = path
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid file path {}", path.display()))?;
+        let temp_path = dir.join(format!(".{}.birch-tmp", file_name.to_string_lossy()));
+        // Left over from an interrupted write.
+        let _ = std::fs::remove_file(&temp_path);
+
+        let result = async {
+            let mut file = create(&temp_path, self.mode)
+                .with_context(|| format!("Failed to create {}", temp_path.display()))?;
+            std::io::Write::write_all(
+                &mut file,
+                format!("{}{}{}", prefix, value, suffix).as_bytes(),
+            )?;
+            file.sync_all()?;
+            drop(file);
+
+            self.chown(&temp_path).await?;
+            std::fs::rename(&temp_path, &path)
+                .with_context(|| format!("Failed to replace {}", path.display()))
+        }
+        .await;
+
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<String> {
+        let path = self.path(name);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(crate::connectors::not_found(format!(
+                    "Secret '{}' not found: {} does not exist",
+                    name,
+                    path.display()
+                )))
+            }
+            Err(e) => return Err(anyhow::anyhow!("Failed to read {}: {}", path.display(), e)),
+        };
+
+        let (prefix, suffix) = self.frame(name);
+        contents
+            .strip_prefix(prefix.as_str())
+            .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+            .map(str::to_string)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{} does not match the template; it was changed outside Birch",
+                    path.display()
+                )
+            })
+    }
+
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        if self.refresh_command.is_none() && self.pidfile.is_none() {
+            println!(
+                "Note: No refresh_command or pidfile configured; restart the service manually"
+            );
+            return Ok(());
+        }
+
+        if let Some(command) = &self.refresh_command {
+            println!("🔄 Running refresh command: {}", command);
+            self.run_command(command).await?;
+        }
+        if let Some(pidfile) = &self.pidfile {
+            self.signal_process(pidfile).await?;
+            println!(
+                "🔄 Sent SIG{} to the process in {}",
+                self.signal,
+                pidfile.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            delete: true,
+            ..Default::default()
+        }
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let path = self.path(name);
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(crate::connectors::not_found(format!(
+                    "Secret '{}' not found: {} does not exist",
+                    name,
+                    path.display()
+                )))
+            }
+            Err(e) => Err(anyhow::anyhow!(
+                "Failed to remove {}: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::{Connector, ConnectorSettings};
+
+    fn connector(dir: &std::path::Path, pairs: &[(&str, &str)]) -> Result<FileConnector> {
+        let mut settings: std::collections::BTreeMap<String, String> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        settings
+            .entry("path".to_string())
+            .or_insert_with(|| dir.join("{{name}}.env").display().to_string());
+        FileConnector::new(
+            &crate::config::Config::default(),
+            &ConnectorSettings(settings),
+        )
+    }
+
+    #[tokio::test]
+    async fn writes_and_reads_through_the_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let connector = connector(
+            dir.path(),
+            &[("template", "# managed by birch\n{{name}}={{value}}\n")],
+        )
+        .unwrap();
+
+        connector.update_secret("API_KEY", "sk_1").await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("API_KEY.env")).unwrap(),
+            "# managed by birch\nAPI_KEY=sk_1\n"
+        );
+        assert_eq!(connector.get_secret("API_KEY").await.unwrap(), "sk_1");
+
+        // Values that look like the template framing still round-trip.
+        connector
+            .update_secret("API_KEY", "a\nAPI_KEY={{value}}\n")
+            .await
+            .unwrap();
+        assert_eq!(
+            connector.get_secret("API_KEY").await.unwrap(),
+            "a\nAPI_KEY={{value}}\n"
+        );
+        assert!(!dir.path().join(".API_KEY.env.birch-tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn rejects_files_changed_outside_birch() {
+        let dir = tempfile::tempdir().unwrap();
+        let connector = connector(dir.path(), &[("template", "KEY={{value}}\n")]).unwrap();
+
+        std::fs::write(dir.path().join("API_KEY.env"), "OTHER=sk_1\n").unwrap();
+        assert!(connector.get_secret("API_KEY").await.is_err());
+
+        std::fs::write(dir.path().join("API_KEY.env"), "KEY=sk_1").unwrap();
+        assert!(connector.get_secret("API_KEY").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn missing_files_are_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let connector = connector(dir.path(), &[]).unwrap();
+
+        let err = connector.get_secret("API_KEY").await.unwrap_err();
+        assert!(crate::connectors::is_not_found(&err));
+
+        connector.update_secret("API_KEY", "sk_1").await.unwrap();
+        connector.delete_secret("API_KEY").await.unwrap();
+        let err = connector.delete_secret("API_KEY").await.unwrap_err();
+        assert!(crate::connectors::is_not_found(&err));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn applies_the_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let connector = connector(dir.path(), &[("mode", "0640")]).unwrap();
+        connector.update_secret("API_KEY", "sk_1").await.unwrap();
+
+        let metadata = std::fs::metadata(dir.path().join("API_KEY.env")).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o7777, 0o640);
+    }
+
+    #[test]
+    fn rejects_invalid_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path();
+
+        assert!(connector(path, &[("template", "no placeholder")]).is_err());
+        assert!(connector(path, &[("template", "{{value}}{{value}}")]).is_err());
+        assert!(connector(path, &[("template", "{{value}}"), ("template_file", "t")]).is_err());
+        assert!(connector(path, &[("mode", "0999")]).is_err());
+        assert!(connector(path, &[("mode", "17777")]).is_err());
+        assert!(connector(path, &[("refresh_timeout", "soon")]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn refresh_command_failures_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("refreshed");
+
+        let ok = connector(
+            dir.path(),
+            &[("refresh_command", &format!("touch {}", marker.display()))],
+        )
+        .unwrap();
+        ok.trigger_refresh(None).await.unwrap();
+        assert!(marker.exists());
+
+        let failing = connector(dir.path(), &[("refresh_command", "exit 3")]).unwrap();
+        assert!(failing.trigger_refresh(None).await.is_err());
+
+        let slow = connector(
+            dir.path(),
+            &[("refresh_command", "sleep 5"), ("refresh_timeout", "100ms")],
+        )
+        .unwrap();
+        assert!(slow.trigger_refresh(None).await.is_err());
+    }
+}
@@ -27,15 +27,15 @@ struct SecretInput {
 }
 
 impl FlyConnector {
-    pub fn new(config: &crate::config::Config) -> Result<Self> {
-        let api_token = config
-            .connector_auth
-            .fly_api_token
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("FLY_API_TOKEN not configured"))?
-            .clone();
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let api_token = settings
+            .credential("api_token", &config.connector_auth.fly_api_token)
+            .ok_or_else(|| anyhow::anyhow!("FLY_API_TOKEN not configured"))?;
 
-        let app_name = std::env::var("FLY_APP_NAME").ok();
+        let app_name = settings.get("app_name", "FLY_APP_NAME");
 
         Ok(Self {
             api_token,
@@ -43,6 +43,33 @@ impl FlyConnector {
             client: reqwest::Client::new(),
         })
     }
+
+    async fn graphql(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let response = self
+            .client
+            .post("https://api.fly.io/graphql")
+            .bearer_auth(&self.api_token)
+            .json(&serde_json::json!({ "query": query, "variables": variables }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Fly.io API error ({}): {}", status, text);
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        if let Some(message) = json["errors"][0]["message"].as_str() {
+            anyhow::bail!("Fly.io API error: {}", message);
+        }
+
+        Ok(json["data"].clone())
+    }
 }
 
 #[async_trait]
@@ -99,4 +126,76 @@ impl crate::connectors::Connector for FlyConnector {
         println!("ℹ️  Fly.io automatically restarts apps when secrets are updated");
         Ok(())
     }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let app_name = self
+            .app_name
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("FLY_APP_NAME not set"))?;
+
+        let data = self
+            .graphql(
+                r#"
+                query($appName: String!) {
+                    app(name: $appName) {
+                        secrets {
+                            name
+                            digest
+                            createdAt
+                        }
+                    }
+                }
+            "#,
+                serde_json::json!({ "appName": app_name }),
+            )
+            .await?;
+
+        let secrets = data["app"]["secrets"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Invalid response format from Fly.io API"))?;
+
+        // Fly only exposes a digest of each value, which doubles as its version.
+        Ok(secrets
+            .iter()
+            .filter_map(|secret| {
+                Some(crate::connectors::SecretMetadata {
+                    name: secret["name"].as_str()?.to_string(),
+                    updated_at: secret["createdAt"].as_str().and_then(|t| t.parse().ok()),
+                    version: secret["digest"].as_str().map(str::to_string),
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let app_name = self
+            .app_name
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("FLY_APP_NAME not set"))?;
+
+        self.graphql(
+            r#"
+                mutation($appName: ID!, $keys: [String!]!) {
+                    unsetSecrets(input: {appId: $appName, keys: $keys}) {
+                        release {
+                            id
+                        }
+                    }
+                }
+            "#,
+            serde_json::json!({ "appName": app_name, "keys": [name] }),
+        )
+        .await?;
+
+        Ok(())
+    }
 }
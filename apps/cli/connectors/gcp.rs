@@ -1,25 +1,50 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use google_secretmanager1::{
-    api::{AddSecretVersionRequest, Secret},
+    api::{
+        AddSecretVersionRequest, DestroySecretVersionRequest, DisableSecretVersionRequest, Secret,
+        SecretVersion,
+    },
     hyper, hyper_rustls, oauth2, SecretManager,
 };
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::connectors::RetireMode;
+
+const CLOUD_RUN_URL: &str = "https://run.googleapis.com/v2";
+const CLOUD_PLATFORM_SCOPE: &str = "https://www.googleapis.com/auth/cloud-platform";
+const DEFAULT_REFRESH_TIMEOUT: Duration = Duration::from_secs(600);
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 pub struct GcpConnector {
     hub: SecretManager<hyper_rustls::HttpsConnector<hyper::client::HttpConnector>>,
     project_id: String,
+    /// `(region, service)` pairs that get a new revision on refresh.
+    cloud_run_services: Vec<(String, String)>,
+    refresh_timeout: Duration,
+    poll_interval: Duration,
+    retire: Option<RetireMode>,
+    client: reqwest::Client,
+    cloud_run_url: String,
 }
 
 impl GcpConnector {
-    pub async fn new_async(config: &crate::config::Config) -> Result<Self> {
-        let credentials_path = config
-            .connector_auth
-            .gcp_credentials_path
-            .as_ref()
+    pub async fn new_async(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let credentials_path = settings
+            .credential(
+                "credentials_path",
+                &config.connector_auth.gcp_credentials_path,
+            )
             .ok_or_else(|| anyhow::anyhow!("GOOGLE_APPLICATION_CREDENTIALS not configured"))?;
 
-        let project_id = std::env::var("GCP_PROJECT_ID")
-            .map_err(|_| anyhow::anyhow!("GCP_PROJECT_ID environment variable not set"))?;
+        let project_id = settings
+            .get("project_id", "GCP_PROJECT_ID")
+            .ok_or_else(|| anyhow::anyhow!("GCP_PROJECT_ID environment variable not set"))?;
 
         let service_account_key = oauth2::read_service_account_key(credentials_path)
             .await
@@ -39,14 +64,176 @@ impl GcpConnector {
 
         let hub = SecretManager::new(hyper::Client::builder().build(connector), auth);
 
-        Ok(Self { hub, project_id })
+        let cloud_run_services = settings
+            .0
+            .get("cloud_run_services")
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| {
+                        s.split_once('/')
+                            .map(|(region, service)| (region.to_string(), service.to_string()))
+                            .ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "Invalid Cloud Run service '{}': expected region/service",
+                                    s
+                                )
+                            })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let refresh_timeout = match settings.0.get("refresh_timeout") {
+            Some(timeout) => humantime::parse_duration(timeout)
+                .with_context(|| format!("Invalid refresh_timeout '{}'", timeout))?,
+            None => DEFAULT_REFRESH_TIMEOUT,
+        };
+
+        Ok(Self {
+            hub,
+            project_id,
+            cloud_run_services,
+            refresh_timeout,
+            poll_interval: POLL_INTERVAL,
+            retire: RetireMode::from_settings(settings)?,
+            client: reqwest::Client::new(),
+            cloud_run_url: CLOUD_RUN_URL.to_string(),
+        })
     }
 
-    pub fn new(config: &crate::config::Config) -> Result<Self> {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
         let rt = tokio::runtime::Handle::try_current()
             .map_err(|_| anyhow::anyhow!("No tokio runtime available"))?;
 
-        rt.block_on(Self::new_async(config))
+        // Connectors are created from async code, where blocking the runtime
+        // thread directly panics.
+        tokio::task::block_in_place(|| rt.block_on(Self::new_async(config, settings)))
+    }
+
+    async fn token(&self) -> Result<String> {
+        self.hub
+            .auth
+            .get_token(&[CLOUD_PLATFORM_SCOPE])
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get GCP access token: {}", e))?
+            .ok_or_else(|| anyhow::anyhow!("GCP returned no access token"))
+    }
+
+    async fn cloud_run(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let mut request = self
+            .client
+            .request(method, format!("{}/{}", self.cloud_run_url, path))
+            .bearer_auth(self.token().await?);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let json: serde_json::Value = response.json().await.unwrap_or_default();
+
+        if !status.is_success() {
+            anyhow::bail!(
+                "Cloud Run API error ({}): {}",
+                status,
+                json["error"]["message"].as_str().unwrap_or_default()
+            );
+        }
+
+        Ok(json)
+    }
+
+    /// Deploys a new revision of the service with an unchanged configuration,
+    /// so new instances read the rotated secret, and waits for it to serve.
+    async fn redeploy_cloud_run(&self, region: &str, service: &str) -> Result<()> {
+        let path = format!(
+            "projects/{}/locations/{}/services/{}",
+            self.project_id, region, service
+        );
+        let mut definition = self.cloud_run(reqwest::Method::GET, &path, None).await?;
+
+        let template = &mut definition["template"];
+        template["annotations"]["birch/rotated-at"] =
+            serde_json::Value::String(Utc::now().to_rfc3339());
+        // A fixed revision name would collide with the existing revision.
+        if let Some(template) = template.as_object_mut() {
+            template.remove("revision");
+        }
+
+        let mut operation = self
+            .cloud_run(reqwest::Method::PATCH, &path, Some(&definition))
+            .await?;
+        let operation_name = operation["name"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Cloud Run returned no operation"))?
+            .to_string();
+        let deadline = tokio::time::Instant::now() + self.refresh_timeout;
+
+        while !operation["done"].as_bool().unwrap_or(false) {
+            if tokio::time::Instant::now() >= deadline {
+                anyhow::bail!(
+                    "Cloud Run service {}/{} did not finish deploying within {}",
+                    region,
+                    service,
+                    humantime::format_duration(self.refresh_timeout)
+                );
+            }
+
+            tokio::time::sleep(self.poll_interval).await;
+            operation = self
+                .cloud_run(reqwest::Method::GET, &operation_name, None)
+                .await?;
+        }
+
+        if let Some(message) = operation["error"]["message"].as_str() {
+            anyhow::bail!(
+                "Cloud Run service {}/{} failed to deploy: {}",
+                region,
+                service,
+                message
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn raw_versions(&self, name: &str) -> Result<Vec<SecretVersion>> {
+        let secret_path = format!("projects/{}/secrets/{}", self.project_id, name);
+        let mut versions = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut call = self.hub.projects().secrets_versions_list(&secret_path);
+            if let Some(token) = &page_token {
+                call = call.page_token(token);
+            }
+
+            let (_, response) = call
+                .doit()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to list secret versions in GCP: {}", e))?;
+
+            versions.extend(response.versions.unwrap_or_default());
+
+            match response.next_page_token.filter(|t| !t.is_empty()) {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        versions.sort_by_key(|v| v.create_time);
+        Ok(versions)
     }
 }
 
@@ -128,7 +315,13 @@ impl crate::connectors::Connector for GcpConnector {
             .secrets_versions_access(&secret_path)
             .doit()
             .await
-            .map_err(|e| anyhow::anyhow!("Failed to get secret from GCP: {}", e))?;
+            .map_err(|e| {
+                if is_not_found(&e) {
+                    crate::connectors::not_found(format!("Secret '{}' not found in GCP", name))
+                } else {
+                    anyhow::anyhow!("Failed to get secret from GCP: {}", e)
+                }
+            })?;
 
         let payload = response
             .payload
@@ -142,16 +335,467 @@ impl crate::connectors::Connector for GcpConnector {
     }
 
     async fn trigger_refresh(&self, service: Option<&str>) -> Result<()> {
+        if !self.cloud_run_services.is_empty() {
+            for (region, cloud_run_service) in &self.cloud_run_services {
+                println!(
+                    "🔄 Deploying new revision of Cloud Run service {}/{}",
+                    region, cloud_run_service
+                );
+                self.redeploy_cloud_run(region, cloud_run_service).await?;
+                println!("   ✓ {}/{} serving new revision", region, cloud_run_service);
+            }
+            return Ok(());
+        }
+
         if let Some(svc) = service {
             println!(
                 "Note: Automatic refresh not implemented for GCP service: {}",
                 svc
             );
-            println!(
-                "Manually restart your service (e.g., Cloud Run revision, Cloud Functions update)"
-            );
+            println!("Configure cloud_run_services, or restart it manually");
+        }
+
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            versions: true,
+            describe: true,
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let parent = format!("projects/{}", self.project_id);
+        let mut secrets = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut call = self.hub.projects().secrets_list(&parent);
+            if let Some(token) = &page_token {
+                call = call.page_token(token);
+            }
+
+            let (_, response) = call
+                .doit()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to list secrets in GCP: {}", e))?;
+
+            for secret in response.secrets.unwrap_or_default() {
+                let Some(name) = secret.name.as_deref().and_then(|n| n.rsplit('/').next()) else {
+                    continue;
+                };
+                secrets.push(crate::connectors::SecretMetadata {
+                    name: name.to_string(),
+                    updated_at: secret.create_time,
+                    version: None,
+                });
+            }
+
+            match response.next_page_token.filter(|t| !t.is_empty()) {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
         }
 
+        Ok(secrets)
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let secret_path = format!("projects/{}/secrets/{}", self.project_id, name);
+
+        self.hub
+            .projects()
+            .secrets_delete(&secret_path)
+            .doit()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to delete secret in GCP: {}", e))?;
+
         Ok(())
     }
+
+    async fn list_versions(&self, name: &str) -> Result<Vec<crate::connectors::RemoteVersion>> {
+        Ok(self
+            .raw_versions(name)
+            .await?
+            .iter()
+            .filter_map(remote_version)
+            .collect())
+    }
+
+    async fn describe(&self, name: &str) -> Result<crate::connectors::SecretMetadata> {
+        let version_path = format!(
+            "projects/{}/secrets/{}/versions/latest",
+            self.project_id, name
+        );
+
+        let (_, version) = self
+            .hub
+            .projects()
+            .secrets_versions_get(&version_path)
+            .doit()
+            .await
+            .map_err(|e| {
+                if is_not_found(&e) {
+                    crate::connectors::not_found(format!("Secret '{}' not found in GCP", name))
+                } else {
+                    anyhow::anyhow!("Failed to describe secret in GCP: {}", e)
+                }
+            })?;
+
+        Ok(crate::connectors::SecretMetadata {
+            name: name.to_string(),
+            updated_at: version.create_time,
+            version: version
+                .name
+                .as_deref()
+                .and_then(|n| n.rsplit('/').next())
+                .map(str::to_string),
+        })
+    }
+
+    async fn retire_versions(
+        &self,
+        name: &str,
+        superseded_before: DateTime<Utc>,
+    ) -> Result<Vec<String>> {
+        let Some(mode) = self.retire else {
+            return Ok(Vec::new());
+        };
+
+        let raw = self.raw_versions(name).await?;
+        let versions: Vec<_> = raw.iter().filter_map(remote_version).collect();
+        let states: HashMap<String, &str> = raw
+            .iter()
+            .filter_map(|v| Some((remote_version(v)?.id, v.state.as_deref()?)))
+            .collect();
+        let mut retired = Vec::new();
+
+        for version in crate::connectors::superseded_before(&versions, superseded_before) {
+            let state = states.get(&version.id).copied();
+            let path = format!(
+                "projects/{}/secrets/{}/versions/{}",
+                self.project_id, name, version.id
+            );
+
+            match (mode, state) {
+                (_, Some("DESTROYED")) | (RetireMode::Disable, Some("DISABLED")) => continue,
+                (RetireMode::Disable, _) => {
+                    self.hub
+                        .projects()
+                        .secrets_versions_disable(DisableSecretVersionRequest::default(), &path)
+                        .doit()
+                        .await
+                        .map_err(|e| {
+                            anyhow::anyhow!("Failed to disable secret version in GCP: {}", e)
+                        })?;
+                }
+                (RetireMode::Destroy, _) => {
+                    self.hub
+                        .projects()
+                        .secrets_versions_destroy(DestroySecretVersionRequest::default(), &path)
+                        .doit()
+                        .await
+                        .map_err(|e| {
+                            anyhow::anyhow!("Failed to destroy secret version in GCP: {}", e)
+                        })?;
+                }
+            }
+
+            retired.push(version.id.clone());
+        }
+
+        Ok(retired)
+    }
+}
+
+/// Whether the API answered 404. Error bodies come back as `BadRequest`,
+/// responses without a JSON body as `Failure`.
+fn is_not_found(error: &google_secretmanager1::Error) -> bool {
+    match error {
+        google_secretmanager1::Error::BadRequest(body) => {
+            body["error"]["code"].as_u64() == Some(404)
+        }
+        google_secretmanager1::Error::Failure(response) => {
+            response.status() == hyper::StatusCode::NOT_FOUND
+        }
+        _ => false,
+    }
+}
+
+fn remote_version(version: &SecretVersion) -> Option<crate::connectors::RemoteVersion> {
+    let id = version.name.as_deref()?.rsplit('/').next()?;
+
+    Some(crate::connectors::RemoteVersion {
+        id: id.to_string(),
+        created_at: version.create_time,
+        enabled: version.state.as_deref() == Some("ENABLED"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::Connector;
+    use mockito::Matcher;
+
+    fn connector(server: &mockito::Server) -> GcpConnector {
+        let https = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_native_roots()
+            .unwrap()
+            .https_or_http()
+            .enable_http1()
+            .build();
+        let mut hub = SecretManager::new(
+            hyper::Client::builder().build(https),
+            "test-token".to_string(),
+        );
+        hub.base_url(format!("{}/", server.url()));
+
+        GcpConnector {
+            hub,
+            project_id: "proj".to_string(),
+            cloud_run_services: Vec::new(),
+            refresh_timeout: DEFAULT_REFRESH_TIMEOUT,
+            poll_interval: Duration::ZERO,
+            retire: None,
+            client: reqwest::Client::new(),
+            cloud_run_url: format!("{}/v2", server.url()),
+        }
+    }
+
+    fn not_found_body() -> &'static str {
+        r#"{"error":{"code":404,"message":"Secret [projects/proj/secrets/API_KEY] not found or has no versions.","status":"NOT_FOUND"}}"#
+    }
+
+    #[tokio::test]
+    async fn missing_secret_reads_as_none() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock(
+                "GET",
+                "/v1/projects/proj/secrets/API_KEY/versions/latest:access",
+            )
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(not_found_body())
+            .create_async()
+            .await;
+
+        let value = crate::connectors::read_secret(&connector(&server), "API_KEY")
+            .await
+            .unwrap();
+
+        assert_eq!(value, None);
+    }
+
+    #[tokio::test]
+    async fn describe_missing_secret_is_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v1/projects/proj/secrets/API_KEY/versions/latest")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .with_header("content-type", "application/json")
+            .with_body(not_found_body())
+            .create_async()
+            .await;
+
+        let err = connector(&server).describe("API_KEY").await.unwrap_err();
+
+        assert!(crate::connectors::is_not_found(&err));
+    }
+
+    #[tokio::test]
+    async fn permission_errors_are_not_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock(
+                "GET",
+                "/v1/projects/proj/secrets/API_KEY/versions/latest:access",
+            )
+            .match_query(Matcher::Any)
+            .with_status(403)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"error":{"code":403,"message":"Permission denied","status":"PERMISSION_DENIED"}}"#)
+            .create_async()
+            .await;
+
+        let err = connector(&server).get_secret("API_KEY").await.unwrap_err();
+
+        assert!(!crate::connectors::is_not_found(&err));
+    }
+
+    fn operation(done: bool, error: Option<&str>) -> String {
+        let mut operation = serde_json::json!({
+            "name": "projects/proj/locations/europe-west1/operations/op-1",
+            "done": done,
+        });
+        if let Some(message) = error {
+            operation["error"] = serde_json::json!({ "code": 9, "message": message });
+        }
+        operation.to_string()
+    }
+
+    async fn cloud_run_service(server: &mut mockito::Server) -> (mockito::Mock, mockito::Mock) {
+        let path = "/v2/projects/proj/locations/europe-west1/services/api";
+        let read = server
+            .mock("GET", path)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"name":"api","template":{"revision":"api-00007","containers":[{"image":"api"}]}}"#,
+            )
+            .create_async()
+            .await;
+        let deploy = server
+            .mock("PATCH", path)
+            .match_header("authorization", "Bearer test-token")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::PartialJson(serde_json::json!({
+                    "template": { "containers": [{ "image": "api" }] }
+                })),
+                Matcher::Regex(r#""birch/rotated-at":"\d{4}-"#.to_string()),
+            ]))
+            .with_header("content-type", "application/json")
+            .with_body(operation(false, None))
+            .create_async()
+            .await;
+        (read, deploy)
+    }
+
+    #[tokio::test]
+    async fn cloud_run_polls_the_operation_until_done() {
+        let mut server = mockito::Server::new_async().await;
+        let (read, deploy) = cloud_run_service(&mut server).await;
+        let operation_path = "/v2/projects/proj/locations/europe-west1/operations/op-1";
+        let running = server
+            .mock("GET", operation_path)
+            .with_body(operation(false, None))
+            .expect(2)
+            .create_async()
+            .await;
+        let done = server
+            .mock("GET", operation_path)
+            .with_body(operation(true, None))
+            .create_async()
+            .await;
+
+        connector(&server)
+            .redeploy_cloud_run("europe-west1", "api")
+            .await
+            .unwrap();
+
+        read.assert_async().await;
+        deploy.assert_async().await;
+        running.assert_async().await;
+        done.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn cloud_run_failed_operation_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        cloud_run_service(&mut server).await;
+        server
+            .mock(
+                "GET",
+                "/v2/projects/proj/locations/europe-west1/operations/op-1",
+            )
+            .with_body(operation(true, Some("Revision 'api-00008' is not ready")))
+            .create_async()
+            .await;
+
+        let err = connector(&server)
+            .redeploy_cloud_run("europe-west1", "api")
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("failed to deploy: Revision 'api-00008' is not ready"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn cloud_run_gives_up_after_the_timeout() {
+        let mut server = mockito::Server::new_async().await;
+        cloud_run_service(&mut server).await;
+
+        let err = GcpConnector {
+            refresh_timeout: Duration::ZERO,
+            ..connector(&server)
+        }
+        .redeploy_cloud_run("europe-west1", "api")
+        .await
+        .unwrap_err();
+
+        assert!(
+            err.to_string().contains("did not finish deploying"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn retires_only_superseded_versions() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v1/projects/proj/secrets/API_KEY/versions")
+            .match_query(Matcher::Any)
+            .with_header("content-type", "application/json")
+            .with_body(
+                serde_json::json!({
+                    "versions": [
+                        { "name": "projects/proj/secrets/API_KEY/versions/3",
+                          "createTime": "2024-03-01T00:00:00Z", "state": "ENABLED" },
+                        { "name": "projects/proj/secrets/API_KEY/versions/2",
+                          "createTime": "2024-02-01T00:00:00Z", "state": "ENABLED" },
+                        { "name": "projects/proj/secrets/API_KEY/versions/1",
+                          "createTime": "2024-01-01T00:00:00Z", "state": "ENABLED" },
+                        { "name": "projects/proj/secrets/API_KEY/versions/0",
+                          "createTime": "2023-12-01T00:00:00Z", "state": "DISABLED" }
+                    ]
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        let disable = server
+            .mock(
+                "POST",
+                "/v1/projects/proj/secrets/API_KEY/versions/1:disable",
+            )
+            .match_query(Matcher::Any)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"name":"projects/proj/secrets/API_KEY/versions/1","state":"DISABLED"}"#)
+            .create_async()
+            .await;
+        let others = server
+            .mock(
+                "POST",
+                Matcher::Regex(r"/versions/[023]:(disable|destroy)$".to_string()),
+            )
+            .match_query(Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let cutoff = "2024-02-15T00:00:00Z".parse().unwrap();
+        let retired = GcpConnector {
+            retire: Some(RetireMode::Disable),
+            ..connector(&server)
+        }
+        .retire_versions("API_KEY", cutoff)
+        .await
+        .unwrap();
+
+        assert_eq!(retired, ["1"]);
+        disable.assert_async().await;
+        others.assert_async().await;
+    }
 }
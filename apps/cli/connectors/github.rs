@@ -0,0 +1,420 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+
+const API_URL: &str = "https://api.github.com";
+const API_VERSION: &str = "2022-11-28";
+
+/// GitHub Actions secrets of a repository, a repository environment or an
+/// organization. Values are sealed against the scope's public key, so they
+/// cannot be read back.
+pub struct GithubConnector {
+    token: String,
+    api_url: String,
+    scope: Scope,
+    /// Organization secret visibility: `all`, `private` or `selected`.
+    visibility: String,
+    /// Workflow file name or id re-run by `trigger_refresh`.
+    workflow: Option<String>,
+    workflow_branch: Option<String>,
+    repository: Option<String>,
+    client: reqwest::Client,
+}
+
+enum Scope {
+    Repository(String),
+    Environment { repository: String, name: String },
+    Organization(String),
+}
+
+impl GithubConnector {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let token = settings
+            .credential("token", &config.connector_auth.github_token)
+            .ok_or_else(|| anyhow::anyhow!("GITHUB_TOKEN not configured"))?;
+
+        let repository = settings.get("repository", "GITHUB_REPOSITORY");
+        let environment = settings.get("environment", "GITHUB_ENVIRONMENT");
+        let organization = settings.get("organization", "GITHUB_ORG");
+
+        let scope = match (organization, &repository, environment) {
+            (Some(org), _, None) => Scope::Organization(org),
+            (None, Some(repository), Some(name)) => Scope::Environment {
+                repository: repository.clone(),
+                name,
+            },
+            (None, Some(repository), None) => Scope::Repository(repository.clone()),
+            (Some(_), _, Some(_)) => {
+                anyhow::bail!(
+                    "GitHub environment secrets belong to a repository, not an organization"
+                )
+            }
+            (None, None, _) => anyhow::bail!("GITHUB_REPOSITORY or GITHUB_ORG not set"),
+        };
+
+        Ok(Self {
+            token,
+            api_url: settings
+                .get("api_url", "GITHUB_API_URL")
+                .unwrap_or_else(|| API_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            scope,
+            visibility: settings
+                .0
+                .get("visibility")
+                .cloned()
+                .unwrap_or_else(|| "private".to_string()),
+            workflow: settings.0.get("workflow").cloned(),
+            workflow_branch: settings.0.get("workflow_branch").cloned(),
+            repository,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    fn secrets_url(&self) -> String {
+        match &self.scope {
+            Scope::Repository(repository) => {
+                format!("{}/repos/{}/actions/secrets", self.api_url, repository)
+            }
+            Scope::Environment { repository, name } => format!(
+                "{}/repos/{}/environments/{}/secrets",
+                self.api_url,
+                repository,
+                urlencoding::encode(name)
+            ),
+            Scope::Organization(org) => format!("{}/orgs/{}/actions/secrets", self.api_url, org),
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", API_VERSION)
+            .header("User-Agent", "birch")
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Option<serde_json::Value>> {
+        let response = request.send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("GitHub API error ({}): {}", status, text);
+        }
+
+        Ok(Some(
+            response.json().await.unwrap_or(serde_json::Value::Null),
+        ))
+    }
+
+    fn metadata(secret: &serde_json::Value) -> Option<crate::connectors::SecretMetadata> {
+        Some(crate::connectors::SecretMetadata {
+            name: secret["name"].as_str()?.to_string(),
+            updated_at: secret["updated_at"].as_str().and_then(|t| t.parse().ok()),
+            version: None,
+        })
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for GithubConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        let public_key = self
+            .send(self.request(
+                reqwest::Method::GET,
+                &format!("{}/public-key", self.secrets_url()),
+            ))
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("GitHub repository, environment or organization not found")
+            })?;
+
+        let key_id = public_key["key_id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("GitHub public key has no key_id"))?;
+        let key: [u8; 32] = base64::engine::general_purpose::STANDARD
+            .decode(public_key["key"].as_str().unwrap_or_default())
+            .context("Invalid GitHub public key")?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("GitHub public key is not 32 bytes"))?;
+
+        let sealed = crate::connectors::sealed_box::seal(&key, value.as_bytes())?;
+
+        let mut body = serde_json::json!({
+            "encrypted_value": base64::engine::general_purpose::STANDARD.encode(sealed),
+            "key_id": key_id,
+        });
+        if matches!(self.scope, Scope::Organization(_)) {
+            body["visibility"] = serde_json::Value::String(self.visibility.clone());
+        }
+
+        let url = format!("{}/{}", self.secrets_url(), name);
+        self.send(self.request(reqwest::Method::PUT, &url).json(&body))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("GitHub secret scope not found"))?;
+
+        Ok(())
+    }
+
+    async fn get_secret(&self, _name: &str) -> Result<String> {
+        anyhow::bail!("GitHub does not expose secret values via API")
+    }
+
+    /// Re-runs the latest run of the configured workflow, so jobs pick up
+    /// the new secret.
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        let Some(workflow) = &self.workflow else {
+            return Ok(());
+        };
+        let repository = self
+            .repository
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("GITHUB_REPOSITORY not set"))?;
+
+        let mut url = format!(
+            "{}/repos/{}/actions/workflows/{}/runs?per_page=1",
+            self.api_url, repository, workflow
+        );
+        if let Some(branch) = &self.workflow_branch {
+            url.push_str(&format!("&branch={}", urlencoding::encode(branch)));
+        }
+
+        let runs = self
+            .send(self.request(reqwest::Method::GET, &url))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("GitHub workflow '{}' not found", workflow))?;
+
+        let run_id = runs["workflow_runs"][0]["id"].as_u64().ok_or_else(|| {
+            anyhow::anyhow!("GitHub workflow '{}' has no runs to re-run", workflow)
+        })?;
+
+        let url = format!(
+            "{}/repos/{}/actions/runs/{}/rerun",
+            self.api_url, repository, run_id
+        );
+        self.send(self.request(reqwest::Method::POST, &url)).await?;
+        println!("🔄 Re-running workflow {} (run {})", workflow, run_id);
+
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let mut secrets = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let url = format!("{}?per_page=100&page={}", self.secrets_url(), page);
+            let json = self
+                .send(self.request(reqwest::Method::GET, &url))
+                .await?
+                .unwrap_or_default();
+
+            let batch = json["secrets"].as_array().cloned().unwrap_or_default();
+            secrets.extend(batch.iter().filter_map(Self::metadata));
+
+            if batch.len() < 100 {
+                return Ok(secrets);
+            }
+            page += 1;
+        }
+    }
+
+    async fn describe(&self, name: &str) -> Result<crate::connectors::SecretMetadata> {
+        let url = format!("{}/{}", self.secrets_url(), name);
+        self.send(self.request(reqwest::Method::GET, &url))
+            .await?
+            .as_ref()
+            .and_then(Self::metadata)
+            .ok_or_else(|| {
+                crate::connectors::not_found(format!("Secret '{}' not found in GitHub", name))
+            })
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let url = format!("{}/{}", self.secrets_url(), name);
+        self.send(self.request(reqwest::Method::DELETE, &url))
+            .await?
+            .ok_or_else(|| {
+                crate::connectors::not_found(format!("Secret '{}' not found in GitHub", name))
+            })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::{Connector, ConnectorSettings};
+    use crypto_box::aead::OsRng;
+    use crypto_box::SecretKey;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn connector(server: &mockito::Server, settings: &[(&str, &str)]) -> Result<GithubConnector> {
+        let mut settings: std::collections::BTreeMap<String, String> = settings
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        settings.insert("token".to_string(), "ghp_test".to_string());
+        settings.insert("api_url".to_string(), server.url());
+
+        GithubConnector::new(
+            &crate::config::Config::default(),
+            &ConnectorSettings(settings),
+        )
+    }
+
+    async fn public_key(server: &mut mockito::Server, path: &str) -> SecretKey {
+        let recipient = SecretKey::generate(&mut OsRng);
+        server
+            .mock("GET", format!("{}/public-key", path).as_str())
+            .match_header("authorization", "Bearer ghp_test")
+            .with_body(
+                json!({
+                    "key_id": "key-1",
+                    "key": base64::engine::general_purpose::STANDARD
+                        .encode(recipient.public_key().as_bytes()),
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+        recipient
+    }
+
+    /// Whether `body` carries `value` sealed to `recipient` under `key-1`.
+    fn opens_to(body: &serde_json::Value, recipient: &SecretKey, value: &str) -> bool {
+        let Ok(sealed) = base64::engine::general_purpose::STANDARD
+            .decode(body["encrypted_value"].as_str().unwrap_or_default())
+        else {
+            return false;
+        };
+        body["key_id"] == "key-1"
+            && recipient
+                .unseal(&sealed)
+                .is_ok_and(|v| v == value.as_bytes())
+    }
+
+    #[tokio::test]
+    async fn repository_secrets_are_sealed_to_the_public_key() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/repos/acme/shop/actions/secrets";
+        let recipient = public_key(&mut server, path).await;
+        let put = server
+            .mock("PUT", format!("{}/API_KEY", path).as_str())
+            .match_request(move |request| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                opens_to(&body, &recipient, "s3cret") && body.get("visibility").is_none()
+            })
+            .with_status(201)
+            .create_async()
+            .await;
+
+        let connector = connector(&server, &[("repository", "acme/shop")]).unwrap();
+        connector.update_secret("API_KEY", "s3cret").await.unwrap();
+
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn environment_secrets_use_the_environment_url() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/repos/acme/shop/environments/release%20candidate/secrets";
+        let recipient = public_key(&mut server, path).await;
+        let put = server
+            .mock("PUT", format!("{}/API_KEY", path).as_str())
+            .match_request(move |request| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                opens_to(&body, &recipient, "s3cret")
+            })
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let connector = connector(
+            &server,
+            &[
+                ("repository", "acme/shop"),
+                ("environment", "release candidate"),
+            ],
+        )
+        .unwrap();
+        connector.update_secret("API_KEY", "s3cret").await.unwrap();
+
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn organization_secrets_carry_their_visibility() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/orgs/acme/actions/secrets";
+        public_key(&mut server, path).await;
+        let put = server
+            .mock("PUT", format!("{}/API_KEY", path).as_str())
+            .match_body(Matcher::PartialJson(
+                json!({ "key_id": "key-1", "visibility": "all" }),
+            ))
+            .with_status(201)
+            .create_async()
+            .await;
+
+        let connector =
+            connector(&server, &[("organization", "acme"), ("visibility", "all")]).unwrap();
+        connector.update_secret("API_KEY", "s3cret").await.unwrap();
+
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn organization_environment_secrets_are_rejected() {
+        let server = mockito::Server::new_async().await;
+        let result = connector(
+            &server,
+            &[("organization", "acme"), ("environment", "production")],
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn missing_scope_and_secret_are_reported() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", Matcher::Any)
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let connector = connector(&server, &[("repository", "acme/shop")]).unwrap();
+        let err = connector
+            .update_secret("API_KEY", "s3cret")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+
+        let err = connector.describe("API_KEY").await.unwrap_err();
+        assert!(crate::connectors::is_not_found(&err));
+    }
+}
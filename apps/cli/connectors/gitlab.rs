@@ -0,0 +1,353 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+const API_URL: &str = "https://gitlab.com";
+
+/// CI/CD variables of a GitLab project or group.
+pub struct GitlabConnector {
+    token: String,
+    api_url: String,
+    /// `projects/<id>` or `groups/<id>`, with paths URL-encoded.
+    scope: String,
+    project: Option<String>,
+    environment_scope: String,
+    masked: bool,
+    protected: bool,
+    /// Ref a new pipeline is run on by `trigger_refresh`.
+    pipeline_ref: Option<String>,
+    client: reqwest::Client,
+}
+
+impl GitlabConnector {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let token = settings
+            .credential("token", &config.connector_auth.gitlab_token)
+            .ok_or_else(|| anyhow::anyhow!("GITLAB_TOKEN not configured"))?;
+
+        let project = settings.get("project", "GITLAB_PROJECT");
+        let group = settings.get("group", "GITLAB_GROUP");
+
+        let scope = match (&project, group) {
+            (Some(project), None) => format!("projects/{}", urlencoding::encode(project)),
+            (None, Some(group)) => format!("groups/{}", urlencoding::encode(&group)),
+            (Some(_), Some(_)) => anyhow::bail!("Set either a GitLab project or a group, not both"),
+            (None, None) => anyhow::bail!("GITLAB_PROJECT or GITLAB_GROUP not set"),
+        };
+
+        let flag = |key: &str, default: bool| -> Result<bool> {
+            match settings.0.get(key) {
+                Some(value) => value
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("GitLab {} must be true or false", key)),
+                None => Ok(default),
+            }
+        };
+
+        Ok(Self {
+            token,
+            api_url: settings
+                .get("api_url", "GITLAB_URL")
+                .unwrap_or_else(|| API_URL.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            scope,
+            project,
+            environment_scope: settings
+                .0
+                .get("environment_scope")
+                .cloned()
+                .unwrap_or_else(|| "*".to_string()),
+            masked: flag("masked", true)?,
+            protected: flag("protected", false)?,
+            pipeline_ref: settings.0.get("pipeline_ref").cloned(),
+            client: reqwest::Client::new(),
+        })
+    }
+
+    fn variables_url(&self) -> String {
+        format!("{}/api/v4/{}/variables", self.api_url, self.scope)
+    }
+
+    /// URL of one variable, restricted to the configured environment scope
+    /// when several variables share the key.
+    fn variable_url(&self, name: &str) -> String {
+        format!(
+            "{}/{}?filter[environment_scope]={}",
+            self.variables_url(),
+            name,
+            urlencoding::encode(&self.environment_scope)
+        )
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .header("PRIVATE-TOKEN", &self.token)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Option<serde_json::Value>> {
+        let response = request.send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("GitLab API error ({}): {}", status, text);
+        }
+
+        Ok(Some(
+            response.json().await.unwrap_or(serde_json::Value::Null),
+        ))
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for GitlabConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        let mut body = serde_json::json!({
+            "value": value,
+            "masked": self.masked,
+            "protected": self.protected,
+            "environment_scope": self.environment_scope,
+        });
+
+        let updated = self
+            .send(
+                self.request(reqwest::Method::PUT, &self.variable_url(name))
+                    .json(&body),
+            )
+            .await?;
+
+        if updated.is_none() {
+            body["key"] = serde_json::Value::String(name.to_string());
+            self.send(
+                self.request(reqwest::Method::POST, &self.variables_url())
+                    .json(&body),
+            )
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("GitLab project or group not found"))?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<String> {
+        let variable = self
+            .send(self.request(reqwest::Method::GET, &self.variable_url(name)))
+            .await?
+            .ok_or_else(|| {
+                crate::connectors::not_found(format!("Secret '{}' not found in GitLab", name))
+            })?;
+
+        variable["value"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("GitLab did not return a value for '{}'", name))
+    }
+
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        let Some(pipeline_ref) = &self.pipeline_ref else {
+            return Ok(());
+        };
+        let project = self
+            .project
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Running a pipeline requires a GitLab project"))?;
+
+        let url = format!(
+            "{}/api/v4/projects/{}/pipeline",
+            self.api_url,
+            urlencoding::encode(project)
+        );
+        let pipeline = self
+            .send(
+                self.request(reqwest::Method::POST, &url)
+                    .json(&serde_json::json!({ "ref": pipeline_ref })),
+            )
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("GitLab project not found"))?;
+
+        println!(
+            "🔄 Started pipeline {} on {}",
+            pipeline["id"].as_u64().unwrap_or_default(),
+            pipeline_ref
+        );
+
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    /// Variables in the configured environment scope.
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let mut secrets = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let url = format!("{}?per_page=100&page={}", self.variables_url(), page);
+            let json = self
+                .send(self.request(reqwest::Method::GET, &url))
+                .await?
+                .unwrap_or_default();
+
+            let batch = json.as_array().cloned().unwrap_or_default();
+            secrets.extend(
+                batch
+                    .iter()
+                    .filter(|v| v["environment_scope"].as_str() == Some(&self.environment_scope))
+                    .filter_map(|v| {
+                        Some(crate::connectors::SecretMetadata {
+                            name: v["key"].as_str()?.to_string(),
+                            updated_at: None,
+                            version: None,
+                        })
+                    }),
+            );
+
+            if batch.len() < 100 {
+                return Ok(secrets);
+            }
+            page += 1;
+        }
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        self.send(self.request(reqwest::Method::DELETE, &self.variable_url(name)))
+            .await?
+            .ok_or_else(|| {
+                crate::connectors::not_found(format!("Secret '{}' not found in GitLab", name))
+            })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::{Connector, ConnectorSettings};
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn connector(server: &mockito::Server, settings: &[(&str, &str)]) -> Result<GitlabConnector> {
+        let mut settings: std::collections::BTreeMap<String, String> = settings
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        settings.insert("token".to_string(), "glpat-test".to_string());
+        settings.insert("api_url".to_string(), server.url());
+
+        GitlabConnector::new(
+            &crate::config::Config::default(),
+            &ConnectorSettings(settings),
+        )
+    }
+
+    #[tokio::test]
+    async fn update_sends_the_configured_flags_and_scope() {
+        let mut server = mockito::Server::new_async().await;
+        let put = server
+            .mock("PUT", "/api/v4/projects/acme%2Fshop/variables/API_KEY")
+            .match_header("private-token", "glpat-test")
+            .match_query(Matcher::UrlEncoded(
+                "filter[environment_scope]".to_string(),
+                "production".to_string(),
+            ))
+            .match_body(Matcher::Json(json!({
+                "value": "s3cret",
+                "masked": false,
+                "protected": true,
+                "environment_scope": "production",
+            })))
+            .with_body(json!({ "key": "API_KEY" }).to_string())
+            .create_async()
+            .await;
+
+        let connector = connector(
+            &server,
+            &[
+                ("project", "acme/shop"),
+                ("environment_scope", "production"),
+                ("masked", "false"),
+                ("protected", "true"),
+            ],
+        )
+        .unwrap();
+        connector.update_secret("API_KEY", "s3cret").await.unwrap();
+
+        put.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn update_creates_a_missing_variable() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("PUT", "/api/v4/groups/acme/variables/API_KEY")
+            .match_query(Matcher::Any)
+            .with_status(404)
+            .create_async()
+            .await;
+        let post = server
+            .mock("POST", "/api/v4/groups/acme/variables")
+            .match_body(Matcher::Json(json!({
+                "key": "API_KEY",
+                "value": "s3cret",
+                "masked": true,
+                "protected": false,
+                "environment_scope": "*",
+            })))
+            .with_status(201)
+            .with_body(json!({ "key": "API_KEY" }).to_string())
+            .create_async()
+            .await;
+
+        let connector = connector(&server, &[("group", "acme")]).unwrap();
+        connector.update_secret("API_KEY", "s3cret").await.unwrap();
+
+        post.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn missing_variable_reads_as_none() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/v4/projects/acme%2Fshop/variables/API_KEY")
+            .match_query(Matcher::UrlEncoded(
+                "filter[environment_scope]".to_string(),
+                "*".to_string(),
+            ))
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let connector = connector(&server, &[("project", "acme/shop")]).unwrap();
+        assert_eq!(
+            crate::connectors::read_secret(&connector, "API_KEY")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_scope_and_flags_are_rejected() {
+        let server = mockito::Server::new_async().await;
+
+        assert!(connector(&server, &[("project", "acme/shop"), ("group", "acme")]).is_err());
+        assert!(connector(&server, &[("project", "acme/shop"), ("masked", "yes")]).is_err());
+    }
+}
@@ -0,0 +1,442 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::time::Duration;
+
+const API_URL: &str = "https://api.heroku.com";
+const ACCEPT: &str = "application/vnd.heroku+json; version=3";
+const DEFAULT_REFRESH_TIMEOUT: Duration = Duration::from_secs(600);
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Config vars of a Heroku app. Every config var change creates a release
+/// that restarts the app's dynos, so a refresh waits for that release
+/// (including its release phase) to succeed and for the dynos to come up on
+/// it.
+pub struct HerokuConnector {
+    api_key: String,
+    app: Option<String>,
+    refresh_timeout: Duration,
+    poll_interval: Duration,
+    client: reqwest::Client,
+    api_url: String,
+}
+
+impl HerokuConnector {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let api_key = settings
+            .credential("api_key", &config.connector_auth.heroku_api_key)
+            .ok_or_else(|| anyhow::anyhow!("HEROKU_API_KEY not configured"))?;
+
+        let refresh_timeout = match settings.0.get("refresh_timeout") {
+            Some(timeout) => humantime::parse_duration(timeout)
+                .with_context(|| format!("Invalid refresh_timeout '{}'", timeout))?,
+            None => DEFAULT_REFRESH_TIMEOUT,
+        };
+
+        Ok(Self {
+            api_key,
+            app: settings.get("app", "HEROKU_APP"),
+            refresh_timeout,
+            poll_interval: POLL_INTERVAL,
+            client: reqwest::Client::new(),
+            api_url: API_URL.to_string(),
+        })
+    }
+
+    fn app(&self) -> Result<&str> {
+        self.app
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("HEROKU_APP not set"))
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> Result<reqwest::RequestBuilder> {
+        Ok(self
+            .client
+            .request(
+                method,
+                format!("{}/apps/{}/{}", self.api_url, self.app()?, path),
+            )
+            .bearer_auth(&self.api_key)
+            .header(reqwest::header::ACCEPT, ACCEPT))
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        let response = request.send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            anyhow::bail!(
+                "Heroku app '{}' does not exist or is not accessible",
+                self.app()?
+            );
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Heroku API error ({}): {}", status, text);
+        }
+
+        Ok(response.json().await?)
+    }
+
+    async fn config_vars(&self) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let vars = self
+            .send(self.request(reqwest::Method::GET, "config-vars")?)
+            .await?;
+        Ok(vars.as_object().cloned().unwrap_or_default())
+    }
+
+    /// Setting a config var to `null` removes it.
+    async fn patch_config_vars(&self, vars: serde_json::Value) -> Result<()> {
+        self.send(
+            self.request(reqwest::Method::PATCH, "config-vars")?
+                .json(&vars),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn latest_release(&self) -> Result<serde_json::Value> {
+        let releases = self
+            .send(
+                self.request(reqwest::Method::GET, "releases")?
+                    .header(reqwest::header::RANGE, "version ..; order=desc, max=1"),
+            )
+            .await?;
+
+        releases
+            .as_array()
+            .and_then(|r| r.first())
+            .cloned()
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Heroku app '{}' has no releases",
+                    self.app.as_deref().unwrap_or_default()
+                )
+            })
+    }
+
+    /// Whether every dyno of the app's formation runs `version` and is up.
+    /// One-off dynos (`heroku run`, release phase, scheduler) are ignored.
+    async fn dynos_on_release(&self, version: u64) -> Result<bool> {
+        let formation = self
+            .send(self.request(reqwest::Method::GET, "formation")?)
+            .await?;
+        let process_types: HashSet<&str> = formation
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|f| f["quantity"].as_u64().unwrap_or(0) > 0)
+            .filter_map(|f| f["type"].as_str())
+            .collect();
+
+        let dynos = self
+            .send(self.request(reqwest::Method::GET, "dynos")?)
+            .await?;
+        let mut live = true;
+
+        for dyno in dynos.as_array().into_iter().flatten() {
+            if !dyno["type"]
+                .as_str()
+                .is_some_and(|t| process_types.contains(t))
+            {
+                continue;
+            }
+            if dyno["release"]["version"].as_u64() != Some(version) {
+                live = false;
+                continue;
+            }
+
+            match dyno["state"].as_str() {
+                // Eco dynos sleep when idle and wake on the next request.
+                Some("up") | Some("idle") => {}
+                Some("crashed") => anyhow::bail!(
+                    "Dyno {} crashed on release v{}",
+                    dyno["name"].as_str().unwrap_or_default(),
+                    version
+                ),
+                _ => live = false,
+            }
+        }
+
+        Ok(live)
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for HerokuConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        self.patch_config_vars(serde_json::json!({ name: value }))
+            .await
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<String> {
+        self.config_vars()
+            .await?
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                crate::connectors::not_found(format!("Secret '{}' not found in Heroku", name))
+            })
+    }
+
+    /// Waits for the release created by the config var change to succeed
+    /// and for the app's dynos to restart on it.
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        let deadline = tokio::time::Instant::now() + self.refresh_timeout;
+        let timed_out = || {
+            anyhow::anyhow!(
+                "Heroku app '{}' did not finish releasing within {}",
+                self.app.as_deref().unwrap_or_default(),
+                humantime::format_duration(self.refresh_timeout)
+            )
+        };
+
+        let mut release = self.latest_release().await?;
+        while release["status"].as_str() == Some("pending") {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(timed_out());
+            }
+            tokio::time::sleep(self.poll_interval).await;
+            release = self.latest_release().await?;
+        }
+
+        let version = release["version"].as_u64().unwrap_or_default();
+        if release["status"].as_str() != Some("succeeded") {
+            anyhow::bail!(
+                "Heroku release v{} {}: {}",
+                version,
+                release["status"].as_str().unwrap_or("failed"),
+                release["description"].as_str().unwrap_or_default()
+            );
+        }
+
+        while !self.dynos_on_release(version).await? {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(timed_out());
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+
+        println!("🔄 Heroku release v{} is live", version);
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        Ok(self
+            .config_vars()
+            .await?
+            .into_iter()
+            .map(|(name, _)| crate::connectors::SecretMetadata {
+                name,
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        // Removing a config var that does not exist succeeds silently.
+        if !self.config_vars().await?.contains_key(name) {
+            return Err(crate::connectors::not_found(format!(
+                "Secret '{}' not found in Heroku",
+                name
+            )));
+        }
+
+        self.patch_config_vars(serde_json::json!({ name: null }))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::Connector;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn connector(server: &mockito::Server) -> HerokuConnector {
+        HerokuConnector {
+            api_key: "token".to_string(),
+            app: Some("shop".to_string()),
+            refresh_timeout: DEFAULT_REFRESH_TIMEOUT,
+            poll_interval: Duration::ZERO,
+            client: reqwest::Client::new(),
+            api_url: server.url(),
+        }
+    }
+
+    fn release(version: u64, status: &str) -> String {
+        json!([{ "version": version, "status": status, "description": "Release phase failed: migrations" }])
+            .to_string()
+    }
+
+    async fn formation(server: &mut mockito::Server) {
+        server
+            .mock("GET", "/apps/shop/formation")
+            .with_body(
+                json!([
+                    { "type": "web", "quantity": 2 },
+                    { "type": "worker", "quantity": 0 }
+                ])
+                .to_string(),
+            )
+            .create_async()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn update_patches_only_the_named_var() {
+        let mut server = mockito::Server::new_async().await;
+        // Heroku merges the patch, so the same call updates an existing var
+        // or creates a missing one without touching the others.
+        let patch = server
+            .mock("PATCH", "/apps/shop/config-vars")
+            .match_header("authorization", "Bearer token")
+            .match_header("accept", ACCEPT)
+            .match_body(Matcher::Json(json!({ "API_KEY": "new" })))
+            .with_body(json!({ "API_KEY": "new", "OTHER": "kept" }).to_string())
+            .create_async()
+            .await;
+
+        connector(&server)
+            .update_secret("API_KEY", "new")
+            .await
+            .unwrap();
+
+        patch.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn missing_var_reads_as_none_and_cannot_be_deleted() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/apps/shop/config-vars")
+            .with_body(json!({ "OTHER": "value" }).to_string())
+            .create_async()
+            .await;
+        let patch = server
+            .mock("PATCH", "/apps/shop/config-vars")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let connector = connector(&server);
+        assert_eq!(
+            crate::connectors::read_secret(&connector, "API_KEY")
+                .await
+                .unwrap(),
+            None
+        );
+        let err = connector.delete_secret("API_KEY").await.unwrap_err();
+        assert!(crate::connectors::is_not_found(&err));
+        patch.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn refresh_waits_for_the_release_and_its_dynos() {
+        let mut server = mockito::Server::new_async().await;
+        let pending = server
+            .mock("GET", "/apps/shop/releases")
+            .match_header("range", "version ..; order=desc, max=1")
+            .with_body(release(42, "pending"))
+            .expect(2)
+            .create_async()
+            .await;
+        let succeeded = server
+            .mock("GET", "/apps/shop/releases")
+            .with_body(release(42, "succeeded"))
+            .create_async()
+            .await;
+        formation(&mut server).await;
+        let restarting = server
+            .mock("GET", "/apps/shop/dynos")
+            .with_body(
+                json!([
+                    { "name": "web.1", "type": "web", "state": "up", "release": { "version": 41 } },
+                    { "name": "web.2", "type": "web", "state": "starting", "release": { "version": 42 } }
+                ])
+                .to_string(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let live = server
+            .mock("GET", "/apps/shop/dynos")
+            .with_body(
+                json!([
+                    { "name": "web.1", "type": "web", "state": "up", "release": { "version": 42 } },
+                    { "name": "web.2", "type": "web", "state": "idle", "release": { "version": 42 } },
+                    { "name": "run.1", "type": "run", "state": "up", "release": { "version": 41 } }
+                ])
+                .to_string(),
+            )
+            .create_async()
+            .await;
+
+        connector(&server).trigger_refresh(None).await.unwrap();
+
+        pending.assert_async().await;
+        succeeded.assert_async().await;
+        restarting.assert_async().await;
+        live.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn failed_release_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/apps/shop/releases")
+            .with_body(release(42, "failed"))
+            .create_async()
+            .await;
+
+        let err = connector(&server).trigger_refresh(None).await.unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Heroku release v42 failed: Release phase failed: migrations"
+        );
+    }
+
+    #[tokio::test]
+    async fn crashed_dyno_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/apps/shop/releases")
+            .with_body(release(42, "succeeded"))
+            .create_async()
+            .await;
+        formation(&mut server).await;
+        server
+            .mock("GET", "/apps/shop/dynos")
+            .with_body(
+                json!([{ "name": "web.1", "type": "web", "state": "crashed", "release": { "version": 42 } }])
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let err = connector(&server).trigger_refresh(None).await.unwrap_err();
+
+        assert!(
+            err.to_string().contains("web.1 crashed on release v42"),
+            "{}",
+            err
+        );
+    }
+}
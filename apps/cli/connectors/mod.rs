@@ -1,13 +1,33 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 pub mod aws;
+mod aws_refresh;
+mod aws_ssm;
 pub mod azure;
 pub mod cloudflare;
+mod database;
+pub mod digitalocean;
+pub mod encrypted_file;
+pub mod file;
 pub mod fly;
 pub mod gcp;
+pub mod github;
+pub mod gitlab;
+pub mod heroku;
+pub mod kubernetes;
+pub mod mysql;
 pub mod netlify;
+pub mod plugin;
+pub mod postgres;
+pub mod railway;
+pub mod redis;
 pub mod render;
+mod sealed_box;
+pub mod vault;
 pub mod vercel;
 
 #[async_trait]
@@ -15,12 +35,409 @@ pub trait Connector: Send + Sync {
     async fn update_secret(&self, name: &str, value: &str) -> Result<()>;
     async fn get_secret(&self, name: &str) -> Result<String>;
     async fn trigger_refresh(&self, service: Option<&str>) -> Result<()>;
+
+    /// Which of the optional operations below the provider supports.
+    async fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            read: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<SecretMetadata>> {
+        Err(unsupported("list_secrets"))
+    }
+
+    async fn delete_secret(&self, _name: &str) -> Result<()> {
+        Err(unsupported("delete_secret"))
+    }
+
+    async fn list_versions(&self, _name: &str) -> Result<Vec<RemoteVersion>> {
+        Err(unsupported("list_versions"))
+    }
+
+    /// Metadata for one secret, without its value. Falls back to
+    /// `list_secrets` for providers without a per-secret lookup.
+    async fn describe(&self, name: &str) -> Result<SecretMetadata> {
+        if !self.capabilities().await.list {
+            return Err(unsupported("describe"));
+        }
+
+        self.list_secrets()
+            .await?
+            .into_iter()
+            .find(|s| s.name == name)
+            .ok_or_else(|| not_found(format!("Secret '{}' not found", name)))
+    }
+
+    /// Shapes a newly generated `value` before it is written to any target,
+    /// e.g. to pair a database password with the user it belongs to.
+    /// `current` is the value the secret holds now, if known.
+    async fn prepare_value(
+        &self,
+        _name: &str,
+        value: &str,
+        _current: Option<&str>,
+    ) -> Result<String> {
+        Ok(value.to_string())
+    }
+
+    /// Disables or destroys, as set by the `retire_versions` setting, the
+    /// versions of `name` that were replaced before `superseded_before`.
+    /// Returns the ids of the retired versions.
+    async fn retire_versions(
+        &self,
+        _name: &str,
+        _superseded_before: DateTime<Utc>,
+    ) -> Result<Vec<String>> {
+        Err(unsupported("retire_versions"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Capabilities {
+    /// `get_secret` returns the plaintext value.
+    pub read: bool,
+    pub list: bool,
+    pub delete: bool,
+    pub versions: bool,
+    pub describe: bool,
+}
+
+impl Capabilities {
+    pub fn names(&self) -> Vec<&'static str> {
+        [
+            ("read", self.read),
+            ("list", self.list),
+            ("delete", self.delete),
+            ("versions", self.versions),
+            ("describe", self.describe),
+        ]
+        .into_iter()
+        .filter(|(_, supported)| *supported)
+        .map(|(name, _)| name)
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretMetadata {
+    pub name: String,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Provider-specific id of the current version (version number, digest,
+    /// env var id, ...).
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteVersion {
+    pub id: String,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
 }
 
+/// What happens to superseded versions once the rollback window has passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetireMode {
+    Disable,
+    Destroy,
+}
+
+impl RetireMode {
+    /// Parses the `retire_versions` setting; unset means versions are kept.
+    pub fn from_settings(settings: &ConnectorSettings) -> Result<Option<Self>> {
+        match settings.0.get("retire_versions").map(String::as_str) {
+            None => Ok(None),
+            Some("disable") => Ok(Some(Self::Disable)),
+            Some("destroy") => Ok(Some(Self::Destroy)),
+            Some(other) => anyhow::bail!(
+                "Invalid retire_versions '{}': expected disable or destroy",
+                other
+            ),
+        }
+    }
+}
+
+/// Versions whose successor was created at or before `cutoff`, i.e. that
+/// stopped being current before it. `versions` must be sorted oldest first;
+/// the latest version is never returned.
+pub fn superseded_before(versions: &[RemoteVersion], cutoff: DateTime<Utc>) -> Vec<&RemoteVersion> {
+    versions
+        .windows(2)
+        .filter(|pair| pair[1].created_at.is_some_and(|t| t <= cutoff))
+        .map(|pair| &pair[0])
+        .collect()
+}
+
+/// Error returned by [`read_secret`] for a secret that exists but cannot be
+/// read back. Callers tell it apart from other failures with
+/// `downcast_ref::<WriteOnly>()`.
+#[derive(Debug)]
+pub struct WriteOnly {
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl std::fmt::Display for WriteOnly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.updated_at {
+            Some(updated_at) => write!(
+                f,
+                "secret is write-only (last updated {})",
+                updated_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+            None => write!(f, "secret is write-only"),
+        }
+    }
+}
+
+impl std::error::Error for WriteOnly {}
+
+/// Error a connector returns when the secret itself does not exist, as
+/// opposed to the project, mount or namespace it would live in. Build it
+/// with [`not_found`]; callers check for it with `downcast_ref::<NotFound>()`.
+#[derive(Debug)]
+pub struct NotFound(pub String);
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+pub fn not_found(message: String) -> anyhow::Error {
+    NotFound(message).into()
+}
+
+pub fn is_not_found(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<NotFound>().is_some()
+}
+
+/// Reads `name`, returning `None` when the secret does not exist. Write-only
+/// providers are checked with `describe`, so a missing secret can still be
+/// told apart from one that exists but cannot be read back ([`WriteOnly`]).
+pub async fn read_secret(connector: &dyn Connector, name: &str) -> Result<Option<String>> {
+    let capabilities = connector.capabilities().await;
+
+    if !capabilities.read && capabilities.describe {
+        return match connector.describe(name).await {
+            Ok(metadata) => Err(WriteOnly {
+                updated_at: metadata.updated_at,
+            }
+            .into()),
+            Err(e) if is_not_found(&e) => Ok(None),
+            Err(e) => Err(e),
+        };
+    }
+
+    match connector.get_secret(name).await {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if is_not_found(&e) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+pub fn unsupported(operation: &str) -> anyhow::Error {
+    anyhow::anyhow!("{} is not supported by this connector", operation)
+}
+
+/// Target identifiers and credentials for a connector (project, site, app,
+/// token, ...), taken from the target and its connector profile. Keys that are
+/// not set fall back to the provider's environment variable or to
+/// `connector_auth`.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectorSettings(pub BTreeMap<String, String>);
+
+impl ConnectorSettings {
+    pub fn get(&self, key: &str, env_var: &str) -> Option<String> {
+        self.0
+            .get(key)
+            .cloned()
+            .or_else(|| std::env::var(env_var).ok())
+    }
+
+    pub fn credential(&self, key: &str, fallback: &Option<String>) -> Option<String> {
+        self.0.get(key).or(fallback.as_ref()).cloned()
+    }
+}
+
+type Factory = fn(&crate::config::Config, &ConnectorSettings) -> Result<Box<dyn Connector>>;
+
+/// Built-in connectors, keyed by service name.
+const BUILTIN: &[(&str, Factory)] = &[
+    ("vercel", |c, s| {
+        Ok(Box::new(vercel::VercelConnector::new(c, s)?))
+    }),
+    ("netlify", |c, s| {
+        Ok(Box::new(netlify::NetlifyConnector::new(c, s)?))
+    }),
+    ("render", |c, s| {
+        Ok(Box::new(render::RenderConnector::new(c, s)?))
+    }),
+    ("cloudflare", |c, s| {
+        Ok(Box::new(cloudflare::CloudflareConnector::new(c, s)?))
+    }),
+    ("fly", |c, s| Ok(Box::new(fly::FlyConnector::new(c, s)?))),
+    ("aws", |c, s| Ok(Box::new(aws::AwsConnector::new(c, s)?))),
+    ("gcp", |c, s| Ok(Box::new(gcp::GcpConnector::new(c, s)?))),
+    ("azure", |c, s| {
+        Ok(Box::new(azure::AzureConnector::new(c, s)?))
+    }),
+    ("kubernetes", |c, s| {
+        Ok(Box::new(kubernetes::KubernetesConnector::new(c, s)?))
+    }),
+    ("vault", |c, s| {
+        Ok(Box::new(vault::VaultConnector::new(c, s)?))
+    }),
+    ("github", |c, s| {
+        Ok(Box::new(github::GithubConnector::new(c, s)?))
+    }),
+    ("gitlab", |c, s| {
+        Ok(Box::new(gitlab::GitlabConnector::new(c, s)?))
+    }),
+    ("postgres", |c, s| {
+        Ok(Box::new(postgres::PostgresConnector::new(c, s)?))
+    }),
+    ("mysql", |c, s| {
+        Ok(Box::new(mysql::MysqlConnector::new(c, s)?))
+    }),
+    ("redis", |c, s| {
+        Ok(Box::new(redis::RedisConnector::new(c, s)?))
+    }),
+    ("heroku", |c, s| {
+        Ok(Box::new(heroku::HerokuConnector::new(c, s)?))
+    }),
+    ("railway", |c, s| {
+        Ok(Box::new(railway::RailwayConnector::new(c, s)?))
+    }),
+    ("digitalocean", |c, s| {
+        Ok(Box::new(digitalocean::DigitalOceanConnector::new(c, s)?))
+    }),
+    ("file", |c, s| Ok(Box::new(file::FileConnector::new(c, s)?))),
+    ("encrypted_file", |c, s| {
+        Ok(Box::new(encrypted_file::EncryptedFileConnector::new(c, s)?))
+    }),
+];
+
+pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+    BUILTIN.iter().map(|(name, _)| *name)
+}
+
+/// Creates the connector registered under `name`. Built-in connectors take
+/// precedence; any other name is looked up as a `birch-connector-<name>`
+/// plugin executable.
+pub fn create(
+    name: &str,
+    config: &crate::config::Config,
+    settings: &ConnectorSettings,
+) -> Result<Box<dyn Connector>> {
+    if let Some((_, factory)) = BUILTIN.iter().find(|(n, _)| *n == name) {
+        return factory(config, settings);
+    }
+
+    match plugin::find(name) {
+        Some(program) => Ok(Box::new(plugin::PluginConnector::new(
+            name, program, settings,
+        ))),
+        None => anyhow::bail!(
+            "Unknown service: {} (no built-in connector or birch-connector-{} plugin)",
+            name,
+            name
+        ),
+    }
+}
+
+pub fn list_connectors() -> Result<()> {
+    println!("Built-in connectors:");
+    for name in builtin_names() {
+        println!("  {}", name);
+    }
+
+    let plugins = plugin::discover();
+    if plugins.is_empty() {
+        println!("\nNo connector plugins found (birch-connector-<name> on PATH or in ~/.birch/connectors)");
+    } else {
+        println!("\nConnector plugins:");
+        for (name, path) in plugins {
+            let shadowed = if builtin_names().any(|n| n == name) {
+                " (shadowed by built-in)"
+            } else {
+                ""
+            };
+            println!("  {:<16} {}{}", name, path.display(), shadowed);
+        }
+    }
+
+    Ok(())
+}
+
+/// Shows the last four characters of a secret. Counts characters, not
+/// bytes, since generated values may contain multi-byte symbols.
 pub fn mask_secret(secret: &str) -> String {
-    if secret.len() <= 4 {
+    if secret.chars().count() <= 4 {
         "***".to_string()
     } else {
-        format!("***{}", &secret[secret.len() - 4..])
+        let tail: String = secret.chars().rev().take(4).collect();
+        format!("***{}", tail.chars().rev().collect::<String>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers every read with the error its function builds.
+    struct Failing(fn() -> anyhow::Error);
+
+    #[async_trait]
+    impl Connector for Failing {
+        async fn update_secret(&self, _name: &str, _value: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn get_secret(&self, _name: &str) -> Result<String> {
+            Err((self.0)())
+        }
+
+        async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_secret_reads_as_none() {
+        let connector = Failing(|| not_found("Secret 'API_KEY' not found".to_string()));
+        assert_eq!(read_secret(&connector, "API_KEY").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn missing_container_is_an_error() {
+        let connector = Failing(|| anyhow::anyhow!("Vault KV v2 mount 'secret' not found"));
+        let err = read_secret(&connector, "API_KEY").await.unwrap_err();
+        assert!(err.to_string().contains("mount"), "{}", err);
+    }
+
+    #[test]
+    fn mask_keeps_the_last_four_characters() {
+        assert_eq!(mask_secret("sk_live_1234"), "***1234");
+        assert_eq!(mask_secret("abcd"), "***");
+        assert_eq!(mask_secret("pa§§wörd€"), "***örd€");
+    }
+
+    #[test]
+    fn not_found_survives_context() {
+        let err = not_found("gone".to_string()).context("reading API_KEY");
+        assert!(is_not_found(&err));
+        assert!(!is_not_found(&anyhow::anyhow!("Secret not found")));
     }
 }
@@ -0,0 +1,119 @@
+use crate::connectors::database::DatabaseUsers;
+use anyhow::Result;
+use async_trait::async_trait;
+use mysql_async::prelude::Queryable;
+
+/// Passwords of MySQL and MariaDB accounts, changed with `ALTER USER` over an
+/// admin connection.
+pub struct MysqlConnector {
+    admin: mysql_async::Opts,
+    /// Host part of the accounts, as in `'app'@'10.0.%'` (default `%`).
+    host: String,
+    users: DatabaseUsers,
+}
+
+impl MysqlConnector {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let admin_url = settings
+            .credential("admin_url", &config.connector_auth.mysql_admin_url)
+            .ok_or_else(|| anyhow::anyhow!("MYSQL_ADMIN_URL not configured"))?;
+        let admin = mysql_async::Opts::from_url(&admin_url)
+            .map_err(|e| anyhow::anyhow!("Invalid MySQL admin URL: {}", e))?;
+
+        Ok(Self {
+            admin,
+            host: settings
+                .0
+                .get("host")
+                .cloned()
+                .unwrap_or_else(|| "%".to_string()),
+            users: DatabaseUsers::new(settings)?,
+        })
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for MysqlConnector {
+    async fn update_secret(&self, _name: &str, value: &str) -> Result<()> {
+        let (user, password) = self.users.parse(value)?;
+
+        let mut admin = mysql_async::Conn::new(self.admin.clone())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to MySQL as admin: {}", e))?;
+        admin
+            .query_drop(format!(
+                "ALTER USER {}@{} IDENTIFIED BY {}",
+                quote_literal(&user),
+                quote_literal(&self.host),
+                quote_literal(&password)
+            ))
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to change the password of user '{}': {}", user, e)
+            })?;
+        admin.disconnect().await?;
+
+        let login = mysql_async::OptsBuilder::from_opts(self.admin.clone())
+            .user(Some(&user))
+            .pass(Some(&password));
+        let mut client = mysql_async::Conn::new(login).await.map_err(|e| {
+            anyhow::anyhow!(
+                "Password of user '{}' was changed, but logging in with it failed: {}",
+                user,
+                e
+            )
+        })?;
+        client.query_drop("SELECT 1").await?;
+        client.disconnect().await?;
+
+        println!("   ✓ Verified MySQL login as {}", user);
+        Ok(())
+    }
+
+    async fn get_secret(&self, _name: &str) -> Result<String> {
+        anyhow::bail!("MySQL passwords cannot be read back")
+    }
+
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities::default()
+    }
+
+    async fn prepare_value(
+        &self,
+        _name: &str,
+        value: &str,
+        current: Option<&str>,
+    ) -> Result<String> {
+        self.users.prepare(value, current)
+    }
+}
+
+/// Quotes a string literal; backslashes are escapes in MySQL's default
+/// SQL mode, so they are doubled along with quotes.
+fn quote_literal(literal: &str) -> String {
+    format!("'{}'", literal.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_literals() {
+        assert_eq!(quote_literal("pw"), "'pw'");
+        assert_eq!(quote_literal("it's"), "'it''s'");
+        assert_eq!(quote_literal("back\\slash"), "'back\\\\slash'");
+        assert_eq!(
+            quote_literal("\\'; DROP USER x; --"),
+            "'\\\\''; DROP USER x; --'"
+        );
+        assert_eq!(quote_literal("%"), "'%'");
+    }
+}
@@ -21,15 +21,15 @@ struct EnvValue {
 }
 
 impl NetlifyConnector {
-    pub fn new(config: &crate::config::Config) -> Result<Self> {
-        let token = config
-            .connector_auth
-            .netlify_auth_token
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("NETLIFY_AUTH_TOKEN not configured"))?
-            .clone();
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let token = settings
+            .credential("token", &config.connector_auth.netlify_auth_token)
+            .ok_or_else(|| anyhow::anyhow!("NETLIFY_AUTH_TOKEN not configured"))?;
 
-        let site_id = std::env::var("NETLIFY_SITE_ID").ok();
+        let site_id = settings.get("site_id", "NETLIFY_SITE_ID");
 
         Ok(Self {
             token,
@@ -102,7 +102,10 @@ impl crate::connectors::Connector for NetlifyConnector {
         if let Some(value) = json.get(name).and_then(|v| v.as_str()) {
             Ok(value.to_string())
         } else {
-            anyhow::bail!("Secret '{}' not found in Netlify site", name)
+            Err(crate::connectors::not_found(format!(
+                "Secret '{}' not found in Netlify site",
+                name
+            )))
         }
     }
 
@@ -129,4 +132,94 @@ impl crate::connectors::Connector for NetlifyConnector {
 
         Ok(())
     }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let site_id = self
+            .site_id
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("NETLIFY_SITE_ID not set"))?;
+
+        let url = format!("https://api.netlify.com/api/v1/accounts/{}/env", site_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Netlify API error ({}): {}", status, text);
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        let secrets = match json {
+            serde_json::Value::Array(envs) => envs
+                .iter()
+                .filter_map(|env| {
+                    Some(crate::connectors::SecretMetadata {
+                        name: env["key"].as_str()?.to_string(),
+                        updated_at: env["updated_at"].as_str().and_then(|t| t.parse().ok()),
+                        version: None,
+                    })
+                })
+                .collect(),
+            serde_json::Value::Object(map) => map
+                .keys()
+                .map(|key| crate::connectors::SecretMetadata {
+                    name: key.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+            _ => anyhow::bail!("Invalid response format from Netlify API"),
+        };
+
+        Ok(secrets)
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let site_id = self
+            .site_id
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("NETLIFY_SITE_ID not set"))?;
+
+        let url = format!(
+            "https://api.netlify.com/api/v1/accounts/{}/env/{}",
+            site_id, name
+        );
+
+        let response = self
+            .client
+            .delete(&url)
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(crate::connectors::not_found(format!(
+                "Secret '{}' not found in Netlify site",
+                name
+            )));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Netlify API error ({}): {}", status, text);
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,384 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const PLUGIN_PREFIX: &str = "birch-connector-";
+const PLUGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Error code a plugin returns when the secret does not exist.
+pub const NOT_FOUND: i64 = 404;
+
+/// JSON-RPC "method not found", returned for operations a plugin does not
+/// implement.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// A connector implemented by an external `birch-connector-<name>`
+/// executable. Each call runs the executable once, writes a single JSON-RPC
+/// 2.0 request to its stdin and reads the response from its stdout. The
+/// connector settings (profile credentials and target IDs) are passed along
+/// with every request.
+pub struct PluginConnector {
+    name: String,
+    program: PathBuf,
+    settings: crate::connectors::ConnectorSettings,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl PluginConnector {
+    pub fn new(
+        name: &str,
+        program: PathBuf,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            program,
+            settings: settings.clone(),
+        }
+    }
+
+    async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let mut params = params;
+        params["settings"] = serde_json::to_value(&self.settings.0)?;
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+
+        let mut child = tokio::process::Command::new(&self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", self.program.display()))?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let mut line = serde_json::to_vec(&request)?;
+        line.push(b'\n');
+        stdin.write_all(&line).await?;
+        drop(stdin);
+
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let exchange = async {
+            let mut output = Vec::new();
+            stdout.read_to_end(&mut output).await?;
+            let status = child.wait().await?;
+            Ok::<_, anyhow::Error>((output, status))
+        };
+
+        let (output, status) = tokio::time::timeout(PLUGIN_TIMEOUT, exchange)
+            .await
+            .map_err(|_| anyhow::anyhow!("Connector plugin '{}' timed out", self.name))??;
+
+        let response = String::from_utf8_lossy(&output);
+        let response = response
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Connector plugin '{}' returned no response ({})",
+                    self.name,
+                    status
+                )
+            })?;
+
+        let response: Response = serde_json::from_str(response).with_context(|| {
+            format!("Connector plugin '{}' returned invalid JSON-RPC", self.name)
+        })?;
+
+        if let Some(error) = response.error {
+            if error.code == METHOD_NOT_FOUND {
+                return Err(crate::connectors::unsupported(method));
+            }
+            if error.code == NOT_FOUND {
+                return Err(crate::connectors::not_found(format!(
+                    "Secret not found: {}",
+                    error.message
+                )));
+            }
+            anyhow::bail!(
+                "Connector plugin '{}' error ({}): {}",
+                self.name,
+                error.code,
+                error.message
+            );
+        }
+
+        Ok(response.result.unwrap_or(serde_json::Value::Null))
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for PluginConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        self.call(
+            "update_secret",
+            serde_json::json!({ "name": name, "value": value }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<String> {
+        let result = self
+            .call("get_secret", serde_json::json!({ "name": name }))
+            .await?;
+
+        match result {
+            serde_json::Value::String(value) => Ok(value),
+            serde_json::Value::Object(map) => match map.get("value") {
+                Some(serde_json::Value::String(value)) => Ok(value.clone()),
+                _ => anyhow::bail!("Connector plugin '{}' returned no value", self.name),
+            },
+            _ => anyhow::bail!("Connector plugin '{}' returned no value", self.name),
+        }
+    }
+
+    async fn trigger_refresh(&self, service: Option<&str>) -> Result<()> {
+        self.call("trigger_refresh", serde_json::json!({ "service": service }))
+            .await?;
+        Ok(())
+    }
+
+    /// Plugins declare what they support in answer to `capabilities`; one
+    /// that does not implement it is assumed to support reads only.
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        match self.call("capabilities", serde_json::json!({})).await {
+            Ok(result) => serde_json::from_value(result).unwrap_or_default(),
+            Err(_) => crate::connectors::Capabilities {
+                read: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let result = self.call("list_secrets", serde_json::json!({})).await?;
+        serde_json::from_value(result)
+            .with_context(|| format!("Connector plugin '{}' returned an invalid list", self.name))
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        self.call("delete_secret", serde_json::json!({ "name": name }))
+            .await?;
+        Ok(())
+    }
+
+    async fn list_versions(&self, name: &str) -> Result<Vec<crate::connectors::RemoteVersion>> {
+        let result = self
+            .call("list_versions", serde_json::json!({ "name": name }))
+            .await?;
+        serde_json::from_value(result).with_context(|| {
+            format!(
+                "Connector plugin '{}' returned an invalid version list",
+                self.name
+            )
+        })
+    }
+
+    async fn describe(&self, name: &str) -> Result<crate::connectors::SecretMetadata> {
+        let result = self
+            .call("describe", serde_json::json!({ "name": name }))
+            .await?;
+        serde_json::from_value(result)
+            .with_context(|| format!("Connector plugin '{}' returned invalid metadata", self.name))
+    }
+}
+
+/// Looks for `birch-connector-<name>` in `~/.birch/connectors` and then on
+/// `PATH`.
+pub fn find(name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}{}{}", PLUGIN_PREFIX, name, std::env::consts::EXE_SUFFIX);
+
+    plugin_dirs()
+        .into_iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| is_executable(path))
+}
+
+/// Names of all connector plugins that can be found.
+pub fn discover() -> Vec<(String, PathBuf)> {
+    let mut plugins: Vec<(String, PathBuf)> = Vec::new();
+
+    for dir in plugin_dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_prefix(PLUGIN_PREFIX))
+                .map(|n| n.trim_end_matches(std::env::consts::EXE_SUFFIX).to_string())
+            else {
+                continue;
+            };
+
+            if is_executable(&path) && !plugins.iter().any(|(n, _)| *n == name) {
+                plugins.push((name, path));
+            }
+        }
+    }
+
+    plugins.sort();
+    plugins
+}
+
+fn plugin_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![crate::config::Config::birch_dir().join("connectors")];
+    if let Some(path) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+    dirs
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::connectors::Connector;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// A plugin that saves its request next to itself and prints `output`.
+    fn plugin(dir: &tempfile::TempDir, output: &str) -> PluginConnector {
+        let program = dir.path().join("birch-connector-test");
+        let script = format!(
+            "#!/bin/sh\ncat > \"$(dirname \"$0\")/request.json\"\ncat <<'OUT'\n{}\nOUT\n",
+            output
+        );
+        std::fs::write(&program, script).unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let settings = crate::connectors::ConnectorSettings(
+            [("project".to_string(), "p1".to_string())].into(),
+        );
+        PluginConnector::new("test", program, &settings)
+    }
+
+    fn request(dir: &tempfile::TempDir) -> serde_json::Value {
+        let request = std::fs::read_to_string(dir.path().join("request.json")).unwrap();
+        assert!(request.ends_with('\n'));
+        assert_eq!(request.lines().count(), 1);
+        serde_json::from_str(&request).unwrap()
+    }
+
+    #[tokio::test]
+    async fn sends_one_request_line_with_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let connector = plugin(&dir, r#"{"jsonrpc":"2.0","id":1,"result":null}"#);
+
+        connector.update_secret("API_KEY", "v").await.unwrap();
+
+        let request = request(&dir);
+        assert_eq!(request["jsonrpc"], "2.0");
+        assert_eq!(request["id"], 1);
+        assert_eq!(request["method"], "update_secret");
+        assert_eq!(request["params"]["name"], "API_KEY");
+        assert_eq!(request["params"]["value"], "v");
+        assert_eq!(request["params"]["settings"]["project"], "p1");
+    }
+
+    #[tokio::test]
+    async fn reads_the_last_line_of_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let connector = plugin(
+            &dir,
+            "progress: fetching\n{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"value\":\"s3cret\"}}\n",
+        );
+
+        assert_eq!(connector.get_secret("API_KEY").await.unwrap(), "s3cret");
+    }
+
+    #[tokio::test]
+    async fn maps_error_codes() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = plugin(
+            &dir,
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":404,"message":"API_KEY"}}"#,
+        );
+        let err = missing.get_secret("API_KEY").await.unwrap_err();
+        assert!(crate::connectors::is_not_found(&err), "{}", err);
+
+        let dir = tempfile::tempdir().unwrap();
+        let unsupported = plugin(
+            &dir,
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"nope"}}"#,
+        );
+        let err = unsupported.delete_secret("API_KEY").await.unwrap_err();
+        assert!(err.to_string().contains("delete_secret"), "{}", err);
+
+        let dir = tempfile::tempdir().unwrap();
+        let failing = plugin(
+            &dir,
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":500,"message":"boom"}}"#,
+        );
+        let err = failing.get_secret("API_KEY").await.unwrap_err();
+        assert!(err.to_string().contains("error (500): boom"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn rejects_empty_and_malformed_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = plugin(&dir, "").get_secret("A").await.unwrap_err();
+        assert!(err.to_string().contains("no response"), "{}", err);
+
+        let dir = tempfile::tempdir().unwrap();
+        let err = plugin(&dir, "not json").get_secret("A").await.unwrap_err();
+        assert!(err.to_string().contains("invalid JSON-RPC"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn capabilities_default_to_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let connector = plugin(
+            &dir,
+            r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601,"message":"nope"}}"#,
+        );
+        let capabilities = connector.capabilities().await;
+        assert!(capabilities.read);
+        assert!(!capabilities.delete);
+
+        let dir = tempfile::tempdir().unwrap();
+        let connector = plugin(
+            &dir,
+            r#"{"jsonrpc":"2.0","id":1,"result":{"read":true,"delete":true}}"#,
+        );
+        assert!(connector.capabilities().await.delete);
+    }
+}
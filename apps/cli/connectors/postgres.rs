@@ -0,0 +1,168 @@
+use crate::connectors::database::DatabaseUsers;
+use anyhow::Result;
+use async_trait::async_trait;
+use postgres_native_tls::MakeTlsConnector;
+use std::str::FromStr;
+
+/// Passwords of PostgreSQL roles, changed with `ALTER ROLE` over an admin
+/// connection. The password is sent as a SCRAM-SHA-256 verifier, so the
+/// plaintext never reaches the server or its logs.
+pub struct PostgresConnector {
+    admin: tokio_postgres::Config,
+    /// Database the new password is verified against (default: the admin
+    /// URL's database).
+    database: Option<String>,
+    users: DatabaseUsers,
+    tls: MakeTlsConnector,
+}
+
+impl PostgresConnector {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let admin_url = settings
+            .credential("admin_url", &config.connector_auth.postgres_admin_url)
+            .ok_or_else(|| anyhow::anyhow!("POSTGRES_ADMIN_URL not configured"))?;
+        let admin = tokio_postgres::Config::from_str(&admin_url)
+            .map_err(|e| anyhow::anyhow!("Invalid PostgreSQL admin URL: {}", e))?;
+
+        let mut tls = native_tls::TlsConnector::builder();
+        if let Some(ca_cert) = settings.0.get("ca_cert") {
+            let pem = std::fs::read(ca_cert).map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to read PostgreSQL CA certificate {}: {}",
+                    ca_cert,
+                    e
+                )
+            })?;
+            tls.add_root_certificate(native_tls::Certificate::from_pem(&pem)?);
+        }
+
+        Ok(Self {
+            admin,
+            database: settings.0.get("database").cloned(),
+            users: DatabaseUsers::new(settings)?,
+            tls: MakeTlsConnector::new(tls.build()?),
+        })
+    }
+
+    async fn connect(&self, config: &tokio_postgres::Config) -> Result<tokio_postgres::Client> {
+        let (client, connection) = config
+            .connect(self.tls.clone())
+            .await
+            .map_err(postgres_error)?;
+        tokio::spawn(async move {
+            let _ = connection.await;
+        });
+
+        Ok(client)
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for PostgresConnector {
+    async fn update_secret(&self, _name: &str, value: &str) -> Result<()> {
+        let (user, password) = self.users.parse(value)?;
+
+        let admin = self
+            .connect(&self.admin)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to PostgreSQL as admin: {}", e))?;
+        let verifier = postgres_protocol::password::scram_sha_256(password.as_bytes());
+        admin
+            .batch_execute(&format!(
+                "ALTER ROLE {} PASSWORD {}",
+                quote_identifier(&user),
+                quote_literal(&verifier)
+            ))
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to change the password of role '{}': {}",
+                    user,
+                    postgres_error(e)
+                )
+            })?;
+
+        let mut login = self.admin.clone();
+        login.user(&user).password(password.as_bytes());
+        if let Some(database) = &self.database {
+            login.dbname(database);
+        }
+        let client = self.connect(&login).await.map_err(|e| {
+            anyhow::anyhow!(
+                "Password of role '{}' was changed, but logging in with it failed: {}",
+                user,
+                e
+            )
+        })?;
+        client
+            .simple_query("SELECT 1")
+            .await
+            .map_err(postgres_error)?;
+
+        println!("   ✓ Verified PostgreSQL login as {}", user);
+        Ok(())
+    }
+
+    async fn get_secret(&self, _name: &str) -> Result<String> {
+        anyhow::bail!("PostgreSQL passwords cannot be read back")
+    }
+
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities::default()
+    }
+
+    async fn prepare_value(
+        &self,
+        _name: &str,
+        value: &str,
+        current: Option<&str>,
+    ) -> Result<String> {
+        self.users.prepare(value, current)
+    }
+}
+
+/// Server errors carry their message in the database error, not in the
+/// `Display` of the outer error.
+fn postgres_error(e: tokio_postgres::Error) -> anyhow::Error {
+    match e.as_db_error() {
+        Some(db) => anyhow::anyhow!("PostgreSQL error ({}): {}", db.code().code(), db.message()),
+        None => anyhow::anyhow!("PostgreSQL error: {}", e),
+    }
+}
+
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn quote_literal(literal: &str) -> String {
+    format!("'{}'", literal.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_identifiers() {
+        assert_eq!(quote_identifier("app"), "\"app\"");
+        assert_eq!(quote_identifier("App User"), "\"App User\"");
+        assert_eq!(
+            quote_identifier("a\"; DROP ROLE x; --"),
+            "\"a\"\"; DROP ROLE x; --\""
+        );
+    }
+
+    #[test]
+    fn quotes_literals() {
+        assert_eq!(quote_literal("pw"), "'pw'");
+        assert_eq!(quote_literal("it's"), "'it''s'");
+        assert_eq!(quote_literal("back\\slash"), "'back\\slash'");
+    }
+}
@@ -0,0 +1,424 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::time::Duration;
+
+const API_URL: &str = "https://backboard.railway.com/graphql/v2";
+const DEFAULT_REFRESH_TIMEOUT: Duration = Duration::from_secs(600);
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Variables of a Railway service, or the shared variables of an
+/// environment when no service is set. Writes skip Railway's automatic
+/// deploy; a refresh redeploys the service once and waits for the new
+/// deployment to succeed.
+pub struct RailwayConnector {
+    token: String,
+    project_id: String,
+    environment_id: String,
+    service_id: Option<String>,
+    refresh_timeout: Duration,
+    poll_interval: Duration,
+    client: reqwest::Client,
+    api_url: String,
+}
+
+impl RailwayConnector {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let token = settings
+            .credential("token", &config.connector_auth.railway_api_token)
+            .ok_or_else(|| anyhow::anyhow!("RAILWAY_API_TOKEN not configured"))?;
+
+        let project_id = settings
+            .get("project_id", "RAILWAY_PROJECT_ID")
+            .ok_or_else(|| anyhow::anyhow!("RAILWAY_PROJECT_ID not set"))?;
+        let environment_id = settings
+            .get("environment_id", "RAILWAY_ENVIRONMENT_ID")
+            .ok_or_else(|| anyhow::anyhow!("RAILWAY_ENVIRONMENT_ID not set"))?;
+
+        let refresh_timeout = match settings.0.get("refresh_timeout") {
+            Some(timeout) => humantime::parse_duration(timeout)
+                .with_context(|| format!("Invalid refresh_timeout '{}'", timeout))?,
+            None => DEFAULT_REFRESH_TIMEOUT,
+        };
+
+        Ok(Self {
+            token,
+            project_id,
+            environment_id,
+            service_id: settings.get("service_id", "RAILWAY_SERVICE_ID"),
+            refresh_timeout,
+            poll_interval: POLL_INTERVAL,
+            client: reqwest::Client::new(),
+            api_url: API_URL.to_string(),
+        })
+    }
+
+    /// Runs a GraphQL operation and returns its `data`.
+    async fn graphql(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let response = self
+            .client
+            .post(&self.api_url)
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({ "query": query, "variables": variables }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Railway API error ({}): {}", status, text);
+        }
+
+        let mut json: serde_json::Value = response.json().await?;
+        if let Some(error) = json["errors"].as_array().and_then(|e| e.first()) {
+            anyhow::bail!(
+                "Railway API error: {}",
+                error["message"].as_str().unwrap_or("unknown error")
+            );
+        }
+
+        Ok(json["data"].take())
+    }
+
+    /// The variables of the service (or shared variables), unrendered so
+    /// references such as `${{Postgres.DATABASE_URL}}` read back as written.
+    async fn variables(&self) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let mut data = self
+            .graphql(
+                "query($projectId: String!, $environmentId: String!, $serviceId: String) {
+                    variables(projectId: $projectId, environmentId: $environmentId, serviceId: $serviceId, unrendered: true)
+                }",
+                serde_json::json!({
+                    "projectId": self.project_id,
+                    "environmentId": self.environment_id,
+                    "serviceId": self.service_id,
+                }),
+            )
+            .await?;
+
+        Ok(data["variables"]
+            .take()
+            .as_object()
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// The newest deployment of the service, as `(id, status)`.
+    async fn latest_deployment(&self, service_id: &str) -> Result<Option<(String, String)>> {
+        let data = self
+            .graphql(
+                "query($input: DeploymentListInput!) {
+                    deployments(first: 1, input: $input) { edges { node { id status } } }
+                }",
+                serde_json::json!({
+                    "input": {
+                        "projectId": self.project_id,
+                        "environmentId": self.environment_id,
+                        "serviceId": service_id,
+                    }
+                }),
+            )
+            .await?;
+
+        let node = &data["deployments"]["edges"][0]["node"];
+        Ok(node["id"].as_str().map(|id| {
+            (
+                id.to_string(),
+                node["status"].as_str().unwrap_or_default().to_string(),
+            )
+        }))
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for RailwayConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        self.graphql(
+            "mutation($input: VariableUpsertInput!) { variableUpsert(input: $input) }",
+            serde_json::json!({
+                "input": {
+                    "projectId": self.project_id,
+                    "environmentId": self.environment_id,
+                    "serviceId": self.service_id,
+                    "name": name,
+                    "value": value,
+                    "skipDeploys": true,
+                }
+            }),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<String> {
+        self.variables()
+            .await?
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                crate::connectors::not_found(format!("Secret '{}' not found in Railway", name))
+            })
+    }
+
+    /// Redeploys the service and waits for the new deployment to succeed.
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        let Some(service_id) = &self.service_id else {
+            println!("Note: Redeploy the services that use the shared variables to apply them");
+            return Ok(());
+        };
+
+        let previous = self.latest_deployment(service_id).await?.map(|(id, _)| id);
+
+        self.graphql(
+            "mutation($serviceId: String!, $environmentId: String!) {
+                serviceInstanceRedeploy(serviceId: $serviceId, environmentId: $environmentId)
+            }",
+            serde_json::json!({
+                "serviceId": service_id,
+                "environmentId": self.environment_id,
+            }),
+        )
+        .await?;
+
+        let deadline = tokio::time::Instant::now() + self.refresh_timeout;
+
+        loop {
+            if let Some((id, status)) = self.latest_deployment(service_id).await? {
+                if Some(&id) != previous.as_ref() {
+                    match status.as_str() {
+                        // Serverless services sleep once the deployment is up.
+                        "SUCCESS" | "SLEEPING" => {
+                            println!("🔄 Railway deployment {} is live", id);
+                            return Ok(());
+                        }
+                        "FAILED" | "CRASHED" | "REMOVED" | "SKIPPED" => {
+                            anyhow::bail!("Railway deployment {} {}", id, status.to_lowercase())
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                anyhow::bail!(
+                    "Railway service {} did not finish deploying within {}",
+                    service_id,
+                    humantime::format_duration(self.refresh_timeout)
+                );
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        Ok(self
+            .variables()
+            .await?
+            .into_iter()
+            .map(|(name, _)| crate::connectors::SecretMetadata {
+                name,
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        if !self.variables().await?.contains_key(name) {
+            return Err(crate::connectors::not_found(format!(
+                "Secret '{}' not found in Railway",
+                name
+            )));
+        }
+
+        self.graphql(
+            "mutation($input: VariableDeleteInput!) { variableDelete(input: $input) }",
+            serde_json::json!({
+                "input": {
+                    "projectId": self.project_id,
+                    "environmentId": self.environment_id,
+                    "serviceId": self.service_id,
+                    "name": name,
+                }
+            }),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::Connector;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn connector(server: &mockito::Server) -> RailwayConnector {
+        RailwayConnector {
+            token: "token".to_string(),
+            project_id: "prj".to_string(),
+            environment_id: "env".to_string(),
+            service_id: Some("svc".to_string()),
+            refresh_timeout: DEFAULT_REFRESH_TIMEOUT,
+            poll_interval: Duration::ZERO,
+            client: reqwest::Client::new(),
+            api_url: format!("{}/graphql/v2", server.url()),
+        }
+    }
+
+    /// A GraphQL call whose query selects the `operation` field.
+    fn graphql(server: &mut mockito::Server, operation: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/graphql/v2")
+            .match_header("authorization", "Bearer token")
+            .match_body(Matcher::Regex(format!(r"{}\(", operation)))
+            .with_header("content-type", "application/json")
+    }
+
+    fn deployment(id: &str, status: &str) -> String {
+        json!({ "data": { "deployments": { "edges": [{ "node": { "id": id, "status": status } }] } } })
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn upserts_without_triggering_a_deploy() {
+        let mut server = mockito::Server::new_async().await;
+        let upsert = graphql(&mut server, "variableUpsert")
+            .match_body(Matcher::PartialJson(json!({
+                "variables": { "input": {
+                    "projectId": "prj",
+                    "environmentId": "env",
+                    "serviceId": "svc",
+                    "name": "API_KEY",
+                    "value": "new",
+                    "skipDeploys": true
+                } }
+            })))
+            .with_body(r#"{"data":{"variableUpsert":true}}"#)
+            .create_async()
+            .await;
+
+        connector(&server)
+            .update_secret("API_KEY", "new")
+            .await
+            .unwrap();
+
+        upsert.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn reads_unrendered_variables() {
+        let mut server = mockito::Server::new_async().await;
+        graphql(&mut server, "variables")
+            .match_body(Matcher::Regex("unrendered: true".to_string()))
+            .with_body(
+                json!({ "data": { "variables": { "DATABASE_URL": "${{Postgres.DATABASE_URL}}" } } })
+                    .to_string(),
+            )
+            .create_async()
+            .await;
+
+        let connector = connector(&server);
+        assert_eq!(
+            connector.get_secret("DATABASE_URL").await.unwrap(),
+            "${{Postgres.DATABASE_URL}}"
+        );
+        assert_eq!(
+            crate::connectors::read_secret(&connector, "API_KEY")
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn graphql_errors_are_reported() {
+        let mut server = mockito::Server::new_async().await;
+        graphql(&mut server, "variableUpsert")
+            .with_body(r#"{"errors":[{"message":"Not Authorized"}],"data":null}"#)
+            .create_async()
+            .await;
+
+        let err = connector(&server)
+            .update_secret("API_KEY", "new")
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "Railway API error: Not Authorized");
+    }
+
+    #[tokio::test]
+    async fn refresh_redeploys_and_waits_for_the_new_deployment() {
+        let mut server = mockito::Server::new_async().await;
+        // The previous deployment is still the latest right after the redeploy.
+        let previous = graphql(&mut server, "deployments")
+            .with_body(deployment("d1", "SUCCESS"))
+            .expect(2)
+            .create_async()
+            .await;
+        let building = graphql(&mut server, "deployments")
+            .with_body(deployment("d2", "BUILDING"))
+            .expect(1)
+            .create_async()
+            .await;
+        let live = graphql(&mut server, "deployments")
+            .with_body(deployment("d2", "SUCCESS"))
+            .create_async()
+            .await;
+        let redeploy = graphql(&mut server, "serviceInstanceRedeploy")
+            .match_body(Matcher::PartialJson(json!({
+                "variables": { "serviceId": "svc", "environmentId": "env" }
+            })))
+            .with_body(r#"{"data":{"serviceInstanceRedeploy":true}}"#)
+            .create_async()
+            .await;
+
+        connector(&server).trigger_refresh(None).await.unwrap();
+
+        redeploy.assert_async().await;
+        previous.assert_async().await;
+        building.assert_async().await;
+        live.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn failed_deployment_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        graphql(&mut server, "deployments")
+            .with_body(deployment("d1", "SUCCESS"))
+            .expect(1)
+            .create_async()
+            .await;
+        graphql(&mut server, "deployments")
+            .with_body(deployment("d2", "FAILED"))
+            .create_async()
+            .await;
+        graphql(&mut server, "serviceInstanceRedeploy")
+            .with_body(r#"{"data":{"serviceInstanceRedeploy":true}}"#)
+            .create_async()
+            .await;
+
+        let err = connector(&server).trigger_refresh(None).await.unwrap_err();
+
+        assert_eq!(err.to_string(), "Railway deployment d2 failed");
+    }
+}
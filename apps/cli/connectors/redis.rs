@@ -0,0 +1,125 @@
+use crate::connectors::database::DatabaseUsers;
+use anyhow::Result;
+use async_trait::async_trait;
+use redis::IntoConnectionInfo;
+
+/// Passwords of Redis ACL users, changed with `ACL SETUSER` over an admin
+/// connection. Each rotation replaces all passwords of the user.
+pub struct RedisConnector {
+    admin: redis::ConnectionInfo,
+    users: DatabaseUsers,
+    /// Runs `ACL SAVE` after the change, for servers with an ACL file.
+    acl_save: bool,
+}
+
+impl RedisConnector {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let admin_url = settings
+            .credential("admin_url", &config.connector_auth.redis_admin_url)
+            .ok_or_else(|| anyhow::anyhow!("REDIS_ADMIN_URL not configured"))?;
+        let admin = admin_url
+            .as_str()
+            .into_connection_info()
+            .map_err(|e| anyhow::anyhow!("Invalid Redis admin URL: {}", e))?;
+
+        let acl_save = match settings.0.get("acl_save") {
+            Some(value) => value
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Redis acl_save must be true or false"))?,
+            None => false,
+        };
+
+        Ok(Self {
+            admin,
+            users: DatabaseUsers::new(settings)?,
+            acl_save,
+        })
+    }
+}
+
+async fn connect(info: redis::ConnectionInfo) -> Result<redis::aio::MultiplexedConnection> {
+    Ok(redis::Client::open(info)?
+        .get_multiplexed_tokio_connection()
+        .await?)
+}
+
+#[async_trait]
+impl crate::connectors::Connector for RedisConnector {
+    async fn update_secret(&self, _name: &str, value: &str) -> Result<()> {
+        let (user, password) = self.users.parse(value)?;
+
+        let mut admin = connect(self.admin.clone())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to Redis as admin: {}", e))?;
+
+        // ACL SETUSER creates unknown users, so a typo would silently add one.
+        let existing: Option<redis::Value> = redis::cmd("ACL")
+            .arg("GETUSER")
+            .arg(&user)
+            .query_async(&mut admin)
+            .await?;
+        if existing.is_none() {
+            anyhow::bail!("Redis ACL user '{}' does not exist", user);
+        }
+
+        redis::cmd("ACL")
+            .arg("SETUSER")
+            .arg(&user)
+            .arg("resetpass")
+            .arg(format!(">{}", password))
+            .query_async::<_, ()>(&mut admin)
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!("Failed to change the password of user '{}': {}", user, e)
+            })?;
+
+        if self.acl_save {
+            redis::cmd("ACL")
+                .arg("SAVE")
+                .query_async::<_, ()>(&mut admin)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to save the ACL file: {}", e))?;
+        }
+
+        let mut login = self.admin.clone();
+        login.redis.username = Some(user.clone());
+        login.redis.password = Some(password);
+        let mut client = connect(login).await.map_err(|e| {
+            anyhow::anyhow!(
+                "Password of user '{}' was changed, but logging in with it failed: {}",
+                user,
+                e
+            )
+        })?;
+        redis::cmd("PING")
+            .query_async::<_, String>(&mut client)
+            .await?;
+
+        println!("   ✓ Verified Redis login as {}", user);
+        Ok(())
+    }
+
+    async fn get_secret(&self, _name: &str) -> Result<String> {
+        anyhow::bail!("Redis passwords cannot be read back")
+    }
+
+    async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities::default()
+    }
+
+    async fn prepare_value(
+        &self,
+        _name: &str,
+        value: &str,
+        current: Option<&str>,
+    ) -> Result<String> {
+        self.users.prepare(value, current)
+    }
+}
@@ -15,15 +15,15 @@ struct UpdateEnvVarRequest {
 }
 
 impl RenderConnector {
-    pub fn new(config: &crate::config::Config) -> Result<Self> {
-        let api_key = config
-            .connector_auth
-            .render_api_key
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("RENDER_API_KEY not configured"))?
-            .clone();
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let api_key = settings
+            .credential("api_key", &config.connector_auth.render_api_key)
+            .ok_or_else(|| anyhow::anyhow!("RENDER_API_KEY not configured"))?;
 
-        let service_id = std::env::var("RENDER_SERVICE_ID").ok();
+        let service_id = settings.get("service_id", "RENDER_SERVICE_ID");
 
         Ok(Self {
             api_key,
@@ -97,7 +97,10 @@ impl crate::connectors::Connector for RenderConnector {
             }
         }
 
-        anyhow::bail!("Secret '{}' not found in Render service", name)
+        Err(crate::connectors::not_found(format!(
+            "Secret '{}' not found in Render service",
+            name
+        )))
     }
 
     async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
@@ -124,4 +127,88 @@ impl crate::connectors::Connector for RenderConnector {
 
         Ok(())
     }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let service_id = self
+            .service_id
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("RENDER_SERVICE_ID not set"))?;
+
+        let url = format!("https://api.render.com/v1/services/{}/env-vars", service_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(&self.api_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Render API error ({}): {}", status, text);
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        let envs = json
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Invalid response format from Render API"))?;
+
+        // Entries are either bare env vars or wrapped in `envVar` with a
+        // pagination cursor.
+        Ok(envs
+            .iter()
+            .map(|env| env.get("envVar").unwrap_or(env))
+            .filter_map(|env| {
+                Some(crate::connectors::SecretMetadata {
+                    name: env["key"].as_str()?.to_string(),
+                    ..Default::default()
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let service_id = self
+            .service_id
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("RENDER_SERVICE_ID not set"))?;
+
+        let url = format!(
+            "https://api.render.com/v1/services/{}/env-vars/{}",
+            service_id, name
+        );
+
+        let response = self
+            .client
+            .delete(&url)
+            .bearer_auth(&self.api_key)
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(crate::connectors::not_found(format!(
+                "Secret '{}' not found in Render service",
+                name
+            )));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Render API error ({}): {}", status, text);
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,45 @@
+//! libsodium `crypto_box_seal`, which GitHub requires for Actions secrets:
+//! an ephemeral X25519 key agreement followed by XSalsa20-Poly1305, with the
+//! nonce derived from both public keys. The ephemeral secret key is zeroized
+//! by `crypto_box` once the box is sealed.
+
+use anyhow::Result;
+use crypto_box::aead::OsRng;
+use crypto_box::PublicKey;
+
+/// Encrypts `message` so only the holder of `public_key`'s secret key can
+/// open it. Returns `ephemeral_pk || tag || ciphertext`.
+pub fn seal(public_key: &[u8; 32], message: &[u8]) -> Result<Vec<u8>> {
+    PublicKey::from(*public_key)
+        .seal(&mut OsRng, message)
+        .map_err(|_| anyhow::anyhow!("Failed to seal secret value"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_box::SecretKey;
+
+    #[test]
+    fn sealed_box_opens_with_recipient_key() {
+        let recipient = SecretKey::generate(&mut OsRng);
+        let sealed = seal(recipient.public_key().as_bytes(), b"ghp_value").unwrap();
+
+        // ephemeral public key, Poly1305 tag, then the ciphertext
+        assert_eq!(sealed.len(), 32 + 16 + b"ghp_value".len());
+        assert_eq!(recipient.unseal(&sealed).unwrap(), b"ghp_value");
+
+        let other = SecretKey::generate(&mut OsRng);
+        assert!(other.unseal(&sealed).is_err());
+    }
+
+    #[test]
+    fn ephemeral_key_differs_per_seal() {
+        let recipient = SecretKey::generate(&mut OsRng);
+        let public_key = recipient.public_key();
+        let first = seal(public_key.as_bytes(), b"value").unwrap();
+        let second = seal(public_key.as_bytes(), b"value").unwrap();
+
+        assert_ne!(first[..32], second[..32]);
+    }
+}
@@ -0,0 +1,699 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tokio::sync::OnceCell;
+
+const DEFAULT_MOUNT: &str = "secret";
+const DEFAULT_PATH: &str = "{name}";
+const DEFAULT_FIELD: &str = "value";
+
+/// HashiCorp Vault KV version 2. Each Birch secret maps to a field of a KV
+/// secret: `path` and `field` are templates in which `{name}` is replaced by
+/// the secret name, so one KV secret per Birch secret (the default) and one
+/// KV secret holding many fields are both possible.
+pub struct VaultConnector {
+    address: String,
+    namespace: Option<String>,
+    mount: String,
+    path: String,
+    field: String,
+    /// Pins reads to one KV version instead of the latest.
+    version: Option<u64>,
+    auth: VaultAuth,
+    token: OnceCell<String>,
+    client: reqwest::Client,
+}
+
+enum VaultAuth {
+    Token(String),
+    AppRole {
+        mount: String,
+        role_id: String,
+        secret_id: String,
+    },
+}
+
+impl VaultConnector {
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let address = settings
+            .get("address", "VAULT_ADDR")
+            .ok_or_else(|| anyhow::anyhow!("VAULT_ADDR not set"))?;
+
+        let auth = match settings.credential("token", &config.connector_auth.vault_token) {
+            Some(token) => VaultAuth::Token(token),
+            None => {
+                let role_id = settings.get("role_id", "VAULT_ROLE_ID");
+                let secret_id = settings.get("secret_id", "VAULT_SECRET_ID");
+
+                match (role_id, secret_id) {
+                    (Some(role_id), Some(secret_id)) => VaultAuth::AppRole {
+                        mount: settings
+                            .0
+                            .get("approle_mount")
+                            .cloned()
+                            .unwrap_or_else(|| "approle".to_string()),
+                        role_id,
+                        secret_id,
+                    },
+                    _ => anyhow::bail!(
+                        "Vault credentials not configured (VAULT_TOKEN, or VAULT_ROLE_ID and VAULT_SECRET_ID)"
+                    ),
+                }
+            }
+        };
+
+        let version = settings
+            .0
+            .get("version")
+            .map(|v| {
+                v.parse()
+                    .with_context(|| format!("Invalid Vault version '{}'", v))
+            })
+            .transpose()?;
+
+        let mut client = reqwest::Client::builder();
+        if let Some(ca_cert) = settings.get("ca_cert", "VAULT_CACERT") {
+            let pem = std::fs::read(&ca_cert)
+                .with_context(|| format!("Failed to read Vault CA certificate {}", ca_cert))?;
+            client = client.add_root_certificate(reqwest::Certificate::from_pem(&pem)?);
+        }
+
+        Ok(Self {
+            address: address.trim_end_matches('/').to_string(),
+            namespace: settings.get("namespace", "VAULT_NAMESPACE"),
+            mount: settings
+                .get("mount", "VAULT_MOUNT")
+                .unwrap_or_else(|| DEFAULT_MOUNT.to_string())
+                .trim_matches('/')
+                .to_string(),
+            path: settings
+                .0
+                .get("path")
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_PATH.to_string()),
+            field: settings
+                .0
+                .get("field")
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_FIELD.to_string()),
+            version,
+            auth,
+            token: OnceCell::new(),
+            client: client.build()?,
+        })
+    }
+
+    fn kv_path(&self, name: &str) -> String {
+        self.path
+            .replace("{name}", name)
+            .trim_matches('/')
+            .to_string()
+    }
+
+    fn field(&self, name: &str) -> String {
+        self.field.replace("{name}", name)
+    }
+
+    fn url(&self, kind: &str, path: &str) -> String {
+        format!("{}/v1/{}/{}/{}", self.address, self.mount, kind, path)
+    }
+
+    async fn token(&self) -> Result<&str> {
+        let token = self
+            .token
+            .get_or_try_init(|| async {
+                match &self.auth {
+                    VaultAuth::Token(token) => Ok(token.clone()),
+                    VaultAuth::AppRole {
+                        mount,
+                        role_id,
+                        secret_id,
+                    } => {
+                        let url = format!("{}/v1/auth/{}/login", self.address, mount);
+                        let body =
+                            serde_json::json!({ "role_id": role_id, "secret_id": secret_id });
+                        let json = self
+                            .send(self.with_namespace(self.client.post(&url)).json(&body))
+                            .await?
+                            .ok_or_else(|| {
+                                anyhow::anyhow!("Vault AppRole mount '{}' not found", mount)
+                            })?;
+
+                        json["auth"]["client_token"]
+                            .as_str()
+                            .map(str::to_string)
+                            .ok_or_else(|| anyhow::anyhow!("Vault AppRole login returned no token"))
+                    }
+                }
+            })
+            .await?;
+
+        Ok(token)
+    }
+
+    fn with_namespace(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.namespace {
+            Some(namespace) => request.header("X-Vault-Namespace", namespace),
+            None => request,
+        }
+    }
+
+    async fn request(&self, method: reqwest::Method, url: &str) -> Result<reqwest::RequestBuilder> {
+        Ok(self
+            .with_namespace(self.client.request(method, url))
+            .header("X-Vault-Token", self.token().await?))
+    }
+
+    /// Sends a request; a 404 is `None` so callers can tell a missing
+    /// secret from a failure.
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Option<serde_json::Value>> {
+        let response = request.send().await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Vault API error ({}): {}", status, text);
+        }
+
+        Ok(Some(
+            response.json().await.unwrap_or(serde_json::Value::Null),
+        ))
+    }
+
+    /// Reads the KV data at `path`, the latest version unless `version` is
+    /// given. Deleted and destroyed versions read as `None`.
+    async fn read_data(
+        &self,
+        path: &str,
+        version: Option<u64>,
+    ) -> Result<Option<(serde_json::Map<String, serde_json::Value>, u64)>> {
+        let mut url = self.url("data", path);
+        if let Some(version) = version {
+            url.push_str(&format!("?version={}", version));
+        }
+
+        let Some(json) = self
+            .send(self.request(reqwest::Method::GET, &url).await?)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let data = match json["data"]["data"].as_object() {
+            Some(data) => data.clone(),
+            None => return Ok(None),
+        };
+        let current = json["data"]["metadata"]["version"].as_u64().unwrap_or(0);
+
+        Ok(Some((data, current)))
+    }
+
+    /// Writes a full KV version with check-and-set, so a concurrent writer
+    /// makes the update fail instead of being overwritten.
+    async fn write_data(
+        &self,
+        path: &str,
+        data: serde_json::Map<String, serde_json::Value>,
+        cas: u64,
+    ) -> Result<()> {
+        let body = serde_json::json!({ "options": { "cas": cas }, "data": data });
+        let url = self.url("data", path);
+
+        self.send(self.request(reqwest::Method::POST, &url).await?.json(&body))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Vault KV v2 mount '{}' not found", self.mount))?;
+
+        Ok(())
+    }
+
+    async fn metadata(&self, path: &str) -> Result<Option<serde_json::Value>> {
+        let url = self.url("metadata", path);
+        let json = self
+            .send(self.request(reqwest::Method::GET, &url).await?)
+            .await?;
+
+        Ok(json.map(|json| json["data"].clone()))
+    }
+}
+
+#[async_trait]
+impl crate::connectors::Connector for VaultConnector {
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        let path = self.kv_path(name);
+
+        // KV v2 writes replace every field of the secret, so merge into the
+        // latest version.
+        let (mut data, cas) = match self.metadata(&path).await? {
+            Some(metadata) => {
+                let current = metadata["current_version"].as_u64().unwrap_or(0);
+                let data = self
+                    .read_data(&path, Some(current))
+                    .await?
+                    .map(|(data, _)| data)
+                    .unwrap_or_default();
+                (data, current)
+            }
+            None => (serde_json::Map::new(), 0),
+        };
+
+        data.insert(
+            self.field(name),
+            serde_json::Value::String(value.to_string()),
+        );
+        self.write_data(&path, data, cas).await
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<String> {
+        let path = self.kv_path(name);
+        let field = self.field(name);
+
+        let (data, _) = self.read_data(&path, self.version).await?.ok_or_else(|| {
+            crate::connectors::not_found(format!(
+                "Secret '{}' not found at Vault path '{}'",
+                name, path
+            ))
+        })?;
+
+        match data.get(&field) {
+            Some(serde_json::Value::String(value)) => Ok(value.clone()),
+            Some(other) => Ok(other.to_string()),
+            None => Err(crate::connectors::not_found(format!(
+                "Secret '{}' not found: Vault path '{}' has no field '{}'",
+                name, path, field
+            ))),
+        }
+    }
+
+    async fn trigger_refresh(&self, service: Option<&str>) -> Result<()> {
+        if let Some(svc) = service {
+            println!(
+                "Note: Automatic refresh not implemented for Vault service: {}",
+                svc
+            );
+            println!("Consumers pick up the new version on their next read (e.g., Vault Agent template renewal)");
+        }
+
+        Ok(())
+    }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            versions: true,
+            describe: true,
+        }
+    }
+
+    /// With a per-secret path (`apps/{name}`), lists the KV secrets under
+    /// the prefix; with a shared path and `field = "{name}"`, lists its fields.
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        if let Some(prefix) = self.path.strip_suffix("{name}") {
+            let prefix = prefix.trim_matches('/');
+            let url = self.url("metadata", prefix);
+            let json = self
+                .send(
+                    self.request(reqwest::Method::from_bytes(b"LIST")?, &url)
+                        .await?,
+                )
+                .await?;
+
+            let keys = json
+                .as_ref()
+                .and_then(|json| json["data"]["keys"].as_array().cloned())
+                .unwrap_or_default();
+
+            return Ok(keys
+                .iter()
+                .filter_map(|k| k.as_str())
+                .filter(|k| !k.ends_with('/'))
+                .map(|k| crate::connectors::SecretMetadata {
+                    name: k.to_string(),
+                    updated_at: None,
+                    version: None,
+                })
+                .collect());
+        }
+
+        if self.field != "{name}" {
+            anyhow::bail!("Listing needs a path ending in {{name}} or field = \"{{name}}\"");
+        }
+
+        let path = self.kv_path("");
+        let Some((data, version)) = self.read_data(&path, None).await? else {
+            return Ok(Vec::new());
+        };
+
+        Ok(data
+            .keys()
+            .map(|k| crate::connectors::SecretMetadata {
+                name: k.clone(),
+                updated_at: None,
+                version: Some(version.to_string()),
+            })
+            .collect())
+    }
+
+    /// Removes the secret's field. A KV secret left without fields has its
+    /// latest version soft-deleted, which `vault kv undelete` can reverse.
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let path = self.kv_path(name);
+        let field = self.field(name);
+
+        let (mut data, version) = self.read_data(&path, None).await?.ok_or_else(|| {
+            crate::connectors::not_found(format!(
+                "Secret '{}' not found at Vault path '{}'",
+                name, path
+            ))
+        })?;
+
+        if data.remove(&field).is_none() {
+            return Err(crate::connectors::not_found(format!(
+                "Secret '{}' not found: Vault path '{}' has no field '{}'",
+                name, path, field
+            )));
+        }
+
+        if !data.is_empty() {
+            return self.write_data(&path, data, version).await;
+        }
+
+        let url = self.url("data", &path);
+        self.send(self.request(reqwest::Method::DELETE, &url).await?)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn list_versions(&self, name: &str) -> Result<Vec<crate::connectors::RemoteVersion>> {
+        let path = self.kv_path(name);
+        let metadata = self.metadata(&path).await?.ok_or_else(|| {
+            crate::connectors::not_found(format!(
+                "Secret '{}' not found at Vault path '{}'",
+                name, path
+            ))
+        })?;
+
+        let mut versions: Vec<(u64, crate::connectors::RemoteVersion)> = metadata["versions"]
+            .as_object()
+            .map(|versions| {
+                versions
+                    .iter()
+                    .filter_map(|(id, v)| {
+                        Some((
+                            id.parse().ok()?,
+                            crate::connectors::RemoteVersion {
+                                id: id.clone(),
+                                created_at: parse_time(&v["created_time"]),
+                                enabled: !v["destroyed"].as_bool().unwrap_or(false)
+                                    && v["deletion_time"].as_str().unwrap_or("").is_empty(),
+                            },
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        versions.sort_by_key(|(id, _)| *id);
+        Ok(versions.into_iter().map(|(_, v)| v).collect())
+    }
+
+    async fn describe(&self, name: &str) -> Result<crate::connectors::SecretMetadata> {
+        let path = self.kv_path(name);
+        let metadata = self.metadata(&path).await?.ok_or_else(|| {
+            crate::connectors::not_found(format!(
+                "Secret '{}' not found at Vault path '{}'",
+                name, path
+            ))
+        })?;
+
+        Ok(crate::connectors::SecretMetadata {
+            name: name.to_string(),
+            updated_at: parse_time(&metadata["updated_time"]),
+            version: metadata["current_version"].as_u64().map(|v| v.to_string()),
+        })
+    }
+}
+
+fn parse_time(value: &serde_json::Value) -> Option<chrono::DateTime<chrono::Utc>> {
+    value.as_str().and_then(|t| t.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::Connector;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    fn connector(server: &mockito::Server, auth: VaultAuth) -> VaultConnector {
+        VaultConnector {
+            address: server.url(),
+            namespace: None,
+            mount: DEFAULT_MOUNT.to_string(),
+            path: "apps/{name}".to_string(),
+            field: DEFAULT_FIELD.to_string(),
+            version: None,
+            auth,
+            token: OnceCell::new(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn token() -> VaultAuth {
+        VaultAuth::Token("root".to_string())
+    }
+
+    fn kv(data: serde_json::Value, version: u64) -> String {
+        json!({ "data": { "data": data, "metadata": { "version": version } } }).to_string()
+    }
+
+    #[tokio::test]
+    async fn reads_the_latest_version() {
+        let mut server = mockito::Server::new_async().await;
+        let read = server
+            .mock("GET", "/v1/secret/data/apps/API_KEY")
+            .match_header("x-vault-token", "root")
+            .match_query(Matcher::Missing)
+            .with_body(kv(json!({ "value": "s3cret" }), 4))
+            .create_async()
+            .await;
+
+        let value = connector(&server, token())
+            .get_secret("API_KEY")
+            .await
+            .unwrap();
+
+        assert_eq!(value, "s3cret");
+        read.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn reads_the_pinned_version() {
+        let mut server = mockito::Server::new_async().await;
+        let read = server
+            .mock("GET", "/v1/secret/data/apps/API_KEY")
+            .match_query(Matcher::UrlEncoded("version".into(), "2".into()))
+            .with_body(kv(json!({ "value": "older" }), 2))
+            .create_async()
+            .await;
+
+        let value = VaultConnector {
+            version: Some(2),
+            ..connector(&server, token())
+        }
+        .get_secret("API_KEY")
+        .await
+        .unwrap();
+
+        assert_eq!(value, "older");
+        read.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn missing_secret_and_missing_field_read_as_none() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v1/secret/data/apps/API_KEY")
+            .with_status(404)
+            .with_body(r#"{"errors":[]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/secret/data/apps/OTHER")
+            .with_body(kv(json!({ "username": "app" }), 1))
+            .create_async()
+            .await;
+
+        let connector = connector(&server, token());
+        for name in ["API_KEY", "OTHER"] {
+            assert_eq!(
+                crate::connectors::read_secret(&connector, name)
+                    .await
+                    .unwrap(),
+                None
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn write_merges_fields_with_check_and_set() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v1/secret/metadata/apps/API_KEY")
+            .with_body(json!({ "data": { "current_version": 3 } }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/v1/secret/data/apps/API_KEY")
+            .match_query(Matcher::UrlEncoded("version".into(), "3".into()))
+            .with_body(kv(json!({ "value": "old", "username": "app" }), 3))
+            .create_async()
+            .await;
+        let write = server
+            .mock("POST", "/v1/secret/data/apps/API_KEY")
+            .match_header("x-vault-token", "root")
+            .match_body(Matcher::Json(json!({
+                "options": { "cas": 3 },
+                "data": { "value": "new", "username": "app" }
+            })))
+            .with_body(json!({ "data": { "version": 4 } }).to_string())
+            .create_async()
+            .await;
+
+        connector(&server, token())
+            .update_secret("API_KEY", "new")
+            .await
+            .unwrap();
+
+        write.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn first_write_requires_the_secret_to_be_absent() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v1/secret/metadata/apps/API_KEY")
+            .with_status(404)
+            .create_async()
+            .await;
+        let write = server
+            .mock("POST", "/v1/secret/data/apps/API_KEY")
+            .match_body(Matcher::Json(json!({
+                "options": { "cas": 0 },
+                "data": { "value": "new" }
+            })))
+            .with_body(json!({ "data": { "version": 1 } }).to_string())
+            .create_async()
+            .await;
+
+        connector(&server, token())
+            .update_secret("API_KEY", "new")
+            .await
+            .unwrap();
+
+        write.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn cas_conflict_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/v1/secret/metadata/apps/API_KEY")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/v1/secret/data/apps/API_KEY")
+            .with_status(400)
+            .with_body(
+                r#"{"errors":["check-and-set parameter did not match the current version"]}"#,
+            )
+            .create_async()
+            .await;
+
+        let err = connector(&server, token())
+            .update_secret("API_KEY", "new")
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("check-and-set"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn approle_logs_in_once_and_uses_the_client_token() {
+        let mut server = mockito::Server::new_async().await;
+        let login = server
+            .mock("POST", "/v1/auth/ci-approle/login")
+            .match_header("x-vault-namespace", "team")
+            .match_body(Matcher::Json(
+                json!({ "role_id": "role", "secret_id": "secret" }),
+            ))
+            .with_body(json!({ "auth": { "client_token": "hvs.approle" } }).to_string())
+            .expect(1)
+            .create_async()
+            .await;
+        let read = server
+            .mock("GET", "/v1/secret/data/apps/API_KEY")
+            .match_header("x-vault-token", "hvs.approle")
+            .match_header("x-vault-namespace", "team")
+            .with_body(kv(json!({ "value": "s3cret" }), 1))
+            .expect(2)
+            .create_async()
+            .await;
+
+        let connector = VaultConnector {
+            namespace: Some("team".to_string()),
+            ..connector(
+                &server,
+                VaultAuth::AppRole {
+                    mount: "ci-approle".to_string(),
+                    role_id: "role".to_string(),
+                    secret_id: "secret".to_string(),
+                },
+            )
+        };
+        for _ in 0..2 {
+            assert_eq!(connector.get_secret("API_KEY").await.unwrap(), "s3cret");
+        }
+
+        login.assert_async().await;
+        read.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn failed_approle_login_is_an_error() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/v1/auth/approle/login")
+            .with_status(400)
+            .with_body(r#"{"errors":["invalid role or secret ID"]}"#)
+            .create_async()
+            .await;
+
+        let err = connector(
+            &server,
+            VaultAuth::AppRole {
+                mount: "approle".to_string(),
+                role_id: "role".to_string(),
+                secret_id: "wrong".to_string(),
+            },
+        )
+        .get_secret("API_KEY")
+        .await
+        .unwrap_err();
+
+        assert!(
+            err.to_string().contains("invalid role or secret ID"),
+            "{}",
+            err
+        );
+    }
+}
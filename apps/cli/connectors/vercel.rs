@@ -2,63 +2,92 @@ use anyhow::Result;
 use async_trait::async_trait;
 use serde::Serialize;
 
+const API_URL: &str = "https://api.vercel.com";
+const STANDARD_TARGETS: &[&str] = &["production", "preview", "development"];
+
 pub struct VercelConnector {
     token: String,
     project_id: Option<String>,
+    team_id: Option<String>,
+    /// Standard environments (`production`, `preview`, `development`).
+    targets: Vec<String>,
+    /// Ids of custom environments, given as any other entry of `targets`.
+    custom_environment_ids: Vec<String>,
+    git_branch: Option<String>,
     client: reqwest::Client,
 }
 
 #[derive(Serialize)]
-struct CreateSecretRequest {
-    key: String,
-    value: String,
-    r#type: String,
-    target: Vec<String>,
+#[serde(rename_all = "camelCase")]
+struct EnvVarRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<&'a str>,
+    value: &'a str,
+    r#type: &'a str,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    target: &'a [String],
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    custom_environment_ids: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_branch: Option<&'a str>,
 }
 
 impl VercelConnector {
-    pub fn new(config: &crate::config::Config) -> Result<Self> {
-        let token = config
-            .connector_auth
-            .vercel_token
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("VERCEL_TOKEN not configured"))?
-            .clone();
+    pub fn new(
+        config: &crate::config::Config,
+        settings: &crate::connectors::ConnectorSettings,
+    ) -> Result<Self> {
+        let token = settings
+            .credential("token", &config.connector_auth.vercel_token)
+            .ok_or_else(|| anyhow::anyhow!("VERCEL_TOKEN not configured"))?;
 
-        let project_id = std::env::var("VERCEL_PROJECT_ID").ok();
+        let project_id = settings.get("project_id", "VERCEL_PROJECT_ID");
+        let team_id = settings.get("team_id", "VERCEL_TEAM_ID");
+        let git_branch = settings.get("git_branch", "VERCEL_GIT_BRANCH");
+
+        let (targets, custom_environment_ids): (Vec<String>, Vec<String>) = settings
+            .get("targets", "VERCEL_TARGETS")
+            .unwrap_or_else(|| "production".to_string())
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .partition(|t| STANDARD_TARGETS.contains(&t.as_str()));
+
+        if git_branch.is_some() && targets.iter().any(|t| t != "preview") {
+            anyhow::bail!("Vercel git_branch can only be used with the 'preview' target");
+        }
 
         Ok(Self {
             token,
             project_id,
+            team_id,
+            targets,
+            custom_environment_ids,
+            git_branch,
             client: reqwest::Client::new(),
         })
     }
-}
 
-#[async_trait]
-impl crate::connectors::Connector for VercelConnector {
-    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+    fn project_url(&self, version: &str, path: &str) -> Result<String> {
         let project_id = self
             .project_id
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("VERCEL_PROJECT_ID not set"))?;
 
-        let url = format!("https://api.vercel.com/v10/projects/{}/env", project_id);
+        let mut url = format!(
+            "{}/{}/projects/{}/env{}",
+            API_URL, version, project_id, path
+        );
+        if let Some(team_id) = &self.team_id {
+            url.push_str(&format!("?teamId={}", team_id));
+        }
 
-        let req = CreateSecretRequest {
-            key: name.to_string(),
-            value: value.to_string(),
-            r#type: "encrypted".to_string(),
-            target: vec!["production".to_string()],
-        };
+        Ok(url)
+    }
 
-        let response = self
-            .client
-            .post(&url)
-            .bearer_auth(&self.token)
-            .json(&req)
-            .send()
-            .await?;
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        let response = request.bearer_auth(&self.token).send().await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -66,44 +95,158 @@ impl crate::connectors::Connector for VercelConnector {
             anyhow::bail!("Vercel API error ({}): {}", status, text);
         }
 
-        Ok(())
+        Ok(response.json().await.unwrap_or(serde_json::Value::Null))
     }
 
-    async fn get_secret(&self, name: &str) -> Result<String> {
-        let project_id = self
-            .project_id
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("VERCEL_PROJECT_ID not set"))?;
-
-        let url = format!("https://api.vercel.com/v9/projects/{}/env", project_id);
+    async fn list_envs(&self) -> Result<Vec<serde_json::Value>> {
+        let url = self.project_url("v9", "")?;
+        let json = self.send(self.client.get(&url)).await?;
 
-        let response = self
-            .client
-            .get(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?;
+        json["envs"]
+            .as_array()
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Invalid response format from Vercel API"))
+    }
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await?;
-            anyhow::bail!("Vercel API error ({}): {}", status, text);
+    /// Whether an env var entry is the one this connector manages: same key,
+    /// same git branch and at least one environment in common.
+    fn matches(&self, env: &serde_json::Value, name: &str) -> bool {
+        if env["key"].as_str() != Some(name)
+            || env["gitBranch"].as_str() != self.git_branch.as_deref()
+        {
+            return false;
         }
 
-        let json: serde_json::Value = response.json().await?;
-        let envs = json["envs"]
-            .as_array()
-            .ok_or_else(|| anyhow::anyhow!("Invalid response format from Vercel API"))?;
+        let strings = |value: &serde_json::Value| -> Vec<String> {
+            match value {
+                serde_json::Value::String(s) => vec![s.clone()],
+                serde_json::Value::Array(items) => items
+                    .iter()
+                    .filter_map(|i| i.as_str().map(str::to_string))
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+
+        strings(&env["target"])
+            .iter()
+            .any(|t| self.targets.contains(t))
+            || strings(&env["customEnvironmentIds"])
+                .iter()
+                .any(|id| self.custom_environment_ids.contains(id))
+    }
+
+    async fn find_env(&self, name: &str) -> Result<Option<serde_json::Value>> {
+        Ok(self
+            .list_envs()
+            .await?
+            .into_iter()
+            .find(|env| self.matches(env, name)))
+    }
+
+    /// Splits an upsert of `name` into the existing vars to update and the
+    /// configured environments no var covers yet.
+    fn plan_upsert<'a>(&self, envs: &'a [serde_json::Value], name: &str) -> Upsert<'a> {
+        let existing: Vec<&serde_json::Value> =
+            envs.iter().filter(|env| self.matches(env, name)).collect();
 
-        for env in envs {
-            if env["key"].as_str() == Some(name) {
-                if let Some(value) = env["value"].as_str() {
-                    return Ok(value.to_string());
+        let covered = |field: &str, wanted: &String| {
+            existing.iter().any(|env| match &env[field] {
+                serde_json::Value::String(s) => s == wanted,
+                serde_json::Value::Array(items) => {
+                    items.iter().any(|i| i.as_str() == Some(wanted.as_str()))
                 }
-            }
+                _ => false,
+            })
+        };
+
+        Upsert {
+            missing_targets: self
+                .targets
+                .iter()
+                .filter(|t| !covered("target", t))
+                .cloned()
+                .collect(),
+            missing_custom_environment_ids: self
+                .custom_environment_ids
+                .iter()
+                .filter(|id| !covered("customEnvironmentIds", id))
+                .cloned()
+                .collect(),
+            existing,
         }
+    }
+}
+
+struct Upsert<'a> {
+    existing: Vec<&'a serde_json::Value>,
+    missing_targets: Vec<String>,
+    missing_custom_environment_ids: Vec<String>,
+}
+
+#[async_trait]
+impl crate::connectors::Connector for VercelConnector {
+    /// Updates every var of `name` in the configured environments and
+    /// branch, then creates one var for the environments none of them cover.
+    async fn update_secret(&self, name: &str, value: &str) -> Result<()> {
+        let envs = self.list_envs().await?;
+        let upsert = self.plan_upsert(&envs, name);
+
+        for existing in &upsert.existing {
+            let id = existing["id"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Vercel env var has no id"))?;
+            let url = self.project_url("v9", &format!("/{}", id))?;
 
-        anyhow::bail!("Secret '{}' not found in Vercel project", name)
+            // Only the value changes; the var keeps the environments it
+            // was already shared with.
+            let req = EnvVarRequest {
+                key: None,
+                value,
+                r#type: existing["type"].as_str().unwrap_or("encrypted"),
+                target: &[],
+                custom_environment_ids: &[],
+                git_branch: None,
+            };
+
+            self.send(self.client.patch(&url).json(&req)).await?;
+        }
+
+        if !upsert.missing_targets.is_empty() || !upsert.missing_custom_environment_ids.is_empty() {
+            let url = self.project_url("v10", "")?;
+
+            let req = EnvVarRequest {
+                key: Some(name),
+                value,
+                r#type: "encrypted",
+                target: &upsert.missing_targets,
+                custom_environment_ids: &upsert.missing_custom_environment_ids,
+                git_branch: self.git_branch.as_deref(),
+            };
+
+            self.send(self.client.post(&url).json(&req)).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_secret(&self, name: &str) -> Result<String> {
+        let env = self.find_env(name).await?.ok_or_else(|| {
+            crate::connectors::not_found(format!("Secret '{}' not found in Vercel project", name))
+        })?;
+
+        let id = env["id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Vercel env var has no id"))?;
+
+        // The list endpoint only returns encrypted values; this one decrypts.
+        let url = self.project_url("v1", &format!("/{}", id))?;
+        let json = self.send(self.client.get(&url)).await?;
+
+        json["value"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Vercel did not return a value for '{}'", name))
     }
 
     async fn trigger_refresh(&self, _service: Option<&str>) -> Result<()> {
@@ -112,7 +255,10 @@ impl crate::connectors::Connector for VercelConnector {
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("VERCEL_PROJECT_ID not set"))?;
 
-        let url = "https://api.vercel.com/v13/deployments".to_string();
+        let mut url = format!("{}/v13/deployments", API_URL);
+        if let Some(team_id) = &self.team_id {
+            url.push_str(&format!("?teamId={}", team_id));
+        }
 
         let body = serde_json::json!({
             "name": project_id,
@@ -135,4 +281,152 @@ impl crate::connectors::Connector for VercelConnector {
 
         Ok(())
     }
+
+    async fn capabilities(&self) -> crate::connectors::Capabilities {
+        crate::connectors::Capabilities {
+            read: true,
+            list: true,
+            delete: true,
+            describe: true,
+            ..Default::default()
+        }
+    }
+
+    async fn list_secrets(&self) -> Result<Vec<crate::connectors::SecretMetadata>> {
+        let envs = self.list_envs().await?;
+
+        Ok(envs
+            .iter()
+            .filter_map(|env| {
+                Some(crate::connectors::SecretMetadata {
+                    name: env["key"].as_str()?.to_string(),
+                    updated_at: env["updatedAt"]
+                        .as_i64()
+                        .and_then(chrono::DateTime::from_timestamp_millis),
+                    version: env["id"].as_str().map(str::to_string),
+                })
+            })
+            .collect())
+    }
+
+    async fn describe(&self, name: &str) -> Result<crate::connectors::SecretMetadata> {
+        let env = self.find_env(name).await?.ok_or_else(|| {
+            crate::connectors::not_found(format!("Secret '{}' not found in Vercel project", name))
+        })?;
+
+        Ok(crate::connectors::SecretMetadata {
+            name: name.to_string(),
+            updated_at: env["updatedAt"]
+                .as_i64()
+                .and_then(chrono::DateTime::from_timestamp_millis),
+            version: env["id"].as_str().map(str::to_string),
+        })
+    }
+
+    /// Removes only the env vars in this connector's targets and branch.
+    async fn delete_secret(&self, name: &str) -> Result<()> {
+        let envs = self.list_envs().await?;
+        let ids: Vec<&str> = envs
+            .iter()
+            .filter(|env| self.matches(env, name))
+            .filter_map(|env| env["id"].as_str())
+            .collect();
+
+        if ids.is_empty() {
+            return Err(crate::connectors::not_found(format!(
+                "Secret '{}' not found in Vercel project",
+                name
+            )));
+        }
+
+        for id in ids {
+            let url = self.project_url("v9", &format!("/{}", id))?;
+            self.send(self.client.delete(&url)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn connector(targets: &[&str], custom: &[&str], git_branch: Option<&str>) -> VercelConnector {
+        VercelConnector {
+            token: "token".to_string(),
+            project_id: Some("prj".to_string()),
+            team_id: None,
+            targets: targets.iter().map(|t| t.to_string()).collect(),
+            custom_environment_ids: custom.iter().map(|t| t.to_string()).collect(),
+            git_branch: git_branch.map(str::to_string),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn ids<'a>(upsert: &Upsert<'a>) -> Vec<&'a str> {
+        upsert
+            .existing
+            .iter()
+            .filter_map(|env| env["id"].as_str())
+            .collect()
+    }
+
+    #[test]
+    fn updates_every_var_in_the_configured_targets() {
+        let envs = vec![
+            json!({"id": "1", "key": "API_KEY", "target": ["production"]}),
+            json!({"id": "2", "key": "API_KEY", "target": ["preview"]}),
+            json!({"id": "3", "key": "API_KEY", "target": ["development"]}),
+            json!({"id": "4", "key": "OTHER", "target": ["production"]}),
+        ];
+        let upsert = connector(&["production", "preview"], &[], None).plan_upsert(&envs, "API_KEY");
+
+        assert_eq!(ids(&upsert), ["1", "2"]);
+        assert!(upsert.missing_targets.is_empty());
+    }
+
+    #[test]
+    fn creates_a_var_for_uncovered_targets() {
+        let envs = vec![json!({"id": "1", "key": "API_KEY", "target": "production"})];
+        let upsert = connector(&["production", "preview", "development"], &["env_1"], None)
+            .plan_upsert(&envs, "API_KEY");
+
+        assert_eq!(ids(&upsert), ["1"]);
+        assert_eq!(upsert.missing_targets, ["preview", "development"]);
+        assert_eq!(upsert.missing_custom_environment_ids, ["env_1"]);
+    }
+
+    #[test]
+    fn matches_custom_environments() {
+        let envs = vec![
+            json!({"id": "1", "key": "API_KEY", "target": [], "customEnvironmentIds": ["env_1"]}),
+            json!({"id": "2", "key": "API_KEY", "customEnvironmentIds": ["env_2"]}),
+        ];
+        let upsert = connector(&[], &["env_1"], None).plan_upsert(&envs, "API_KEY");
+
+        assert_eq!(ids(&upsert), ["1"]);
+        assert!(upsert.missing_custom_environment_ids.is_empty());
+    }
+
+    #[test]
+    fn git_branch_must_match() {
+        let envs = vec![
+            json!({"id": "1", "key": "API_KEY", "target": ["preview"]}),
+            json!({"id": "2", "key": "API_KEY", "target": ["preview"], "gitBranch": "main"}),
+            json!({"id": "3", "key": "API_KEY", "target": ["preview"], "gitBranch": "staging"}),
+        ];
+
+        let branch = connector(&["preview"], &[], Some("staging")).plan_upsert(&envs, "API_KEY");
+        assert_eq!(ids(&branch), ["3"]);
+        assert!(branch.missing_targets.is_empty());
+
+        let all_branches = connector(&["preview"], &[], None).plan_upsert(&envs, "API_KEY");
+        assert_eq!(ids(&all_branches), ["1"]);
+
+        let new_branch = connector(&["preview"], &[], Some("dev")).plan_upsert(&envs, "API_KEY");
+        assert!(ids(&new_branch).is_empty());
+        assert_eq!(new_branch.missing_targets, ["preview"]);
+    }
 }
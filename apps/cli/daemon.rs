@@ -1,8 +1,9 @@
 use anyhow::Result;
 use std::fs;
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
-pub async fn start(bind: &str) -> Result<()> {
+pub async fn start(bind: &str, manifest: Option<&str>) -> Result<()> {
     let pid_file = get_pid_file();
 
     if pid_file.exists() {
@@ -20,10 +21,16 @@ pub async fn start(bind: &str) -> Result<()> {
     println!("🚀 Starting Birch daemon on {}", bind);
 
     let exe = std::env::current_exe()?;
-    let child = Command::new(exe)
-        .arg("daemon-internal-run")
-        .arg("--bind")
-        .arg(bind)
+    let mut command = Command::new(exe);
+    command.arg("daemon-internal-run").arg("--bind").arg(bind);
+
+    if let Some(manifest) = manifest {
+        let path = fs::canonicalize(manifest)
+            .map_err(|e| anyhow::anyhow!("Manifest {} not found: {}", manifest, e))?;
+        command.arg("--manifest").arg(path);
+    }
+
+    let child = command
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .spawn()?;
@@ -85,6 +92,7 @@ pub async fn status() -> Result<()> {
 
     if is_process_running(pid) {
         println!("✅ Daemon is running (PID {})", pid);
+        crate::scheduler::print_schedule()?;
     } else {
         println!("❌ Daemon is not running (stale PID file)");
     }
@@ -92,9 +100,38 @@ pub async fn status() -> Result<()> {
     Ok(())
 }
 
-pub async fn run_daemon(bind: String) -> Result<()> {
+pub async fn run_daemon(bind: String, manifest: Option<PathBuf>) -> Result<()> {
     println!("Starting daemon on {}", bind);
 
+    match manifest.or(crate::config::Config::load()?.manifest_path) {
+        Some(path) => {
+            tokio::spawn(crate::scheduler::run_scheduler(path.clone()));
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = crate::revocation::retire_due_versions(&path).await {
+                        tracing::error!("Version retirement sweep failed: {}", e);
+                    }
+                }
+            });
+        }
+        None => println!("No manifest configured; scheduled rotations are disabled"),
+    }
+
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = crate::staged::retire_due() {
+                tracing::error!("Staged rotation retirement sweep failed: {}", e);
+            }
+            if let Err(e) = crate::revocation::revoke_due_keys().await {
+                tracing::error!("Key revocation sweep failed: {}", e);
+            }
+        }
+    });
+
     crate::signals::start_server(&bind).await?;
 
     Ok(())
@@ -1,95 +1,166 @@
 use anyhow::{Context, Result};
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub async fn update_env_file(
+/// Writes a secret into a local file. The format (dotenv, JSON, YAML, TOML or
+/// a Kubernetes Secret manifest) follows the file; `key_path` addresses the
+/// value inside it and defaults to the secret name. The parts of a bundle
+/// are written under their own names, in one write of the file.
+pub async fn update_local_file(
     secret_name: &str,
     new_value: &str,
-    env_file: Option<&str>,
+    file: Option<&str>,
+    key_path: Option<&str>,
 ) -> Result<()> {
-    let env_path = if let Some(path) = env_file {
-        PathBuf::from(path)
-    } else {
-        PathBuf::from(".env")
-    };
-
-    if !env_path.exists() {
-        anyhow::bail!(".env file not found at: {}", env_path.display());
-    }
-
-    let original_contents = fs::read_to_string(&env_path).context("Failed to read .env file")?;
-
-    let rollback_path = PathBuf::from(".birch-rollback");
-    fs::write(&rollback_path, &original_contents)?;
-
-    let mut new_contents = String::new();
-    let mut found = false;
-
-    for line in original_contents.lines() {
-        let trimmed = line.trim();
+    let path = resolve_env_path(file);
 
-        if trimmed.starts_with('#') || trimmed.is_empty() {
-            new_contents.push_str(line);
-            new_contents.push('\n');
-            continue;
-        }
-
-        if let Some(pos) = line.find('=') {
-            let key = line[..pos].trim();
-            if key == secret_name {
-                new_contents.push_str(&format!("{}={}\n", secret_name, new_value));
-                found = true;
-                continue;
-            }
-        }
-
-        new_contents.push_str(line);
-        new_contents.push('\n');
+    if !path.exists() {
+        anyhow::bail!("{} not found at: {}", file_kind(&path), path.display());
     }
 
-    if !found {
-        new_contents.push_str(&format!("{}={}\n", secret_name, new_value));
+    let entries = match crate::bundle::parts(secret_name)? {
+        Some(parts) => crate::bundle::split(&parts, new_value)?,
+        None => vec![(
+            key_path.unwrap_or(secret_name).to_string(),
+            new_value.to_string(),
+        )],
+    };
+    let format = crate::local_files::format_for(&path);
+
+    let original_contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut new_contents = original_contents.clone();
+    for (key_path, value) in &entries {
+        let keys = crate::local_files::parse_key_path(key_path)?;
+        new_contents = format
+            .set(&new_contents, &keys, value)
+            .with_context(|| format!("Failed to update {}", path.display()))?;
     }
 
-    let temp_path = env_path.with_extension("tmp");
-    fs::write(&temp_path, &new_contents)?;
-    fs::rename(&temp_path, &env_path)?;
+    let rollback_path = snapshot_path(&path)?;
+    fs::create_dir_all(snapshots_dir())?;
+    fs::write(&rollback_path, &original_contents)?;
 
-    println!("📝 Updated {} in {}", secret_name, env_path.display());
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, new_contents)?;
+    fs::rename(&temp_path, &path)?;
+
+    println!(
+        "📝 Updated {} in {}",
+        entries
+            .iter()
+            .map(|(key_path, _)| key_path.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        path.display()
+    );
     println!("💡 Restart your application to use the new secret");
     println!("🔙 Rollback saved to {}", rollback_path.display());
 
     Ok(())
 }
 
-pub fn get_env_secret(secret_name: &str, env_file: Option<&str>) -> Result<Option<String>> {
-    let env_path = if let Some(path) = env_file {
-        PathBuf::from(path)
-    } else {
-        PathBuf::from(".env")
-    };
+pub fn get_local_secret(
+    secret_name: &str,
+    file: Option<&str>,
+    key_path: Option<&str>,
+) -> Result<Option<String>> {
+    let path = resolve_env_path(file);
 
-    if !env_path.exists() {
+    if !path.exists() {
         return Ok(None);
     }
 
-    let contents = fs::read_to_string(&env_path).context("Failed to read .env file")?;
-
-    for line in contents.lines() {
-        let trimmed = line.trim();
-
-        if trimmed.starts_with('#') || trimmed.is_empty() {
-            continue;
-        }
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let format = crate::local_files::format_for(&path);
+    let get = |key_path: &str| -> Result<Option<String>> {
+        let keys = crate::local_files::parse_key_path(key_path)?;
+        format
+            .get(&contents, &keys)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    };
 
-        if let Some(pos) = line.find('=') {
-            let key = line[..pos].trim();
-            if key == secret_name {
-                let value = line[pos + 1..].trim();
-                return Ok(Some(value.to_string()));
+    match crate::bundle::parts(secret_name)? {
+        Some(parts) => {
+            let mut values = Vec::new();
+            for part in parts {
+                let value = get(&part)?;
+                values.push((part, value));
             }
+            crate::bundle::combine(values)
         }
+        None => get(key_path.unwrap_or(secret_name)),
+    }
+}
+
+/// Location of the pre-update snapshot of `env_path`. Snapshots live under
+/// `~/.birch/env-snapshots/`, one per file, so they never end up next to (or
+/// committed with) the project. The name is the file's base name plus a hash
+/// of its absolute path, which keeps it short however deep the project is.
+pub fn snapshot_path(env_path: &Path) -> Result<PathBuf> {
+    let absolute = env_path
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", env_path.display()))?;
+
+    let base_name: String = absolute
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .take(64)
+        .collect();
+    let digest = Blake2b::<U32>::digest(absolute.as_os_str().as_encoded_bytes());
+
+    Ok(snapshots_dir().join(format!("{}-{}", base_name, hex::encode(&digest[..8]))))
+}
+
+fn snapshots_dir() -> PathBuf {
+    crate::config::Config::birch_dir().join("env-snapshots")
+}
+
+fn file_kind(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") | Some("yaml") | Some("yml") | Some("toml") => "File",
+        _ => ".env file",
     }
+}
+
+fn resolve_env_path(env_file: Option<&str>) -> PathBuf {
+    PathBuf::from(env_file.unwrap_or(".env"))
+}
 
-    Ok(None)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_names_stay_short_for_deep_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let deep = ["a", "b", "c", "d"]
+            .iter()
+            .fold(dir.path().to_path_buf(), |path, c| path.join(c.repeat(120)));
+        fs::create_dir_all(&deep).unwrap();
+        let env_path = deep.join(".env.local");
+        fs::write(&env_path, "API_KEY=old\n").unwrap();
+        assert!(env_path.as_os_str().len() > 255);
+
+        let snapshot = snapshot_path(&env_path).unwrap();
+        let name = snapshot.file_name().unwrap().to_string_lossy().into_owned();
+        assert!(name.starts_with(".env.local-"), "{}", name);
+        assert!(name.len() < 100, "{}", name);
+        assert_eq!(snapshot.parent(), Some(snapshots_dir().as_path()));
+
+        // Stable per file, distinct across files with the same name.
+        assert_eq!(snapshot_path(&env_path).unwrap(), snapshot);
+        let other = dir.path().join(".env.local");
+        fs::write(&other, "API_KEY=old\n").unwrap();
+        assert_ne!(snapshot_path(&other).unwrap(), snapshot);
+    }
 }
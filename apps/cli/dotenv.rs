@@ -0,0 +1,476 @@
+use anyhow::Result;
+
+/// A parsed `.env` file that keeps the original text of every line so that
+/// writing it back only changes the entries that were explicitly set.
+#[derive(Debug, Clone, Default)]
+pub struct EnvFile {
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, Clone)]
+enum Line {
+    /// Comments, blank lines and anything that is not a `KEY=value` entry.
+    Raw(String),
+    Entry(Entry),
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    raw: String,
+    indent: String,
+    export: bool,
+    key: String,
+    value: String,
+    quote: Quote,
+    /// Whitespace and `# comment` following the value, without line ending.
+    comment: String,
+    line_ending: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quote {
+    None,
+    Single,
+    Double,
+    Backtick,
+}
+
+impl EnvFile {
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut lines = Vec::new();
+        let mut physical = contents.split_inclusive('\n').enumerate();
+
+        while let Some((number, line)) = physical.next() {
+            let (body, _) = split_line_ending(line);
+            let trimmed = body.trim_start();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                lines.push(Line::Raw(line.to_string()));
+                continue;
+            }
+
+            let indent = body[..body.len() - trimmed.len()].to_string();
+            let (export, rest) = match trimmed.strip_prefix("export") {
+                Some(rest) if rest.starts_with([' ', '\t']) => (true, rest.trim_start()),
+                _ => (false, trimmed),
+            };
+
+            let Some(eq) = rest.find('=') else {
+                lines.push(Line::Raw(line.to_string()));
+                continue;
+            };
+
+            let key = rest[..eq].trim_end();
+            if !is_valid_key(key) {
+                lines.push(Line::Raw(line.to_string()));
+                continue;
+            }
+
+            let mut raw = line.to_string();
+            let mut value_text = rest[eq + 1..].trim_start().to_string();
+            let quote = match value_text.chars().next() {
+                Some('"') => Quote::Double,
+                Some('\'') => Quote::Single,
+                Some('`') => Quote::Backtick,
+                _ => Quote::None,
+            };
+
+            let (value, comment) = if quote == Quote::None {
+                parse_unquoted(&value_text)
+            } else {
+                // Quoted values may continue over several physical lines.
+                loop {
+                    if let Some(parsed) = parse_quoted(&value_text, quote) {
+                        break parsed;
+                    }
+                    match physical.next() {
+                        Some((_, next)) => {
+                            value_text.push('\n');
+                            value_text.push_str(split_line_ending(next).0);
+                            raw.push_str(next);
+                        }
+                        None => anyhow::bail!(
+                            "Unterminated quoted value for '{}' starting at line {}",
+                            key,
+                            number + 1
+                        ),
+                    }
+                }
+            };
+
+            let line_ending = split_line_ending(&raw).1.to_string();
+
+            lines.push(Line::Entry(Entry {
+                raw,
+                indent,
+                export,
+                key: key.to_string(),
+                value,
+                quote,
+                comment,
+                line_ending,
+            }));
+        }
+
+        Ok(Self { lines })
+    }
+
+    /// Returns the value of `key`. As with most dotenv loaders, the last
+    /// definition wins when a key appears more than once.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries()
+            .filter(|e| e.key == key)
+            .last()
+            .map(|e| e.value.as_str())
+    }
+
+    /// Sets `key` to `value`, keeping the position, `export` prefix, quoting
+    /// style and inline comment of existing definitions. New keys are appended.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let mut found = false;
+
+        for line in &mut self.lines {
+            if let Line::Entry(entry) = line {
+                if entry.key == key {
+                    entry.value = value.to_string();
+                    entry.quote = choose_quote(value, entry.quote);
+                    entry.raw = entry.render();
+                    found = true;
+                }
+            }
+        }
+
+        if found {
+            return;
+        }
+
+        if let Some(last) = self.lines.last_mut() {
+            let text = match last {
+                Line::Raw(text) => text,
+                Line::Entry(entry) => &mut entry.raw,
+            };
+            if !text.ends_with('\n') {
+                text.push('\n');
+                if let Line::Entry(entry) = last {
+                    entry.line_ending = "\n".to_string();
+                }
+            }
+        }
+
+        // Follow the file's convention when every existing entry is exported.
+        let export = self.entries().next().is_some() && self.entries().all(|e| e.export);
+
+        let mut entry = Entry {
+            raw: String::new(),
+            indent: String::new(),
+            export,
+            key: key.to_string(),
+            value: value.to_string(),
+            quote: choose_quote(value, Quote::None),
+            comment: String::new(),
+            line_ending: "\n".to_string(),
+        };
+        entry.raw = entry.render();
+        self.lines.push(Line::Entry(entry));
+    }
+
+    /// Every key, once, in the order of first definition.
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = Vec::new();
+        for entry in self.entries() {
+            if !keys.contains(&entry.key.as_str()) {
+                keys.push(&entry.key);
+            }
+        }
+        keys
+    }
+
+    /// Removes every definition of `key`. Returns false if there was none.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.lines.len();
+        self.lines
+            .retain(|line| !matches!(line, Line::Entry(entry) if entry.key == key));
+        self.lines.len() != before
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Entry(entry) => Some(entry),
+            Line::Raw(_) => None,
+        })
+    }
+}
+
+impl std::fmt::Display for EnvFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            match line {
+                Line::Raw(text) => f.write_str(text)?,
+                Line::Entry(entry) => f.write_str(&entry.raw)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Entry {
+    fn render(&self) -> String {
+        format!(
+            "{}{}{}={}{}{}",
+            self.indent,
+            if self.export { "export " } else { "" },
+            self.key,
+            quote_value(&self.value, self.quote),
+            self.comment,
+            self.line_ending
+        )
+    }
+}
+
+fn split_line_ending(line: &str) -> (&str, &str) {
+    if let Some(body) = line.strip_suffix("\r\n") {
+        (body, "\r\n")
+    } else if let Some(body) = line.strip_suffix('\n') {
+        (body, "\n")
+    } else {
+        (line, "")
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// Unquoted values end at the first `#` preceded by whitespace.
+fn parse_unquoted(text: &str) -> (String, String) {
+    let comment_start = text
+        .char_indices()
+        .find(|&(i, c)| c == '#' && i > 0 && text[..i].ends_with([' ', '\t']))
+        .map(|(i, _)| i);
+
+    match comment_start {
+        Some(i) => {
+            let value = text[..i].trim_end();
+            (value.to_string(), text[value.len()..].to_string())
+        }
+        None => (text.trim_end().to_string(), String::new()),
+    }
+}
+
+/// Returns `None` while the closing quote has not been seen yet.
+fn parse_quoted(text: &str, quote: Quote) -> Option<(String, String)> {
+    let delimiter = match quote {
+        Quote::Double => '"',
+        Quote::Single => '\'',
+        Quote::Backtick => '`',
+        Quote::None => unreachable!("parse_quoted called for an unquoted value"),
+    };
+
+    let mut value = String::new();
+    let mut chars = text.char_indices().skip(1);
+
+    while let Some((i, c)) = chars.next() {
+        if c == delimiter {
+            let rest = &text[i + 1..];
+            return Some((value, rest.to_string()));
+        }
+
+        if c == '\\' && quote == Quote::Double {
+            match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, escaped @ ('"' | '\\' | '$'))) => value.push(escaped),
+                Some((_, other)) => {
+                    value.push('\\');
+                    value.push(other);
+                }
+                None => value.push('\\'),
+            }
+            continue;
+        }
+
+        value.push(c);
+    }
+
+    None
+}
+
+/// Keeps the existing quoting style when it can represent `value`, otherwise
+/// picks the least surprising one.
+fn choose_quote(value: &str, current: Quote) -> Quote {
+    let needs_quotes = value.is_empty()
+        || value.starts_with(['"', '\'', '`'])
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '#' | '\\' | '$'));
+    let multiline = value.contains(['\n', '\r']);
+
+    match current {
+        Quote::Double => Quote::Double,
+        Quote::Single if !value.contains('\'') && !multiline => Quote::Single,
+        Quote::Backtick if !value.contains('`') && !multiline => Quote::Backtick,
+        Quote::None if !needs_quotes => Quote::None,
+        // Single quotes avoid `$` expansion by loaders that support it.
+        _ if value.contains('$') && !value.contains('\'') && !multiline => Quote::Single,
+        _ => Quote::Double,
+    }
+}
+
+fn quote_value(value: &str, quote: Quote) -> String {
+    match quote {
+        Quote::None => value.to_string(),
+        Quote::Single => format!("'{}'", value),
+        Quote::Backtick => format!("`{}`", value),
+        Quote::Double => {
+            let mut quoted = String::from("\"");
+            for c in value.chars() {
+                match c {
+                    '"' => quoted.push_str("\\\""),
+                    '\\' => quoted.push_str("\\\\"),
+                    // Keeps loaders that interpolate from expanding `$VAR`.
+                    '$' => quoted.push_str("\\$"),
+                    '\n' => quoted.push_str("\\n"),
+                    '\r' => quoted.push_str("\\r"),
+                    '\t' => quoted.push_str("\\t"),
+                    c => quoted.push(c),
+                }
+            }
+            quoted.push('"');
+            quoted
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> EnvFile {
+        EnvFile::parse(contents).unwrap()
+    }
+
+    #[test]
+    fn parses_unquoted_values_and_inline_comments() {
+        let env = parse("A=plain\nB=with#hash\nC=value # comment\nD=\n");
+        assert_eq!(env.get("A"), Some("plain"));
+        assert_eq!(env.get("B"), Some("with#hash"));
+        assert_eq!(env.get("C"), Some("value"));
+        assert_eq!(env.get("D"), Some(""));
+    }
+
+    #[test]
+    fn parses_quoted_values() {
+        let env = parse("A=\"two words\" # c\nB='single # kept'\nC='no $expand \\n'\nD=`tick`\n");
+        assert_eq!(env.get("A"), Some("two words"));
+        assert_eq!(env.get("B"), Some("single # kept"));
+        assert_eq!(env.get("C"), Some("no $expand \\n"));
+        assert_eq!(env.get("D"), Some("tick"));
+    }
+
+    #[test]
+    fn parses_double_quote_escapes() {
+        let env = parse(r#"A="line\nnext\ttab \"q\" \\ \$HOME \x""#);
+        assert_eq!(env.get("A"), Some("line\nnext\ttab \"q\" \\ $HOME \\x"));
+    }
+
+    #[test]
+    fn parses_export_prefix() {
+        let env = parse("export A=1\n  export\tB=2\nexported=3\n");
+        assert_eq!(env.get("A"), Some("1"));
+        assert_eq!(env.get("B"), Some("2"));
+        assert_eq!(env.get("exported"), Some("3"));
+    }
+
+    #[test]
+    fn parses_multiline_values() {
+        let contents = "KEY=\"-----BEGIN-----\nabc\n-----END-----\"\nNEXT=1\n";
+        let env = parse(contents);
+        assert_eq!(env.get("KEY"), Some("-----BEGIN-----\nabc\n-----END-----"));
+        assert_eq!(env.get("NEXT"), Some("1"));
+        assert_eq!(env.to_string(), contents);
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        let err = EnvFile::parse("A=1\nB=\"open\nC=2\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn keeps_comments_blank_lines_and_invalid_lines() {
+        let contents = "# header\n\nnot an entry\n1BAD=x\nA=1 # keep\r\n";
+        let env = parse(contents);
+        assert_eq!(env.keys(), vec!["A"]);
+        assert_eq!(env.to_string(), contents);
+    }
+
+    #[test]
+    fn last_definition_wins() {
+        let env = parse("A=1\nA=2\n");
+        assert_eq!(env.get("A"), Some("2"));
+        assert_eq!(env.keys(), vec!["A"]);
+    }
+
+    #[test]
+    fn set_keeps_layout_of_existing_entries() {
+        let mut env = parse("# c\nexport A='old' # note\nB=keep\n");
+        env.set("A", "new");
+        assert_eq!(env.to_string(), "# c\nexport A='new' # note\nB=keep\n");
+    }
+
+    #[test]
+    fn set_appends_new_keys() {
+        let mut env = parse("A=1");
+        env.set("B", "two words");
+        assert_eq!(env.to_string(), "A=1\nB=\"two words\"\n");
+
+        let mut exported = parse("export A=1\n");
+        exported.set("B", "2");
+        assert_eq!(exported.to_string(), "export A=1\nexport B=2\n");
+    }
+
+    #[test]
+    fn set_round_trips_awkward_values() {
+        for value in [
+            "",
+            "a b",
+            "quote\"d",
+            "single'q",
+            "back\\slash",
+            "multi\nline",
+            "$VAR",
+            "it's $HOME",
+            "#hash",
+        ] {
+            let mut env = parse("A=1\n");
+            env.set("A", value);
+            let written = env.to_string();
+            assert_eq!(parse(&written).get("A"), Some(value), "{}", written);
+        }
+    }
+
+    #[test]
+    fn dollar_is_never_left_for_interpolation() {
+        let mut env = parse("A=1\n");
+        env.set("A", "$VAR");
+        assert_eq!(env.to_string(), "A='$VAR'\n");
+
+        env.set("A", "it's $VAR");
+        assert_eq!(env.to_string(), "A=\"it's \\$VAR\"\n");
+
+        let mut double = parse("A=\"x\"\n");
+        double.set("A", "$VAR");
+        assert_eq!(double.to_string(), "A=\"\\$VAR\"\n");
+    }
+
+    #[test]
+    fn remove_drops_every_definition() {
+        let mut env = parse("A=1\nB=2\nA=3\n");
+        assert!(env.remove("A"));
+        assert!(!env.remove("A"));
+        assert_eq!(env.to_string(), "B=2\n");
+    }
+}
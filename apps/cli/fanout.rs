@@ -0,0 +1,688 @@
+use crate::connectors::{mask_secret, ConnectorSettings};
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Target {
+    pub env: String,
+    pub service: Option<String>,
+    pub env_file: Option<String>,
+    /// Key path inside a structured `env_file` (defaults to the secret name).
+    pub path: Option<String>,
+    #[serde(default)]
+    pub redeploy: bool,
+    #[serde(flatten)]
+    pub settings: BTreeMap<String, String>,
+}
+
+impl Target {
+    pub fn service(env: &str, service: &str, redeploy: bool) -> Self {
+        Self {
+            env: env.to_string(),
+            service: Some(service.to_string()),
+            env_file: None,
+            path: None,
+            redeploy,
+            settings: BTreeMap::new(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match (&self.service, &self.env_file) {
+            (Some(service), _) => format!("{}/{}", self.env, service),
+            (None, Some(env_file)) => match &self.path {
+                Some(path) => format!("{}/{}:{}", self.env, env_file, path),
+                None => format!("{}/{}", self.env, env_file),
+            },
+            (None, None) => format!("{}/.env", self.env),
+        }
+    }
+
+    pub fn connector_settings(&self) -> ConnectorSettings {
+        ConnectorSettings(self.settings.clone())
+    }
+
+    pub async fn read(&self, secret_name: &str) -> Result<Option<String>> {
+        if self.env == "dev" {
+            return crate::dev::get_local_secret(
+                secret_name,
+                self.env_file.as_deref(),
+                self.path.as_deref(),
+            );
+        }
+
+        let connector =
+            crate::prod::get_connector(self.service.as_deref(), &self.connector_settings())?;
+        let Some(parts) = crate::bundle::parts(secret_name)? else {
+            return crate::connectors::read_secret(connector.as_ref(), secret_name).await;
+        };
+
+        let mut values = Vec::new();
+        for part in parts {
+            let value = crate::connectors::read_secret(connector.as_ref(), &part).await?;
+            values.push((part, value));
+        }
+        crate::bundle::combine(values)
+    }
+
+    async fn write(&self, secret_name: &str, value: &str) -> Result<()> {
+        if self.env == "dev" {
+            return crate::dev::update_local_file(
+                secret_name,
+                value,
+                self.env_file.as_deref(),
+                self.path.as_deref(),
+            )
+            .await;
+        }
+
+        let connector =
+            crate::prod::get_connector(self.service.as_deref(), &self.connector_settings())?;
+        let Some(parts) = crate::bundle::parts(secret_name)? else {
+            return connector.update_secret(secret_name, value).await;
+        };
+
+        for (part, part_value) in crate::bundle::split(&parts, value)? {
+            connector.update_secret(&part, &part_value).await?;
+        }
+        Ok(())
+    }
+
+    /// Removes the secret from a target that did not hold it before.
+    async fn remove(&self, secret_name: &str) -> Result<()> {
+        if self.env == "dev" {
+            anyhow::bail!("local files do not support removal");
+        }
+
+        let connector =
+            crate::prod::get_connector(self.service.as_deref(), &self.connector_settings())?;
+        if !connector.capabilities().await.delete {
+            return Err(crate::connectors::unsupported("delete_secret"));
+        }
+        match crate::bundle::parts(secret_name)? {
+            Some(parts) => {
+                for part in parts {
+                    connector.delete_secret(&part).await?;
+                }
+                Ok(())
+            }
+            None => connector.delete_secret(secret_name).await,
+        }
+    }
+
+    async fn refresh(&self) -> Result<()> {
+        if self.env == "dev" || !self.redeploy {
+            return Ok(());
+        }
+
+        let connector =
+            crate::prod::get_connector(self.service.as_deref(), &self.connector_settings())?;
+        connector.trigger_refresh(self.service.as_deref()).await
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TargetStatus {
+    Updated,
+    Failed { error: String },
+    Restored,
+    RestoreFailed { error: String },
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetResult {
+    pub target: String,
+    #[serde(flatten)]
+    pub status: TargetStatus,
+}
+
+/// Passes a newly generated value through the connector of every target, in
+/// order, before anything is written. Connectors that need to (databases
+/// alternating between users) shape it there, so staged rotations, version
+/// history and rollbacks all carry the final value.
+pub async fn prepare_value(secret_name: &str, value: String, targets: &[Target]) -> Result<String> {
+    let mut value = value;
+
+    for target in targets {
+        if target.env == "dev" || target.service.is_none() {
+            continue;
+        }
+
+        let connector =
+            crate::prod::get_connector(target.service.as_deref(), &target.connector_settings())?;
+        let current = crate::versions::current_value(&target.env, secret_name)?;
+        value = connector
+            .prepare_value(secret_name, &value, current.as_deref())
+            .await?;
+    }
+
+    Ok(value)
+}
+
+/// Writes `value` to every target in order. If a target fails, targets that
+/// were already written are restored to the value they held before, newest
+/// first, so the secret is never left split across targets.
+pub async fn apply(secret_name: &str, value: &str, targets: &[Target]) -> Result<()> {
+    let results = apply_all(secret_name, value, targets).await;
+
+    print_report(&results);
+
+    if let Some(failed) = results.iter().find_map(|r| match &r.status {
+        TargetStatus::Failed { error } => Some((r.target.clone(), error.clone())),
+        _ => None,
+    }) {
+        let restored = results
+            .iter()
+            .filter(|r| matches!(r.status, TargetStatus::Restored))
+            .count();
+        anyhow::bail!(
+            "Update of {} failed ({}); {} target(s) restored",
+            failed.0,
+            failed.1,
+            restored
+        );
+    }
+
+    crate::revocation::retire_versions(secret_name, targets).await;
+
+    Ok(())
+}
+
+pub async fn apply_all(secret_name: &str, value: &str, targets: &[Target]) -> Vec<TargetResult> {
+    apply_with(&Live, secret_name, value, targets).await
+}
+
+/// What applying a value does to targets, so the write and restore order can
+/// be checked without real connectors.
+#[async_trait]
+trait TargetIo: Sync {
+    async fn read(&self, target: &Target, secret_name: &str) -> Result<Option<String>>;
+    async fn write(&self, target: &Target, secret_name: &str, value: &str) -> Result<()>;
+    async fn remove(&self, target: &Target, secret_name: &str) -> Result<()>;
+    async fn refresh(&self, target: &Target) -> Result<()>;
+    /// The value birch last recorded for the secret.
+    fn recorded(&self, target: &Target, secret_name: &str) -> Result<Option<String>>;
+    /// Whether a failed write may have left some parts of a bundle written.
+    fn is_bundle(&self, secret_name: &str) -> bool;
+    fn log(
+        &self,
+        secret_name: &str,
+        target: &Target,
+        action: crate::audit::AuditAction,
+        success: bool,
+        value: &str,
+    );
+}
+
+struct Live;
+
+#[async_trait]
+impl TargetIo for Live {
+    async fn read(&self, target: &Target, secret_name: &str) -> Result<Option<String>> {
+        target.read(secret_name).await
+    }
+
+    async fn write(&self, target: &Target, secret_name: &str, value: &str) -> Result<()> {
+        target.write(secret_name, value).await
+    }
+
+    async fn remove(&self, target: &Target, secret_name: &str) -> Result<()> {
+        target.remove(secret_name).await
+    }
+
+    async fn refresh(&self, target: &Target) -> Result<()> {
+        target.refresh().await
+    }
+
+    fn recorded(&self, target: &Target, secret_name: &str) -> Result<Option<String>> {
+        crate::versions::current_value(&target.env, secret_name)
+    }
+
+    fn is_bundle(&self, secret_name: &str) -> bool {
+        matches!(crate::bundle::parts(secret_name), Ok(Some(_)))
+    }
+
+    fn log(
+        &self,
+        secret_name: &str,
+        target: &Target,
+        action: crate::audit::AuditAction,
+        success: bool,
+        value: &str,
+    ) {
+        log(secret_name, target, action, success, value)
+    }
+}
+
+/// The value `target` holds before it is written. Write-only targets (GitHub,
+/// Vercel, ...) cannot be read back, so the value birch last recorded for the
+/// secret stands in for it.
+async fn previous_value(
+    io: &dyn TargetIo,
+    target: &Target,
+    secret_name: &str,
+) -> Result<Option<String>> {
+    let read_error = match io.read(target, secret_name).await {
+        Ok(previous) => return Ok(previous),
+        Err(e) => e,
+    };
+
+    match io.recorded(target, secret_name) {
+        Ok(Some(recorded)) => Ok(Some(recorded)),
+        Ok(None) => Err(anyhow::anyhow!(
+            "previous value was unreadable ({}) and no version is recorded",
+            read_error
+        )),
+        Err(e) => Err(anyhow::anyhow!(
+            "previous value was unreadable ({}) and the recorded version could not be loaded: {}",
+            read_error,
+            e
+        )),
+    }
+}
+
+async fn apply_with(
+    io: &dyn TargetIo,
+    secret_name: &str,
+    value: &str,
+    targets: &[Target],
+) -> Vec<TargetResult> {
+    let mut results: Vec<TargetResult> = targets
+        .iter()
+        .map(|t| TargetResult {
+            target: t.label(),
+            status: TargetStatus::Skipped,
+        })
+        .collect();
+
+    // (index, previous value) of every target that received the new value
+    let mut written: Vec<(usize, Result<Option<String>>)> = Vec::new();
+    let mut failure = None;
+
+    for (i, target) in targets.iter().enumerate() {
+        let previous = previous_value(io, target, secret_name).await;
+
+        if let Err(e) = io.write(target, secret_name, value).await {
+            // Some parts of a bundle may have been written, so the target is
+            // restored like the ones before it.
+            if io.is_bundle(secret_name) {
+                written.push((i, previous));
+            }
+            failure = Some((i, e));
+            break;
+        }
+        written.push((i, previous));
+
+        if let Err(e) = io.refresh(target).await {
+            failure = Some((i, e));
+            break;
+        }
+
+        results[i].status = TargetStatus::Updated;
+        io.log(
+            secret_name,
+            target,
+            crate::audit::AuditAction::Update,
+            true,
+            value,
+        );
+    }
+
+    let Some((failed_index, error)) = failure else {
+        return results;
+    };
+
+    let failed_target = &targets[failed_index];
+    results[failed_index].status = TargetStatus::Failed {
+        error: error.to_string(),
+    };
+    io.log(
+        secret_name,
+        failed_target,
+        crate::audit::AuditAction::Update,
+        false,
+        value,
+    );
+
+    for (i, previous) in written.into_iter().rev() {
+        let target = &targets[i];
+
+        let restore = match &previous {
+            Ok(Some(previous)) => io.write(target, secret_name, previous).await,
+            // The target did not hold the secret before, so it is removed again.
+            Ok(None) => io.remove(target, secret_name).await.map_err(|e| {
+                anyhow::anyhow!("target had no previous value and removal failed: {}", e)
+            }),
+            Err(e) => Err(anyhow::anyhow!("{}", e)),
+        };
+        // Targets before the failed one were refreshed onto the new value, so
+        // they are refreshed again to pick the restored one up.
+        let restore = match restore {
+            Ok(()) if i < failed_index => io
+                .refresh(target)
+                .await
+                .map_err(|e| anyhow::anyhow!("restored but refresh failed: {}", e)),
+            restore => restore,
+        };
+
+        match restore {
+            Ok(()) => {
+                if i != failed_index {
+                    results[i].status = TargetStatus::Restored;
+                }
+                if let Ok(previous) = &previous {
+                    io.log(
+                        secret_name,
+                        target,
+                        crate::audit::AuditAction::Restore,
+                        true,
+                        previous.as_deref().unwrap_or_default(),
+                    );
+                }
+            }
+            Err(e) => {
+                if i != failed_index {
+                    results[i].status = TargetStatus::RestoreFailed {
+                        error: e.to_string(),
+                    };
+                }
+                io.log(
+                    secret_name,
+                    target,
+                    crate::audit::AuditAction::Restore,
+                    false,
+                    previous
+                        .as_ref()
+                        .ok()
+                        .and_then(|p| p.as_deref())
+                        .unwrap_or_default(),
+                );
+            }
+        }
+    }
+
+    results
+}
+
+pub fn print_report(results: &[TargetResult]) {
+    println!("📦 Target results:");
+    for result in results {
+        let (symbol, detail) = match &result.status {
+            TargetStatus::Updated => ("✓", "updated".to_string()),
+            TargetStatus::Failed { error } => ("✗", format!("failed: {}", error)),
+            TargetStatus::Restored => ("↺", "restored previous value".to_string()),
+            TargetStatus::RestoreFailed { error } => ("⚠", format!("restore failed: {}", error)),
+            TargetStatus::Skipped => ("-", "skipped".to_string()),
+        };
+        println!("   {} {:<28} {}", symbol, result.target, detail);
+    }
+}
+
+fn log(
+    secret_name: &str,
+    target: &Target,
+    action: crate::audit::AuditAction,
+    success: bool,
+    value: &str,
+) {
+    let result = crate::audit::AuditLogger::new().and_then(|logger| {
+        logger.log(
+            secret_name.to_string(),
+            target.env.clone(),
+            Some(target.label()),
+            action,
+            success,
+            (!value.is_empty()).then(|| mask_secret(value)),
+        )
+    });
+
+    if let Err(e) = result {
+        tracing::warn!("Failed to write audit entry: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::sync::Mutex;
+
+    /// Targets kept in memory, keyed by label. Writing one of the
+    /// `failing_writes` (label, value) pairs or refreshing one of the
+    /// `failing_refreshes` labels fails; `ops` records every call in order.
+    #[derive(Default)]
+    struct Fake {
+        values: Mutex<BTreeMap<String, String>>,
+        write_only: BTreeSet<String>,
+        recorded: Option<String>,
+        failing_writes: BTreeSet<(String, String)>,
+        failing_refreshes: BTreeSet<String>,
+        ops: Mutex<Vec<String>>,
+    }
+
+    impl Fake {
+        fn with(values: &[(&str, &str)]) -> Self {
+            Self {
+                values: Mutex::new(
+                    values
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                ),
+                ..Default::default()
+            }
+        }
+
+        fn op(&self, op: String) {
+            self.ops.lock().unwrap().push(op);
+        }
+
+        fn ops(&self) -> Vec<String> {
+            self.ops.lock().unwrap().clone()
+        }
+
+        fn value(&self, label: &str) -> Option<String> {
+            self.values.lock().unwrap().get(label).cloned()
+        }
+    }
+
+    #[async_trait]
+    impl TargetIo for Fake {
+        async fn read(&self, target: &Target, _secret_name: &str) -> Result<Option<String>> {
+            if self.write_only.contains(&target.label()) {
+                return Err(crate::connectors::WriteOnly { updated_at: None }.into());
+            }
+            Ok(self.value(&target.label()))
+        }
+
+        async fn write(&self, target: &Target, _secret_name: &str, value: &str) -> Result<()> {
+            let label = target.label();
+            self.op(format!("write {} {}", label, value));
+            if self
+                .failing_writes
+                .contains(&(label.clone(), value.to_string()))
+            {
+                anyhow::bail!("write rejected");
+            }
+            self.values.lock().unwrap().insert(label, value.to_string());
+            Ok(())
+        }
+
+        async fn remove(&self, target: &Target, _secret_name: &str) -> Result<()> {
+            self.op(format!("remove {}", target.label()));
+            self.values.lock().unwrap().remove(&target.label());
+            Ok(())
+        }
+
+        async fn refresh(&self, target: &Target) -> Result<()> {
+            let label = target.label();
+            self.op(format!("refresh {}", label));
+            if self.failing_refreshes.contains(&label) {
+                anyhow::bail!("deploy failed");
+            }
+            Ok(())
+        }
+
+        fn recorded(&self, _target: &Target, _secret_name: &str) -> Result<Option<String>> {
+            Ok(self.recorded.clone())
+        }
+
+        fn is_bundle(&self, _secret_name: &str) -> bool {
+            false
+        }
+
+        fn log(
+            &self,
+            _secret_name: &str,
+            _target: &Target,
+            _action: crate::audit::AuditAction,
+            _success: bool,
+            _value: &str,
+        ) {
+        }
+    }
+
+    fn targets(names: &[&str]) -> Vec<Target> {
+        names
+            .iter()
+            .map(|name| Target::service("prod", name, true))
+            .collect()
+    }
+
+    fn statuses(results: &[TargetResult]) -> Vec<&'static str> {
+        results
+            .iter()
+            .map(|r| match r.status {
+                TargetStatus::Updated => "updated",
+                TargetStatus::Failed { .. } => "failed",
+                TargetStatus::Restored => "restored",
+                TargetStatus::RestoreFailed { .. } => "restore_failed",
+                TargetStatus::Skipped => "skipped",
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn writes_targets_in_order() {
+        let io = Fake::with(&[("prod/a", "old"), ("prod/b", "old")]);
+        let results = apply_with(&io, "API_KEY", "new", &targets(&["a", "b"])).await;
+
+        assert_eq!(statuses(&results), ["updated", "updated"]);
+        assert_eq!(
+            io.ops(),
+            [
+                "write prod/a new",
+                "refresh prod/a",
+                "write prod/b new",
+                "refresh prod/b"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_write_restores_earlier_targets_newest_first() {
+        let mut io = Fake::with(&[
+            ("prod/a", "a-old"),
+            ("prod/b", "b-old"),
+            ("prod/c", "c-old"),
+        ]);
+        io.failing_writes
+            .insert(("prod/c".to_string(), "new".to_string()));
+
+        let results = apply_with(&io, "API_KEY", "new", &targets(&["a", "b", "c", "d"])).await;
+
+        assert_eq!(
+            statuses(&results),
+            ["restored", "restored", "failed", "skipped"]
+        );
+        assert_eq!(
+            io.ops(),
+            [
+                "write prod/a new",
+                "refresh prod/a",
+                "write prod/b new",
+                "refresh prod/b",
+                "write prod/c new",
+                "write prod/b b-old",
+                "refresh prod/b",
+                "write prod/a a-old",
+                "refresh prod/a",
+            ]
+        );
+        assert_eq!(io.value("prod/a").as_deref(), Some("a-old"));
+        assert_eq!(io.value("prod/b").as_deref(), Some("b-old"));
+        assert_eq!(io.value("prod/c").as_deref(), Some("c-old"));
+    }
+
+    #[tokio::test]
+    async fn failed_refresh_restores_the_target_itself() {
+        let mut io = Fake::with(&[("prod/a", "a-old"), ("prod/b", "b-old")]);
+        io.failing_refreshes.insert("prod/b".to_string());
+
+        let results = apply_with(&io, "API_KEY", "new", &targets(&["a", "b"])).await;
+
+        assert_eq!(statuses(&results), ["restored", "failed"]);
+        assert_eq!(
+            io.ops(),
+            [
+                "write prod/a new",
+                "refresh prod/a",
+                "write prod/b new",
+                "refresh prod/b",
+                "write prod/b b-old",
+                "write prod/a a-old",
+                "refresh prod/a",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn targets_without_a_previous_value_are_removed() {
+        let mut io = Fake::with(&[("prod/b", "b-old")]);
+        io.failing_writes
+            .insert(("prod/b".to_string(), "new".to_string()));
+
+        let results = apply_with(&io, "API_KEY", "new", &targets(&["a", "b"])).await;
+
+        assert_eq!(statuses(&results), ["restored", "failed"]);
+        assert!(io.ops().contains(&"remove prod/a".to_string()));
+        assert_eq!(io.value("prod/a"), None);
+    }
+
+    #[tokio::test]
+    async fn write_only_targets_restore_the_recorded_value() {
+        let mut io = Fake::with(&[("prod/a", "a-old"), ("prod/b", "b-old")]);
+        io.write_only.insert("prod/a".to_string());
+        io.recorded = Some("recorded".to_string());
+        io.failing_writes
+            .insert(("prod/b".to_string(), "new".to_string()));
+
+        let results = apply_with(&io, "API_KEY", "new", &targets(&["a", "b"])).await;
+
+        assert_eq!(statuses(&results), ["restored", "failed"]);
+        assert_eq!(io.value("prod/a").as_deref(), Some("recorded"));
+    }
+
+    #[tokio::test]
+    async fn write_only_targets_without_a_recorded_value_fail_to_restore() {
+        let mut io = Fake::with(&[("prod/a", "a-old"), ("prod/b", "b-old")]);
+        io.write_only.insert("prod/a".to_string());
+        io.failing_writes
+            .insert(("prod/b".to_string(), "new".to_string()));
+
+        let results = apply_with(&io, "API_KEY", "new", &targets(&["a", "b"])).await;
+
+        assert_eq!(statuses(&results), ["restore_failed", "failed"]);
+        match &results[0].status {
+            TargetStatus::RestoreFailed { error } => {
+                assert!(error.contains("no version is recorded"), "{}", error)
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!(io.value("prod/a").as_deref(), Some("new"));
+    }
+}
@@ -0,0 +1,338 @@
+use crate::config::{CharClass, GenerateConfig, SecretFormat};
+use anyhow::{Context, Result};
+use base64::Engine;
+use rand::rngs::OsRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!#$%&*+-.:=?@^_~";
+
+pub fn generate(profile: &GenerateConfig) -> Result<String> {
+    validate(profile)?;
+
+    let body = match profile.format {
+        SecretFormat::Charset => generate_from_charset(profile)?,
+        SecretFormat::Hex => hex::encode(random_bytes(profile.length.unwrap_or(32))),
+        SecretFormat::Base64 => base64::engine::general_purpose::STANDARD
+            .encode(random_bytes(profile.length.unwrap_or(32))),
+        SecretFormat::Base64url => base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(random_bytes(profile.length.unwrap_or(32))),
+        SecretFormat::Uuid => uuid::Uuid::new_v4().to_string(),
+        SecretFormat::Passphrase => generate_passphrase(profile),
+        SecretFormat::Ed25519 => generate_ed25519()?,
+    };
+
+    Ok(match &profile.prefix {
+        Some(prefix) => format!("{}{}", prefix, body),
+        None => body,
+    })
+}
+
+/// Writes (or prints) the public half of a generated keypair so it can be
+/// distributed to verifiers before the private key goes live.
+pub fn publish_public_key(profile: &GenerateConfig, value: &str) -> Result<()> {
+    if profile.format != SecretFormat::Ed25519 {
+        return Ok(());
+    }
+
+    let private_pem = value
+        .strip_prefix(profile.prefix.as_deref().unwrap_or(""))
+        .unwrap_or(value);
+    let public_pem = ed25519_public_key_pem(private_pem)?;
+
+    match &profile.public_key_file {
+        Some(path) => {
+            std::fs::write(path, &public_pem)
+                .with_context(|| format!("Failed to write public key to {}", path))?;
+            println!("🔏 Public key written to {}", path);
+        }
+        None => println!("🔏 Public key:\n{}", public_pem),
+    }
+
+    Ok(())
+}
+
+/// Rejects profiles that would panic or produce an empty secret.
+pub fn validate(profile: &GenerateConfig) -> Result<()> {
+    let uses_length = !matches!(profile.format, SecretFormat::Uuid | SecretFormat::Ed25519);
+    if uses_length && profile.length == Some(0) {
+        anyhow::bail!("Generation profile length must be at least 1");
+    }
+
+    if profile.format != SecretFormat::Charset {
+        return Ok(());
+    }
+
+    let length = profile.length.unwrap_or(32);
+    let classes = &profile.charset;
+
+    if classes.is_empty() {
+        anyhow::bail!("Generation profile has an empty charset");
+    }
+
+    if classes.contains(&CharClass::Symbols)
+        && profile.symbols.as_deref().is_some_and(str::is_empty)
+    {
+        anyhow::bail!("Generation profile has an empty symbols set");
+    }
+
+    for class in &profile.require {
+        if !classes.contains(class) {
+            anyhow::bail!(
+                "Required character class '{:?}' is not part of the charset",
+                class
+            );
+        }
+    }
+
+    if profile.require.len() > length {
+        anyhow::bail!(
+            "Length {} is too short to include {} required character classes",
+            length,
+            profile.require.len()
+        );
+    }
+
+    Ok(())
+}
+
+fn generate_from_charset(profile: &GenerateConfig) -> Result<String> {
+    let length = profile.length.unwrap_or(32);
+    let classes = &profile.charset;
+
+    let alphabet: Vec<char> = classes
+        .iter()
+        .flat_map(|class| class_chars(profile, *class).chars())
+        .collect();
+
+    let mut rng = OsRng;
+    let mut chars: Vec<char> = profile
+        .require
+        .iter()
+        .map(|class| {
+            let set: Vec<char> = class_chars(profile, *class).chars().collect();
+            set[rng.gen_range(0..set.len())]
+        })
+        .collect();
+
+    while chars.len() < length {
+        chars.push(alphabet[rng.gen_range(0..alphabet.len())]);
+    }
+
+    chars.shuffle(&mut rng);
+
+    Ok(chars.into_iter().collect())
+}
+
+fn class_chars(profile: &GenerateConfig, class: CharClass) -> &str {
+    match class {
+        CharClass::Lower => LOWER,
+        CharClass::Upper => UPPER,
+        CharClass::Digits => DIGITS,
+        CharClass::Symbols => profile.symbols.as_deref().unwrap_or(SYMBOLS),
+    }
+}
+
+fn generate_passphrase(profile: &GenerateConfig) -> String {
+    let words = bip39::Language::English.word_list();
+    let count = profile.length.unwrap_or(6);
+    let separator = profile.separator.as_deref().unwrap_or("-");
+
+    let mut rng = OsRng;
+    (0..count)
+        .map(|_| words[rng.gen_range(0..words.len())])
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn generate_ed25519() -> Result<String> {
+    use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+    use ed25519_dalek::pkcs8::EncodePrivateKey;
+
+    let mut seed = [0u8; 32];
+    OsRng.fill_bytes(&mut seed);
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&seed);
+
+    Ok(signing_key
+        .to_pkcs8_pem(LineEnding::LF)
+        .context("Failed to encode Ed25519 private key")?
+        .to_string())
+}
+
+fn ed25519_public_key_pem(private_pem: &str) -> Result<String> {
+    use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+    use ed25519_dalek::pkcs8::{DecodePrivateKey, EncodePublicKey};
+
+    let signing_key = ed25519_dalek::SigningKey::from_pkcs8_pem(private_pem)
+        .context("Secret value is not an Ed25519 private key")?;
+
+    signing_key
+        .verifying_key()
+        .to_public_key_pem(LineEnding::LF)
+        .context("Failed to encode Ed25519 public key")
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn charset(length: usize, classes: &[CharClass]) -> GenerateConfig {
+        GenerateConfig {
+            length: Some(length),
+            charset: classes.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn charset_respects_length_and_alphabet() {
+        let profile = charset(40, &[CharClass::Digits]);
+        let value = generate(&profile).unwrap();
+
+        assert_eq!(value.len(), 40);
+        assert!(value.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn charset_includes_every_required_class() {
+        let mut profile = charset(
+            4,
+            &[
+                CharClass::Lower,
+                CharClass::Upper,
+                CharClass::Digits,
+                CharClass::Symbols,
+            ],
+        );
+        profile.require = profile.charset.clone();
+        profile.symbols = Some("#".to_string());
+
+        for _ in 0..20 {
+            let value = generate(&profile).unwrap();
+            assert!(value.chars().any(|c| c.is_ascii_lowercase()));
+            assert!(value.chars().any(|c| c.is_ascii_uppercase()));
+            assert!(value.chars().any(|c| c.is_ascii_digit()));
+            assert!(value.contains('#'));
+        }
+    }
+
+    #[test]
+    fn prefix_is_prepended() {
+        let mut profile = charset(8, &[CharClass::Lower]);
+        profile.prefix = Some("sk_".to_string());
+
+        let value = generate(&profile).unwrap();
+        assert!(value.starts_with("sk_"));
+        assert_eq!(value.len(), 11);
+    }
+
+    #[test]
+    fn empty_symbols_are_rejected() {
+        let mut profile = charset(16, &[CharClass::Symbols]);
+        profile.symbols = Some(String::new());
+
+        assert!(generate(&profile).is_err());
+    }
+
+    #[test]
+    fn empty_charset_is_rejected() {
+        assert!(generate(&charset(16, &[])).is_err());
+    }
+
+    #[test]
+    fn zero_length_is_rejected() {
+        assert!(generate(&charset(0, &[CharClass::Lower])).is_err());
+
+        for format in [
+            SecretFormat::Hex,
+            SecretFormat::Base64,
+            SecretFormat::Passphrase,
+        ] {
+            let profile = GenerateConfig {
+                format,
+                length: Some(0),
+                ..Default::default()
+            };
+            assert!(generate(&profile).is_err(), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn required_class_outside_charset_is_rejected() {
+        let mut profile = charset(16, &[CharClass::Lower]);
+        profile.require = vec![CharClass::Digits];
+
+        assert!(generate(&profile).is_err());
+    }
+
+    #[test]
+    fn length_shorter_than_required_classes_is_rejected() {
+        let mut profile = charset(1, &[CharClass::Lower, CharClass::Digits]);
+        profile.require = profile.charset.clone();
+
+        assert!(generate(&profile).is_err());
+    }
+
+    #[test]
+    fn byte_formats_encode_length_bytes() {
+        let hex = GenerateConfig {
+            format: SecretFormat::Hex,
+            length: Some(16),
+            ..Default::default()
+        };
+        assert_eq!(generate(&hex).unwrap().len(), 32);
+
+        let base64url = GenerateConfig {
+            format: SecretFormat::Base64url,
+            length: Some(32),
+            ..Default::default()
+        };
+        assert_eq!(generate(&base64url).unwrap().len(), 43);
+    }
+
+    #[test]
+    fn multi_byte_symbols_can_be_masked() {
+        let mut profile = charset(12, &[CharClass::Symbols]);
+        profile.symbols = Some("§€ü".to_string());
+
+        for _ in 0..20 {
+            let value = generate(&profile).unwrap();
+            assert_eq!(value.chars().count(), 12);
+            let masked = crate::connectors::mask_secret(&value);
+            assert!(value.ends_with(masked.trim_start_matches('*')));
+        }
+    }
+
+    #[test]
+    fn passphrase_has_length_words() {
+        let profile = GenerateConfig {
+            format: SecretFormat::Passphrase,
+            length: Some(5),
+            separator: Some(" ".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(generate(&profile).unwrap().split(' ').count(), 5);
+    }
+
+    #[test]
+    fn ed25519_public_key_derives_from_generated_key() {
+        let profile = GenerateConfig {
+            format: SecretFormat::Ed25519,
+            ..Default::default()
+        };
+        let private_pem = generate(&profile).unwrap();
+
+        let public_pem = ed25519_public_key_pem(&private_pem).unwrap();
+        assert!(public_pem.starts_with("-----BEGIN PUBLIC KEY-----"));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::connectors::{Connector, ConnectorSettings};
+use anyhow::Result;
+
+/// Lists the secrets held by each service of an environment, or the
+/// metadata and remote versions of one secret. Values are never read.
+pub async fn show_inventory(
+    secret_name: Option<String>,
+    env: String,
+    service: Option<String>,
+) -> Result<()> {
+    let config = crate::config::Config::load()?;
+    let services = crate::prod::resolve_services(&config, &env, service.as_deref());
+
+    if services.is_empty() {
+        anyhow::bail!(
+            "--service is required (or bind connector profiles under [environments.{}])",
+            env
+        );
+    }
+
+    for service in &services {
+        let connector =
+            match crate::prod::get_connector(Some(service), &ConnectorSettings::default()) {
+                Ok(connector) => connector,
+                Err(e) => {
+                    println!("\n📦 {}/{}\n   ⚠️  {}", env, service, e);
+                    continue;
+                }
+            };
+        let capabilities = connector.capabilities().await;
+
+        println!(
+            "\n📦 {}/{}  [{}]",
+            env,
+            service,
+            capabilities.names().join(", ")
+        );
+
+        let result = match &secret_name {
+            Some(secret_name) => show_secret(connector.as_ref(), secret_name).await,
+            None => show_secrets(connector.as_ref()).await,
+        };
+
+        if let Err(e) = result {
+            println!("   ⚠️  {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn show_secrets(connector: &dyn Connector) -> Result<()> {
+    let mut secrets = connector.list_secrets().await?;
+    secrets.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if secrets.is_empty() {
+        println!("   (no secrets)");
+        return Ok(());
+    }
+
+    println!("   {:<32} {:<22} VERSION", "NAME", "UPDATED");
+    for secret in secrets {
+        println!(
+            "   {:<32} {:<22} {}",
+            secret.name,
+            format_time(secret.updated_at),
+            secret.version.as_deref().unwrap_or("-")
+        );
+    }
+
+    Ok(())
+}
+
+async fn show_secret(connector: &dyn Connector, secret_name: &str) -> Result<()> {
+    let metadata = connector.describe(secret_name).await?;
+    println!("   Updated: {}", format_time(metadata.updated_at));
+    println!("   Version: {}", metadata.version.as_deref().unwrap_or("-"));
+
+    if !connector.capabilities().await.versions {
+        return Ok(());
+    }
+
+    let versions = connector.list_versions(secret_name).await?;
+    println!("\n   {:<40} {:<22} STATE", "VERSION", "CREATED");
+    for version in versions.iter().rev() {
+        println!(
+            "   {:<40} {:<22} {}",
+            version.id,
+            format_time(version.created_at),
+            if version.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+    }
+
+    Ok(())
+}
+
+fn format_time(time: Option<chrono::DateTime<chrono::Utc>>) -> String {
+    time.map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
@@ -1,15 +1,29 @@
+pub mod approval;
 pub mod audit;
 pub mod auth_callback;
+pub mod bundle;
 pub mod cli;
 pub mod config;
 pub mod connectors;
 pub mod daemon;
 pub mod dev;
+pub mod dotenv;
+pub mod fanout;
+pub mod generator;
+pub mod inventory;
+pub mod local_files;
 pub mod lock;
+pub mod manifest;
+pub mod minters;
 pub mod pool;
 pub mod prod;
+pub mod revocation;
 pub mod rollback;
 pub mod rotation;
 pub mod saas;
+pub mod scheduler;
+pub mod secrets_file;
 pub mod signals;
+pub mod staged;
 pub mod tui;
+pub mod versions;
@@ -0,0 +1,18 @@
+use crate::dotenv::EnvFile;
+use anyhow::Result;
+
+/// `.env` files are flat, so a dotted path is simply a key containing dots.
+pub struct EnvFileFormat;
+
+impl super::FileFormat for EnvFileFormat {
+    fn get(&self, contents: &str, key_path: &[String]) -> Result<Option<String>> {
+        let env = EnvFile::parse(contents)?;
+        Ok(env.get(&key_path.join(".")).map(str::to_string))
+    }
+
+    fn set(&self, contents: &str, key_path: &[String], value: &str) -> Result<String> {
+        let mut env = EnvFile::parse(contents)?;
+        env.set(&key_path.join("."), value);
+        Ok(env.to_string())
+    }
+}
@@ -0,0 +1,411 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+/// JSON documents are edited in place: only the bytes of the target value
+/// change, or a new member is spliced into its parent object, so key order
+/// and the layout of every other value are preserved. Numeric path segments
+/// index into arrays.
+pub struct JsonFile;
+
+impl super::FileFormat for JsonFile {
+    fn get(&self, contents: &str, key_path: &[String]) -> Result<Option<String>> {
+        let document: Value = serde_json::from_str(contents).context("Invalid JSON")?;
+
+        let mut current = &document;
+        for key in key_path {
+            current = match current {
+                Value::Object(map) => match map.get(key) {
+                    Some(value) => value,
+                    None => return Ok(None),
+                },
+                Value::Array(items) => match key.parse::<usize>().ok().and_then(|i| items.get(i)) {
+                    Some(value) => value,
+                    None => return Ok(None),
+                },
+                _ => return Ok(None),
+            };
+        }
+
+        match current {
+            Value::String(s) => Ok(Some(s.clone())),
+            Value::Null => Ok(None),
+            Value::Object(_) | Value::Array(_) => {
+                anyhow::bail!("'{}' is not a scalar value", key_path.join("."))
+            }
+            other => Ok(Some(other.to_string())),
+        }
+    }
+
+    fn set(&self, contents: &str, key_path: &[String], value: &str) -> Result<String> {
+        if contents.trim().is_empty() {
+            let mut output = pretty(&nested(key_path, value), "  ")?;
+            output.push('\n');
+            return Ok(output);
+        }
+
+        // Validate up front so the scanner below can assume well-formed JSON.
+        serde_json::from_str::<Value>(contents).context("Invalid JSON")?;
+
+        let bytes = contents.as_bytes();
+        let mut start = skip_whitespace(bytes, 0);
+        let mut end = value_end(bytes, start);
+
+        for (i, key) in key_path.iter().enumerate() {
+            match bytes[start] {
+                b'{' => {
+                    let (members, close) = object_members(bytes, start);
+                    // As with serde_json, the last duplicate key wins.
+                    let member = members.iter().rev().find(|m| {
+                        serde_json::from_str::<String>(&contents[m.key_start..m.key_end])
+                            .is_ok_and(|k| k == *key)
+                    });
+                    match member {
+                        Some(member) => (start, end) = (member.value_start, member.value_end),
+                        None => {
+                            return insert_member(
+                                contents,
+                                start,
+                                &members,
+                                close,
+                                &key_path[i..],
+                                value,
+                            )
+                        }
+                    }
+                }
+                b'[' => {
+                    let index: usize = key
+                        .parse()
+                        .with_context(|| format!("'{}' is an array; expected an index", key))?;
+                    (start, end) = *array_elements(bytes, start)
+                        .get(index)
+                        .with_context(|| format!("Index {} is out of bounds", index))?;
+                }
+                _ => anyhow::bail!(
+                    "Cannot descend into '{}': parent is not an object",
+                    key_path[..=i].join(".")
+                ),
+            }
+        }
+
+        if matches!(bytes[start], b'{' | b'[') {
+            anyhow::bail!("'{}' is not a scalar value", key_path.join("."));
+        }
+
+        Ok(format!(
+            "{}{}{}",
+            &contents[..start],
+            serde_json::to_string(value)?,
+            &contents[end..]
+        ))
+    }
+}
+
+/// Byte offsets of one `"key": value` member of an object.
+struct Member {
+    key_start: usize,
+    key_end: usize,
+    value_start: usize,
+    value_end: usize,
+}
+
+/// Adds `path[0]` to the object opening at `open`, creating the objects for
+/// the rest of `path`, in the style of the members already there.
+fn insert_member(
+    contents: &str,
+    open: usize,
+    members: &[Member],
+    close: usize,
+    path: &[String],
+    value: &str,
+) -> Result<String> {
+    let key = serde_json::to_string(&path[0])?;
+    let member_value = nested(&path[1..], value);
+    let unit = detect_indent(contents);
+
+    let (at, insertion) = match (members.first(), members.last()) {
+        (Some(first), Some(last)) => {
+            let separator = &contents[open + 1..first.key_start];
+            let colon = &contents[first.key_end..first.value_start];
+            let rendered = match separator.rfind('\n') {
+                Some(newline) => pretty(&member_value, &unit)?
+                    .replace('\n', &format!("\n{}", &separator[newline + 1..])),
+                None => serde_json::to_string(&member_value)?,
+            };
+            (
+                last.value_end,
+                format!(",{}{}{}{}", separator, key, colon, rendered),
+            )
+        }
+        _ if contents.contains('\n') => {
+            let line_start = contents[..open].rfind('\n').map_or(0, |i| i + 1);
+            let outer: String = contents[line_start..open]
+                .chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect();
+            let inner = format!("{}{}", outer, unit);
+            let rendered = pretty(&member_value, &unit)?.replace('\n', &format!("\n{}", inner));
+            (
+                open + 1,
+                format!("\n{}{}: {}\n{}", inner, key, rendered, outer),
+            )
+        }
+        _ => (
+            open + 1,
+            format!("{}:{}", key, serde_json::to_string(&member_value)?),
+        ),
+    };
+
+    // An empty object may hold whitespace, which the new member replaces.
+    let resume = if members.is_empty() { close } else { at };
+    Ok(format!(
+        "{}{}{}",
+        &contents[..at],
+        insertion,
+        &contents[resume..]
+    ))
+}
+
+/// `value` wrapped in one object per key of `path`.
+fn nested(path: &[String], value: &str) -> Value {
+    path.iter()
+        .rev()
+        .fold(Value::String(value.to_string()), |inner, key| {
+            let mut map = serde_json::Map::new();
+            map.insert(key.clone(), inner);
+            Value::Object(map)
+        })
+}
+
+fn pretty(value: &Value, indent: &str) -> Result<String> {
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut output = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8(output)?)
+}
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+/// End of the string starting with the quote at `i`.
+fn string_end(bytes: &[u8], mut i: usize) -> usize {
+    i += 1;
+    while bytes[i] != b'"' {
+        i += if bytes[i] == b'\\' { 2 } else { 1 };
+    }
+    i + 1
+}
+
+/// End of the value starting at `i`.
+fn value_end(bytes: &[u8], i: usize) -> usize {
+    match bytes[i] {
+        b'"' => string_end(bytes, i),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            let mut j = i;
+            loop {
+                match bytes[j] {
+                    b'"' => {
+                        j = string_end(bytes, j);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return j + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                j += 1;
+            }
+        }
+        _ => {
+            let mut j = i;
+            while j < bytes.len() && !matches!(bytes[j], b',' | b'}' | b']') {
+                j += 1;
+            }
+            while bytes[j - 1].is_ascii_whitespace() {
+                j -= 1;
+            }
+            j
+        }
+    }
+}
+
+/// The members of the object opening at `open`, and the offset of its `}`.
+fn object_members(bytes: &[u8], open: usize) -> (Vec<Member>, usize) {
+    let mut members = Vec::new();
+    let mut i = skip_whitespace(bytes, open + 1);
+
+    while bytes[i] != b'}' {
+        let key_end = string_end(bytes, i);
+        let value_start = skip_whitespace(bytes, skip_whitespace(bytes, key_end) + 1);
+        let value_end = value_end(bytes, value_start);
+        members.push(Member {
+            key_start: i,
+            key_end,
+            value_start,
+            value_end,
+        });
+
+        i = skip_whitespace(bytes, value_end);
+        if bytes[i] == b',' {
+            i = skip_whitespace(bytes, i + 1);
+        }
+    }
+
+    (members, i)
+}
+
+/// The `(start, end)` of every element of the array opening at `open`.
+fn array_elements(bytes: &[u8], open: usize) -> Vec<(usize, usize)> {
+    let mut elements = Vec::new();
+    let mut i = skip_whitespace(bytes, open + 1);
+
+    while bytes[i] != b']' {
+        let end = value_end(bytes, i);
+        elements.push((i, end));
+
+        i = skip_whitespace(bytes, end);
+        if bytes[i] == b',' {
+            i = skip_whitespace(bytes, i + 1);
+        }
+    }
+
+    elements
+}
+
+fn detect_indent(contents: &str) -> String {
+    contents
+        .lines()
+        .skip(1)
+        .find(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect::<String>()
+        })
+        .filter(|indent| !indent.is_empty())
+        .unwrap_or_else(|| "  ".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_files::FileFormat;
+
+    fn path(expression: &str) -> Vec<String> {
+        crate::local_files::parse_key_path(expression).unwrap()
+    }
+
+    fn set(contents: &str, key_path: &str, value: &str) -> String {
+        JsonFile.set(contents, &path(key_path), value).unwrap()
+    }
+
+    #[test]
+    fn replaces_only_the_target_value() {
+        let contents = "{\n    \"z\": 1.50,\n    \"db\": {\"password\": \"old\", \"port\": 5432},\n    \"a\": \"\\u00e9\"\n}\n";
+        assert_eq!(
+            set(contents, "db.password", "new"),
+            "{\n    \"z\": 1.50,\n    \"db\": {\"password\": \"new\", \"port\": 5432},\n    \"a\": \"\\u00e9\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn replaces_non_string_scalars() {
+        assert_eq!(
+            set(r#"{"a":null,"b":true}"#, "a", "x"),
+            r#"{"a":"x","b":true}"#
+        );
+        assert_eq!(set(r#"{"a":1 , "b":2}"#, "a", "x"), r#"{"a":"x" , "b":2}"#);
+    }
+
+    #[test]
+    fn escapes_the_new_value() {
+        assert_eq!(set(r#"{"a":"b"}"#, "a", "q\"\n"), r#"{"a":"q\"\n"}"#);
+    }
+
+    #[test]
+    fn indexes_into_arrays() {
+        let contents = r#"{"keys": ["one", {"v": "two"}]}"#;
+        assert_eq!(
+            set(contents, "keys.1.v", "new"),
+            r#"{"keys": ["one", {"v": "new"}]}"#
+        );
+        assert_eq!(
+            set(contents, "keys.0", "new"),
+            r#"{"keys": ["new", {"v": "two"}]}"#
+        );
+        assert!(JsonFile.set(contents, &path("keys.2"), "x").is_err());
+        assert!(JsonFile.set(contents, &path("keys.x"), "x").is_err());
+    }
+
+    #[test]
+    fn appends_members_in_the_existing_style() {
+        assert_eq!(
+            set("{\n  \"a\": 1\n}\n", "b", "v"),
+            "{\n  \"a\": 1,\n  \"b\": \"v\"\n}\n"
+        );
+        assert_eq!(set(r#"{"a":1}"#, "b", "v"), r#"{"a":1,"b":"v"}"#);
+    }
+
+    #[test]
+    fn creates_missing_parents() {
+        assert_eq!(
+            set("{\n    \"a\": 1\n}\n", "db.password", "v"),
+            "{\n    \"a\": 1,\n    \"db\": {\n        \"password\": \"v\"\n    }\n}\n"
+        );
+        assert_eq!(
+            set("{\n  \"db\": {}\n}\n", "db.password", "v"),
+            "{\n  \"db\": {\n    \"password\": \"v\"\n  }\n}\n"
+        );
+        assert_eq!(set("{ }", "a.b", "v"), r#"{"a":{"b":"v"}}"#);
+    }
+
+    #[test]
+    fn starts_an_empty_file() {
+        assert_eq!(
+            set("", "db.password", "v"),
+            "{\n  \"db\": {\n    \"password\": \"v\"\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn last_duplicate_key_wins() {
+        assert_eq!(
+            set(r#"{"a":"1","a":"2"}"#, "a", "x"),
+            r#"{"a":"1","a":"x"}"#
+        );
+    }
+
+    #[test]
+    fn rejects_non_scalar_targets_and_parents() {
+        assert!(JsonFile.set(r#"{"a":{"b":1}}"#, &path("a"), "x").is_err());
+        assert!(JsonFile.set(r#"{"a":"s"}"#, &path("a.b"), "x").is_err());
+        assert!(JsonFile.set("{", &path("a"), "x").is_err());
+    }
+
+    #[test]
+    fn reads_back_what_it_wrote() {
+        let written = set("{\n  \"x\": [1, 2]\n}\n", "db.password", "p@ss");
+        assert_eq!(
+            JsonFile
+                .get(&written, &path("db.password"))
+                .unwrap()
+                .as_deref(),
+            Some("p@ss")
+        );
+        assert_eq!(
+            JsonFile.get(&written, &path("x.1")).unwrap().as_deref(),
+            Some("2")
+        );
+    }
+}
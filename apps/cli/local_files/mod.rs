@@ -0,0 +1,57 @@
+use anyhow::Result;
+use std::path::Path;
+
+pub mod env_file;
+pub mod json_file;
+pub mod toml_file;
+pub mod yaml_file;
+
+/// A local file format birch can read a secret from and write it back to
+/// without disturbing the rest of the document.
+pub trait FileFormat {
+    fn get(&self, contents: &str, key_path: &[String]) -> Result<Option<String>>;
+    fn set(&self, contents: &str, key_path: &[String], value: &str) -> Result<String>;
+}
+
+/// Picks the format from the file extension; anything unrecognised is
+/// treated as a dotenv file. YAML files holding a Kubernetes `Secret` are
+/// detected by content.
+pub fn format_for(path: &Path) -> Box<dyn FileFormat> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    match extension.as_deref() {
+        Some("json") => Box::new(json_file::JsonFile),
+        Some("yaml") | Some("yml") => Box::new(yaml_file::YamlFile),
+        Some("toml") => Box::new(toml_file::TomlFile),
+        _ => Box::new(env_file::EnvFileFormat),
+    }
+}
+
+/// Splits a path expression such as `database.password` into its keys. A
+/// backslash escapes a literal dot (`tls\.crt`).
+pub fn parse_key_path(expression: &str) -> Result<Vec<String>> {
+    let mut keys = Vec::new();
+    let mut current = String::new();
+    let mut chars = expression.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped) => current.push(escaped),
+                None => current.push('\\'),
+            },
+            '.' => keys.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    keys.push(current);
+
+    if keys.iter().any(|k| k.is_empty()) {
+        anyhow::bail!("Invalid key path '{}'", expression);
+    }
+
+    Ok(keys)
+}
@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// TOML documents are edited in place, so comments, ordering and
+/// formatting of everything but the target value are preserved.
+pub struct TomlFile;
+
+impl super::FileFormat for TomlFile {
+    fn get(&self, contents: &str, key_path: &[String]) -> Result<Option<String>> {
+        let document: DocumentMut = contents.parse().context("Invalid TOML")?;
+
+        let mut current = document.as_item();
+        for key in key_path {
+            current = match current.get(key) {
+                Some(item) => item,
+                None => return Ok(None),
+            };
+        }
+
+        match current.as_value() {
+            Some(value) => Ok(Some(match value.as_str() {
+                Some(s) => s.to_string(),
+                None => value.to_string().trim().to_string(),
+            })),
+            None => anyhow::bail!("'{}' is not a scalar value", key_path.join(".")),
+        }
+    }
+
+    fn set(&self, contents: &str, key_path: &[String], value: &str) -> Result<String> {
+        let mut document: DocumentMut = contents.parse().context("Invalid TOML")?;
+
+        let (last, parents) = key_path
+            .split_last()
+            .ok_or_else(|| anyhow::anyhow!("Empty key path"))?;
+
+        let mut current = document.as_item_mut();
+        for (i, key) in parents.iter().enumerate() {
+            if current.get(key).is_none() {
+                let table = current.as_table_like_mut().ok_or_else(|| {
+                    anyhow::anyhow!("'{}' is not a table", key_path[..i].join("."))
+                })?;
+                let mut new_table = Table::new();
+                new_table.set_implicit(true);
+                table.insert(key, Item::Table(new_table));
+            }
+
+            current = current
+                .get_mut(key)
+                .filter(|item| item.is_table_like())
+                .ok_or_else(|| anyhow::anyhow!("'{}' is not a table", key_path[..=i].join(".")))?;
+        }
+
+        let table = current
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow::anyhow!("'{}' is not a table", parents.join(".")))?;
+
+        match table.get_mut(last) {
+            Some(item) if item.is_value() => {
+                // Keep the comments and spacing around the old value.
+                let decor = item.as_value().map(|v| v.decor().clone());
+                let mut new_value = toml_edit::Value::from(value);
+                if let Some(decor) = decor {
+                    *new_value.decor_mut() = decor;
+                }
+                *item = Item::Value(new_value);
+            }
+            Some(_) => anyhow::bail!("'{}' is not a scalar value", key_path.join(".")),
+            None => {
+                table.insert(last, toml_edit::value(value));
+            }
+        }
+
+        Ok(document.to_string())
+    }
+}
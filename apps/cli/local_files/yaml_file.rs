@@ -0,0 +1,793 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde_yaml::Value;
+
+/// YAML files are edited line by line so comments, anchors and layout
+/// survive a rotation. Only block-style mappings (and `KEY=value` lists such
+/// as docker-compose `environment:`) can be addressed.
+///
+/// A document with `kind: Secret` is treated as a Kubernetes Secret: the
+/// path names a key, written to `stringData` if it is already there and
+/// base64-encoded into `data` otherwise.
+pub struct YamlFile;
+
+impl super::FileFormat for YamlFile {
+    fn get(&self, contents: &str, key_path: &[String]) -> Result<Option<String>> {
+        let documents = parse_documents(contents)?;
+
+        if let Some(secret) = documents.iter().find(|d| is_kubernetes_secret(d)) {
+            return kubernetes_get(secret, &key_path.join("."));
+        }
+
+        for document in &documents {
+            if let Some(value) = lookup(document, key_path)? {
+                return Ok(Some(value));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn set(&self, contents: &str, key_path: &[String], value: &str) -> Result<String> {
+        let documents = parse_documents(contents)?;
+        let secret_index = documents.iter().position(is_kubernetes_secret);
+
+        let (index, edit_path, written) = match secret_index {
+            Some(index) => {
+                let key = key_path.join(".");
+                let in_string_data = documents[index]
+                    .get("stringData")
+                    .and_then(|d| d.get(key.as_str()))
+                    .is_some();
+
+                if in_string_data {
+                    (
+                        index,
+                        vec!["stringData".to_string(), key],
+                        value.to_string(),
+                    )
+                } else {
+                    let encoded = base64::engine::general_purpose::STANDARD.encode(value);
+                    (index, vec!["data".to_string(), key], encoded)
+                }
+            }
+            None => {
+                let index = documents
+                    .iter()
+                    .position(|d| matches!(lookup(d, key_path), Ok(Some(_))))
+                    .unwrap_or(0);
+                (index, key_path.to_vec(), value.to_string())
+            }
+        };
+
+        let mut editor = Editor::new(contents);
+        editor.set(index, &edit_path, &written)?;
+        let output = editor.to_string();
+
+        // Never write a document we cannot read back ourselves.
+        let reread = parse_documents(&output)?;
+        let document = reread.get(index).unwrap_or(&Value::Null);
+        let read_back = match secret_index {
+            Some(_) => kubernetes_get(document, &key_path.join("."))?,
+            None => lookup(document, key_path)?,
+        };
+        if read_back.as_deref() != Some(value) {
+            anyhow::bail!("Failed to update '{}' in YAML document", key_path.join("."));
+        }
+
+        Ok(output)
+    }
+}
+
+fn parse_documents(contents: &str) -> Result<Vec<Value>> {
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(contents) {
+        documents.push(serde::Deserialize::deserialize(document).context("Invalid YAML")?);
+    }
+    Ok(documents)
+}
+
+fn is_kubernetes_secret(document: &Value) -> bool {
+    document.get("kind").and_then(Value::as_str) == Some("Secret")
+        && document.get("apiVersion").is_some()
+}
+
+fn kubernetes_get(secret: &Value, key: &str) -> Result<Option<String>> {
+    if let Some(value) = secret.get("stringData").and_then(|d| d.get(key)) {
+        return Ok(scalar_to_string(value));
+    }
+
+    match secret
+        .get("data")
+        .and_then(|d| d.get(key))
+        .and_then(Value::as_str)
+    {
+        Some(encoded) => {
+            let decoded = base64::engine::general_purpose::STANDARD
+                .decode(encoded.trim())
+                .with_context(|| format!("data.{} is not valid base64", key))?;
+            Ok(Some(String::from_utf8(decoded).with_context(|| {
+                format!("data.{} is not valid UTF-8", key)
+            })?))
+        }
+        None => Ok(None),
+    }
+}
+
+fn lookup(document: &Value, key_path: &[String]) -> Result<Option<String>> {
+    let Some((last, parents)) = key_path.split_last() else {
+        return Ok(None);
+    };
+
+    let mut current = document;
+    for key in parents {
+        current = match current.get(key.as_str()) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+    }
+
+    match current {
+        Value::Sequence(items) => Ok(items.iter().find_map(|item| {
+            item.as_str()
+                .and_then(|s| s.strip_prefix(last.as_str()))
+                .and_then(|s| s.strip_prefix('='))
+                .map(str::to_string)
+        })),
+        _ => match current.get(last.as_str()) {
+            Some(Value::Mapping(_) | Value::Sequence(_)) => {
+                anyhow::bail!("'{}' is not a scalar value", key_path.join("."))
+            }
+            Some(value) => Ok(scalar_to_string(value)),
+            None => Ok(None),
+        },
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Plain,
+    Single,
+    Double,
+}
+
+/// Line-based editor over the raw text of a (possibly multi-document) file.
+struct Editor {
+    lines: Vec<String>,
+}
+
+struct Located {
+    line: usize,
+    indent: usize,
+    kind: LocatedKind,
+}
+
+enum LocatedKind {
+    /// `key: value`, with the column right after the colon.
+    Mapping { value_column: usize },
+    /// `- KEY=value` inside a sequence.
+    ListItem,
+}
+
+struct Missing {
+    insert_at: usize,
+    indent: usize,
+    /// Index of the first key in the path that does not exist yet.
+    first_missing: usize,
+    in_sequence: bool,
+}
+
+impl Editor {
+    fn new(contents: &str) -> Self {
+        Self {
+            lines: contents.split_inclusive('\n').map(str::to_string).collect(),
+        }
+    }
+
+    /// Line range of the `index`-th document, split on `---` markers.
+    fn document_range(&self, index: usize) -> (usize, usize) {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+
+        for (i, line) in self.lines.iter().enumerate() {
+            let marker = line.trim_end();
+            if marker == "---" || marker.starts_with("--- ") {
+                ranges.push((start, i));
+                start = i + 1;
+            }
+        }
+        ranges.push((start, self.lines.len()));
+
+        // A leading marker does not open an empty first document.
+        if ranges.len() > 1 {
+            let (first_start, first_end) = ranges[0];
+            if (first_start..first_end).all(|l| content_indent(&self.lines[l]).is_none()) {
+                ranges.remove(0);
+            }
+        }
+
+        ranges
+            .get(index)
+            .copied()
+            .unwrap_or((start, self.lines.len()))
+    }
+
+    fn set(&mut self, document: usize, key_path: &[String], value: &str) -> Result<()> {
+        let (start, end) = self.document_range(document);
+
+        match self.locate(start, end, key_path)? {
+            Ok(located) => self.replace(located, key_path, value),
+            Err(missing) => {
+                self.insert(missing, key_path, value);
+                Ok(())
+            }
+        }
+    }
+
+    fn locate(
+        &self,
+        mut start: usize,
+        mut end: usize,
+        key_path: &[String],
+    ) -> Result<std::result::Result<Located, Missing>> {
+        let mut parent_indent: Option<usize> = None;
+
+        for (i, key) in key_path.iter().enumerate() {
+            let last = i == key_path.len() - 1;
+            let child = (start..end).find_map(|l| {
+                content_indent(&self.lines[l])
+                    .filter(|indent| parent_indent.is_none_or(|p| *indent >= p))
+                    .map(|indent| (l, indent))
+            });
+
+            let Some((child_line, child_indent)) = child else {
+                return Ok(Err(Missing {
+                    insert_at: self.last_content_line(start, end),
+                    indent: parent_indent.map_or(0, |p| p + 2),
+                    first_missing: i,
+                    in_sequence: false,
+                }));
+            };
+
+            let in_sequence = body_at(&self.lines[child_line], child_indent).starts_with("- ");
+
+            let mut found = None;
+            for l in start..end {
+                if content_indent(&self.lines[l]) != Some(child_indent) {
+                    continue;
+                }
+                let text = body_at(&self.lines[l], child_indent);
+
+                if in_sequence {
+                    if last && list_item_key(text).as_deref() == Some(key.as_str()) {
+                        found = Some(Located {
+                            line: l,
+                            indent: child_indent,
+                            kind: LocatedKind::ListItem,
+                        });
+                        break;
+                    }
+                } else if let Some((k, column)) = mapping_key(text) {
+                    if k == *key {
+                        found = Some(Located {
+                            line: l,
+                            indent: child_indent,
+                            kind: LocatedKind::Mapping {
+                                value_column: child_indent + column,
+                            },
+                        });
+                        break;
+                    }
+                }
+            }
+
+            let Some(located) = found else {
+                if in_sequence && !last {
+                    anyhow::bail!(
+                        "'{}' is a list; only KEY=value entries can be addressed",
+                        key_path[..i].join(".")
+                    );
+                }
+                return Ok(Err(Missing {
+                    insert_at: self.last_content_line(start, end),
+                    indent: child_indent,
+                    first_missing: i,
+                    in_sequence,
+                }));
+            };
+
+            if last {
+                return Ok(Ok(located));
+            }
+
+            let block_end = self.block_end(located.line, located.indent, end);
+            parent_indent = Some(located.indent + 1);
+            start = located.line + 1;
+            end = block_end;
+
+            // Sequences may sit at the same indentation as their key.
+            let child_at_same_indent = (start..end).find_map(|l| {
+                content_indent(&self.lines[l]).map(|indent| {
+                    indent == located.indent && body_at(&self.lines[l], indent).starts_with("- ")
+                })
+            });
+            if child_at_same_indent == Some(true) {
+                parent_indent = Some(located.indent);
+            }
+        }
+
+        unreachable!("key path is never empty")
+    }
+
+    /// First line after `line` that belongs to a sibling or parent of the key
+    /// at `indent`.
+    fn block_end(&self, line: usize, indent: usize, end: usize) -> usize {
+        for l in line + 1..end {
+            if let Some(child) = content_indent(&self.lines[l]) {
+                let sequence_at_same_indent =
+                    child == indent && body_at(&self.lines[l], child).starts_with("- ");
+                if child < indent || (child == indent && !sequence_at_same_indent) {
+                    return l;
+                }
+            }
+        }
+        end
+    }
+
+    /// Index just after the last non-blank, non-comment line in the range.
+    fn last_content_line(&self, start: usize, end: usize) -> usize {
+        (start..end)
+            .rev()
+            .find(|l| content_indent(&self.lines[*l]).is_some())
+            .map(|l| l + 1)
+            .unwrap_or(start)
+    }
+
+    fn replace(&mut self, located: Located, key_path: &[String], value: &str) -> Result<()> {
+        let line = &self.lines[located.line];
+        let (body, ending) = split_line_ending(line);
+        let ending = ending.to_string();
+
+        match located.kind {
+            LocatedKind::ListItem => {
+                let key = &key_path[key_path.len() - 1];
+                let item = format!("{}={}", key, value);
+                let old = body[located.indent + 2..].trim_start();
+                let style = match old.chars().next() {
+                    Some('\'') => Style::Single,
+                    Some('"') => Style::Double,
+                    _ => Style::Plain,
+                };
+                self.lines[located.line] = format!(
+                    "{}- {}{}",
+                    " ".repeat(located.indent),
+                    render_scalar(&item, style),
+                    ending
+                );
+            }
+            LocatedKind::Mapping { value_column } => {
+                let head = body[..value_column].to_string();
+                let rest = &body[value_column..];
+                let old = rest.trim_start();
+
+                let (style, comment) = match old.chars().next() {
+                    None => {
+                        let block_end =
+                            self.block_end(located.line, located.indent, self.lines.len());
+                        if (located.line + 1..block_end)
+                            .any(|l| content_indent(&self.lines[l]).is_some())
+                        {
+                            anyhow::bail!("'{}' is not a scalar value", key_path.join("."));
+                        }
+                        (Style::Plain, String::new())
+                    }
+                    Some('#') => (Style::Plain, format!(" {}", old)),
+                    Some('|') | Some('>') => {
+                        let block_end = self.last_content_line(
+                            located.line + 1,
+                            self.block_end(located.line, located.indent, self.lines.len()),
+                        );
+                        self.lines
+                            .drain(located.line + 1..block_end.max(located.line + 1));
+                        (Style::Double, String::new())
+                    }
+                    Some('{') | Some('[') => {
+                        anyhow::bail!("'{}' is not a scalar value", key_path.join("."))
+                    }
+                    Some('"') => {
+                        let close = closing_quote(old, '"').ok_or_else(|| {
+                            anyhow::anyhow!("Multi-line quoted values are not supported")
+                        })?;
+                        (Style::Double, old[close + 1..].to_string())
+                    }
+                    Some('\'') => {
+                        let close = closing_quote(old, '\'').ok_or_else(|| {
+                            anyhow::anyhow!("Multi-line quoted values are not supported")
+                        })?;
+                        (Style::Single, old[close + 1..].to_string())
+                    }
+                    Some(_) => match old.find(" #") {
+                        // Keep the spacing between the value and its comment.
+                        Some(i) => (Style::Plain, old[old[..i].trim_end().len()..].to_string()),
+                        None => (Style::Plain, String::new()),
+                    },
+                };
+
+                self.lines[located.line] = format!(
+                    "{} {}{}{}",
+                    head,
+                    render_scalar(value, style),
+                    comment,
+                    ending
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn insert(&mut self, missing: Missing, key_path: &[String], value: &str) {
+        let mut new_lines = Vec::new();
+        let remaining = &key_path[missing.first_missing..];
+
+        if missing.in_sequence {
+            let key = &key_path[key_path.len() - 1];
+            new_lines.push(format!(
+                "{}- {}\n",
+                " ".repeat(missing.indent),
+                render_scalar(&format!("{}={}", key, value), Style::Plain)
+            ));
+        } else {
+            for (depth, key) in remaining.iter().enumerate() {
+                let indent = " ".repeat(missing.indent + depth * 2);
+                if depth == remaining.len() - 1 {
+                    new_lines.push(format!(
+                        "{}{}: {}\n",
+                        indent,
+                        render_key(key),
+                        render_scalar(value, Style::Plain)
+                    ));
+                } else {
+                    new_lines.push(format!("{}{}:\n", indent, render_key(key)));
+                }
+            }
+        }
+
+        if missing.insert_at > 0 {
+            let previous = &mut self.lines[missing.insert_at - 1];
+            if !previous.ends_with('\n') {
+                previous.push('\n');
+            }
+        }
+
+        self.lines
+            .splice(missing.insert_at..missing.insert_at, new_lines);
+    }
+}
+
+impl std::fmt::Display for Editor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in &self.lines {
+            f.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
+/// Indentation of a line, or `None` for blank lines, comments and document
+/// markers.
+fn content_indent(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start_matches(' ');
+    let body = trimmed.trim_end();
+
+    if body.is_empty() || body.starts_with('#') || body == "---" || body == "..." {
+        return None;
+    }
+
+    Some(line.len() - trimmed.len())
+}
+
+fn body_at(line: &str, indent: usize) -> &str {
+    split_line_ending(line).0.get(indent..).unwrap_or("")
+}
+
+fn split_line_ending(line: &str) -> (&str, &str) {
+    if let Some(body) = line.strip_suffix("\r\n") {
+        (body, "\r\n")
+    } else if let Some(body) = line.strip_suffix('\n') {
+        (body, "\n")
+    } else {
+        (line, "")
+    }
+}
+
+/// Parses `key: ...` and returns the key and the column just after the colon.
+fn mapping_key(text: &str) -> Option<(String, usize)> {
+    if text.starts_with("- ") || text == "-" {
+        return None;
+    }
+
+    if let Some(quote @ ('"' | '\'')) = text.chars().next() {
+        let close = closing_quote(text, quote)?;
+        let after = &text[close + 1..];
+        let colon = after.find(':')?;
+        if !after[..colon].trim().is_empty() {
+            return None;
+        }
+        let key: String = serde_yaml::from_str(&text[..=close]).ok()?;
+        return Some((key, close + 1 + colon + 1));
+    }
+
+    let bytes = text.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        if b == b':' && (i + 1 == bytes.len() || bytes[i + 1] == b' ' || bytes[i + 1] == b'\t') {
+            return Some((text[..i].trim_end().to_string(), i + 1));
+        }
+        if b == b'#' && i > 0 && bytes[i - 1] == b' ' {
+            return None;
+        }
+    }
+
+    None
+}
+
+fn list_item_key(text: &str) -> Option<String> {
+    let item = text.strip_prefix("- ")?.trim();
+    let item: String = match item.chars().next() {
+        Some('"') | Some('\'') => serde_yaml::from_str(item).ok()?,
+        _ => item.to_string(),
+    };
+    item.split_once('=').map(|(key, _)| key.to_string())
+}
+
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1).peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            ('"', '\\') => {
+                chars.next();
+            }
+            ('\'', '\'') if chars.peek().map(|(_, c)| *c) == Some('\'') => {
+                chars.next();
+            }
+            _ if c == quote => return Some(i),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn render_key(key: &str) -> String {
+    if is_plain_safe(key) {
+        key.to_string()
+    } else {
+        serde_json::to_string(key).unwrap_or_else(|_| key.to_string())
+    }
+}
+
+fn render_scalar(value: &str, style: Style) -> String {
+    let multiline = value.contains(['\n', '\r']);
+
+    match style {
+        Style::Plain if is_plain_safe(value) => value.to_string(),
+        Style::Single if !multiline => format!("'{}'", value.replace('\'', "''")),
+        // JSON strings are valid YAML double-quoted scalars.
+        _ => serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value)),
+    }
+}
+
+/// Whether `value` can be written unquoted and still read back as the same
+/// string (and not as a number, boolean or null).
+fn is_plain_safe(value: &str) -> bool {
+    let Some(first) = value.chars().next() else {
+        return false;
+    };
+
+    if !(first.is_ascii_alphanumeric() || matches!(first, '_' | '/' | '.')) {
+        return false;
+    }
+
+    if !value.chars().all(|c| {
+        c.is_ascii_alphanumeric()
+            || matches!(c, '_' | '-' | '.' | '/' | '+' | '=' | '@' | '%' | '~' | '^')
+    }) {
+        return false;
+    }
+
+    let lower = value.to_lowercase();
+    let reserved = [
+        "true", "false", "yes", "no", "on", "off", "y", "n", "null", "~", ".inf", ".nan",
+    ];
+    if reserved.contains(&lower.as_str()) {
+        return false;
+    }
+
+    value.parse::<f64>().is_err()
+        && !lower.starts_with("0x")
+        && !lower.starts_with("0o")
+        && !value
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '_' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_files::FileFormat;
+
+    fn path(expression: &str) -> Vec<String> {
+        crate::local_files::parse_key_path(expression).unwrap()
+    }
+
+    fn set(contents: &str, key_path: &str, value: &str) -> String {
+        YamlFile.set(contents, &path(key_path), value).unwrap()
+    }
+
+    fn get(contents: &str, key_path: &str) -> Option<String> {
+        YamlFile.get(contents, &path(key_path)).unwrap()
+    }
+
+    #[test]
+    fn replaces_nested_keys_and_keeps_the_rest() {
+        let contents = "\
+# app config
+database:
+  host: db.internal # primary
+  password: old   # rotated by birch
+  port: 5432
+cache:
+  password: other
+";
+        let written = set(contents, "database.password", "new");
+        assert_eq!(
+            written,
+            "\
+# app config
+database:
+  host: db.internal # primary
+  password: new   # rotated by birch
+  port: 5432
+cache:
+  password: other
+"
+        );
+        assert_eq!(get(&written, "cache.password").as_deref(), Some("other"));
+    }
+
+    #[test]
+    fn keeps_the_quoting_style() {
+        let contents = "a: 'old' # c\nb: \"old\"\n";
+        assert_eq!(set(contents, "a", "it's"), "a: 'it''s' # c\nb: \"old\"\n");
+        assert_eq!(set(contents, "b", "x\"y"), "a: 'old' # c\nb: \"x\\\"y\"\n");
+    }
+
+    #[test]
+    fn quotes_values_that_would_change_type() {
+        for value in ["true", "123", "null", "a: b", "# not a comment", ""] {
+            let written = set("key: old\n", "key", value);
+            assert_eq!(get(&written, "key").as_deref(), Some(value), "{}", written);
+        }
+    }
+
+    #[test]
+    fn replaces_block_scalars() {
+        let contents = "\
+cert: |
+  -----BEGIN-----
+  abc
+  -----END-----
+next: 1
+";
+        let written = set(contents, "cert", "line one\nline two");
+        assert_eq!(written, "cert: \"line one\\nline two\"\nnext: 1\n");
+        assert_eq!(get(&written, "cert").as_deref(), Some("line one\nline two"));
+
+        let folded = set("a: >\n  folded\n  text\nb: 2\n", "a", "x");
+        assert_eq!(folded, "a: \"x\"\nb: 2\n");
+    }
+
+    #[test]
+    fn inserts_missing_keys_and_parents() {
+        assert_eq!(
+            set("database:\n  host: h\n", "database.password", "p"),
+            "database:\n  host: h\n  password: p\n"
+        );
+        assert_eq!(
+            set("other: 1\n", "database.password", "p"),
+            "other: 1\ndatabase:\n  password: p\n"
+        );
+        assert_eq!(set("", "a.b", "v"), "a:\n  b: v\n");
+    }
+
+    #[test]
+    fn refuses_flow_and_nested_targets() {
+        assert!(YamlFile
+            .set("db: {password: x}\n", &path("db"), "v")
+            .is_err());
+        assert!(YamlFile
+            .set("db:\n  password: x\n", &path("db"), "v")
+            .is_err());
+
+        // Keys inside flow mappings are read but never rewritten.
+        let flow = "db: {password: x}\n";
+        assert_eq!(get(flow, "db.password").as_deref(), Some("x"));
+        assert!(YamlFile.set(flow, &path("db.password"), "v").is_err());
+    }
+
+    #[test]
+    fn edits_key_value_lists() {
+        let contents = "\
+services:
+  api:
+    environment:
+      - LOG_LEVEL=info
+      - API_KEY=old
+";
+        let written = set(contents, "services.api.environment.API_KEY", "new");
+        assert!(written.contains("      - API_KEY=new\n"), "{}", written);
+        assert!(written.contains("      - LOG_LEVEL=info\n"));
+
+        let added = set(contents, "services.api.environment.TOKEN", "t");
+        assert!(
+            added.ends_with("      - API_KEY=old\n      - TOKEN=t\n"),
+            "{}",
+            added
+        );
+    }
+
+    #[test]
+    fn edits_the_document_holding_the_key() {
+        let contents = "a: 1\n---\nsecret: old\n";
+        assert_eq!(set(contents, "secret", "new"), "a: 1\n---\nsecret: new\n");
+    }
+
+    #[test]
+    fn writes_kubernetes_data_base64_encoded() {
+        let contents = "\
+apiVersion: v1
+kind: Secret
+metadata:
+  name: app
+data:
+  API_KEY: b2xk # old
+";
+        let written = set(contents, "API_KEY", "new");
+        assert!(written.contains("  API_KEY: bmV3 # old\n"), "{}", written);
+        assert_eq!(get(&written, "API_KEY").as_deref(), Some("new"));
+
+        let added = set(contents, "tls\\.key", "k");
+        assert!(added.ends_with("  tls.key: aw==\n"), "{}", added);
+    }
+
+    #[test]
+    fn writes_kubernetes_string_data_in_place() {
+        let contents = "\
+apiVersion: v1
+kind: Secret
+metadata:
+  name: app
+stringData:
+  API_KEY: old
+data:
+  OTHER: b3RoZXI=
+";
+        let written = set(contents, "API_KEY", "new");
+        assert!(
+            written.contains("stringData:\n  API_KEY: new\n"),
+            "{}",
+            written
+        );
+        assert_eq!(get(&written, "OTHER").as_deref(), Some("other"));
+    }
+}
@@ -1,18 +1,32 @@
+mod approval;
 mod audit;
 mod auth_callback;
+mod bundle;
 mod cli;
 mod config;
 mod connectors;
 mod daemon;
 mod dev;
+mod dotenv;
+mod fanout;
+mod generator;
+mod inventory;
+mod local_files;
 mod lock;
+mod manifest;
+mod minters;
 mod pool;
 mod prod;
+mod revocation;
 mod rollback;
 mod rotation;
 mod saas;
+mod scheduler;
+mod secrets_file;
 mod signals;
+mod staged;
 mod tui;
+mod versions;
 
 use anyhow::Result;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -0,0 +1,808 @@
+use crate::approval::ApprovalMode;
+use crate::config::{Config, GenerateConfig};
+use crate::connectors::mask_secret;
+use crate::fanout::Target;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_MANIFEST_PATH: &str = "birch.toml";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Manifest {
+    #[serde(default)]
+    pub secrets: BTreeMap<String, ManifestSecret>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestSecret {
+    pub targets: Vec<Target>,
+    pub generate: Option<GenerateConfig>,
+    pub pool: Option<String>,
+    pub schedule: Option<String>,
+    pub max_age: Option<String>,
+}
+
+impl Manifest {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+        let mut manifest: Manifest = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse manifest {}", path.display()))?;
+
+        manifest.validate()?;
+
+        // Env files are relative to the manifest, not to wherever birch runs from.
+        if let Some(base) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            for target in manifest.secrets.values_mut().flat_map(|s| &mut s.targets) {
+                if target.env == "dev" {
+                    let env_file = target.env_file.as_deref().unwrap_or(".env");
+                    target.env_file = Some(base.join(env_file).to_string_lossy().into_owned());
+                }
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, secret) in &self.secrets {
+            if secret.targets.is_empty() {
+                anyhow::bail!("Secret '{}' has no targets", name);
+            }
+
+            for target in &secret.targets {
+                if target.env != "dev" && target.service.is_none() {
+                    anyhow::bail!(
+                        "Target '{}' of secret '{}' needs a service",
+                        target.env,
+                        name
+                    );
+                }
+            }
+
+            if let Some(max_age) = &secret.max_age {
+                humantime::parse_duration(max_age)
+                    .with_context(|| format!("Invalid max_age for '{}'", name))?;
+            }
+
+            if let Some(schedule) = &secret.schedule {
+                crate::scheduler::next_cron_run(schedule, Utc::now())
+                    .with_context(|| format!("Invalid schedule for '{}'", name))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Observed {
+    Value(String),
+    Missing,
+    /// The secret exists but its value cannot be read back.
+    WriteOnly(String),
+    Unreadable(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    InSync,
+    Unknown,
+    Create {
+        targets: Vec<usize>,
+    },
+    Sync {
+        source: usize,
+        targets: Vec<usize>,
+    },
+    Rotate {
+        reason: String,
+    },
+    /// A change is needed but cannot be made safely without the user.
+    Blocked {
+        reason: String,
+    },
+}
+
+impl Action {
+    pub fn is_change(&self) -> bool {
+        matches!(
+            self,
+            Action::Create { .. } | Action::Sync { .. } | Action::Rotate { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SecretPlan {
+    pub secret_name: String,
+    pub observed: Vec<Observed>,
+    pub action: Action,
+}
+
+pub async fn plan(manifest_path: String) -> Result<()> {
+    let manifest = Manifest::load(Path::new(&manifest_path))?;
+    let plans = build_plans(&manifest).await?;
+
+    print_plans(&manifest, &plans);
+    Ok(())
+}
+
+pub async fn apply(manifest_path: String, dry_run: bool, approval: ApprovalMode) -> Result<()> {
+    if dry_run {
+        println!("🔍 DRY RUN: No changes will be made");
+    }
+
+    let config = Config::load()?;
+    let manifest = Manifest::load(Path::new(&manifest_path))?;
+
+    // The plan only holds while nothing else rotates these secrets.
+    let _locks = if dry_run {
+        Vec::new()
+    } else {
+        lock_secrets(
+            manifest
+                .secrets
+                .iter()
+                .map(|(name, secret)| (name.as_str(), secret)),
+        )?
+    };
+    let plans = build_plans(&manifest).await?;
+
+    print_plans(&manifest, &plans);
+
+    let changes: Vec<&SecretPlan> = plans.iter().filter(|p| p.action.is_change()).collect();
+    let blocked = plans
+        .iter()
+        .filter(|p| matches!(p.action, Action::Blocked { .. }))
+        .count();
+
+    if changes.is_empty() {
+        if blocked > 0 {
+            anyhow::bail!("{} secret(s) blocked; see the plan above", blocked);
+        }
+        println!("✅ Everything is up to date");
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("✅ Dry run complete (no changes made)");
+        return Ok(());
+    }
+
+    if !approval.confirm(&format!("Apply {} change(s)?", changes.len()))? {
+        anyhow::bail!("Aborted by user");
+    }
+
+    let touches_prod = changes.iter().any(|plan| {
+        manifest.secrets[&plan.secret_name]
+            .targets
+            .iter()
+            .any(|t| t.env != "dev")
+    });
+
+    if touches_prod && !crate::prod::check_maintenance_window(&config)? {
+        println!("⚠️  Outside maintenance window");
+        let scope = changes.iter().flat_map(|plan| {
+            manifest.secrets[&plan.secret_name]
+                .targets
+                .iter()
+                .filter(|t| t.env != "dev")
+                .map(|t| (t.env.as_str(), plan.secret_name.as_str()))
+        });
+        if !approval.confirm_outside_window(&config, scope)? {
+            anyhow::bail!("Aborted: outside maintenance window");
+        }
+    }
+
+    let mut failures = 0;
+
+    for plan in changes {
+        let secret = &manifest.secrets[&plan.secret_name];
+        println!("\n🔄 {}", plan.secret_name);
+
+        if let Err(e) = apply_secret(&config, secret, plan).await {
+            println!("❌ {}: {}", plan.secret_name, e);
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} secret(s) failed to apply", failures);
+    }
+    if blocked > 0 {
+        anyhow::bail!("{} secret(s) blocked; see the plan above", blocked);
+    }
+
+    println!("\n✅ Apply complete");
+    Ok(())
+}
+
+/// Locks every env of every secret, failing if any lock is held elsewhere.
+fn lock_secrets<'a>(
+    secrets: impl IntoIterator<Item = (&'a str, &'a ManifestSecret)>,
+) -> Result<Vec<crate::lock::Lock>> {
+    let mut locks = Vec::new();
+
+    for (name, secret) in secrets {
+        for env in target_envs(secret) {
+            let mut lock = crate::lock::Lock::new(env, name)?;
+            lock.acquire("apply")?;
+            locks.push(lock);
+        }
+    }
+
+    Ok(locks)
+}
+
+fn target_envs(secret: &ManifestSecret) -> Vec<&str> {
+    let mut envs: Vec<&str> = secret.targets.iter().map(|t| t.env.as_str()).collect();
+    envs.sort();
+    envs.dedup();
+    envs
+}
+
+async fn build_plans(manifest: &Manifest) -> Result<Vec<SecretPlan>> {
+    let mut plans = Vec::new();
+
+    for (name, secret) in &manifest.secrets {
+        let mut observed = Vec::new();
+        for target in &secret.targets {
+            observed.push(observe(name, target).await);
+        }
+
+        let mut values: Vec<&str> = observed
+            .iter()
+            .filter_map(|o| match o {
+                Observed::Value(value) => Some(value.as_str()),
+                _ => None,
+            })
+            .collect();
+        values.sort();
+        values.dedup();
+
+        let recorded = if values.len() > 1 {
+            recorded_value(name, secret)?
+        } else {
+            None
+        };
+        let rotated_at = if secret.max_age.is_some() {
+            oldest_rotation(name, secret)?
+        } else {
+            None
+        };
+
+        let action = decide(
+            secret,
+            &observed,
+            recorded.as_deref(),
+            rotated_at,
+            Utc::now(),
+        )?;
+
+        plans.push(SecretPlan {
+            secret_name: name.clone(),
+            observed,
+            action,
+        });
+    }
+
+    Ok(plans)
+}
+
+async fn observe(secret_name: &str, target: &Target) -> Observed {
+    match target.read(secret_name).await {
+        Ok(Some(value)) => Observed::Value(value),
+        Ok(None) => Observed::Missing,
+        Err(e) if e.downcast_ref::<crate::connectors::WriteOnly>().is_some() => {
+            Observed::WriteOnly(e.to_string())
+        }
+        Err(e) => Observed::Unreadable(e.to_string()),
+    }
+}
+
+/// Decides what `birch apply` does for one secret. `recorded` is the newest
+/// value birch recorded for it, used to pick the source when readable targets
+/// disagree; `rotated_at` is its oldest rotation across envs.
+fn decide(
+    secret: &ManifestSecret,
+    observed: &[Observed],
+    recorded: Option<&str>,
+    rotated_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<Action> {
+    let missing: Vec<usize> = observed
+        .iter()
+        .enumerate()
+        .filter(|(_, o)| matches!(o, Observed::Missing))
+        .map(|(i, _)| i)
+        .collect();
+    let values: Vec<(usize, &str)> = observed
+        .iter()
+        .enumerate()
+        .filter_map(|(i, o)| match o {
+            Observed::Value(value) => Some((i, value.as_str())),
+            _ => None,
+        })
+        .collect();
+
+    let Some(&(first, first_value)) = values.first() else {
+        if missing.is_empty() {
+            // Nothing can be compared, but the value's age still counts.
+            return Ok(match rotation_due(secret, rotated_at, now)? {
+                Some(reason) => Action::Rotate { reason },
+                None => Action::Unknown,
+            });
+        }
+
+        // Creating a value would overwrite, or split from, one that cannot
+        // be read.
+        for (target, o) in secret.targets.iter().zip(observed) {
+            let reason = match o {
+                Observed::WriteOnly(_) => format!(
+                    "{} already holds a write-only value; rotate the secret to replace it",
+                    target.label()
+                ),
+                Observed::Unreadable(reason) => {
+                    format!("{} could not be read: {}", target.label(), reason)
+                }
+                Observed::Value(_) | Observed::Missing => continue,
+            };
+            return Ok(Action::Blocked { reason });
+        }
+
+        return Ok(Action::Create { targets: missing });
+    };
+
+    let (source, source_value) =
+        if values.iter().all(|(_, v)| *v == first_value) {
+            (first, first_value)
+        } else {
+            match recorded.and_then(|r| values.iter().find(|(_, v)| *v == r)) {
+                Some(&(i, value)) => (i, value),
+                None => return Ok(Action::Blocked {
+                    reason:
+                        "targets hold different values and none matches the last recorded version"
+                            .to_string(),
+                }),
+            }
+        };
+
+    let drifted: Vec<usize> = observed
+        .iter()
+        .enumerate()
+        .filter(|(_, o)| match o {
+            Observed::Value(value) => value != source_value,
+            Observed::Missing => true,
+            Observed::WriteOnly(_) | Observed::Unreadable(_) => false,
+        })
+        .map(|(i, _)| i)
+        .collect();
+
+    if !drifted.is_empty() {
+        return Ok(Action::Sync {
+            source,
+            targets: drifted,
+        });
+    }
+
+    Ok(match rotation_due(secret, rotated_at, now)? {
+        Some(reason) => Action::Rotate { reason },
+        None => Action::InSync,
+    })
+}
+
+/// When the secret falls due under its `max_age`: that long after its last
+/// rotation, or `now` when birch has no rotation record and the value's age
+/// is unknown. `plan`/`apply` and the scheduler both go by this.
+pub fn max_age_due_at(
+    secret: &ManifestSecret,
+    rotated_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    let Some(max_age) = &secret.max_age else {
+        return Ok(None);
+    };
+    let max_age = chrono::Duration::from_std(humantime::parse_duration(max_age)?)?;
+
+    Ok(Some(rotated_at.map_or(now, |t| t + max_age)))
+}
+
+/// Why the secret is due under its `max_age`, or `None` when it is not.
+fn rotation_due(
+    secret: &ManifestSecret,
+    rotated_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<Option<String>> {
+    match (max_age_due_at(secret, rotated_at, now)?, rotated_at) {
+        (Some(due_at), _) if due_at > now => Ok(None),
+        (Some(_), None) => Ok(Some("age unknown (never rotated by birch)".to_string())),
+        (Some(_), Some(rotated_at)) => Ok(Some(format!(
+            "last rotated {}",
+            rotated_at.format("%Y-%m-%d %H:%M UTC")
+        ))),
+        (None, _) => Ok(None),
+    }
+}
+
+/// The newest version recorded for the secret across its envs.
+fn recorded_value(secret_name: &str, secret: &ManifestSecret) -> Result<Option<String>> {
+    let mut newest: Option<crate::versions::SecretVersion> = None;
+
+    for env in target_envs(secret) {
+        let history = crate::versions::VersionHistory::load(env, secret_name)?;
+        if let Some(current) = history.current() {
+            if newest
+                .as_ref()
+                .is_none_or(|n| current.created_at > n.created_at)
+            {
+                newest = Some(current.clone());
+            }
+        }
+    }
+
+    match newest {
+        Some(version) => Ok(Some(
+            crate::audit::AuditLogger::new()?.decrypt_secret(&version.encrypted_value)?,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// The oldest rotation of the secret across its envs, or `None` when any env
+/// has no rotation record and the age of its value is unknown.
+pub fn oldest_rotation(
+    secret_name: &str,
+    secret: &ManifestSecret,
+) -> Result<Option<DateTime<Utc>>> {
+    let mut oldest: Option<DateTime<Utc>> = None;
+
+    for env in target_envs(secret) {
+        let Some(rotated_at) = crate::rotation::last_rotation(env, secret_name)? else {
+            return Ok(None);
+        };
+        oldest = Some(oldest.map_or(rotated_at, |o| o.min(rotated_at)));
+    }
+
+    Ok(oldest)
+}
+
+fn print_plans(manifest: &Manifest, plans: &[SecretPlan]) {
+    let (mut create, mut sync, mut rotate, mut unchanged, mut blocked) = (0, 0, 0, 0, 0);
+
+    println!("📋 Plan\n");
+
+    for plan in plans {
+        let secret = &manifest.secrets[&plan.secret_name];
+
+        let (symbol, summary) = match &plan.action {
+            Action::InSync => {
+                unchanged += 1;
+                ("=", "in sync".to_string())
+            }
+            Action::Unknown => {
+                unchanged += 1;
+                ("?", "state unknown (no readable target)".to_string())
+            }
+            Action::Create { targets } => {
+                create += 1;
+                let labels: Vec<String> =
+                    targets.iter().map(|i| secret.targets[*i].label()).collect();
+                ("+", format!("create in {}", labels.join(", ")))
+            }
+            Action::Sync { source, targets } => {
+                sync += 1;
+                let labels: Vec<String> =
+                    targets.iter().map(|i| secret.targets[*i].label()).collect();
+                (
+                    "~",
+                    format!(
+                        "sync {} from {}",
+                        labels.join(", "),
+                        secret.targets[*source].label()
+                    ),
+                )
+            }
+            Action::Rotate { reason } => {
+                rotate += 1;
+                (
+                    "↻",
+                    format!(
+                        "rotate ({}; max_age {})",
+                        reason,
+                        secret.max_age.as_deref().unwrap_or("-")
+                    ),
+                )
+            }
+            Action::Blocked { reason } => {
+                blocked += 1;
+                ("!", format!("blocked: {}", reason))
+            }
+        };
+
+        println!("  {} {:<30} {}", symbol, plan.secret_name, summary);
+
+        for (target, observed) in secret.targets.iter().zip(&plan.observed) {
+            let state = match observed {
+                Observed::Value(value) => mask_secret(value),
+                Observed::Missing => "(missing)".to_string(),
+                Observed::WriteOnly(reason) => format!("(exists; {})", reason),
+                Observed::Unreadable(reason) => format!("? ({})", reason),
+            };
+            println!("      {:<28} {}", target.label(), state);
+        }
+
+        if let Some(pool) = &secret.pool {
+            println!("      pool: {}", pool);
+        }
+        if let Some(schedule) = &secret.schedule {
+            match crate::scheduler::next_cron_run(schedule, Utc::now()) {
+                Ok(next) => println!(
+                    "      schedule: {} (next: {})",
+                    schedule,
+                    next.format("%Y-%m-%d %H:%M UTC")
+                ),
+                Err(_) => println!("      schedule: {}", schedule),
+            }
+        }
+    }
+
+    println!(
+        "\nPlan: {} to create, {} to sync, {} to rotate, {} unchanged{}",
+        create,
+        sync,
+        rotate,
+        unchanged,
+        if blocked > 0 {
+            format!(", {} blocked", blocked)
+        } else {
+            String::new()
+        }
+    );
+}
+
+pub async fn rotate_secret(
+    config: &Config,
+    secret_name: &str,
+    secret: &ManifestSecret,
+) -> Result<()> {
+    let _locks = lock_secrets([(secret_name, secret)])?;
+
+    crate::rotation::rotate_targets(config, secret_name, &secret.targets, &value_source(secret))
+        .await?;
+    Ok(())
+}
+
+async fn apply_secret(config: &Config, secret: &ManifestSecret, plan: &SecretPlan) -> Result<()> {
+    let name = &plan.secret_name;
+    let select = |indices: &[usize]| -> Vec<Target> {
+        indices.iter().map(|&i| secret.targets[i].clone()).collect()
+    };
+
+    match &plan.action {
+        Action::Sync { source, targets } => {
+            let Observed::Value(value) = &plan.observed[*source] else {
+                unreachable!("sync source is always a readable target");
+            };
+            let targets = select(targets);
+            crate::fanout::apply(name, value, &targets).await?;
+
+            let source_env = &secret.targets[*source].env;
+            let key_id = match crate::versions::find_version(source_env, name, value)? {
+                Some(version) => crate::versions::key_id(source_env, name, version)?,
+                None => None,
+            };
+            crate::rotation::record_written(name, value, &targets, key_id.as_deref())
+        }
+        Action::Create { targets } => {
+            crate::rotation::rotate_targets(config, name, &select(targets), &value_source(secret))
+                .await?;
+            Ok(())
+        }
+        Action::Rotate { .. } => {
+            crate::rotation::rotate_targets(config, name, &secret.targets, &value_source(secret))
+                .await?;
+            Ok(())
+        }
+        Action::InSync | Action::Unknown | Action::Blocked { .. } => Ok(()),
+    }
+}
+
+fn value_source(secret: &ManifestSecret) -> crate::rotation::ValueSource {
+    crate::rotation::ValueSource {
+        pool: secret.pool.clone(),
+        generate: secret.generate.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(targets: usize, max_age: Option<&str>) -> ManifestSecret {
+        ManifestSecret {
+            targets: (0..targets)
+                .map(|i| Target::service("prod", &format!("svc{}", i), false))
+                .collect(),
+            generate: None,
+            pool: None,
+            schedule: None,
+            max_age: max_age.map(str::to_string),
+        }
+    }
+
+    fn value(v: &str) -> Observed {
+        Observed::Value(v.to_string())
+    }
+
+    fn decide_now(
+        secret: &ManifestSecret,
+        observed: &[Observed],
+        recorded: Option<&str>,
+    ) -> Action {
+        decide(secret, observed, recorded, None, Utc::now()).unwrap()
+    }
+
+    #[test]
+    fn create_writes_only_missing_targets() {
+        let action = decide_now(
+            &secret(2, None),
+            &[Observed::Missing, Observed::Missing],
+            None,
+        );
+        assert!(matches!(action, Action::Create { targets } if targets == [0, 1]));
+    }
+
+    #[test]
+    fn create_is_blocked_by_existing_write_only_target() {
+        let action = decide_now(
+            &secret(2, None),
+            &[
+                Observed::Missing,
+                Observed::WriteOnly("secret is write-only".to_string()),
+            ],
+            None,
+        );
+        assert!(matches!(action, Action::Blocked { reason } if reason.contains("prod/svc1")));
+    }
+
+    #[test]
+    fn create_is_blocked_by_unreadable_target() {
+        let action = decide_now(
+            &secret(2, None),
+            &[
+                Observed::Unreadable("timeout".to_string()),
+                Observed::Missing,
+            ],
+            None,
+        );
+        assert!(matches!(action, Action::Blocked { reason } if reason.contains("timeout")));
+    }
+
+    #[test]
+    fn nothing_readable_or_missing_is_unknown() {
+        let action = decide_now(
+            &secret(1, None),
+            &[Observed::WriteOnly("secret is write-only".to_string())],
+            None,
+        );
+        assert!(matches!(action, Action::Unknown));
+    }
+
+    #[test]
+    fn write_only_secret_rotates_after_max_age() {
+        let secret = secret(2, Some("1d"));
+        let observed = [
+            Observed::WriteOnly("secret is write-only".to_string()),
+            Observed::Unreadable("timeout".to_string()),
+        ];
+        let now = Utc::now();
+
+        let fresh = decide(&secret, &observed, None, Some(now), now).unwrap();
+        assert!(matches!(fresh, Action::Unknown));
+
+        let stale = decide(
+            &secret,
+            &observed,
+            None,
+            Some(now - chrono::Duration::days(2)),
+            now,
+        )
+        .unwrap();
+        assert!(matches!(stale, Action::Rotate { reason } if reason.contains("last rotated")));
+    }
+
+    #[test]
+    fn agreeing_values_sync_missing_targets() {
+        let action = decide_now(
+            &secret(3, None),
+            &[
+                Observed::Missing,
+                value("a"),
+                Observed::WriteOnly("secret is write-only".to_string()),
+            ],
+            None,
+        );
+        assert!(matches!(action, Action::Sync { source: 1, targets } if targets == [0]));
+    }
+
+    #[test]
+    fn disagreeing_values_sync_from_the_recorded_version() {
+        let action = decide_now(&secret(2, None), &[value("old"), value("new")], Some("new"));
+        assert!(matches!(action, Action::Sync { source: 1, targets } if targets == [0]));
+    }
+
+    #[test]
+    fn disagreeing_values_without_record_are_blocked() {
+        let action = decide_now(&secret(2, None), &[value("a"), value("b")], Some("c"));
+        assert!(matches!(action, Action::Blocked { .. }));
+
+        let action = decide_now(&secret(2, None), &[value("a"), value("b")], None);
+        assert!(matches!(action, Action::Blocked { .. }));
+    }
+
+    #[test]
+    fn in_sync_secret_rotates_after_max_age() {
+        let secret = secret(2, Some("1d"));
+        let observed = [value("a"), value("a")];
+        let now = Utc::now();
+
+        let fresh = decide(&secret, &observed, None, Some(now), now).unwrap();
+        assert!(matches!(fresh, Action::InSync));
+
+        let stale = decide(
+            &secret,
+            &observed,
+            None,
+            Some(now - chrono::Duration::days(2)),
+            now,
+        )
+        .unwrap();
+        assert!(matches!(stale, Action::Rotate { .. }));
+    }
+
+    #[test]
+    fn secret_never_rotated_by_birch_rotates_under_max_age() {
+        let observed = [value("a"), value("a")];
+        let now = Utc::now();
+
+        let never = decide(&secret(2, Some("30d")), &observed, None, None, now).unwrap();
+        assert!(matches!(never, Action::Rotate { reason } if reason.contains("age unknown")));
+
+        let unbounded = decide(&secret(2, None), &observed, None, None, now).unwrap();
+        assert!(matches!(unbounded, Action::InSync));
+    }
+
+    #[test]
+    fn max_age_is_due_after_rotation_or_now_when_unknown() {
+        let secret = secret(1, Some("1d"));
+        let now = Utc::now();
+        let rotated_at = now - chrono::Duration::hours(6);
+
+        assert_eq!(max_age_due_at(&secret, None, now).unwrap(), Some(now));
+        assert_eq!(
+            max_age_due_at(&secret, Some(rotated_at), now).unwrap(),
+            Some(rotated_at + chrono::Duration::days(1))
+        );
+        assert_eq!(
+            max_age_due_at(&self::secret(1, None), None, now).unwrap(),
+            None
+        );
+
+        // `decide` rotates exactly when the shared rule says the secret is due.
+        let observed = [value("a")];
+        for rotated_at in [
+            None,
+            Some(rotated_at),
+            Some(now - chrono::Duration::days(2)),
+        ] {
+            let due = max_age_due_at(&secret, rotated_at, now).unwrap().unwrap() <= now;
+            let action = decide(&secret, &observed, None, rotated_at, now).unwrap();
+            assert_eq!(matches!(action, Action::Rotate { .. }), due);
+        }
+    }
+}
@@ -0,0 +1,449 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use aws_sdk_iam::error::DisplayErrorContext;
+use aws_sdk_iam::types::StatusType;
+use aws_sdk_iam::Client as IamClient;
+use aws_sdk_secretsmanager::config::{Credentials, SharedCredentialsProvider};
+use std::time::Duration;
+
+/// New access keys take a few seconds to work everywhere.
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(60);
+const VERIFY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Access keys of an IAM user. A minted key is a bundle of the access key ID
+/// and the secret access key; replaced keys are deactivated, then deleted.
+pub struct AwsIamMinter {
+    user: String,
+    access_key_id_name: String,
+    secret_access_key_name: String,
+    access_key_id: Option<String>,
+    secret_access_key: Option<String>,
+    region: Option<String>,
+    verify_interval: Duration,
+}
+
+struct AccessKey {
+    id: String,
+    active: bool,
+}
+
+impl AwsIamMinter {
+    pub fn new(
+        config: &crate::config::Config,
+        user: &str,
+        access_key_id_name: &str,
+        secret_access_key_name: &str,
+    ) -> Self {
+        let auth = &config.connector_auth;
+
+        Self {
+            user: user.to_string(),
+            access_key_id_name: access_key_id_name.to_string(),
+            secret_access_key_name: secret_access_key_name.to_string(),
+            access_key_id: auth.aws_access_key_id.clone(),
+            secret_access_key: auth.aws_secret_access_key.clone(),
+            region: auth.aws_region.clone(),
+            verify_interval: VERIFY_INTERVAL,
+        }
+    }
+
+    async fn sdk_config(&self) -> aws_config::SdkConfig {
+        let region = aws_config::meta::region::RegionProviderChain::first_try(
+            self.region.clone().map(aws_config::Region::new),
+        )
+        .or_default_provider()
+        .or_else("us-east-1");
+
+        let mut loader = aws_config::from_env().region(region);
+        if let (Some(access_key_id), Some(secret_access_key)) =
+            (&self.access_key_id, &self.secret_access_key)
+        {
+            loader = loader.credentials_provider(Credentials::new(
+                access_key_id,
+                secret_access_key,
+                None,
+                None,
+                "birch-connector-auth",
+            ));
+        }
+
+        loader.load().await
+    }
+
+    async fn list_keys(&self, iam: &IamClient) -> Result<Vec<AccessKey>> {
+        let response = iam
+            .list_access_keys()
+            .user_name(&self.user)
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to list access keys of IAM user '{}': {}",
+                    self.user,
+                    DisplayErrorContext(e)
+                )
+            })?;
+
+        Ok(response
+            .access_key_metadata()
+            .iter()
+            .filter_map(|key| {
+                Some(AccessKey {
+                    id: key.access_key_id()?.to_string(),
+                    active: key.status() == Some(&StatusType::Active),
+                })
+            })
+            .collect())
+    }
+
+    async fn delete_key(&self, iam: &IamClient, key_id: &str) -> Result<()> {
+        iam.delete_access_key()
+            .user_name(&self.user)
+            .access_key_id(key_id)
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to delete access key {}: {}",
+                    key_id,
+                    DisplayErrorContext(e)
+                )
+            })?;
+        Ok(())
+    }
+
+    /// Makes room for a new key: IAM allows two per user. Inactive keys are
+    /// leftovers of earlier rotations and are deleted; active ones are not
+    /// touched.
+    async fn free_slot(&self, iam: &IamClient) -> Result<()> {
+        let keys = self.list_keys(iam).await?;
+        if keys.len() < 2 {
+            return Ok(());
+        }
+
+        if let Some(inactive) = keys.iter().find(|k| !k.active) {
+            self.delete_key(iam, &inactive.id).await?;
+            println!(
+                "🗑️  Deleted inactive access key {} to stay within IAM's two-key limit",
+                inactive.id
+            );
+            return Ok(());
+        }
+
+        anyhow::bail!(
+            "IAM user '{}' already has two active access keys ({}); revoke the superseded one with `birch revoke` or deactivate it in IAM first",
+            self.user,
+            keys.iter().map(|k| k.id.as_str()).collect::<Vec<_>>().join(", ")
+        )
+    }
+
+    /// Calls STS with the new key until it works, returning the caller ARN.
+    async fn verify(
+        &self,
+        sdk_config: &aws_config::SdkConfig,
+        id: &str,
+        secret: &str,
+    ) -> Result<String> {
+        let sts = aws_sdk_sts::Client::new(
+            &sdk_config
+                .to_builder()
+                .credentials_provider(SharedCredentialsProvider::new(Credentials::new(
+                    id,
+                    secret,
+                    None,
+                    None,
+                    "birch-minted",
+                )))
+                .build(),
+        );
+        let deadline = tokio::time::Instant::now() + VERIFY_TIMEOUT;
+
+        loop {
+            match sts.get_caller_identity().send().await {
+                Ok(identity) => {
+                    return identity
+                        .arn()
+                        .map(str::to_string)
+                        .ok_or_else(|| anyhow::anyhow!("STS returned no caller ARN"))
+                }
+                Err(e) if tokio::time::Instant::now() >= deadline => {
+                    anyhow::bail!("{}", DisplayErrorContext(e))
+                }
+                Err(_) => tokio::time::sleep(self.verify_interval).await,
+            }
+        }
+    }
+
+    async fn mint(&self, sdk_config: &aws_config::SdkConfig) -> Result<crate::minters::MintedKey> {
+        let iam = IamClient::new(sdk_config);
+
+        self.free_slot(&iam).await?;
+
+        let response = iam
+            .create_access_key()
+            .user_name(&self.user)
+            .send()
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to create access key for IAM user '{}': {}",
+                    self.user,
+                    DisplayErrorContext(e)
+                )
+            })?;
+        let key = response
+            .access_key()
+            .ok_or_else(|| anyhow::anyhow!("IAM response did not include an access key"))?;
+        let (id, secret) = (key.access_key_id(), key.secret_access_key());
+
+        match self.verify(sdk_config, id, secret).await {
+            Ok(arn) => println!("   ✓ Verified new access key {} as {}", id, arn),
+            Err(e) => {
+                // An unusable key would only take up one of the two slots.
+                let _ = self.delete_key(&iam, id).await;
+                anyhow::bail!("New access key {} failed STS verification: {}", id, e);
+            }
+        }
+
+        Ok(crate::minters::MintedKey {
+            value: crate::bundle::join(&[
+                (self.access_key_id_name.clone(), id.to_string()),
+                (self.secret_access_key_name.clone(), secret.to_string()),
+            ]),
+            id: Some(id.to_string()),
+        })
+    }
+
+    async fn revoke(&self, iam: &IamClient, key_id: &str) -> Result<()> {
+        // Deactivating first leaves the key unusable even if it cannot be
+        // deleted.
+        match iam
+            .update_access_key()
+            .user_name(&self.user)
+            .access_key_id(key_id)
+            .status(StatusType::Inactive)
+            .send()
+            .await
+        {
+            Ok(_) => {}
+            Err(e)
+                if e.as_service_error()
+                    .is_some_and(|service| service.is_no_such_entity_exception()) =>
+            {
+                return Ok(())
+            }
+            Err(e) => {
+                anyhow::bail!(
+                    "Failed to deactivate access key {}: {}",
+                    key_id,
+                    DisplayErrorContext(e)
+                )
+            }
+        }
+
+        self.delete_key(iam, key_id).await
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyMinter for AwsIamMinter {
+    async fn mint_key(&self, _name: &str) -> Result<crate::minters::MintedKey> {
+        self.mint(&self.sdk_config().await).await
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyRevoker for AwsIamMinter {
+    async fn revoke_key(&self, key_id: &str) -> Result<()> {
+        let iam = IamClient::new(&self.sdk_config().await);
+        self.revoke(&iam, key_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::aws::tests::sdk_config;
+    use mockito::Matcher;
+
+    fn minter() -> AwsIamMinter {
+        AwsIamMinter {
+            verify_interval: Duration::ZERO,
+            ..AwsIamMinter::new(
+                &crate::config::Config::default(),
+                "ci",
+                "AWS_ACCESS_KEY_ID",
+                "AWS_SECRET_ACCESS_KEY",
+            )
+        }
+    }
+
+    /// A Query API call such as `Action=ListAccessKeys`.
+    fn action(server: &mut mockito::Server, action: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::Regex(format!("Action={}(&|$)", action)))
+            .with_header("content-type", "text/xml")
+    }
+
+    fn access_keys(keys: &[(&str, &str)]) -> String {
+        let members: String = keys
+            .iter()
+            .map(|(id, status)| {
+                format!(
+                    "<member><UserName>ci</UserName><AccessKeyId>{}</AccessKeyId><Status>{}</Status></member>",
+                    id, status
+                )
+            })
+            .collect();
+        format!(
+            r#"<ListAccessKeysResponse xmlns="https://iam.amazonaws.com/doc/2010-05-08/">
+                <ListAccessKeysResult>
+                    <AccessKeyMetadata>{}</AccessKeyMetadata>
+                    <IsTruncated>false</IsTruncated>
+                </ListAccessKeysResult>
+            </ListAccessKeysResponse>"#,
+            members
+        )
+    }
+
+    fn iam_error(code: &str) -> String {
+        format!(
+            "<ErrorResponse><Error><Type>Sender</Type><Code>{}</Code><Message>The user with name ci cannot be found.</Message></Error><RequestId>1</RequestId></ErrorResponse>",
+            code
+        )
+    }
+
+    #[tokio::test]
+    async fn free_slot_deletes_the_inactive_key() {
+        let mut server = mockito::Server::new_async().await;
+        action(&mut server, "ListAccessKeys")
+            .with_body(access_keys(&[
+                ("AKIACURRENT", "Active"),
+                ("AKIAOLD", "Inactive"),
+            ]))
+            .create_async()
+            .await;
+        let delete = action(&mut server, "DeleteAccessKey")
+            .match_body(Matcher::Regex("AccessKeyId=AKIAOLD".to_string()))
+            .with_body("<DeleteAccessKeyResponse/>")
+            .create_async()
+            .await;
+
+        minter()
+            .free_slot(&IamClient::new(&sdk_config(&server)))
+            .await
+            .unwrap();
+
+        delete.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn free_slot_keeps_two_active_keys() {
+        let mut server = mockito::Server::new_async().await;
+        action(&mut server, "ListAccessKeys")
+            .with_body(access_keys(&[("AKIAONE", "Active"), ("AKIATWO", "Active")]))
+            .create_async()
+            .await;
+        let delete = action(&mut server, "DeleteAccessKey")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let err = minter()
+            .free_slot(&IamClient::new(&sdk_config(&server)))
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string().contains("two active access keys"),
+            "{}",
+            err
+        );
+        delete.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn verify_polls_until_sts_accepts_the_key() {
+        let mut server = mockito::Server::new_async().await;
+        let signed_with_new_key = Matcher::Regex("Credential=AKIANEW/".to_string());
+        let rejected = action(&mut server, "GetCallerIdentity")
+            .match_header("authorization", signed_with_new_key.clone())
+            .with_status(403)
+            .with_body(
+                "<ErrorResponse><Error><Type>Sender</Type><Code>InvalidClientTokenId</Code><Message>The security token included in the request is invalid.</Message></Error></ErrorResponse>",
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let accepted = action(&mut server, "GetCallerIdentity")
+            .match_header("authorization", signed_with_new_key)
+            .with_body(
+                r#"<GetCallerIdentityResponse xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
+                    <GetCallerIdentityResult>
+                        <Arn>arn:aws:iam::123456789012:user/ci</Arn>
+                        <UserId>AIDA</UserId>
+                        <Account>123456789012</Account>
+                    </GetCallerIdentityResult>
+                </GetCallerIdentityResponse>"#,
+            )
+            .create_async()
+            .await;
+
+        let arn = minter()
+            .verify(&sdk_config(&server), "AKIANEW", "new-secret")
+            .await
+            .unwrap();
+
+        assert_eq!(arn, "arn:aws:iam::123456789012:user/ci");
+        rejected.assert_async().await;
+        accepted.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn revoking_a_deleted_key_succeeds() {
+        let mut server = mockito::Server::new_async().await;
+        action(&mut server, "UpdateAccessKey")
+            .with_status(404)
+            .with_body(iam_error("NoSuchEntity"))
+            .create_async()
+            .await;
+        let delete = action(&mut server, "DeleteAccessKey")
+            .expect(0)
+            .create_async()
+            .await;
+
+        minter()
+            .revoke(&IamClient::new(&sdk_config(&server)), "AKIAGONE")
+            .await
+            .unwrap();
+
+        delete.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn revoke_deactivates_then_deletes() {
+        let mut server = mockito::Server::new_async().await;
+        let deactivate = action(&mut server, "UpdateAccessKey")
+            .match_body(Matcher::AllOf(vec![
+                Matcher::Regex("AccessKeyId=AKIAOLD".to_string()),
+                Matcher::Regex("Status=Inactive".to_string()),
+            ]))
+            .with_body("<UpdateAccessKeyResponse/>")
+            .create_async()
+            .await;
+        let delete = action(&mut server, "DeleteAccessKey")
+            .match_body(Matcher::Regex("AccessKeyId=AKIAOLD".to_string()))
+            .with_body("<DeleteAccessKeyResponse/>")
+            .create_async()
+            .await;
+
+        minter()
+            .revoke(&IamClient::new(&sdk_config(&server)), "AKIAOLD")
+            .await
+            .unwrap();
+
+        deactivate.assert_async().await;
+        delete.assert_async().await;
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub struct DatadogMinter {
+    api_key: String,
+    app_key: String,
+    site: String,
+    client: reqwest::Client,
+}
+
+impl DatadogMinter {
+    pub fn new(config: &crate::config::Config, site: &str) -> Result<Self> {
+        let api_key = config
+            .connector_auth
+            .datadog_api_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("DATADOG_API_KEY not configured"))?
+            .clone();
+
+        let app_key = config
+            .connector_auth
+            .datadog_app_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("DATADOG_APP_KEY not configured"))?
+            .clone();
+
+        Ok(Self {
+            api_key,
+            app_key,
+            site: site.to_string(),
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyMinter for DatadogMinter {
+    async fn mint_key(&self, name: &str) -> Result<crate::minters::MintedKey> {
+        let url = format!("https://api.{}/api/v2/api_keys", self.site);
+
+        let body = serde_json::json!({
+            "data": {
+                "type": "api_keys",
+                "attributes": { "name": name }
+            }
+        });
+
+        let response = self
+            .client
+            .post(&url)
+            .header("DD-API-KEY", &self.api_key)
+            .header("DD-APPLICATION-KEY", &self.app_key)
+            .json(&body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Datadog API error ({}): {}", status, text);
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        let value = json["data"]["attributes"]["key"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Datadog response did not include an API key"))?;
+        let id = json["data"]["id"].as_str().map(|s| s.to_string());
+
+        Ok(crate::minters::MintedKey {
+            value: value.to_string(),
+            id,
+        })
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyRevoker for DatadogMinter {
+    async fn revoke_key(&self, key_id: &str) -> Result<()> {
+        let url = format!("https://api.{}/api/v2/api_keys/{}", self.site, key_id);
+
+        let response = self
+            .client
+            .delete(&url)
+            .header("DD-API-KEY", &self.api_key)
+            .header("DD-APPLICATION-KEY", &self.app_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Datadog API error ({}): {}", status, text);
+        }
+
+        Ok(())
+    }
+}
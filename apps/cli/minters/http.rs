@@ -0,0 +1,136 @@
+use crate::config::HttpMinterConfig;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+
+pub struct HttpMinter {
+    config: HttpMinterConfig,
+    client: reqwest::Client,
+}
+
+impl HttpMinter {
+    pub fn new(config: HttpMinterConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyMinter for HttpMinter {
+    async fn mint_key(&self, name: &str) -> Result<crate::minters::MintedKey> {
+        let vars = [("name", name)];
+        let url = expand_template(&self.config.url, &vars)?;
+        let method = reqwest::Method::from_bytes(self.config.method.to_uppercase().as_bytes())
+            .context("Invalid HTTP method for minter")?;
+
+        let mut request = self.client.request(method, &url);
+
+        for (header, value) in &self.config.headers {
+            request = request.header(header, expand_template(value, &vars)?);
+        }
+
+        if let Some(body) = &self.config.body {
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(expand_template(body, &vars)?);
+        }
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Minter endpoint error ({}): {}", status, text);
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        let value = json
+            .pointer(&self.config.value_pointer)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Minter response has no string at '{}'",
+                    self.config.value_pointer
+                )
+            })?;
+
+        let id = match &self.config.id_pointer {
+            Some(pointer) => Some(
+                json.pointer(pointer)
+                    .and_then(|v| match v {
+                        serde_json::Value::String(s) => Some(s.clone()),
+                        serde_json::Value::Number(n) => Some(n.to_string()),
+                        _ => None,
+                    })
+                    .ok_or_else(|| anyhow::anyhow!("Minter response has no id at '{}'", pointer))?,
+            ),
+            None => None,
+        };
+
+        Ok(crate::minters::MintedKey {
+            value: value.to_string(),
+            id,
+        })
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyRevoker for HttpMinter {
+    async fn revoke_key(&self, key_id: &str) -> Result<()> {
+        let revoke_url = self
+            .config
+            .revoke_url
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No revoke_url configured for HTTP minter"))?;
+
+        let vars = [("id", key_id)];
+        let url = expand_template(revoke_url, &vars)?;
+        let method =
+            reqwest::Method::from_bytes(self.config.revoke_method.to_uppercase().as_bytes())
+                .context("Invalid HTTP method for revoker")?;
+
+        let mut request = self.client.request(method, &url);
+
+        for (header, value) in &self.config.headers {
+            request = request.header(header, expand_template(value, &vars)?);
+        }
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("Revoke endpoint error ({}): {}", status, text);
+        }
+
+        Ok(())
+    }
+}
+
+pub fn expand_template(template: &str, vars: &[(&str, &str)]) -> Result<String> {
+    let mut result = String::new();
+    let mut rest = template.to_string();
+
+    for (key, value) in vars {
+        rest = rest.replace(&format!("{{{{{}}}}}", key), value);
+    }
+
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .map(|i| start + i)
+            .ok_or_else(|| anyhow::anyhow!("Unterminated '${{' in minter template"))?;
+
+        let var = &rest[start + 2..end];
+        let value = std::env::var(var)
+            .map_err(|_| anyhow::anyhow!("{} environment variable not set", var))?;
+
+        result.push_str(&rest[..start]);
+        result.push_str(&value);
+        rest = rest[end + 1..].to_string();
+    }
+
+    result.push_str(&rest);
+    Ok(result)
+}
@@ -0,0 +1,453 @@
+use crate::config::{Config, MinterConfig};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+pub mod aws_iam;
+pub mod datadog;
+pub mod http;
+pub mod openai;
+pub mod sendgrid;
+
+#[derive(Debug, Clone)]
+pub struct MintedKey {
+    pub value: String,
+    pub id: Option<String>,
+}
+
+#[async_trait]
+pub trait KeyMinter: Send + Sync {
+    async fn mint_key(&self, name: &str) -> Result<MintedKey>;
+}
+
+#[async_trait]
+pub trait KeyRevoker: Send + Sync {
+    async fn revoke_key(&self, key_id: &str) -> Result<()>;
+}
+
+/// A provider that both mints keys and revokes the ones it minted.
+pub trait Minter: KeyMinter + KeyRevoker {}
+
+impl<T: KeyMinter + KeyRevoker> Minter for T {}
+
+pub fn get_minter(config: &Config, minter: &MinterConfig) -> Result<Box<dyn Minter>> {
+    let minter: Box<dyn Minter> = match minter {
+        MinterConfig::Openai { project_id } => {
+            Box::new(openai::OpenAiMinter::new(config, project_id)?)
+        }
+        MinterConfig::Sendgrid { scopes } => {
+            Box::new(sendgrid::SendGridMinter::new(config, scopes.clone())?)
+        }
+        MinterConfig::Datadog { site } => Box::new(datadog::DatadogMinter::new(config, site)?),
+        MinterConfig::Http(http_config) => Box::new(http::HttpMinter::new(http_config.clone())),
+        MinterConfig::AwsIam {
+            user,
+            access_key_id_name,
+            secret_access_key_name,
+        } => Box::new(aws_iam::AwsIamMinter::new(
+            config,
+            user,
+            access_key_id_name,
+            secret_access_key_name,
+        )),
+    };
+
+    Ok(minter)
+}
+
+pub fn key_name(secret_name: &str, env: &str) -> String {
+    format!(
+        "birch-{}-{}-{}",
+        env,
+        secret_name.to_lowercase(),
+        Utc::now().format("%Y%m%d%H%M%S")
+    )
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedKey {
+    pub id: String,
+    pub minter: String,
+    pub issued_at: DateTime<Utc>,
+    pub superseded_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyLedger {
+    pub secret_name: String,
+    pub env: String,
+    pub current: Option<IssuedKey>,
+    #[serde(default)]
+    pub superseded: Vec<IssuedKey>,
+}
+
+impl KeyLedger {
+    pub fn load(env: &str, secret_name: &str) -> Result<Self> {
+        let path = Self::ledger_path(env, secret_name);
+
+        if !path.exists() {
+            return Ok(Self {
+                secret_name: secret_name.to_string(),
+                env: env.to_string(),
+                current: None,
+                superseded: Vec::new(),
+            });
+        }
+
+        let contents = fs::read_to_string(&path).context("Failed to read key ledger")?;
+        serde_json::from_str(&contents).context("Failed to parse key ledger")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let dir = Self::keys_dir();
+        fs::create_dir_all(&dir)?;
+
+        let path = Self::ledger_path(&self.env, &self.secret_name);
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    pub fn record_issued(&mut self, minter: &str, key_id: Option<&str>) {
+        let now = Utc::now();
+
+        if let Some(mut previous) = self.current.take() {
+            previous.superseded_at = Some(now);
+            self.superseded.push(previous);
+        }
+
+        self.current = key_id.map(|id| IssuedKey {
+            id: id.to_string(),
+            minter: minter.to_string(),
+            issued_at: now,
+            superseded_at: None,
+            revoked_at: None,
+        });
+    }
+
+    /// Fails if `key_id` was already revoked, so a rollback does not deploy
+    /// a dead key.
+    pub fn ensure_restorable(&self, key_id: Option<&str>) -> Result<()> {
+        let Some(id) = key_id else {
+            return Ok(());
+        };
+
+        if let Some(revoked_at) = self
+            .superseded
+            .iter()
+            .find(|key| key.id == id)
+            .and_then(|key| key.revoked_at)
+        {
+            anyhow::bail!(
+                "Key {} was revoked at {}; cannot roll back to it",
+                id,
+                revoked_at.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Makes `key_id` (the key of the version rolled back to, `None` when
+    /// that version holds no minted key) current again and supersedes the
+    /// key it replaces.
+    pub fn record_restored(&mut self, minter: &str, key_id: Option<&str>) -> Result<()> {
+        if self.current.as_ref().map(|key| key.id.as_str()) == key_id {
+            return Ok(());
+        }
+        self.ensure_restorable(key_id)?;
+
+        let now = Utc::now();
+        let restored = key_id.map(
+            |id| match self.superseded.iter().position(|key| key.id == id) {
+                Some(i) => {
+                    let mut key = self.superseded.remove(i);
+                    key.superseded_at = None;
+                    key
+                }
+                None => IssuedKey {
+                    id: id.to_string(),
+                    minter: minter.to_string(),
+                    issued_at: now,
+                    superseded_at: None,
+                    revoked_at: None,
+                },
+            },
+        );
+
+        if let Some(mut replaced) = self.current.take() {
+            replaced.superseded_at = Some(now);
+            self.superseded.push(replaced);
+        }
+
+        self.current = restored;
+        Ok(())
+    }
+
+    pub fn record_discarded(&mut self, minter: &str, key_id: &str) {
+        let now = Utc::now();
+        self.superseded.push(IssuedKey {
+            id: key_id.to_string(),
+            minter: minter.to_string(),
+            issued_at: now,
+            superseded_at: Some(now),
+            revoked_at: None,
+        });
+    }
+
+    pub fn list_all() -> Result<Vec<Self>> {
+        let dir = Self::keys_dir();
+        let mut ledgers = Vec::new();
+
+        if !dir.exists() {
+            return Ok(ledgers);
+        }
+
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+
+            let contents = fs::read_to_string(&path)?;
+            match serde_json::from_str::<KeyLedger>(&contents) {
+                Ok(ledger) => ledgers.push(ledger),
+                Err(e) => tracing::warn!("Skipping invalid key ledger {}: {}", path.display(), e),
+            }
+        }
+
+        Ok(ledgers)
+    }
+
+    fn keys_dir() -> PathBuf {
+        crate::config::Config::birch_dir().join("keys")
+    }
+
+    fn ledger_path(env: &str, secret_name: &str) -> PathBuf {
+        Self::keys_dir().join(format!("{}-{}.json", env, secret_name))
+    }
+}
+
+pub fn record_promoted_key(env: &str, secret_name: &str, key_id: Option<&str>) -> Result<()> {
+    let Some(minter) = configured_minter(secret_name)? else {
+        return Ok(());
+    };
+
+    let mut ledger = KeyLedger::load(env, secret_name)?;
+    ledger.record_issued(minter.kind(), key_id);
+    ledger.save()
+}
+
+pub fn check_restorable_key(env: &str, secret_name: &str, key_id: Option<&str>) -> Result<()> {
+    if configured_minter(secret_name)?.is_none() {
+        return Ok(());
+    }
+
+    KeyLedger::load(env, secret_name)?.ensure_restorable(key_id)
+}
+
+pub fn record_restored_key(env: &str, secret_name: &str, key_id: Option<&str>) -> Result<()> {
+    let Some(minter) = configured_minter(secret_name)? else {
+        return Ok(());
+    };
+
+    let mut ledger = KeyLedger::load(env, secret_name)?;
+    ledger.record_restored(minter.kind(), key_id)?;
+    ledger.save()
+}
+
+pub fn record_discarded_key(env: &str, secret_name: &str, key_id: &str) -> Result<()> {
+    let Some(minter) = configured_minter(secret_name)? else {
+        return Ok(());
+    };
+
+    let mut ledger = KeyLedger::load(env, secret_name)?;
+    ledger.record_discarded(minter.kind(), key_id);
+    ledger.save()?;
+
+    println!(
+        "🗑️  Unused minted key {} will be revoked after the rollback window",
+        key_id
+    );
+    Ok(())
+}
+
+/// A freshly minted key that no target has taken yet. Unless it is handed
+/// off with [`PendingKey::keep`], dropping it records the key as discarded
+/// so the revocation sweep removes it, whichever step failed.
+pub struct PendingKey {
+    env: String,
+    secret_name: String,
+    id: Option<String>,
+}
+
+impl PendingKey {
+    pub fn new(env: &str, secret_name: &str, id: Option<String>) -> Self {
+        Self {
+            env: env.to_string(),
+            secret_name: secret_name.to_string(),
+            id,
+        }
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Hands the key off to whoever now tracks it (the ledger once the
+    /// value is live, or a saved staged rotation).
+    pub fn keep(mut self) -> Option<String> {
+        self.id.take()
+    }
+}
+
+impl Drop for PendingKey {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            if let Err(e) = record_discarded_key(&self.env, &self.secret_name, &id) {
+                tracing::warn!("Failed to record unused minted key {}: {}", id, e);
+            }
+        }
+    }
+}
+
+fn configured_minter(secret_name: &str) -> Result<Option<MinterConfig>> {
+    let config = Config::load()?;
+    Ok(config
+        .secrets
+        .get(secret_name)
+        .and_then(|s| s.minter.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ledger() -> KeyLedger {
+        KeyLedger {
+            secret_name: "API_KEY".to_string(),
+            env: "prod".to_string(),
+            current: None,
+            superseded: Vec::new(),
+        }
+    }
+
+    fn current_id(ledger: &KeyLedger) -> Option<&str> {
+        ledger.current.as_ref().map(|key| key.id.as_str())
+    }
+
+    fn superseded_ids(ledger: &KeyLedger) -> Vec<&str> {
+        ledger
+            .superseded
+            .iter()
+            .map(|key| key.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn rotation_supersedes_the_current_key() {
+        let mut ledger = ledger();
+        ledger.record_issued("openai", Some("k1"));
+        ledger.record_issued("openai", Some("k2"));
+
+        assert_eq!(current_id(&ledger), Some("k2"));
+        assert_eq!(superseded_ids(&ledger), ["k1"]);
+        assert!(ledger.superseded[0].superseded_at.is_some());
+    }
+
+    #[test]
+    fn discarded_key_never_becomes_current() {
+        let mut ledger = ledger();
+        ledger.record_issued("openai", Some("k1"));
+        ledger.record_discarded("openai", "k2");
+
+        assert_eq!(current_id(&ledger), Some("k1"));
+        assert_eq!(superseded_ids(&ledger), ["k2"]);
+    }
+
+    #[test]
+    fn rollback_after_failed_apply_restores_the_version_key() {
+        let mut ledger = ledger();
+        ledger.record_issued("openai", Some("k1"));
+        ledger.record_issued("openai", Some("k2"));
+        ledger.record_discarded("openai", "k3");
+
+        ledger.record_restored("openai", Some("k1")).unwrap();
+
+        assert_eq!(current_id(&ledger), Some("k1"));
+        assert!(ledger.current.as_ref().unwrap().superseded_at.is_none());
+        assert_eq!(superseded_ids(&ledger), ["k3", "k2"]);
+    }
+
+    #[test]
+    fn rollback_to_the_current_key_changes_nothing() {
+        let mut ledger = ledger();
+        ledger.record_issued("openai", Some("k1"));
+
+        ledger.record_restored("openai", Some("k1")).unwrap();
+
+        assert_eq!(current_id(&ledger), Some("k1"));
+        assert!(ledger.superseded.is_empty());
+    }
+
+    #[test]
+    fn rollback_to_a_value_without_minted_key_supersedes_current() {
+        let mut ledger = ledger();
+        ledger.record_issued("openai", Some("k1"));
+
+        ledger.record_restored("openai", None).unwrap();
+
+        assert_eq!(current_id(&ledger), None);
+        assert_eq!(superseded_ids(&ledger), ["k1"]);
+    }
+
+    #[test]
+    fn rollback_to_an_untracked_key_makes_it_current() {
+        let mut ledger = ledger();
+        ledger.record_issued("openai", Some("k2"));
+
+        ledger.record_restored("openai", Some("k1")).unwrap();
+
+        assert_eq!(current_id(&ledger), Some("k1"));
+        assert_eq!(superseded_ids(&ledger), ["k2"]);
+    }
+
+    #[test]
+    fn rollback_to_a_revoked_key_is_refused() {
+        let mut ledger = ledger();
+        ledger.record_issued("openai", Some("k1"));
+        ledger.record_issued("openai", Some("k2"));
+        ledger.superseded[0].revoked_at = Some(Utc::now());
+
+        assert!(ledger.ensure_restorable(Some("k1")).is_err());
+        assert!(ledger.record_restored("openai", Some("k1")).is_err());
+        assert_eq!(current_id(&ledger), Some("k2"));
+        assert!(ledger.ensure_restorable(Some("k2")).is_ok());
+        assert!(ledger.ensure_restorable(None).is_ok());
+    }
+
+    #[test]
+    fn revoked_keys_stay_revoked_across_rotations() {
+        let mut ledger = ledger();
+        ledger.record_issued("openai", Some("k1"));
+        ledger.record_issued("openai", Some("k2"));
+        ledger.superseded[0].revoked_at = Some(Utc::now());
+        ledger.record_issued("openai", Some("k3"));
+
+        assert_eq!(current_id(&ledger), Some("k3"));
+        assert_eq!(superseded_ids(&ledger), ["k1", "k2"]);
+        let unrevoked: Vec<&str> = ledger
+            .superseded
+            .iter()
+            .filter(|key| key.revoked_at.is_none())
+            .map(|key| key.id.as_str())
+            .collect();
+        assert_eq!(unrevoked, ["k2"]);
+    }
+}
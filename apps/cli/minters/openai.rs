@@ -0,0 +1,95 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+pub struct OpenAiMinter {
+    admin_key: String,
+    project_id: String,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct CreateServiceAccountRequest {
+    name: String,
+}
+
+impl OpenAiMinter {
+    pub fn new(config: &crate::config::Config, project_id: &str) -> Result<Self> {
+        let admin_key = config
+            .connector_auth
+            .openai_admin_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("OPENAI_ADMIN_KEY not configured"))?
+            .clone();
+
+        Ok(Self {
+            admin_key,
+            project_id: project_id.to_string(),
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyMinter for OpenAiMinter {
+    async fn mint_key(&self, name: &str) -> Result<crate::minters::MintedKey> {
+        let url = format!(
+            "https://api.openai.com/v1/organization/projects/{}/service_accounts",
+            self.project_id
+        );
+
+        let req = CreateServiceAccountRequest {
+            name: name.to_string(),
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.admin_key)
+            .json(&req)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("OpenAI API error ({}): {}", status, text);
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        let value = json["api_key"]["value"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("OpenAI response did not include an API key"))?;
+        let id = json["id"].as_str().map(|s| s.to_string());
+
+        Ok(crate::minters::MintedKey {
+            value: value.to_string(),
+            id,
+        })
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyRevoker for OpenAiMinter {
+    async fn revoke_key(&self, key_id: &str) -> Result<()> {
+        let url = format!(
+            "https://api.openai.com/v1/organization/projects/{}/service_accounts/{}",
+            self.project_id, key_id
+        );
+
+        let response = self
+            .client
+            .delete(&url)
+            .bearer_auth(&self.admin_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("OpenAI API error ({}): {}", status, text);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
+
+pub struct SendGridMinter {
+    admin_api_key: String,
+    scopes: Vec<String>,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct CreateApiKeyRequest {
+    name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    scopes: Vec<String>,
+}
+
+impl SendGridMinter {
+    pub fn new(config: &crate::config::Config, scopes: Vec<String>) -> Result<Self> {
+        let admin_api_key = config
+            .connector_auth
+            .sendgrid_admin_api_key
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("SENDGRID_ADMIN_API_KEY not configured"))?
+            .clone();
+
+        Ok(Self {
+            admin_api_key,
+            scopes,
+            client: reqwest::Client::new(),
+        })
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyMinter for SendGridMinter {
+    async fn mint_key(&self, name: &str) -> Result<crate::minters::MintedKey> {
+        let req = CreateApiKeyRequest {
+            name: name.to_string(),
+            scopes: self.scopes.clone(),
+        };
+
+        let response = self
+            .client
+            .post("https://api.sendgrid.com/v3/api_keys")
+            .bearer_auth(&self.admin_api_key)
+            .json(&req)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("SendGrid API error ({}): {}", status, text);
+        }
+
+        let json: serde_json::Value = response.json().await?;
+        let value = json["api_key"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("SendGrid response did not include an API key"))?;
+        let id = json["api_key_id"].as_str().map(|s| s.to_string());
+
+        Ok(crate::minters::MintedKey {
+            value: value.to_string(),
+            id,
+        })
+    }
+}
+
+#[async_trait]
+impl crate::minters::KeyRevoker for SendGridMinter {
+    async fn revoke_key(&self, key_id: &str) -> Result<()> {
+        let url = format!("https://api.sendgrid.com/v3/api_keys/{}", key_id);
+
+        let response = self
+            .client
+            .delete(&url)
+            .bearer_auth(&self.admin_api_key)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await?;
+            anyhow::bail!("SendGrid API error ({}): {}", status, text);
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
+use crate::approval::ApprovalMode;
 use anyhow::Result;
 use chrono::{Datelike, Timelike};
-use dialoguer::Confirm;
 
 pub async fn update_production_secret(
     secret_name: &str,
@@ -8,55 +8,114 @@ pub async fn update_production_secret(
     env: &str,
     service: Option<&str>,
     redeploy: bool,
+    approval: ApprovalMode,
 ) -> Result<()> {
     let config = crate::config::Config::load()?;
 
     if !check_maintenance_window(&config)? {
         println!("⚠️  Outside maintenance window");
-        if !Confirm::new()
-            .with_prompt("Continue anyway?")
-            .default(false)
-            .interact()?
-        {
-            anyhow::bail!("Aborted by user");
+        if !approval.confirm_outside_window(&config, [(env, secret_name)])? {
+            anyhow::bail!("Aborted: outside maintenance window");
         }
     }
 
     let masked = crate::connectors::mask_secret(new_value);
     println!("Preview: New secret value: {}", masked);
 
-    if !Confirm::new()
-        .with_prompt(format!(
-            "Update '{}' in {} environment{}?",
-            secret_name,
-            env,
-            service
-                .map(|s| format!(" for service '{}'", s))
-                .unwrap_or_default()
-        ))
-        .default(false)
-        .interact()?
-    {
+    let services = resolve_services(&config, env, service);
+    if services.is_empty() {
+        anyhow::bail!(
+            "--service is required for production (or bind connector profiles under [environments.{}])",
+            env
+        );
+    }
+
+    if !approval.confirm(&format!(
+        "Update '{}' in {} environment for service{} '{}'?",
+        secret_name,
+        env,
+        if services.len() > 1 { "s" } else { "" },
+        services.join("', '")
+    ))? {
         anyhow::bail!("Aborted by user");
     }
 
-    let connector = get_connector(service)?;
+    if redeploy {
+        println!("🚀 Redeploy will be triggered after each update");
+    }
 
-    connector.update_secret(secret_name, new_value).await?;
+    let targets: Vec<crate::fanout::Target> = services
+        .iter()
+        .map(|s| crate::fanout::Target::service(env, s, redeploy))
+        .collect();
+
+    crate::fanout::apply(secret_name, new_value, &targets).await?;
     println!("✅ Secret updated");
 
-    if redeploy {
-        println!("🚀 Triggering redeploy...");
-        connector.trigger_refresh(service).await?;
-        println!("✅ Redeploy triggered");
-    } else {
+    if !redeploy {
         println!("💡 Use --redeploy to trigger automatic redeploy");
     }
 
     Ok(())
 }
 
-fn check_maintenance_window(config: &crate::config::Config) -> Result<bool> {
+/// Splits a `--service` value such as `vercel,aws` into individual services.
+pub fn parse_services(service: Option<&str>) -> Vec<&str> {
+    service
+        .map(|s| {
+            s.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Services to write for `env`: the `--service` list when one is given,
+/// otherwise the connector profiles bound to the environment in config.toml.
+pub fn resolve_services(
+    config: &crate::config::Config,
+    env: &str,
+    service: Option<&str>,
+) -> Vec<String> {
+    let services = parse_services(service);
+    if !services.is_empty() {
+        return services.into_iter().map(str::to_string).collect();
+    }
+
+    config
+        .environments
+        .get(env)
+        .map(|e| e.profiles.clone())
+        .unwrap_or_default()
+}
+
+/// Splits a service such as `vercel.marketing` into the provider and the
+/// settings of that connector profile. Settings given on the target take
+/// precedence over the profile's.
+pub fn resolve_profile(
+    config: &crate::config::Config,
+    service: &str,
+    settings: &crate::connectors::ConnectorSettings,
+) -> Result<(String, crate::connectors::ConnectorSettings)> {
+    let Some((provider, profile_name)) = service.split_once('.') else {
+        return Ok((service.to_lowercase(), settings.clone()));
+    };
+
+    let provider = provider.to_lowercase();
+    let profile = config
+        .profiles
+        .get(&provider)
+        .and_then(|profiles| profiles.get(profile_name))
+        .ok_or_else(|| anyhow::anyhow!("Unknown connector profile: {}", service))?;
+
+    let mut merged = profile.clone();
+    merged.extend(settings.0.clone());
+
+    Ok((provider, crate::connectors::ConnectorSettings(merged)))
+}
+
+pub fn check_maintenance_window(config: &crate::config::Config) -> Result<bool> {
     if config.maintenance_windows.is_empty() {
         return Ok(true);
     }
@@ -80,26 +139,109 @@ fn check_maintenance_window(config: &crate::config::Config) -> Result<bool> {
     Ok(false)
 }
 
-fn get_connector(service: Option<&str>) -> Result<Box<dyn crate::connectors::Connector>> {
+pub fn get_connector(
+    service: Option<&str>,
+    settings: &crate::connectors::ConnectorSettings,
+) -> Result<Box<dyn crate::connectors::Connector>> {
     let config = crate::config::Config::load()?;
 
     let service_name =
         service.ok_or_else(|| anyhow::anyhow!("--service is required for production"))?;
+    let (provider, settings) = resolve_profile(&config, service_name, settings)?;
+
+    crate::connectors::create(&provider, &config, &settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::connectors::ConnectorSettings;
+
+    fn config() -> Config {
+        toml::from_str(
+            r#"
+[profiles.vercel.marketing]
+project = "marketing-site"
+team = "acme"
+
+[profiles.aws.prod]
+region = "us-east-1"
+
+[environments.prod]
+profiles = ["vercel.marketing", "aws.prod"]
+"#,
+        )
+        .unwrap()
+    }
+
+    fn settings(pairs: &[(&str, &str)]) -> ConnectorSettings {
+        ConnectorSettings(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn target_settings_override_the_profile() {
+        let (provider, merged) = resolve_profile(
+            &config(),
+            "Vercel.marketing",
+            &settings(&[("project", "preview-site")]),
+        )
+        .unwrap();
+
+        assert_eq!(provider, "vercel");
+        assert_eq!(merged.0["project"], "preview-site");
+        assert_eq!(merged.0["team"], "acme");
+
+        // Profile values also win over the connector's env var defaults.
+        std::env::set_var("BIRCH_TEST_PROFILE_TEAM", "from-env");
+        assert_eq!(
+            merged.get("team", "BIRCH_TEST_PROFILE_TEAM").as_deref(),
+            Some("acme")
+        );
+        assert_eq!(
+            merged.get("org", "BIRCH_TEST_PROFILE_TEAM").as_deref(),
+            Some("from-env")
+        );
+    }
+
+    #[test]
+    fn plain_provider_keeps_the_target_settings() {
+        let (provider, merged) =
+            resolve_profile(&config(), "Netlify", &settings(&[("site", "docs")])).unwrap();
+
+        assert_eq!(provider, "netlify");
+        assert_eq!(merged.0, settings(&[("site", "docs")]).0);
+    }
 
-    let connector: Box<dyn crate::connectors::Connector> =
-        match service_name.to_lowercase().as_str() {
-            "vercel" => Box::new(crate::connectors::vercel::VercelConnector::new(&config)?),
-            "netlify" => Box::new(crate::connectors::netlify::NetlifyConnector::new(&config)?),
-            "render" => Box::new(crate::connectors::render::RenderConnector::new(&config)?),
-            "cloudflare" => Box::new(crate::connectors::cloudflare::CloudflareConnector::new(
-                &config,
-            )?),
-            "fly" => Box::new(crate::connectors::fly::FlyConnector::new(&config)?),
-            "aws" => Box::new(crate::connectors::aws::AwsConnector::new(&config)?),
-            "gcp" => Box::new(crate::connectors::gcp::GcpConnector::new(&config)?),
-            "azure" => Box::new(crate::connectors::azure::AzureConnector::new(&config)?),
-            _ => anyhow::bail!("Unknown service: {}", service_name),
-        };
-
-    Ok(connector)
+    #[test]
+    fn unknown_profile_is_an_error() {
+        let err = resolve_profile(&config(), "vercel.missing", &settings(&[])).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown connector profile: vercel.missing");
+
+        let err = resolve_profile(&config(), "render.marketing", &settings(&[])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown connector profile: render.marketing"
+        );
+    }
+
+    #[test]
+    fn services_fall_back_to_the_env_binding() {
+        let config = config();
+
+        assert_eq!(
+            resolve_services(&config, "prod", None),
+            ["vercel.marketing", "aws.prod"]
+        );
+        assert_eq!(
+            resolve_services(&config, "prod", Some("aws.prod, fly")),
+            ["aws.prod", "fly"]
+        );
+        assert!(resolve_services(&config, "staging", None).is_empty());
+    }
 }
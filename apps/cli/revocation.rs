@@ -0,0 +1,436 @@
+use crate::fanout::Target;
+use crate::minters::{IssuedKey, KeyLedger};
+use crate::staged::RotationState;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::path::Path;
+
+pub async fn revoke(secret_name: String, env: String, dry_run: bool) -> Result<()> {
+    if dry_run {
+        println!("🔍 DRY RUN: No changes will be made");
+    }
+
+    let config = crate::config::Config::load()?;
+
+    let mut lock = crate::lock::Lock::new(&env, &secret_name)?;
+    lock.acquire("revoke")?;
+
+    let mut ledger = KeyLedger::load(&env, &secret_name)?;
+    let pending: Vec<usize> = ledger
+        .superseded
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| key.revoked_at.is_none())
+        .map(|(i, _)| i)
+        .collect();
+
+    if pending.is_empty() {
+        println!(
+            "No superseded keys to revoke for '{}' in env '{}'",
+            secret_name, env
+        );
+        return Ok(());
+    }
+
+    let window = Duration::seconds(config.rollback_window_seconds as i64);
+    for &i in &pending {
+        check_revocable(&env, &secret_name, &ledger.superseded[i], window)?;
+    }
+
+    println!(
+        "🔒 Revoking {} superseded key(s) for '{}' in env '{}'",
+        pending.len(),
+        secret_name,
+        env
+    );
+
+    if dry_run {
+        for &i in &pending {
+            println!("   Would revoke {}", ledger.superseded[i].id);
+        }
+        println!("✅ Dry run complete (no changes made)");
+        return Ok(());
+    }
+
+    let mut failures = 0;
+    for &i in &pending {
+        if let Err(e) = revoke_key(&config, &mut ledger, i).await {
+            println!("❌ Failed to revoke {}: {}", ledger.superseded[i].id, e);
+            failures += 1;
+        } else {
+            println!("✅ Revoked {}", ledger.superseded[i].id);
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} key(s) could not be revoked", failures);
+    }
+
+    Ok(())
+}
+
+pub async fn revoke_due_keys() -> Result<usize> {
+    let config = crate::config::Config::load()?;
+    let window = Duration::seconds(config.rollback_window_seconds as i64);
+    let mut revoked = 0;
+
+    for ledger in KeyLedger::list_all()? {
+        let Ok(staged) = RotationState::load(&ledger.env, &ledger.secret_name) else {
+            continue;
+        };
+        if due_keys(&ledger, window, staged.as_ref(), Utc::now()).is_empty() {
+            continue;
+        }
+
+        let mut lock = crate::lock::Lock::new(&ledger.env, &ledger.secret_name)?;
+        if let Err(e) = lock.acquire("revoke") {
+            tracing::info!(
+                "Skipping revocation for {}/{}: {}",
+                ledger.env,
+                ledger.secret_name,
+                e
+            );
+            continue;
+        }
+
+        // Reload under the lock in case a rotation or rollback changed it.
+        let mut ledger = KeyLedger::load(&ledger.env, &ledger.secret_name)?;
+        let Ok(staged) = RotationState::load(&ledger.env, &ledger.secret_name) else {
+            continue;
+        };
+
+        for i in due_keys(&ledger, window, staged.as_ref(), Utc::now()) {
+            match revoke_key(&config, &mut ledger, i).await {
+                Ok(()) => {
+                    tracing::info!(
+                        "Revoked key {} for {}/{}",
+                        ledger.superseded[i].id,
+                        ledger.env,
+                        ledger.secret_name
+                    );
+                    revoked += 1;
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to revoke key {} for {}/{}: {}",
+                    ledger.superseded[i].id,
+                    ledger.env,
+                    ledger.secret_name,
+                    e
+                ),
+            }
+        }
+    }
+
+    Ok(revoked)
+}
+
+/// Superseded keys of `ledger` that are not revoked yet and may be now.
+fn due_keys(
+    ledger: &KeyLedger,
+    window: Duration,
+    staged: Option<&RotationState>,
+    now: DateTime<Utc>,
+) -> Vec<usize> {
+    ledger
+        .superseded
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| {
+            key.revoked_at.is_none() && revocable_at(key, window, staged, now).is_ok()
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn check_revocable(env: &str, secret_name: &str, key: &IssuedKey, window: Duration) -> Result<()> {
+    let staged = RotationState::load(env, secret_name)?;
+    revocable_at(key, window, staged.as_ref(), Utc::now())
+}
+
+/// Refuses to revoke a key that is still in use, may still be needed for a
+/// rollback, or belongs to a secret whose staged rotation is in progress.
+fn revocable_at(
+    key: &IssuedKey,
+    window: Duration,
+    staged: Option<&RotationState>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let Some(superseded_at) = key.superseded_at else {
+        anyhow::bail!("Key {} is still in use", key.id);
+    };
+
+    let rollback_until: DateTime<Utc> = superseded_at + window;
+    if now < rollback_until {
+        anyhow::bail!(
+            "Key {} may still be needed for rollback until {}",
+            key.id,
+            rollback_until.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+    }
+
+    if let Some(state) = staged.filter(|s| s.phase.is_active()) {
+        anyhow::bail!(
+            "Staged rotation for '{}' in env '{}' is {:?}; finish or abort it first",
+            state.secret_name,
+            state.env,
+            state.phase
+        );
+    }
+
+    Ok(())
+}
+
+async fn revoke_key(
+    config: &crate::config::Config,
+    ledger: &mut KeyLedger,
+    index: usize,
+) -> Result<()> {
+    let key = &ledger.superseded[index];
+
+    let minter = config
+        .secrets
+        .get(&ledger.secret_name)
+        .and_then(|s| s.minter.as_ref())
+        .filter(|m| m.kind() == key.minter)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No '{}' minter configured for '{}'; cannot revoke",
+                key.minter,
+                ledger.secret_name
+            )
+        })?;
+
+    let revoker = crate::minters::get_minter(config, minter)?;
+    let result = revoker.revoke_key(&key.id).await;
+
+    let logger = crate::audit::AuditLogger::new()?;
+    logger.log(
+        ledger.secret_name.clone(),
+        ledger.env.clone(),
+        Some(key.minter.clone()),
+        crate::audit::AuditAction::Revoke,
+        result.is_ok(),
+        None,
+    )?;
+
+    result?;
+
+    ledger.superseded[index].revoked_at = Some(Utc::now());
+    ledger.save()
+}
+
+/// Retires remote versions of `secret_name` that were superseded more than
+/// the rollback window ago, on targets with `retire_versions` set. Runs after
+/// every successful update; failures are reported without failing it.
+pub async fn retire_versions(secret_name: &str, targets: &[Target]) {
+    let config = match crate::config::Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!("Skipping version retirement: {}", e);
+            return;
+        }
+    };
+
+    for target in targets {
+        match retire_target(&config, secret_name, target).await {
+            Ok(retired) if !retired.is_empty() => println!(
+                "🗄️  Retired {} superseded version(s) in {}: {}",
+                retired.len(),
+                target.label(),
+                retired.join(", ")
+            ),
+            Ok(_) => {}
+            Err(e) => println!(
+                "⚠️  Could not retire superseded versions in {}: {}",
+                target.label(),
+                e
+            ),
+        }
+    }
+}
+
+/// Daemon sweep: retires superseded versions for every manifest target with
+/// `retire_versions` set, so they go once the window passes even if the
+/// secret is not rotated again.
+pub async fn retire_due_versions(manifest_path: &Path) -> Result<usize> {
+    let config = crate::config::Config::load()?;
+    let manifest = crate::manifest::Manifest::load(manifest_path)?;
+    let mut retired = 0;
+
+    for (name, secret) in &manifest.secrets {
+        for target in &secret.targets {
+            if !retires_versions(&config, target) {
+                continue;
+            }
+
+            let mut lock = crate::lock::Lock::new(&target.env, name)?;
+            if let Err(e) = lock.acquire("retire") {
+                tracing::info!(
+                    "Skipping version retirement for {}/{}: {}",
+                    target.env,
+                    name,
+                    e
+                );
+                continue;
+            }
+
+            match retire_target(&config, name, target).await {
+                Ok(ids) => {
+                    for id in &ids {
+                        tracing::info!("Retired version {} of {} in {}", id, name, target.label());
+                    }
+                    retired += ids.len();
+                }
+                Err(e) => tracing::warn!(
+                    "Failed to retire versions of {} in {}: {}",
+                    name,
+                    target.label(),
+                    e
+                ),
+            }
+        }
+    }
+
+    Ok(retired)
+}
+
+fn retires_versions(config: &crate::config::Config, target: &Target) -> bool {
+    if target.env == "dev" {
+        return false;
+    }
+
+    let Some(service) = target.service.as_deref() else {
+        return false;
+    };
+
+    crate::prod::resolve_profile(config, service, &target.connector_settings())
+        .is_ok_and(|(_, settings)| settings.0.contains_key("retire_versions"))
+}
+
+async fn retire_target(
+    config: &crate::config::Config,
+    secret_name: &str,
+    target: &Target,
+) -> Result<Vec<String>> {
+    if !retires_versions(config, target) {
+        return Ok(Vec::new());
+    }
+
+    // A staged rotation still needs the previous value until it completes.
+    if let Some(state) = crate::staged::RotationState::load(&target.env, secret_name)? {
+        if state.phase.is_active() {
+            return Ok(Vec::new());
+        }
+    }
+
+    let cutoff = Utc::now() - Duration::seconds(config.rollback_window_seconds as i64);
+    let connector =
+        crate::prod::get_connector(target.service.as_deref(), &target.connector_settings())?;
+    let result = connector.retire_versions(secret_name, cutoff).await;
+
+    if !matches!(&result, Ok(retired) if retired.is_empty()) {
+        let logger = crate::audit::AuditLogger::new()?;
+        logger.log(
+            secret_name.to_string(),
+            target.env.clone(),
+            Some(target.label()),
+            crate::audit::AuditAction::Retire,
+            result.is_ok(),
+            None,
+        )?;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::staged::RotationPhase;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, hour, 0, 0).unwrap()
+    }
+
+    fn key(id: &str, superseded_at: Option<DateTime<Utc>>) -> IssuedKey {
+        IssuedKey {
+            id: id.to_string(),
+            minter: "aws_iam".to_string(),
+            issued_at: at(0),
+            superseded_at,
+            revoked_at: None,
+        }
+    }
+
+    fn staged(phase: RotationPhase) -> RotationState {
+        let mut state = RotationState::new("API_KEY", "prod", None, None, None, false, 3600);
+        state.phase = phase;
+        state
+    }
+
+    #[test]
+    fn key_past_the_rollback_window_is_revocable() {
+        let key = key("AKIA1", Some(at(1)));
+
+        assert!(revocable_at(&key, Duration::hours(2), None, at(3)).is_ok());
+        let retired = staged(RotationPhase::Retired);
+        assert!(revocable_at(&key, Duration::hours(2), Some(&retired), at(3)).is_ok());
+    }
+
+    #[test]
+    fn key_within_the_rollback_window_is_refused() {
+        let key = key("AKIA1", Some(at(1)));
+
+        let err = revocable_at(&key, Duration::hours(2), None, at(2)).unwrap_err();
+        assert!(err.to_string().contains("needed for rollback"), "{}", err);
+    }
+
+    #[test]
+    fn key_never_superseded_is_refused() {
+        let key = key("AKIA1", None);
+
+        let err = revocable_at(&key, Duration::zero(), None, at(3)).unwrap_err();
+        assert!(err.to_string().contains("still in use"), "{}", err);
+    }
+
+    #[test]
+    fn active_staged_rotation_blocks_revocation() {
+        let key = key("AKIA1", Some(at(1)));
+
+        for phase in [
+            RotationPhase::Staged,
+            RotationPhase::Verified,
+            RotationPhase::Promoted,
+        ] {
+            let state = staged(phase);
+            let err = revocable_at(&key, Duration::hours(2), Some(&state), at(3)).unwrap_err();
+            assert!(err.to_string().contains("finish or abort"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn due_keys_skip_revoked_and_recent_keys() {
+        let mut revoked = key("AKIA1", Some(at(0)));
+        revoked.revoked_at = Some(at(2));
+        let ledger = KeyLedger {
+            secret_name: "API_KEY".to_string(),
+            env: "prod".to_string(),
+            current: Some(key("AKIA4", None)),
+            superseded: vec![
+                revoked,
+                key("AKIA2", Some(at(0))),
+                key("AKIA3", Some(at(2))),
+            ],
+        };
+
+        assert_eq!(due_keys(&ledger, Duration::hours(2), None, at(3)), [1]);
+        assert!(due_keys(
+            &ledger,
+            Duration::hours(2),
+            Some(&staged(RotationPhase::Promoted)),
+            at(3)
+        )
+        .is_empty());
+    }
+}
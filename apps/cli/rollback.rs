@@ -1,74 +1,150 @@
+use crate::approval::{ApprovalMode, Decision, Operation};
+use crate::rotation::{RotationOutcome, RotationStatus};
 use anyhow::Result;
 use chrono::{Duration, Utc};
-use dialoguer::Confirm;
 use std::fs;
 
-pub async fn rollback(
-    secret_name: String,
-    env: String,
-    service: Option<String>,
-    redeploy: bool,
-    dry_run: bool,
-) -> Result<()> {
+#[derive(Debug, Clone, Default)]
+pub struct RollbackParams {
+    pub secret_name: String,
+    pub env: String,
+    pub service: Option<String>,
+    pub to: Option<u32>,
+    pub env_file: Option<String>,
+    pub key_path: Option<String>,
+    pub redeploy: bool,
+    pub dry_run: bool,
+    pub approval: ApprovalMode,
+}
+
+pub async fn rollback(params: RollbackParams) -> Result<RotationOutcome> {
+    let RollbackParams {
+        secret_name,
+        env,
+        service,
+        to,
+        env_file,
+        key_path,
+        redeploy,
+        dry_run,
+        approval,
+    } = params;
+
     if dry_run {
         println!("🔍 DRY RUN: No changes will be made");
     }
 
+    let config = crate::config::Config::load()?;
+
+    if approval == ApprovalMode::Policy {
+        if let Decision::Denied { reason } =
+            crate::approval::evaluate(&config, Operation::Rollback, &env, &secret_name)
+        {
+            println!("⛔ Rollback not auto-approved: {}", reason);
+            let logger = crate::audit::AuditLogger::new()?;
+            logger.log(
+                secret_name.clone(),
+                env.clone(),
+                service.clone(),
+                crate::audit::AuditAction::Rollback,
+                false,
+                None,
+            )?;
+            return Ok(RotationOutcome::denied(&secret_name, &env, reason));
+        }
+    }
+
     let mut lock = crate::lock::Lock::new(&env, &secret_name)?;
     lock.acquire("rollback")?;
 
-    let config = crate::config::Config::load()?;
     let rollback_window = Duration::seconds(config.rollback_window_seconds as i64);
 
-    check_rollback_window(&env, &secret_name, rollback_window)?;
+    check_rollback_window(&env, &secret_name, rollback_window, approval)?;
 
-    let previous_value = get_previous_value(&secret_name, &env)?;
+    let (version, previous_value, key_id) =
+        crate::versions::resolve_rollback_target(&env, &secret_name, to)?;
+    crate::minters::check_restorable_key(&env, &secret_name, key_id.as_deref())?;
     let masked = crate::connectors::mask_secret(&previous_value);
 
-    println!("🔙 Rolling back secret '{}' in env '{}'", secret_name, env);
-    println!("   Previous value: {}", masked);
+    println!(
+        "🔙 Rolling back secret '{}' in env '{}' to version {}",
+        secret_name, env, version
+    );
+    println!("   Value: {}", masked);
 
-    if !dry_run {
-        if !Confirm::new()
-            .with_prompt("Confirm rollback?")
-            .default(false)
-            .interact()?
-        {
-            anyhow::bail!("Rollback cancelled by user");
-        }
+    if dry_run {
+        println!("✅ Dry run complete (no changes made)");
+        return Ok(RotationOutcome {
+            secret_name,
+            env,
+            status: RotationStatus::DryRun,
+            masked_value: Some(masked),
+            reason: None,
+        });
+    }
 
-        if env == "dev" {
-            crate::dev::update_env_file(&secret_name, &previous_value, None).await?;
-        } else {
-            crate::prod::update_production_secret(
-                &secret_name,
-                &previous_value,
-                &env,
-                service.as_deref(),
-                redeploy,
-            )
-            .await?;
-        }
+    if !approval.confirm("Confirm rollback?")? {
+        anyhow::bail!("Rollback cancelled by user");
+    }
 
-        let logger = crate::audit::AuditLogger::new()?;
-        logger.log(
-            secret_name.clone(),
-            env.clone(),
-            service.clone(),
-            crate::audit::AuditAction::Rollback,
-            true,
-            Some(masked),
-        )?;
-
-        println!("✅ Secret rolled back successfully");
+    if env == "dev" {
+        crate::dev::update_local_file(
+            &secret_name,
+            &previous_value,
+            env_file.as_deref(),
+            key_path.as_deref(),
+        )
+        .await?;
     } else {
-        println!("✅ Dry run complete (no changes made)");
+        crate::prod::update_production_secret(
+            &secret_name,
+            &previous_value,
+            &env,
+            service.as_deref(),
+            redeploy,
+            approval,
+        )
+        .await?;
     }
 
-    Ok(())
+    crate::versions::record_version(
+        &env,
+        &secret_name,
+        &previous_value,
+        crate::audit::AuditAction::Rollback,
+        Some(version),
+        key_id.as_deref(),
+    )?;
+
+    let logger = crate::audit::AuditLogger::new()?;
+    logger.log(
+        secret_name.clone(),
+        env.clone(),
+        service.clone(),
+        crate::audit::AuditAction::Rollback,
+        true,
+        Some(masked.clone()),
+    )?;
+
+    crate::minters::record_restored_key(&env, &secret_name, key_id.as_deref())?;
+
+    println!("✅ Secret rolled back successfully");
+
+    Ok(RotationOutcome {
+        secret_name,
+        env,
+        status: RotationStatus::Applied,
+        masked_value: Some(masked),
+        reason: None,
+    })
 }
 
-fn check_rollback_window(env: &str, secret_name: &str, window: Duration) -> Result<()> {
+fn check_rollback_window(
+    env: &str,
+    secret_name: &str,
+    window: Duration,
+    approval: ApprovalMode,
+) -> Result<()> {
     let birch_dir = crate::config::Config::birch_dir();
     let cooldown_file = birch_dir
         .join("cooldowns")
@@ -91,11 +167,7 @@ fn check_rollback_window(env: &str, secret_name: &str, window: Duration) -> Resu
         );
         println!("   Old key may have been revoked at provider");
 
-        if !Confirm::new()
-            .with_prompt("Continue with rollback anyway?")
-            .default(false)
-            .interact()?
-        {
+        if !approval.confirm_override("Continue with rollback anyway?")? {
             anyhow::bail!("Rollback cancelled");
         }
     }
@@ -103,34 +175,6 @@ fn check_rollback_window(env: &str, secret_name: &str, window: Duration) -> Resu
     Ok(())
 }
 
-fn get_previous_value(secret_name: &str, env: &str) -> Result<String> {
-    let logger = crate::audit::AuditLogger::new()?;
-    let entries = logger.read_logs(Some(secret_name.to_string()), Some(env.to_string()), None)?;
-
-    let rotate_entries: Vec<_> = entries
-        .iter()
-        .filter(|e| matches!(e.action, crate::audit::AuditAction::Rotate) && e.success)
-        .collect();
-
-    if rotate_entries.len() < 2 {
-        anyhow::bail!(
-            "No previous value found in audit logs (need at least 2 successful rotations, found {})",
-            rotate_entries.len()
-        );
-    }
-
-    let previous_entry = rotate_entries[1];
-
-    if let Some(ref encrypted_value) = previous_entry.encrypted_secret_value {
-        logger.decrypt_secret(encrypted_value)
-    } else {
-        anyhow::bail!(
-            "Previous rotation did not store encrypted value (audit entry from: {})",
-            previous_entry.timestamp
-        );
-    }
-}
-
 fn format_duration(d: Duration) -> String {
     let seconds = d.num_seconds();
     if seconds < 60 {
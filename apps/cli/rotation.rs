@@ -1,19 +1,76 @@
+use crate::approval::{ApprovalMode, Decision, Operation};
 use crate::pool::KeyPool;
 use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
 use std::fs;
 
-#[allow(clippy::too_many_arguments)]
-pub async fn rotate(
-    secret_name: Option<String>,
-    env: Option<String>,
-    service: Option<String>,
-    _from_signal: bool,
-    redeploy: bool,
-    value: Option<String>,
-    env_file: Option<String>,
-    dry_run: bool,
-) -> Result<()> {
+#[derive(Debug, Clone, Default)]
+pub struct RotateParams {
+    pub secret_name: Option<String>,
+    pub env: Option<String>,
+    pub service: Option<String>,
+    pub from_signal: bool,
+    pub redeploy: bool,
+    pub value: Option<String>,
+    pub env_file: Option<String>,
+    pub key_path: Option<String>,
+    pub dry_run: bool,
+    pub approval: ApprovalMode,
+    pub staged: bool,
+    pub resume: bool,
+    pub abort: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationStatus {
+    Applied,
+    DryRun,
+    Denied,
+    Aborted,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RotationOutcome {
+    pub secret_name: String,
+    pub env: String,
+    pub status: RotationStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub masked_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl RotationOutcome {
+    pub fn denied(secret_name: &str, env: &str, reason: String) -> Self {
+        Self {
+            secret_name: secret_name.to_string(),
+            env: env.to_string(),
+            status: RotationStatus::Denied,
+            masked_value: None,
+            reason: Some(reason),
+        }
+    }
+}
+
+pub async fn rotate(params: RotateParams) -> Result<RotationOutcome> {
+    let RotateParams {
+        secret_name,
+        env,
+        service,
+        from_signal,
+        redeploy,
+        value,
+        env_file,
+        key_path,
+        dry_run,
+        approval,
+        staged,
+        resume,
+        abort,
+    } = params;
+
     let secret_name = secret_name.ok_or_else(|| anyhow::anyhow!("SECRET_NAME is required"))?;
 
     let env = env.ok_or_else(|| anyhow::anyhow!("--env is required"))?;
@@ -22,86 +79,463 @@ pub async fn rotate(
         println!("🔍 DRY RUN: No changes will be made");
     }
 
+    if from_signal {
+        println!("📡 Rotation triggered by app signal");
+    }
+
+    // Aborting only puts the previous value back, so a policy that denies
+    // rotating the secret must not leave a staged rotation stuck on it.
+    if abort {
+        return crate::staged::abort(&secret_name, &env, dry_run, approval).await;
+    }
+
+    let config = crate::config::Config::load()?;
+
+    if approval == ApprovalMode::Policy {
+        if let Decision::Denied { reason } =
+            crate::approval::evaluate(&config, Operation::Rotate, &env, &secret_name)
+        {
+            println!("⛔ Rotation not auto-approved: {}", reason);
+            let logger = crate::audit::AuditLogger::new()?;
+            logger.log(
+                secret_name.clone(),
+                env.clone(),
+                service.clone(),
+                crate::audit::AuditAction::Rotate,
+                false,
+                None,
+            )?;
+            return Ok(RotationOutcome::denied(&secret_name, &env, reason));
+        }
+    }
+
+    if resume {
+        return crate::staged::resume(&secret_name, &env, dry_run, approval).await;
+    }
+
     let mut lock = crate::lock::Lock::new(&env, &secret_name)?;
     lock.acquire("rotate")?;
 
+    crate::staged::ensure_idle(&env, &secret_name)?;
     check_cooldown(&env, &secret_name)?;
 
-    let new_value = if let Some(v) = value {
-        v
-    } else if let Some(mut pool) = KeyPool::load(&secret_name)? {
-        println!(
-            "🎱 Using key pool for '{}' ({} available, {} exhausted)",
-            secret_name,
-            pool.count_available(),
-            pool.count_exhausted()
-        );
+    let secret_config = config.secrets.get(&secret_name);
+    let staged = staged || secret_config.is_some_and(|s| s.staged);
 
-        if let Ok(current) = get_current_secret_value(&secret_name, &env, service.as_deref()).await
-        {
-            if let Ok(()) = pool.mark_exhausted(&current) {
-                println!("   ✓ Marked current key as exhausted");
-            }
+    if dry_run {
+        if let Some(minter) = secret_config.and_then(|s| s.minter.as_ref()) {
+            println!(
+                "🔑 Would mint a new key for '{}' via {} minter",
+                secret_name,
+                minter.kind()
+            );
+            println!("✅ Dry run complete (no changes made)");
+            return Ok(RotationOutcome {
+                secret_name,
+                env,
+                status: RotationStatus::DryRun,
+                masked_value: None,
+                reason: None,
+            });
         }
+    }
 
-        match pool.get_next_available() {
-            Ok(next_key) => {
-                pool.save()?;
-                let remaining = pool.count_available();
-                if remaining <= 2 {
-                    println!("⚠️  Warning: Only {} key(s) remaining in pool!", remaining);
-                }
-                next_key
-            }
-            Err(e) => {
-                println!("⚠️  Pool exhausted, falling back to random generation");
-                println!("   Error: {}", e);
-                generate_secret()?
-            }
-        }
+    let old_value = if staged {
+        get_current_secret_value(
+            &secret_name,
+            &env,
+            service.as_deref(),
+            env_file.as_deref(),
+            key_path.as_deref(),
+        )
+        .await
+        .ok()
+    } else {
+        None
+    };
+
+    let generated = value.is_none();
+    let (new_value, key_id) = resolve_new_value(
+        &config,
+        &secret_name,
+        &env,
+        service.as_deref(),
+        env_file.as_deref(),
+        key_path.as_deref(),
+        value,
+    )
+    .await?;
+    let key = crate::minters::PendingKey::new(&env, &secret_name, key_id);
+    let new_value = if env == "dev" {
+        new_value
     } else {
-        generate_secret()?
+        let targets: Vec<crate::fanout::Target> =
+            crate::prod::resolve_services(&config, &env, service.as_deref())
+                .iter()
+                .map(|s| crate::fanout::Target::service(&env, s, redeploy))
+                .collect();
+        crate::fanout::prepare_value(&secret_name, new_value, &targets).await?
     };
 
+    if staged {
+        let grace_period_seconds = secret_config
+            .and_then(|s| s.grace_period_seconds)
+            .unwrap_or(config.rotation_grace_period_seconds);
+        let state = crate::staged::RotationState::new(
+            &secret_name,
+            &env,
+            service,
+            env_file,
+            key_path,
+            redeploy,
+            grace_period_seconds,
+        );
+        if generated && !dry_run {
+            publish_public_key(&config, &secret_name, &ValueSource::default(), &new_value)?;
+        }
+        return crate::staged::start(state, old_value, new_value, key, dry_run, approval).await;
+    }
+
     let masked = crate::connectors::mask_secret(&new_value);
     println!("🔄 Rotating secret '{}' in env '{}'", secret_name, env);
     println!("   New value: {}", masked);
 
-    if !dry_run {
-        if env == "dev" {
-            crate::dev::update_env_file(&secret_name, &new_value, env_file.as_deref()).await?;
-        } else {
-            crate::prod::update_production_secret(
-                &secret_name,
-                &new_value,
-                &env,
-                service.as_deref(),
-                redeploy,
-            )
-            .await?;
+    if dry_run {
+        println!("✅ Dry run complete (no changes made)");
+        return Ok(RotationOutcome {
+            secret_name,
+            env,
+            status: RotationStatus::DryRun,
+            masked_value: Some(masked),
+            reason: None,
+        });
+    }
+
+    if generated {
+        publish_public_key(&config, &secret_name, &ValueSource::default(), &new_value)?;
+    }
+
+    // A failed apply drops `key`, which records the minted key as discarded.
+    apply_value(
+        &secret_name,
+        &new_value,
+        &env,
+        service.as_deref(),
+        env_file.as_deref(),
+        key_path.as_deref(),
+        redeploy,
+        approval,
+    )
+    .await?;
+
+    let key_id = key.keep();
+    crate::minters::record_promoted_key(&env, &secret_name, key_id.as_deref())?;
+    record_rotation(&env, &secret_name)?;
+    crate::versions::record_version(
+        &env,
+        &secret_name,
+        &new_value,
+        crate::audit::AuditAction::Rotate,
+        None,
+        key_id.as_deref(),
+    )?;
+
+    let logger = crate::audit::AuditLogger::new()?;
+    logger.log_with_value(crate::audit::LogParams {
+        secret_name: secret_name.clone(),
+        env: env.clone(),
+        service: service.clone(),
+        action: crate::audit::AuditAction::Rotate,
+        success: true,
+        masked_secret_preview: Some(masked.clone()),
+        secret_value: Some(new_value.clone()),
+    })?;
+
+    println!("✅ Secret rotated successfully");
+
+    Ok(RotationOutcome {
+        secret_name,
+        env,
+        status: RotationStatus::Applied,
+        masked_value: Some(masked),
+        reason: None,
+    })
+}
+
+async fn resolve_new_value(
+    config: &crate::config::Config,
+    secret_name: &str,
+    env: &str,
+    service: Option<&str>,
+    env_file: Option<&str>,
+    key_path: Option<&str>,
+    value: Option<String>,
+) -> Result<(String, Option<String>)> {
+    if let Some(v) = value {
+        return Ok((v, None));
+    }
+
+    if let Some(key) = mint_key(config, secret_name, env).await? {
+        return Ok((key.value, key.id));
+    }
+
+    let source = ValueSource::default();
+    let Some(mut pool) = source.load_pool(secret_name)? else {
+        return Ok((generate_secret(config, secret_name, &source)?, None));
+    };
+
+    let current: Vec<String> =
+        get_current_secret_value(secret_name, env, service, env_file, key_path)
+            .await
+            .into_iter()
+            .collect();
+
+    Ok((
+        next_pool_key(config, secret_name, &source, &mut pool, &current)?,
+        None,
+    ))
+}
+
+/// Mints a key when `secret_name` has a minter configured.
+async fn mint_key(
+    config: &crate::config::Config,
+    secret_name: &str,
+    env: &str,
+) -> Result<Option<crate::minters::MintedKey>> {
+    let Some(minter_config) = config
+        .secrets
+        .get(secret_name)
+        .and_then(|s| s.minter.as_ref())
+    else {
+        return Ok(None);
+    };
+
+    let minter = crate::minters::get_minter(config, minter_config)?;
+    let key = minter
+        .mint_key(&crate::minters::key_name(secret_name, env))
+        .await?;
+    println!(
+        "🔑 Minted new key via {} minter{}",
+        minter_config.kind(),
+        key.id
+            .as_ref()
+            .map(|id| format!(" (id: {})", id))
+            .unwrap_or_default()
+    );
+
+    Ok(Some(key))
+}
+
+/// Marks the values currently live as exhausted and takes the next key of
+/// `pool`, falling back to generation once the pool runs dry.
+fn next_pool_key(
+    config: &crate::config::Config,
+    secret_name: &str,
+    source: &ValueSource,
+    pool: &mut KeyPool,
+    current: &[String],
+) -> Result<String> {
+    println!(
+        "🎱 Using key pool for '{}' ({} available, {} exhausted)",
+        secret_name,
+        pool.count_available(),
+        pool.count_exhausted()
+    );
+
+    for value in current {
+        if let Ok(()) = pool.mark_exhausted(value) {
+            println!("   ✓ Marked current key as exhausted");
+        }
+    }
+
+    match pool.get_next_available() {
+        Ok(next_key) => {
+            pool.save()?;
+            let remaining = pool.count_available();
+            if remaining <= 2 {
+                println!("⚠️  Warning: Only {} key(s) remaining in pool!", remaining);
+            }
+            Ok(next_key)
+        }
+        Err(e) => {
+            // Keys marked exhausted above stay marked.
+            pool.save()?;
+            println!("⚠️  Pool exhausted, falling back to random generation");
+            println!("   Error: {}", e);
+            generate_secret(config, secret_name, source)
+        }
+    }
+}
+
+/// Where a new value comes from when none is given: the secret's minter,
+/// then its key pool, then the generator.
+#[derive(Debug, Clone, Default)]
+pub struct ValueSource {
+    /// Key pool to draw from instead of the one named after the secret.
+    pub pool: Option<String>,
+    /// Generation profile used instead of the secret's configured one.
+    pub generate: Option<crate::config::GenerateConfig>,
+}
+
+impl ValueSource {
+    fn load_pool(&self, secret_name: &str) -> Result<Option<KeyPool>> {
+        match &self.pool {
+            Some(name) => KeyPool::load(name)?
+                .map(Some)
+                .ok_or_else(|| anyhow::anyhow!("Key pool '{}' does not exist", name)),
+            None => KeyPool::load(secret_name),
         }
+    }
+
+    fn profile(
+        &self,
+        config: &crate::config::Config,
+        secret_name: &str,
+    ) -> Option<crate::config::GenerateConfig> {
+        self.generate.clone().or_else(|| {
+            config
+                .secrets
+                .get(secret_name)
+                .and_then(|s| s.generate.clone())
+        })
+    }
+}
+
+/// Rotates `secret_name` on `targets`, which may span several envs, the way
+/// `birch apply` and the scheduler do: one new value from the minter, pool or
+/// generator is written to every target, or to none of them. The caller
+/// holds the locks of every env involved.
+pub async fn rotate_targets(
+    config: &crate::config::Config,
+    secret_name: &str,
+    targets: &[crate::fanout::Target],
+    source: &ValueSource,
+) -> Result<String> {
+    let mut envs: Vec<&str> = targets.iter().map(|t| t.env.as_str()).collect();
+    envs.sort();
+    envs.dedup();
+    let Some(&env) = envs.first() else {
+        anyhow::bail!("Secret '{}' has no targets", secret_name);
+    };
+
+    let secret_config = config.secrets.get(secret_name);
+    if secret_config.is_some_and(|s| s.staged) {
+        anyhow::bail!(
+            "'{}' uses staged rotation; rotate it with `birch rotate {} --env <ENV>`",
+            secret_name,
+            secret_name
+        );
+    }
+    // The key ledger is per env, so one minted key must not be shared.
+    if secret_config.is_some_and(|s| s.minter.is_some()) && envs.len() > 1 {
+        anyhow::bail!(
+            "'{}' mints its keys, so its targets must all be in one env (found {})",
+            secret_name,
+            envs.join(", ")
+        );
+    }
+
+    for env in &envs {
+        crate::staged::ensure_idle(env, secret_name)?;
+        check_cooldown(env, secret_name)?;
+    }
+
+    let (value, key_id) = match mint_key(config, secret_name, env).await? {
+        Some(key) => (key.value, key.id),
+        None => match source.load_pool(secret_name)? {
+            Some(mut pool) => {
+                let mut current = Vec::new();
+                for target in targets {
+                    if let Ok(Some(value)) = target.read(secret_name).await {
+                        if !current.contains(&value) {
+                            current.push(value);
+                        }
+                    }
+                }
+                let value = next_pool_key(config, secret_name, source, &mut pool, &current)?;
+                (value, None)
+            }
+            None => (generate_secret(config, secret_name, source)?, None),
+        },
+    };
+    let key = crate::minters::PendingKey::new(env, secret_name, key_id);
+
+    let value = crate::fanout::prepare_value(secret_name, value, targets).await?;
+    publish_public_key(config, secret_name, source, &value)?;
+
+    // A failed apply drops `key`, which records the minted key as discarded.
+    crate::fanout::apply(secret_name, &value, targets).await?;
 
-        record_rotation(&env, &secret_name)?;
+    let key_id = key.keep();
+    crate::minters::record_promoted_key(env, secret_name, key_id.as_deref())?;
+    record_written(secret_name, &value, targets, key_id.as_deref())?;
 
-        let logger = crate::audit::AuditLogger::new()?;
+    Ok(crate::connectors::mask_secret(&value))
+}
+
+/// Records `value` as the new version of `secret_name` in every env of
+/// `targets` after they were written, with one audit entry per env.
+pub fn record_written(
+    secret_name: &str,
+    value: &str,
+    targets: &[crate::fanout::Target],
+    key_id: Option<&str>,
+) -> Result<()> {
+    let mut written: std::collections::BTreeMap<&str, Vec<&str>> =
+        std::collections::BTreeMap::new();
+    for target in targets {
+        written
+            .entry(target.env.as_str())
+            .or_default()
+            .push(target.service.as_deref().unwrap_or("env-file"));
+    }
+
+    let logger = crate::audit::AuditLogger::new()?;
+    let masked = crate::connectors::mask_secret(value);
+
+    for (env, services) in written {
+        record_rotation(env, secret_name)?;
+        crate::versions::record_version(
+            env,
+            secret_name,
+            value,
+            crate::audit::AuditAction::Rotate,
+            None,
+            key_id,
+        )?;
         logger.log_with_value(crate::audit::LogParams {
-            secret_name: secret_name.clone(),
-            env: env.clone(),
-            service: service.clone(),
+            secret_name: secret_name.to_string(),
+            env: env.to_string(),
+            service: Some(services.join(",")),
             action: crate::audit::AuditAction::Rotate,
             success: true,
-            masked_secret_preview: Some(masked),
-            secret_value: Some(new_value.clone()),
+            masked_secret_preview: Some(masked.clone()),
+            secret_value: Some(value.to_string()),
         })?;
-
-        println!("✅ Secret rotated successfully");
-    } else {
-        println!("✅ Dry run complete (no changes made)");
     }
 
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
+pub async fn apply_value(
+    secret_name: &str,
+    value: &str,
+    env: &str,
+    service: Option<&str>,
+    env_file: Option<&str>,
+    key_path: Option<&str>,
+    redeploy: bool,
+    approval: ApprovalMode,
+) -> Result<()> {
+    if env == "dev" {
+        crate::dev::update_local_file(secret_name, value, env_file, key_path).await
+    } else {
+        crate::prod::update_production_secret(secret_name, value, env, service, redeploy, approval)
+            .await
+    }
+}
+
 fn check_cooldown(env: &str, secret_name: &str) -> Result<()> {
     let config = crate::config::Config::load()?;
     let birch_dir = crate::config::Config::birch_dir();
@@ -131,7 +565,7 @@ fn check_cooldown(env: &str, secret_name: &str) -> Result<()> {
     Ok(())
 }
 
-fn record_rotation(env: &str, secret_name: &str) -> Result<()> {
+pub fn record_rotation(env: &str, secret_name: &str) -> Result<()> {
     let birch_dir = crate::config::Config::birch_dir();
     let cooldown_dir = birch_dir.join("cooldowns");
     fs::create_dir_all(&cooldown_dir)?;
@@ -142,52 +576,71 @@ fn record_rotation(env: &str, secret_name: &str) -> Result<()> {
     Ok(())
 }
 
-fn generate_secret() -> Result<String> {
-    use rand::Rng;
-    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
-    const SECRET_LEN: usize = 32;
+pub fn last_rotation(env: &str, secret_name: &str) -> Result<Option<DateTime<Utc>>> {
+    let cooldown_file = crate::config::Config::birch_dir()
+        .join("cooldowns")
+        .join(format!("{}-{}", env, secret_name));
 
-    let mut rng = rand::thread_rng();
-    let secret: String = (0..SECRET_LEN)
-        .map(|_| {
-            let idx = rng.gen_range(0..CHARSET.len());
-            CHARSET[idx] as char
-        })
-        .collect();
+    if !cooldown_file.exists() {
+        return Ok(None);
+    }
 
-    Ok(secret)
+    Ok(Some(fs::read_to_string(&cooldown_file)?.trim().parse()?))
 }
 
-async fn get_current_secret_value(
+fn generate_secret(
+    config: &crate::config::Config,
+    secret_name: &str,
+    source: &ValueSource,
+) -> Result<String> {
+    let profile = source.profile(config, secret_name).unwrap_or_default();
+    crate::generator::generate(&profile)
+}
+
+fn publish_public_key(
+    config: &crate::config::Config,
+    secret_name: &str,
+    source: &ValueSource,
+    value: &str,
+) -> Result<()> {
+    match source.profile(config, secret_name) {
+        Some(profile) => crate::generator::publish_public_key(&profile, value),
+        None => Ok(()),
+    }
+}
+
+pub async fn get_current_secret_value(
     secret_name: &str,
     env: &str,
     service: Option<&str>,
+    env_file: Option<&str>,
+    key_path: Option<&str>,
 ) -> Result<String> {
     if env == "dev" {
-        if let Some(value) = crate::dev::get_env_secret(secret_name, None)? {
+        if let Some(value) = crate::dev::get_local_secret(secret_name, env_file, key_path)? {
             return Ok(value);
         }
-        anyhow::bail!("Secret not found in .env file")
+        anyhow::bail!("Secret not found in {}", env_file.unwrap_or(".env file"))
     } else {
         let config = crate::config::Config::load()?;
-        let service_name =
-            service.ok_or_else(|| anyhow::anyhow!("--service is required for production"))?;
-
-        let connector: Box<dyn crate::connectors::Connector> =
-            match service_name.to_lowercase().as_str() {
-                "vercel" => Box::new(crate::connectors::vercel::VercelConnector::new(&config)?),
-                "netlify" => Box::new(crate::connectors::netlify::NetlifyConnector::new(&config)?),
-                "render" => Box::new(crate::connectors::render::RenderConnector::new(&config)?),
-                "cloudflare" => Box::new(crate::connectors::cloudflare::CloudflareConnector::new(
-                    &config,
-                )?),
-                "fly" => Box::new(crate::connectors::fly::FlyConnector::new(&config)?),
-                "aws" => Box::new(crate::connectors::aws::AwsConnector::new(&config)?),
-                "gcp" => Box::new(crate::connectors::gcp::GcpConnector::new(&config)?),
-                "azure" => Box::new(crate::connectors::azure::AzureConnector::new(&config)?),
-                _ => anyhow::bail!("Unknown service: {}", service_name),
-            };
-
-        connector.get_secret(secret_name).await
+        // With several services the first one is the source of the current value.
+        let service_name = crate::prod::resolve_services(&config, env, service)
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("--service is required for production"))?;
+        let connector = crate::prod::get_connector(
+            Some(&service_name),
+            &crate::connectors::ConnectorSettings::default(),
+        )?;
+
+        let Some(parts) = crate::bundle::parts(secret_name)? else {
+            return connector.get_secret(secret_name).await;
+        };
+        let mut values = Vec::new();
+        for part in parts {
+            let value = connector.get_secret(&part).await?;
+            values.push((part, value));
+        }
+        Ok(crate::bundle::join(&values))
     }
 }
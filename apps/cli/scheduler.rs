@@ -0,0 +1,479 @@
+use crate::approval::{Decision, Operation};
+use crate::config::Config;
+use crate::manifest::{Manifest, ManifestSecret};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TICK_SECONDS: u64 = 60;
+const RETRY_SECONDS: i64 = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    pub secret_name: String,
+    pub schedule: Option<String>,
+    pub max_age: Option<String>,
+    pub first_seen: DateTime<Utc>,
+    pub next_run: Option<DateTime<Utc>>,
+    pub last_run: Option<DateTime<Utc>>,
+    pub retry_after: Option<DateTime<Utc>>,
+    pub last_result: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleState {
+    pub manifest_path: Option<PathBuf>,
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub entries: BTreeMap<String, ScheduleEntry>,
+}
+
+enum RunOutcome {
+    Rotated,
+    Waiting(String),
+}
+
+impl ScheduleEntry {
+    fn new(secret_name: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            secret_name: secret_name.to_string(),
+            schedule: None,
+            max_age: None,
+            first_seen: now,
+            next_run: None,
+            last_run: None,
+            retry_after: None,
+            last_result: None,
+        }
+    }
+
+    /// Whether the entry should run at `now`: its next run has come and it is
+    /// not backing off after a failure.
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_run.is_some_and(|t| t <= now) && self.retry_after.is_none_or(|t| t <= now)
+    }
+
+    fn record(&mut self, outcome: &Result<RunOutcome>, now: DateTime<Utc>) {
+        match outcome {
+            Ok(RunOutcome::Rotated) => {
+                self.last_run = Some(now);
+                self.retry_after = None;
+                self.last_result = Some("rotated".to_string());
+            }
+            Ok(RunOutcome::Waiting(reason)) => {
+                self.last_result = Some(format!("waiting: {}", reason));
+            }
+            Err(e) => {
+                self.retry_after = Some(now + Duration::seconds(RETRY_SECONDS));
+                self.last_result = Some(format!("failed: {}", e));
+            }
+        }
+    }
+}
+
+impl ScheduleState {
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::state_path())
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        fs::create_dir_all(Config::birch_dir())?;
+        self.save_to(&Self::state_path())
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path).context("Failed to read schedule state")?;
+        serde_json::from_str(&contents).context("Failed to parse schedule state")
+    }
+
+    fn save_to(&mut self, path: &Path) -> Result<()> {
+        self.updated_at = Some(Utc::now());
+
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&temp_path, path)?;
+
+        Ok(())
+    }
+
+    fn state_path() -> PathBuf {
+        Config::birch_dir().join("schedule.json")
+    }
+}
+
+pub fn next_cron_run(expression: &str, after: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let cron = croner::Cron::new(expression)
+        .parse()
+        .with_context(|| format!("Invalid schedule '{}'", expression))?;
+
+    cron.find_next_occurrence(&after, false)
+        .with_context(|| format!("No upcoming run for schedule '{}'", expression))
+}
+
+pub async fn run_scheduler(manifest_path: PathBuf) {
+    println!("⏰ Scheduler using manifest {}", manifest_path.display());
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(TICK_SECONDS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = tick(&manifest_path).await {
+            tracing::error!("Scheduler tick failed: {}", e);
+        }
+    }
+}
+
+async fn tick(manifest_path: &Path) -> Result<()> {
+    let config = Config::load()?;
+    let manifest = Manifest::load(manifest_path)?;
+    let mut state = ScheduleState::load()?;
+    let now = Utc::now();
+
+    state.manifest_path = Some(manifest_path.to_path_buf());
+    state.entries.retain(|name, _| {
+        manifest
+            .secrets
+            .get(name)
+            .is_some_and(|s| s.schedule.is_some() || s.max_age.is_some())
+    });
+
+    for (name, secret) in &manifest.secrets {
+        if secret.schedule.is_none() && secret.max_age.is_none() {
+            continue;
+        }
+
+        let entry = state
+            .entries
+            .entry(name.clone())
+            .or_insert_with(|| ScheduleEntry::new(name, now));
+
+        entry.schedule = secret.schedule.clone();
+        entry.max_age = secret.max_age.clone();
+        entry.next_run = next_run(name, secret, entry, now)?;
+
+        if !entry.is_due(now) {
+            continue;
+        }
+
+        let outcome = run_due(&config, name, secret).await;
+        match &outcome {
+            Ok(RunOutcome::Rotated) => tracing::info!("Scheduled rotation of {} completed", name),
+            Ok(RunOutcome::Waiting(reason)) => {
+                tracing::info!("Scheduled rotation of {} deferred: {}", name, reason)
+            }
+            Err(e) => tracing::warn!("Scheduled rotation of {} failed: {}", name, e),
+        }
+
+        entry.record(&outcome, now);
+        if matches!(outcome, Ok(RunOutcome::Rotated)) {
+            entry.next_run = next_run(name, secret, entry, now)?;
+        }
+    }
+
+    state.save()
+}
+
+fn next_run(
+    secret_name: &str,
+    secret: &ManifestSecret,
+    entry: &ScheduleEntry,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    let rotated_at = if secret.max_age.is_some() {
+        crate::manifest::oldest_rotation(secret_name, secret)?
+    } else {
+        None
+    };
+
+    next_run_at(secret, entry, rotated_at, now)
+}
+
+/// The earlier of the next cron run and the moment the value, rotated at
+/// `rotated_at`, falls due under `max_age` (see
+/// [`crate::manifest::max_age_due_at`]).
+fn next_run_at(
+    secret: &ManifestSecret,
+    entry: &ScheduleEntry,
+    rotated_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    let mut candidates = Vec::new();
+
+    if let Some(schedule) = &secret.schedule {
+        let after = entry.last_run.unwrap_or(entry.first_seen);
+        candidates.push(next_cron_run(schedule, after)?);
+    }
+
+    candidates.extend(crate::manifest::max_age_due_at(secret, rotated_at, now)?);
+
+    Ok(candidates.into_iter().min())
+}
+
+async fn run_due(
+    config: &Config,
+    secret_name: &str,
+    secret: &ManifestSecret,
+) -> Result<RunOutcome> {
+    let mut envs: Vec<&str> = secret.targets.iter().map(|t| t.env.as_str()).collect();
+    envs.sort();
+    envs.dedup();
+
+    let cooldown = Duration::seconds(config.cooldown_seconds as i64);
+
+    // Staged rotations need `birch rotate`; rotating across targets would fail.
+    if config.secrets.get(secret_name).is_some_and(|s| s.staged) {
+        return Ok(RunOutcome::Waiting(
+            "uses staged rotation; run `birch rotate` for it".to_string(),
+        ));
+    }
+
+    for env in envs {
+        if let Decision::Denied { reason } =
+            crate::approval::evaluate(config, Operation::Rotate, env, secret_name)
+        {
+            return Ok(RunOutcome::Waiting(reason));
+        }
+
+        let staged = crate::staged::RotationState::load(env, secret_name)?;
+        if let Some(reason) = staged_wait(env, staged.as_ref(), Utc::now()) {
+            return Ok(RunOutcome::Waiting(reason));
+        }
+
+        let rotated_at = crate::rotation::last_rotation(env, secret_name)?;
+        if in_cooldown(rotated_at, cooldown, Utc::now()) {
+            return Ok(RunOutcome::Waiting(format!("cooldown active in '{}'", env)));
+        }
+    }
+
+    crate::manifest::rotate_secret(config, secret_name, secret).await?;
+    Ok(RunOutcome::Rotated)
+}
+
+/// Why an unfinished staged rotation in `env` holds the secret back. A
+/// promoted one past its grace period does not; the rotation retires it.
+fn staged_wait(
+    env: &str,
+    state: Option<&crate::staged::RotationState>,
+    now: DateTime<Utc>,
+) -> Option<String> {
+    state
+        .filter(|s| s.phase.is_active() && !s.retirement_due(now))
+        .map(|s| format!("staged rotation in '{}' is {:?}", env, s.phase))
+}
+
+fn in_cooldown(rotated_at: Option<DateTime<Utc>>, cooldown: Duration, now: DateTime<Utc>) -> bool {
+    rotated_at.is_some_and(|t| now - t < cooldown)
+}
+
+pub fn print_schedule() -> Result<()> {
+    let state = ScheduleState::load()?;
+
+    if state.entries.is_empty() {
+        println!("No scheduled rotations");
+        return Ok(());
+    }
+
+    println!("\n⏰ Scheduled rotations");
+    if let Some(path) = &state.manifest_path {
+        println!("   Manifest: {}", path.display());
+    }
+
+    for entry in state.entries.values() {
+        let next = entry
+            .next_run
+            .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        println!("\n   {}", entry.secret_name);
+        println!("      Next run:    {}", next);
+        if let Some(schedule) = &entry.schedule {
+            println!("      Schedule:    {}", schedule);
+        }
+        if let Some(max_age) = &entry.max_age {
+            println!("      Max age:     {}", max_age);
+        }
+        if let Some(last_run) = entry.last_run {
+            println!(
+                "      Last run:    {}",
+                last_run.format("%Y-%m-%d %H:%M UTC")
+            );
+        }
+        if let Some(result) = &entry.last_result {
+            println!("      Last result: {}", result);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, hour, minute, 0).unwrap()
+    }
+
+    fn secret(schedule: Option<&str>, max_age: Option<&str>) -> ManifestSecret {
+        ManifestSecret {
+            targets: vec![crate::fanout::Target::service("prod", "api", false)],
+            generate: None,
+            pool: None,
+            schedule: schedule.map(str::to_string),
+            max_age: max_age.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn cron_runs_after_the_given_time() {
+        assert_eq!(next_cron_run("0 3 * * *", at(2, 0)).unwrap(), at(3, 0));
+        assert_eq!(
+            next_cron_run("0 3 * * *", at(3, 0)).unwrap(),
+            at(3, 0) + Duration::days(1)
+        );
+        assert_eq!(next_cron_run("*/15 * * * *", at(9, 7)).unwrap(), at(9, 15));
+    }
+
+    #[test]
+    fn invalid_cron_is_rejected() {
+        let err = next_cron_run("every day", at(0, 0)).unwrap_err();
+        assert!(err.to_string().contains("Invalid schedule"), "{}", err);
+    }
+
+    #[test]
+    fn schedule_counts_from_last_run_or_first_seen() {
+        let secret = secret(Some("0 3 * * *"), None);
+        let mut entry = ScheduleEntry::new("API_KEY", at(1, 0));
+
+        assert_eq!(
+            next_run_at(&secret, &entry, None, at(1, 0)).unwrap(),
+            Some(at(3, 0))
+        );
+
+        entry.last_run = Some(at(3, 0));
+        assert_eq!(
+            next_run_at(&secret, &entry, None, at(3, 0)).unwrap(),
+            Some(at(3, 0) + Duration::days(1))
+        );
+    }
+
+    #[test]
+    fn max_age_follows_the_manifest_rule() {
+        let secret = secret(None, Some("12h"));
+        let entry = ScheduleEntry::new("API_KEY", at(0, 0));
+
+        // Never rotated by birch: due right away, as `birch apply` would.
+        for rotated_at in [None, Some(at(0, 0))] {
+            assert_eq!(
+                next_run_at(&secret, &entry, rotated_at, at(1, 0)).unwrap(),
+                crate::manifest::max_age_due_at(&secret, rotated_at, at(1, 0)).unwrap()
+            );
+        }
+        assert_eq!(
+            next_run_at(&secret, &entry, None, at(1, 0)).unwrap(),
+            Some(at(1, 0))
+        );
+        assert_eq!(
+            next_run_at(&secret, &entry, Some(at(0, 0)), at(1, 0)).unwrap(),
+            Some(at(12, 0))
+        );
+    }
+
+    #[test]
+    fn earliest_of_schedule_and_max_age_wins() {
+        let secret = secret(Some("0 3 * * *"), Some("1h"));
+        let entry = ScheduleEntry::new("API_KEY", at(1, 0));
+
+        assert_eq!(
+            next_run_at(&secret, &entry, Some(at(1, 0)), at(1, 0)).unwrap(),
+            Some(at(2, 0))
+        );
+    }
+
+    #[test]
+    fn failure_backs_off_before_retrying() {
+        let mut entry = ScheduleEntry::new("API_KEY", at(0, 0));
+        entry.next_run = Some(at(1, 0));
+        assert!(!entry.is_due(at(0, 59)));
+        assert!(entry.is_due(at(1, 0)));
+
+        entry.record(&Err(anyhow::anyhow!("provider down")), at(1, 0));
+        assert_eq!(entry.retry_after, Some(at(1, 5)));
+        assert_eq!(entry.last_run, None);
+        assert!(!entry.is_due(at(1, 4)));
+        assert!(entry.is_due(at(1, 5)));
+
+        entry.record(&Ok(RunOutcome::Rotated), at(1, 5));
+        assert_eq!(entry.retry_after, None);
+        assert_eq!(entry.last_run, Some(at(1, 5)));
+        assert_eq!(entry.last_result.as_deref(), Some("rotated"));
+    }
+
+    #[test]
+    fn waiting_keeps_the_entry_due() {
+        let mut entry = ScheduleEntry::new("API_KEY", at(0, 0));
+        entry.next_run = Some(at(1, 0));
+
+        entry.record(&Ok(RunOutcome::Waiting("cooldown".to_string())), at(1, 0));
+        assert_eq!(entry.retry_after, None);
+        assert!(entry.is_due(at(1, 1)));
+        assert_eq!(entry.last_result.as_deref(), Some("waiting: cooldown"));
+    }
+
+    #[test]
+    fn cooldown_skips_recent_rotations() {
+        let cooldown = Duration::minutes(30);
+
+        assert!(!in_cooldown(None, cooldown, at(1, 0)));
+        assert!(in_cooldown(Some(at(0, 45)), cooldown, at(1, 0)));
+        assert!(!in_cooldown(Some(at(0, 30)), cooldown, at(1, 0)));
+    }
+
+    #[test]
+    fn unfinished_staged_rotation_waits() {
+        use crate::staged::{RotationPhase, RotationState};
+
+        let mut state = RotationState::new("API_KEY", "prod", None, None, None, false, 3600);
+        assert_eq!(
+            staged_wait("prod", Some(&state), Utc::now()).as_deref(),
+            Some("staged rotation in 'prod' is Staged")
+        );
+
+        state.phase = RotationPhase::Promoted;
+        state.promoted_at = Some(Utc::now() - Duration::hours(2));
+        assert_eq!(staged_wait("prod", Some(&state), Utc::now()), None);
+
+        state.phase = RotationPhase::Aborted;
+        assert_eq!(staged_wait("prod", Some(&state), Utc::now()), None);
+        assert_eq!(staged_wait("prod", None, Utc::now()), None);
+    }
+
+    #[test]
+    fn state_persists_to_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedule.json");
+
+        assert!(ScheduleState::load_from(&path).unwrap().entries.is_empty());
+
+        let mut entry = ScheduleEntry::new("API_KEY", at(0, 0));
+        entry.schedule = Some("0 3 * * *".to_string());
+        entry.next_run = Some(at(3, 0));
+        let mut state = ScheduleState::default();
+        state.entries.insert("API_KEY".to_string(), entry);
+        state.save_to(&path).unwrap();
+
+        let loaded = ScheduleState::load_from(&path).unwrap();
+        assert!(loaded.updated_at.is_some());
+        let entry = &loaded.entries["API_KEY"];
+        assert_eq!(entry.next_run, Some(at(3, 0)));
+        assert_eq!(entry.schedule.as_deref(), Some("0 3 * * *"));
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+}
@@ -0,0 +1,909 @@
+//! Secrets files committed to a repository, sops-style: a `.env` or YAML file
+//! whose values are encrypted while keys and layout stay readable, so changes
+//! show up in diffs.
+//!
+//! Every file has a random data key. Values are encrypted with it using
+//! ChaCha20-Poly1305, bound to their key path, and written as
+//! `ENC[chacha20poly1305,...]`. The data key is sealed to each recipient's
+//! X25519 public key with a `crypto_box` sealed box, and a MAC over all
+//! entries detects values that were removed, added or moved. Recipients and the MAC are kept at the end of the
+//! file: `# birch:` comments in `.env` files, a top-level `birch` key in YAML.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const TOKEN_PREFIX: &str = "ENC[chacha20poly1305,";
+const ENV_META_PREFIX: &str = "# birch:";
+const YAML_META_KEY: &str = "birch";
+const MAC_AAD: &[u8] = b"birch:mac";
+
+/// Environment variable holding the identity (base64 X25519 secret key),
+/// for CI jobs that decrypt without a key file.
+pub const IDENTITY_ENV: &str = "BIRCH_SECRETS_KEY";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Env,
+    Yaml,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Metadata {
+    recipients: Vec<Recipient>,
+    mac: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Recipient {
+    public_key: String,
+    /// `ephemeral public key || nonce || encrypted data key`, base64.
+    data_key: String,
+}
+
+#[derive(Deserialize)]
+struct YamlMetadata {
+    birch: Metadata,
+}
+
+/// The X25519 key pair that opens secrets files, kept in
+/// `~/.birch/secrets-key` or taken from `BIRCH_SECRETS_KEY`.
+pub struct Identity {
+    secret: [u8; 32],
+}
+
+impl Identity {
+    /// Loads the identity, creating the key file on first use.
+    pub fn load() -> Result<Self> {
+        if let Ok(encoded) = std::env::var(IDENTITY_ENV) {
+            return Self::decode(&encoded).context("Invalid BIRCH_SECRETS_KEY");
+        }
+
+        let path = Self::path();
+        if path.exists() {
+            let encoded = std::fs::read_to_string(&path)?;
+            return Self::decode(&encoded)
+                .with_context(|| format!("Invalid identity in {}", path.display()));
+        }
+
+        let mut secret = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut secret);
+        std::fs::create_dir_all(crate::config::Config::birch_dir())?;
+        write_private(&path, format!("{}\n", encode(&secret)).as_bytes())?;
+        eprintln!("🔑 Created secrets identity {}", path.display());
+
+        Ok(Self { secret })
+    }
+
+    fn path() -> PathBuf {
+        crate::config::Config::birch_dir().join("secrets-key")
+    }
+
+    fn decode(encoded: &str) -> Result<Self> {
+        Ok(Self {
+            secret: decode_key(encoded)?,
+        })
+    }
+
+    pub fn public_key(&self) -> String {
+        encode(
+            crypto_box::SecretKey::from(self.secret)
+                .public_key()
+                .as_bytes(),
+        )
+    }
+}
+
+/// An encrypted secrets file. `body` is the file without its metadata, with
+/// every value in encrypted form.
+pub struct SecretsFile {
+    path: PathBuf,
+    format: Format,
+    body: String,
+    recipients: Vec<Recipient>,
+    data_key: [u8; 32],
+    /// Plaintext of every entry, by key path.
+    values: Vec<(Vec<String>, String)>,
+}
+
+impl SecretsFile {
+    /// Opens and decrypts `path` with `identity`.
+    pub fn open(path: &Path, identity: &Identity) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let format = format_of(path);
+        let (body, metadata) = split_metadata(format, &contents)?;
+        let metadata = metadata.ok_or_else(|| {
+            anyhow::anyhow!("{} is not an encrypted secrets file", path.display())
+        })?;
+
+        let public_key = identity.public_key();
+        let recipient = metadata
+            .recipients
+            .iter()
+            .find(|r| r.public_key == public_key)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Your key {} is not a recipient of {}; ask a recipient to run `birch secrets recipients add {} {}`",
+                    public_key,
+                    path.display(),
+                    path.display(),
+                    public_key
+                )
+            })?;
+        let data_key = unwrap_key(&recipient.data_key, identity)?;
+
+        let mut values = Vec::new();
+        for (key_path, token) in entries(format, &body)? {
+            let value = decrypt_value(&data_key, &key_path, &token).with_context(|| {
+                format!(
+                    "Failed to decrypt '{}' in {}",
+                    key_path.join("."),
+                    path.display()
+                )
+            })?;
+            values.push((key_path, value));
+        }
+
+        let file = Self {
+            path: path.to_path_buf(),
+            format,
+            body,
+            recipients: metadata.recipients,
+            data_key,
+            values,
+        };
+        if decrypt(&data_key, &metadata.mac, MAC_AAD)? != file.mac_input() {
+            anyhow::bail!(
+                "MAC mismatch in {}: entries were added, removed or moved without the data key",
+                path.display()
+            );
+        }
+
+        Ok(file)
+    }
+
+    /// Starts a new, empty file encrypted to `recipients`.
+    pub fn create(path: &Path, recipients: &[String]) -> Result<Self> {
+        let mut data_key = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut data_key);
+
+        let mut file = Self {
+            path: path.to_path_buf(),
+            format: format_of(path),
+            body: String::new(),
+            recipients: Vec::new(),
+            data_key,
+            values: Vec::new(),
+        };
+        file.add_recipients(recipients)?;
+        Ok(file)
+    }
+
+    pub fn get(&self, key_path: &[String]) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|(path, _)| path == key_path)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &[String]> {
+        self.values.iter().map(|(path, _)| path.as_slice())
+    }
+
+    pub fn set(&mut self, key_path: &[String], value: &str) -> Result<()> {
+        let token = encrypt_value(&self.data_key, key_path, value)?;
+        self.body = self.editor().set(&self.body, key_path, &token)?;
+
+        match self.values.iter_mut().find(|(path, _)| path == key_path) {
+            Some((_, current)) => *current = value.to_string(),
+            None => self.values.push((key_path.to_vec(), value.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Removes a top-level `.env` entry. Returns false if there was none.
+    pub fn remove(&mut self, key: &str) -> Result<bool> {
+        if self.format != Format::Env {
+            anyhow::bail!("Removing entries is only supported in .env files");
+        }
+
+        let mut env = crate::dotenv::EnvFile::parse(&self.body)?;
+        if !env.remove(key) {
+            return Ok(false);
+        }
+        self.body = env.to_string();
+        self.values.retain(|(path, _)| path.join(".") != key);
+        Ok(true)
+    }
+
+    /// Wraps the data key for each public key that is not a recipient yet.
+    /// Returns how many were added.
+    pub fn add_recipients(&mut self, public_keys: &[String]) -> Result<usize> {
+        let mut added = 0;
+        for public_key in public_keys {
+            let key = decode_key(public_key)
+                .with_context(|| format!("Invalid public key '{}'", public_key))?;
+            let public_key = encode(&key);
+            if self.recipients.iter().any(|r| r.public_key == public_key) {
+                continue;
+            }
+
+            self.recipients.push(Recipient {
+                data_key: wrap_key(&self.data_key, &key)?,
+                public_key,
+            });
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    /// The file with every value decrypted and no metadata.
+    pub fn plaintext(&self) -> Result<String> {
+        let format = self.editor();
+        let mut text = self.body.clone();
+        for (key_path, value) in &self.values {
+            text = format.set(&text, key_path, value)?;
+        }
+        Ok(text)
+    }
+
+    /// Replaces the contents with an edited plaintext version. Values that did
+    /// not change keep their ciphertext, so the diff shows only what changed.
+    pub fn replace_plaintext(&mut self, plaintext: &str) -> Result<()> {
+        let (plaintext, _) = split_metadata(self.format, plaintext)?;
+        let format = self.editor();
+        let previous = std::mem::take(&mut self.values);
+        let old_tokens: Vec<(Vec<String>, String)> = entries(self.format, &self.body)?;
+
+        let mut body = plaintext.clone();
+        for (key_path, value) in entries(self.format, &plaintext)? {
+            let unchanged = previous
+                .iter()
+                .any(|(path, old)| *path == key_path && *old == value);
+            let token = match old_tokens.iter().find(|(path, _)| *path == key_path) {
+                Some((_, token)) if unchanged => token.clone(),
+                _ => encrypt_value(&self.data_key, &key_path, &value)?,
+            };
+            body = format.set(&body, &key_path, &token)?;
+            self.values.push((key_path, value));
+        }
+
+        self.body = body;
+        Ok(())
+    }
+
+    /// Writes the file, replacing it atomically.
+    pub fn save(&self) -> Result<()> {
+        let mac = encrypt(&self.data_key, self.mac_input().as_bytes(), MAC_AAD)?;
+        let metadata = Metadata {
+            recipients: self.recipients.clone(),
+            mac,
+        };
+
+        let mut contents = self.body.clone();
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        match self.format {
+            Format::Env => {
+                for recipient in &metadata.recipients {
+                    contents.push_str(&format!(
+                        "{}recipient {} {}\n",
+                        ENV_META_PREFIX, recipient.public_key, recipient.data_key
+                    ));
+                }
+                contents.push_str(&format!("{}mac {}\n", ENV_META_PREFIX, metadata.mac));
+            }
+            Format::Yaml => {
+                let mut wrapper = serde_yaml::Mapping::new();
+                wrapper.insert(YAML_META_KEY.into(), serde_yaml::to_value(&metadata)?);
+                contents.push_str(&serde_yaml::to_string(&wrapper)?);
+            }
+        }
+
+        let temp_path = self.path.with_extension("tmp");
+        std::fs::write(&temp_path, contents)?;
+        std::fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    fn editor(&self) -> Box<dyn crate::local_files::FileFormat> {
+        match self.format {
+            Format::Env => Box::new(crate::local_files::env_file::EnvFileFormat),
+            Format::Yaml => Box::new(crate::local_files::yaml_file::YamlFile),
+        }
+    }
+
+    /// Key paths and plaintexts in a stable order; what the MAC covers.
+    fn mac_input(&self) -> String {
+        let mut entries: Vec<String> = self
+            .values
+            .iter()
+            .map(|(path, value)| format!("{}\0{}\0", path.join("."), value))
+            .collect();
+        entries.sort();
+        encode(&Blake2b::<U32>::digest(entries.concat()))
+    }
+}
+
+fn format_of(path: &Path) -> Format {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .as_deref()
+    {
+        Some("yaml") | Some("yml") => Format::Yaml,
+        _ => Format::Env,
+    }
+}
+
+/// Splits the metadata off the end of a file.
+fn split_metadata(format: Format, contents: &str) -> Result<(String, Option<Metadata>)> {
+    match format {
+        Format::Env => {
+            let mut body = String::new();
+            let mut recipients = Vec::new();
+            let mut mac = None;
+
+            for line in contents.split_inclusive('\n') {
+                let Some(meta) = line.trim_end().strip_prefix(ENV_META_PREFIX) else {
+                    body.push_str(line);
+                    continue;
+                };
+                let fields: Vec<&str> = meta.split_whitespace().collect();
+                match fields.as_slice() {
+                    ["recipient", public_key, data_key] => recipients.push(Recipient {
+                        public_key: public_key.to_string(),
+                        data_key: data_key.to_string(),
+                    }),
+                    ["mac", value] => mac = Some(value.to_string()),
+                    _ => anyhow::bail!("Invalid metadata line: {}", line.trim_end()),
+                }
+            }
+
+            let metadata = mac.map(|mac| Metadata { recipients, mac });
+            Ok((body, metadata))
+        }
+        Format::Yaml => {
+            let start = contents
+                .match_indices(&format!("{}:", YAML_META_KEY))
+                .map(|(i, _)| i)
+                .find(|&i| i == 0 || contents[..i].ends_with('\n'));
+            let Some(start) = start else {
+                return Ok((contents.to_string(), None));
+            };
+
+            // The block runs until the next top-level line.
+            let block_len = contents[start..]
+                .split_inclusive('\n')
+                .enumerate()
+                .take_while(|(i, line)| {
+                    *i == 0 || line.trim().is_empty() || line.starts_with([' ', '\t', '#'])
+                })
+                .map(|(_, line)| line.len())
+                .sum::<usize>();
+            let block = &contents[start..start + block_len];
+            let metadata: YamlMetadata =
+                serde_yaml::from_str(block).context("Invalid birch metadata")?;
+
+            let body = format!("{}{}", &contents[..start], &contents[start + block_len..]);
+            Ok((body, Some(metadata.birch)))
+        }
+    }
+}
+
+/// Every value in the file, by key path.
+fn entries(format: Format, body: &str) -> Result<Vec<(Vec<String>, String)>> {
+    match format {
+        Format::Env => {
+            let env = crate::dotenv::EnvFile::parse(body)?;
+            Ok(env
+                .keys()
+                .into_iter()
+                .filter_map(|key| Some((vec![key.to_string()], env.get(key)?.to_string())))
+                .collect())
+        }
+        Format::Yaml => {
+            let document: serde_yaml::Value = if body.trim().is_empty() {
+                serde_yaml::Value::Null
+            } else {
+                serde_yaml::from_str(body).context("Invalid YAML")?
+            };
+            let mut entries = Vec::new();
+            collect_yaml(&document, &mut Vec::new(), &mut entries)?;
+            Ok(entries)
+        }
+    }
+}
+
+fn collect_yaml(
+    value: &serde_yaml::Value,
+    path: &mut Vec<String>,
+    entries: &mut Vec<(Vec<String>, String)>,
+) -> Result<()> {
+    use serde_yaml::Value;
+
+    match value {
+        Value::Null => {}
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping {
+                let key = match key {
+                    Value::String(s) => s.clone(),
+                    Value::Number(n) => n.to_string(),
+                    Value::Bool(b) => b.to_string(),
+                    _ => anyhow::bail!("Unsupported YAML key under '{}'", path.join(".")),
+                };
+                path.push(key);
+                collect_yaml(value, path, entries)?;
+                path.pop();
+            }
+        }
+        // `KEY=value` lists, as in docker-compose `environment:`.
+        Value::Sequence(items) => {
+            for item in items {
+                let Some((key, value)) = item.as_str().and_then(|s| s.split_once('=')) else {
+                    anyhow::bail!(
+                        "'{}' is a list; only KEY=value lists can be encrypted",
+                        path.join(".")
+                    );
+                };
+                let mut item_path = path.clone();
+                item_path.push(key.to_string());
+                entries.push((item_path, value.to_string()));
+            }
+        }
+        Value::String(s) => entries.push((path.clone(), s.clone())),
+        Value::Bool(b) => entries.push((path.clone(), b.to_string())),
+        Value::Number(n) => entries.push((path.clone(), n.to_string())),
+        Value::Tagged(_) => anyhow::bail!("Tagged YAML values are not supported"),
+    }
+    Ok(())
+}
+
+fn encrypt_value(data_key: &[u8; 32], key_path: &[String], value: &str) -> Result<String> {
+    let aad = key_path.join(".");
+    Ok(format!(
+        "{}{}]",
+        TOKEN_PREFIX,
+        encrypt(data_key, value.as_bytes(), aad.as_bytes())?
+    ))
+}
+
+fn decrypt_value(data_key: &[u8; 32], key_path: &[String], token: &str) -> Result<String> {
+    let encrypted = token
+        .strip_prefix(TOKEN_PREFIX)
+        .and_then(|t| t.strip_suffix(']'))
+        .ok_or_else(|| anyhow::anyhow!("value is not encrypted"))?;
+    let aad = key_path.join(".");
+    decrypt(data_key, encrypted, aad.as_bytes())
+}
+
+fn encrypt(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Result<String> {
+    Ok(encode(&seal(key, plaintext, aad)?))
+}
+
+fn decrypt(key: &[u8; 32], encrypted: &str, aad: &[u8]) -> Result<String> {
+    let combined = base64::engine::general_purpose::STANDARD
+        .decode(encrypted)
+        .context("Failed to decode base64")?;
+    let plaintext = open(key, &combined, aad)
+        .map_err(|_| anyhow::anyhow!("Decryption failed: wrong key or tampered value"))?;
+
+    String::from_utf8(plaintext).context("Invalid UTF-8 in decrypted value")
+}
+
+/// ChaCha20-Poly1305 with a random nonce; returns `nonce || ciphertext`.
+fn seal(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; 12];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+    let ciphertext = ChaCha20Poly1305::new(key.into())
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
+
+    let mut combined = nonce.to_vec();
+    combined.extend(ciphertext);
+    Ok(combined)
+}
+
+fn open(key: &[u8; 32], combined: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    if combined.len() < 12 {
+        anyhow::bail!("Invalid encrypted data: too short");
+    }
+
+    let (nonce, ciphertext) = combined.split_at(12);
+    ChaCha20Poly1305::new(key.into())
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|e| anyhow::anyhow!("Decryption failed: {}", e))
+}
+
+/// Encrypts the data key to `public_key` as a sealed box.
+fn wrap_key(data_key: &[u8; 32], public_key: &[u8; 32]) -> Result<String> {
+    // A low-order public key yields an all-zero shared secret, whatever the
+    // ephemeral key, so anyone could open the box.
+    if MontgomeryPoint(*public_key)
+        .mul_clamped([1u8; 32])
+        .to_bytes()
+        .iter()
+        .all(|b| *b == 0)
+    {
+        anyhow::bail!("Invalid recipient public key");
+    }
+
+    let sealed = crypto_box::PublicKey::from(*public_key)
+        .seal(&mut crypto_box::aead::OsRng, data_key)
+        .map_err(|e| anyhow::anyhow!("Failed to wrap the data key: {}", e))?;
+    Ok(encode(&sealed))
+}
+
+fn unwrap_key(wrapped: &str, identity: &Identity) -> Result<[u8; 32]> {
+    let sealed = base64::engine::general_purpose::STANDARD
+        .decode(wrapped)
+        .context("Invalid wrapped data key")?;
+
+    crypto_box::SecretKey::from(identity.secret)
+        .unseal(&sealed)
+        .map_err(|_| anyhow::anyhow!("Failed to unwrap the data key"))?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Invalid data key length"))
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; 32]> {
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .context("Key is not valid base64")?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Key must be 32 bytes"))
+}
+
+/// Writes a file only the current user can read.
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    std::io::Write::write_all(&mut file, contents)?;
+    Ok(())
+}
+
+pub async fn edit(file: String, recipients: Vec<String>) -> Result<()> {
+    let path = PathBuf::from(&file);
+    let identity = Identity::load()?;
+
+    let exists = path.exists();
+    let (mut secrets, added) = if exists {
+        let mut secrets = SecretsFile::open(&path, &identity)?;
+        let added = secrets.add_recipients(&recipients)?;
+        (secrets, added)
+    } else {
+        let mut all = vec![identity.public_key()];
+        all.extend(recipients);
+        println!("📄 Creating {}", path.display());
+        (SecretsFile::create(&path, &all)?, 0)
+    };
+
+    let original = secrets.plaintext()?;
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "secrets".to_string());
+    let edit_path = crate::config::Config::birch_dir().join(format!(
+        "edit-{}-{}",
+        uuid::Uuid::new_v4(),
+        file_name
+    ));
+    write_private(&edit_path, original.as_bytes())?;
+
+    let edited = run_editor(&edit_path)
+        .await
+        .and_then(|()| Ok(std::fs::read_to_string(&edit_path)?));
+    let _ = std::fs::remove_file(&edit_path);
+    let edited = edited?;
+
+    if exists && added == 0 && edited == original {
+        println!("No changes");
+        return Ok(());
+    }
+
+    secrets.replace_plaintext(&edited)?;
+    secrets.save()?;
+    if added > 0 {
+        println!("👥 Added {} recipient(s)", added);
+    }
+    println!("✅ Saved {}", path.display());
+    Ok(())
+}
+
+async fn run_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("EDITOR is empty"))?;
+
+    let status = tokio::process::Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .await
+        .with_context(|| format!("Failed to start editor '{}'", editor))?;
+    if !status.success() {
+        anyhow::bail!("Editor exited with {}; changes discarded", status);
+    }
+    Ok(())
+}
+
+pub async fn decrypt_file(file: String, output: Option<String>) -> Result<()> {
+    let secrets = SecretsFile::open(Path::new(&file), &Identity::load()?)?;
+    let plaintext = secrets.plaintext()?;
+
+    match output {
+        Some(output) => {
+            write_private(Path::new(&output), plaintext.as_bytes())?;
+            eprintln!("🔓 Decrypted {} to {}", file, output);
+        }
+        None => print!("{}", plaintext),
+    }
+    Ok(())
+}
+
+pub async fn recipients_add(file: String, public_keys: Vec<String>) -> Result<()> {
+    let path = Path::new(&file);
+    let mut secrets = SecretsFile::open(path, &Identity::load()?)?;
+
+    let added = secrets.add_recipients(&public_keys)?;
+    if added == 0 {
+        println!("All keys are already recipients of {}", file);
+        return Ok(());
+    }
+
+    secrets.save()?;
+    println!("✅ Added {} recipient(s) to {}", added, file);
+    Ok(())
+}
+
+pub async fn recipients_list(file: String) -> Result<()> {
+    let identity = Identity::load()?;
+    let contents =
+        std::fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file))?;
+    let (_, metadata) = split_metadata(format_of(Path::new(&file)), &contents)?;
+    let metadata =
+        metadata.ok_or_else(|| anyhow::anyhow!("{} is not an encrypted secrets file", file))?;
+
+    let own = identity.public_key();
+    for recipient in metadata.recipients {
+        let marker = if recipient.public_key == own {
+            " (you)"
+        } else {
+            ""
+        };
+        println!("{}{}", recipient.public_key, marker);
+    }
+    Ok(())
+}
+
+pub async fn public_key() -> Result<()> {
+    println!("{}", Identity::load()?.public_key());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity() -> Identity {
+        let mut secret = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut secret);
+        Identity { secret }
+    }
+
+    fn key(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    fn create(path: &Path, identity: &Identity, entries: &[(&str, &str)]) -> SecretsFile {
+        let mut file = SecretsFile::create(path, &[identity.public_key()]).unwrap();
+        for (k, v) in entries {
+            file.set(&key(k), v).unwrap();
+        }
+        file.save().unwrap();
+        file
+    }
+
+    #[test]
+    fn env_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.env");
+        let identity = identity();
+        create(
+            &path,
+            &identity,
+            &[("API_KEY", "sk_live_1"), ("DB_PASSWORD", "p@ss word")],
+        );
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("sk_live_1"));
+        assert!(contents.contains("API_KEY=ENC[chacha20poly1305,"));
+        assert!(contents.contains(&format!("# birch:recipient {}", identity.public_key())));
+        assert!(contents.contains("# birch:mac "));
+
+        let file = SecretsFile::open(&path, &identity).unwrap();
+        assert_eq!(file.get(&key("API_KEY")), Some("sk_live_1"));
+        assert_eq!(file.get(&key("DB_PASSWORD")), Some("p@ss word"));
+        assert_eq!(
+            file.plaintext().unwrap(),
+            "API_KEY=sk_live_1\nDB_PASSWORD=\"p@ss word\"\n"
+        );
+    }
+
+    #[test]
+    fn yaml_round_trip_with_nested_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.yaml");
+        let identity = identity();
+        create(
+            &path,
+            &identity,
+            &[("stripe.api_key", "sk_1"), ("db.primary.password", "pw")],
+        );
+
+        let file = SecretsFile::open(&path, &identity).unwrap();
+        assert_eq!(file.get(&key("stripe.api_key")), Some("sk_1"));
+        assert_eq!(file.get(&key("db.primary.password")), Some("pw"));
+        assert_eq!(file.keys().count(), 2);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("birch:\n"));
+        assert!(!contents.contains("sk_1"));
+    }
+
+    #[test]
+    fn only_recipients_can_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.env");
+        let owner = identity();
+        let teammate = identity();
+        let mut file = create(&path, &owner, &[("API_KEY", "sk_1")]);
+
+        assert!(SecretsFile::open(&path, &teammate).is_err());
+
+        assert_eq!(file.add_recipients(&[teammate.public_key()]).unwrap(), 1);
+        assert_eq!(file.add_recipients(&[teammate.public_key()]).unwrap(), 0);
+        file.save().unwrap();
+
+        let opened = SecretsFile::open(&path, &teammate).unwrap();
+        assert_eq!(opened.get(&key("API_KEY")), Some("sk_1"));
+        assert!(SecretsFile::open(&path, &owner).is_ok());
+    }
+
+    #[test]
+    fn wrapped_keys_open_only_for_their_recipient() {
+        let (owner, other) = (identity(), identity());
+        let data_key = [7u8; 32];
+        let public_key = decode_key(&owner.public_key()).unwrap();
+
+        let wrapped = wrap_key(&data_key, &public_key).unwrap();
+        assert_eq!(unwrap_key(&wrapped, &owner).unwrap(), data_key);
+        assert!(unwrap_key(&wrapped, &other).is_err());
+        assert_ne!(wrap_key(&data_key, &public_key).unwrap(), wrapped);
+    }
+
+    #[test]
+    fn rejects_invalid_recipients() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.env");
+
+        assert!(SecretsFile::create(&path, &["not base64".to_string()]).is_err());
+        assert!(SecretsFile::create(&path, &[encode(&[1u8; 16])]).is_err());
+        // Low-order point: the shared secret would be all zeros.
+        assert!(SecretsFile::create(&path, &[encode(&[0u8; 32])]).is_err());
+    }
+
+    #[test]
+    fn detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.env");
+        let identity = identity();
+        create(&path, &identity, &[("A", "one"), ("B", "two")]);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+
+        // A value moved to another key fails its key-path binding.
+        let token_a = lines[0].strip_prefix("A=").unwrap();
+        let token_b = lines[1].strip_prefix("B=").unwrap();
+        let swapped = contents
+            .replacen(token_a, "SWAP", 1)
+            .replacen(token_b, token_a, 1)
+            .replacen("SWAP", token_b, 1);
+        std::fs::write(&path, swapped).unwrap();
+        assert!(SecretsFile::open(&path, &identity).is_err());
+
+        // A removed entry no longer matches the MAC.
+        std::fs::write(&path, contents.replacen(&format!("{}\n", lines[1]), "", 1)).unwrap();
+        let err = SecretsFile::open(&path, &identity).err().unwrap();
+        assert!(err.to_string().contains("MAC mismatch"));
+
+        // A modified ciphertext fails authentication.
+        let mut combined = base64::engine::general_purpose::STANDARD
+            .decode(
+                token_a
+                    .strip_prefix(TOKEN_PREFIX)
+                    .unwrap()
+                    .trim_end_matches(']'),
+            )
+            .unwrap();
+        *combined.last_mut().unwrap() ^= 1;
+        let tampered = format!("{}{}]", TOKEN_PREFIX, encode(&combined));
+        std::fs::write(&path, contents.replacen(token_a, &tampered, 1)).unwrap();
+        assert!(SecretsFile::open(&path, &identity).is_err());
+    }
+
+    #[test]
+    fn edits_keep_unchanged_ciphertexts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.env");
+        let identity = identity();
+        create(
+            &path,
+            &identity,
+            &[("A", "one"), ("B", "two"), ("C", "three")],
+        );
+        let before = std::fs::read_to_string(&path).unwrap();
+
+        let mut file = SecretsFile::open(&path, &identity).unwrap();
+        file.replace_plaintext("A=one\nB=changed\nC=three\n")
+            .unwrap();
+        assert!(file.remove("C").unwrap());
+        assert!(!file.remove("C").unwrap());
+        file.save().unwrap();
+        let after = std::fs::read_to_string(&path).unwrap();
+
+        let line = |contents: &str, prefix: &str| {
+            contents
+                .lines()
+                .find(|l| l.starts_with(prefix))
+                .map(str::to_string)
+        };
+        assert_eq!(line(&before, "A="), line(&after, "A="));
+        assert_ne!(line(&before, "B="), line(&after, "B="));
+        assert_eq!(line(&after, "C="), None);
+
+        let file = SecretsFile::open(&path, &identity).unwrap();
+        assert_eq!(file.get(&key("B")), Some("changed"));
+        assert_eq!(file.get(&key("C")), None);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::approval::{ApprovalMode, Decision, Operation};
 use crate::pool::KeyPool;
+use crate::rotation::RotationStatus;
 use anyhow::Result;
 use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::post, Json, Router};
 use chrono::{DateTime, Utc};
@@ -54,6 +56,7 @@ pub async fn start_server(bind: &str) -> Result<()> {
         .route("/rotate", post(handle_rotate))
         .route("/rollback", post(handle_rollback))
         .route("/audit", axum::routing::get(handle_audit))
+        .route("/schedule", axum::routing::get(handle_schedule))
         .route("/health", axum::routing::get(handle_health))
         .with_state(Arc::new(state));
 
@@ -149,6 +152,38 @@ async fn handle_rotate(
         tracing::error!("Failed to log signal: {}", e);
     }
 
+    let config = match crate::config::Config::load() {
+        Ok(c) => c,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(RotateResponse {
+                    success: false,
+                    message: format!("Failed to load config: {}", e),
+                    pool_status: None,
+                    new_value: None,
+                }),
+            );
+        }
+    };
+
+    if let Decision::Denied { reason } = crate::approval::evaluate(
+        &config,
+        Operation::Rotate,
+        &payload.env,
+        &payload.secret_name,
+    ) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(RotateResponse {
+                success: false,
+                message: format!("Rotation not auto-approved: {}", reason),
+                pool_status: None,
+                new_value: None,
+            }),
+        );
+    }
+
     let (pool_status, new_value) = if let Ok(Some(mut pool)) = KeyPool::load(&payload.secret_name) {
         let status = Some(PoolStatus {
             total_keys: pool.keys.len(),
@@ -165,20 +200,25 @@ async fn handle_rotate(
     };
 
     tokio::spawn(async move {
-        let result = crate::rotation::rotate(
-            Some(payload.secret_name),
-            Some(payload.env),
-            payload.service,
-            true,
-            false,
-            None,
-            None,
-            false,
-        )
+        let result = crate::rotation::rotate(crate::rotation::RotateParams {
+            secret_name: Some(payload.secret_name),
+            env: Some(payload.env),
+            service: payload.service,
+            from_signal: true,
+            approval: ApprovalMode::Policy,
+            ..Default::default()
+        })
         .await;
 
-        if let Err(e) = result {
-            tracing::error!("App-signal rotation failed: {}", e);
+        match result {
+            Ok(outcome) if outcome.status == RotationStatus::Denied => {
+                tracing::warn!(
+                    "App-signal rotation denied: {}",
+                    outcome.reason.unwrap_or_default()
+                );
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("App-signal rotation failed: {}", e),
         }
     });
 
@@ -277,18 +317,58 @@ async fn handle_rollback(
         tracing::error!("Failed to log rollback signal: {}", e);
     }
 
+    let config = match crate::config::Config::load() {
+        Ok(c) => c,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(RotateResponse {
+                    success: false,
+                    message: format!("Failed to load config: {}", e),
+                    pool_status: None,
+                    new_value: None,
+                }),
+            );
+        }
+    };
+
+    if let Decision::Denied { reason } = crate::approval::evaluate(
+        &config,
+        Operation::Rollback,
+        &payload.env,
+        &payload.secret_name,
+    ) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(RotateResponse {
+                success: false,
+                message: format!("Rollback not auto-approved: {}", reason),
+                pool_status: None,
+                new_value: None,
+            }),
+        );
+    }
+
     tokio::spawn(async move {
-        let result = crate::rollback::rollback(
-            payload.secret_name,
-            payload.env,
-            payload.service,
-            payload.redeploy,
-            false,
-        )
+        let result = crate::rollback::rollback(crate::rollback::RollbackParams {
+            secret_name: payload.secret_name,
+            env: payload.env,
+            service: payload.service,
+            redeploy: payload.redeploy,
+            approval: ApprovalMode::Policy,
+            ..Default::default()
+        })
         .await;
 
-        if let Err(e) = result {
-            tracing::error!("App-signal rollback failed: {}", e);
+        match result {
+            Ok(outcome) if outcome.status == RotationStatus::Denied => {
+                tracing::warn!(
+                    "App-signal rollback denied: {}",
+                    outcome.reason.unwrap_or_default()
+                );
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("App-signal rollback failed: {}", e),
         }
     });
 
@@ -341,6 +421,27 @@ async fn handle_audit(
     }
 }
 
+async fn handle_schedule() -> impl IntoResponse {
+    match crate::scheduler::ScheduleState::load() {
+        Ok(state) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "success": true,
+                "manifest_path": state.manifest_path,
+                "updated_at": state.updated_at,
+                "entries": state.entries.into_values().collect::<Vec<_>>()
+            })),
+        ),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "success": false,
+                "message": format!("Failed to read schedule: {}", e)
+            })),
+        ),
+    }
+}
+
 async fn handle_health() -> impl IntoResponse {
     (StatusCode::OK, "OK")
 }
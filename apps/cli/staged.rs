@@ -0,0 +1,689 @@
+//! Staged rotation: the new value is staged next to the old one, checked
+//! with the secret's `verify_command`, then promoted. The old value is kept
+//! (encrypted) until the grace period after promotion elapses, so
+//! `--abort` can still put it back; a minted key it holds is not revoked
+//! before then. Retiring the rotation drops the old value.
+
+use crate::approval::ApprovalMode;
+use crate::rotation::{RotationOutcome, RotationStatus};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationPhase {
+    Staged,
+    Verified,
+    Promoted,
+    Retired,
+    Aborted,
+}
+
+impl RotationPhase {
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            RotationPhase::Staged | RotationPhase::Verified | RotationPhase::Promoted
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotationState {
+    pub id: uuid::Uuid,
+    pub secret_name: String,
+    pub env: String,
+    pub service: Option<String>,
+    pub env_file: Option<String>,
+    #[serde(default)]
+    pub key_path: Option<String>,
+    pub redeploy: bool,
+    pub phase: RotationPhase,
+    pub encrypted_old_value: Option<String>,
+    pub encrypted_new_value: Option<String>,
+    #[serde(default)]
+    pub new_key_id: Option<String>,
+    pub grace_period_seconds: u64,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub promoted_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+impl RotationState {
+    pub fn new(
+        secret_name: &str,
+        env: &str,
+        service: Option<String>,
+        env_file: Option<String>,
+        key_path: Option<String>,
+        redeploy: bool,
+        grace_period_seconds: u64,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4(),
+            secret_name: secret_name.to_string(),
+            env: env.to_string(),
+            service,
+            env_file,
+            key_path,
+            redeploy,
+            phase: RotationPhase::Staged,
+            encrypted_old_value: None,
+            encrypted_new_value: None,
+            new_key_id: None,
+            grace_period_seconds,
+            started_at: now,
+            updated_at: now,
+            promoted_at: None,
+            last_error: None,
+        }
+    }
+
+    pub fn load(env: &str, secret_name: &str) -> Result<Option<Self>> {
+        let path = Self::state_path(env, secret_name);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(&path).context("Failed to read rotation state")?;
+        let state: RotationState =
+            serde_json::from_str(&contents).context("Failed to parse rotation state")?;
+
+        Ok(Some(state))
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        let dir = Self::rotations_dir();
+        fs::create_dir_all(&dir)?;
+
+        self.updated_at = Utc::now();
+
+        let path = Self::state_path(&self.env, &self.secret_name);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&temp_path, &path)?;
+
+        Ok(())
+    }
+
+    pub fn retire_at(&self) -> Option<DateTime<Utc>> {
+        self.promoted_at
+            .map(|t| t + Duration::seconds(self.grace_period_seconds as i64))
+    }
+
+    /// Whether the rotation is promoted and its grace period has elapsed.
+    pub fn retirement_due(&self, now: DateTime<Utc>) -> bool {
+        self.phase == RotationPhase::Promoted && self.retire_at().is_some_and(|t| t <= now)
+    }
+
+    /// Every saved rotation state, in any phase.
+    pub fn list_all() -> Result<Vec<Self>> {
+        let dir = Self::rotations_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut states = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            match fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|c| serde_json::from_str(&c).map_err(anyhow::Error::from))
+            {
+                Ok(state) => states.push(state),
+                Err(e) => tracing::warn!("Skipping rotation state {}: {}", path.display(), e),
+            }
+        }
+
+        Ok(states)
+    }
+
+    fn rotations_dir() -> PathBuf {
+        crate::config::Config::birch_dir().join("rotations")
+    }
+
+    fn state_path(env: &str, secret_name: &str) -> PathBuf {
+        Self::rotations_dir().join(format!("{}-{}.json", env, secret_name))
+    }
+
+    fn outcome(&self, status: RotationStatus, reason: Option<String>) -> RotationOutcome {
+        RotationOutcome {
+            secret_name: self.secret_name.clone(),
+            env: self.env.clone(),
+            status,
+            masked_value: None,
+            reason,
+        }
+    }
+}
+
+pub fn ensure_idle(env: &str, secret_name: &str) -> Result<()> {
+    if let Some(mut state) = RotationState::load(env, secret_name)? {
+        if clear_for_new_rotation(&mut state, Utc::now())? {
+            state.save()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `state` does not block a new rotation. A promoted rotation
+/// whose grace period has elapsed is retired on the spot; any other active
+/// one has to be resumed or aborted first. Returns whether `state` changed.
+fn clear_for_new_rotation(state: &mut RotationState, now: DateTime<Utc>) -> Result<bool> {
+    if !state.phase.is_active() {
+        return Ok(false);
+    }
+
+    if state.retirement_due(now) {
+        retire(state);
+        return Ok(true);
+    }
+
+    anyhow::bail!(
+        "Staged rotation for '{}' in env '{}' is {:?}; use --resume or --abort",
+        state.secret_name,
+        state.env,
+        state.phase
+    );
+}
+
+/// Daemon sweep: retires promoted rotations whose grace period has elapsed,
+/// so they stop blocking later rotations without a manual `--resume`.
+pub fn retire_due() -> Result<usize> {
+    let mut retired = 0;
+
+    for state in RotationState::list_all()? {
+        if !state.retirement_due(Utc::now()) {
+            continue;
+        }
+
+        let mut lock = crate::lock::Lock::new(&state.env, &state.secret_name)?;
+        if let Err(e) = lock.acquire("rotate-retire") {
+            tracing::info!(
+                "Skipping retirement of {}/{}: {}",
+                state.env,
+                state.secret_name,
+                e
+            );
+            continue;
+        }
+
+        // Reload under the lock in case it was resumed or aborted meanwhile.
+        let Some(mut state) = RotationState::load(&state.env, &state.secret_name)? else {
+            continue;
+        };
+        if !state.retirement_due(Utc::now()) {
+            continue;
+        }
+
+        retire(&mut state);
+        state.save()?;
+        tracing::info!(
+            "Retired staged rotation of {}/{}",
+            state.env,
+            state.secret_name
+        );
+        retired += 1;
+    }
+
+    Ok(retired)
+}
+
+pub async fn start(
+    mut state: RotationState,
+    old_value: Option<String>,
+    new_value: String,
+    new_key: crate::minters::PendingKey,
+    dry_run: bool,
+    approval: ApprovalMode,
+) -> Result<RotationOutcome> {
+    let masked = crate::connectors::mask_secret(&new_value);
+
+    println!(
+        "🌱 Staging rotation of '{}' in env '{}'",
+        state.secret_name, state.env
+    );
+    println!("   New value: {}", masked);
+    match old_value.as_deref() {
+        Some(old) => println!(
+            "   Old value: {} (kept for {}s after promotion)",
+            crate::connectors::mask_secret(old),
+            state.grace_period_seconds
+        ),
+        None => println!("   Old value: unknown (abort after promotion will not restore it)"),
+    }
+
+    if dry_run {
+        println!("✅ Dry run complete (no changes made)");
+        let mut outcome = state.outcome(RotationStatus::DryRun, None);
+        outcome.masked_value = Some(masked);
+        return Ok(outcome);
+    }
+
+    let logger = crate::audit::AuditLogger::new()?;
+    state.encrypted_new_value = Some(logger.encrypt_secret(&new_value)?);
+    state.encrypted_old_value = match old_value {
+        Some(old) => Some(logger.encrypt_secret(&old)?),
+        None => None,
+    };
+    state.new_key_id = new_key.id().map(str::to_string);
+    state.save()?;
+    // The saved state now owns the key; aborting it discards the key.
+    new_key.keep();
+
+    let mut outcome = advance(&mut state, approval).await?;
+    outcome.masked_value = Some(masked);
+    Ok(outcome)
+}
+
+pub async fn resume(
+    secret_name: &str,
+    env: &str,
+    dry_run: bool,
+    approval: ApprovalMode,
+) -> Result<RotationOutcome> {
+    let mut lock = crate::lock::Lock::new(env, secret_name)?;
+    lock.acquire("rotate-resume")?;
+
+    let mut state = load_active(env, secret_name)?;
+
+    println!(
+        "▶️  Resuming rotation of '{}' in env '{}' (phase: {:?})",
+        secret_name, env, state.phase
+    );
+
+    if dry_run {
+        println!("✅ Dry run complete (no changes made)");
+        return Ok(state.outcome(RotationStatus::DryRun, None));
+    }
+
+    advance(&mut state, approval).await
+}
+
+pub async fn abort(
+    secret_name: &str,
+    env: &str,
+    dry_run: bool,
+    approval: ApprovalMode,
+) -> Result<RotationOutcome> {
+    let mut lock = crate::lock::Lock::new(env, secret_name)?;
+    lock.acquire("rotate-abort")?;
+
+    let mut state = load_active(env, secret_name)?;
+
+    println!(
+        "⏹️  Aborting rotation of '{}' in env '{}' (phase: {:?})",
+        secret_name, env, state.phase
+    );
+
+    if dry_run {
+        println!("✅ Dry run complete (no changes made)");
+        return Ok(state.outcome(RotationStatus::DryRun, None));
+    }
+
+    let logger = crate::audit::AuditLogger::new()?;
+
+    if state.phase == RotationPhase::Promoted {
+        let encrypted_old = state.encrypted_old_value.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("Previous value was not captured; cannot restore it on abort")
+        })?;
+        let old_value = logger.decrypt_secret(encrypted_old)?;
+        let masked = crate::connectors::mask_secret(&old_value);
+        let restored_from =
+            crate::versions::find_version(&state.env, &state.secret_name, &old_value)?;
+        let old_key_id = match restored_from {
+            Some(version) => crate::versions::key_id(&state.env, &state.secret_name, version)?,
+            None => None,
+        };
+        crate::minters::check_restorable_key(
+            &state.env,
+            &state.secret_name,
+            old_key_id.as_deref(),
+        )?;
+
+        println!("   Restoring previous value: {}", masked);
+        crate::rotation::apply_value(
+            &state.secret_name,
+            &old_value,
+            &state.env,
+            state.service.as_deref(),
+            state.env_file.as_deref(),
+            state.key_path.as_deref(),
+            state.redeploy,
+            approval,
+        )
+        .await?;
+
+        crate::versions::record_version(
+            &state.env,
+            &state.secret_name,
+            &old_value,
+            crate::audit::AuditAction::Rollback,
+            restored_from,
+            old_key_id.as_deref(),
+        )?;
+
+        logger.log(
+            state.secret_name.clone(),
+            state.env.clone(),
+            state.service.clone(),
+            crate::audit::AuditAction::Rollback,
+            true,
+            Some(masked),
+        )?;
+
+        crate::minters::record_restored_key(&state.env, &state.secret_name, old_key_id.as_deref())?;
+    } else if let Some(id) = &state.new_key_id {
+        crate::minters::record_discarded_key(&state.env, &state.secret_name, id)?;
+    }
+
+    state.phase = RotationPhase::Aborted;
+    state.encrypted_old_value = None;
+    state.encrypted_new_value = None;
+    state.save()?;
+
+    println!("✅ Staged rotation aborted");
+
+    Ok(state.outcome(RotationStatus::Aborted, None))
+}
+
+fn load_active(env: &str, secret_name: &str) -> Result<RotationState> {
+    let state = RotationState::load(env, secret_name)?
+        .filter(|s| s.phase.is_active())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No staged rotation in progress for '{}' in env '{}'",
+                secret_name,
+                env
+            )
+        })?;
+
+    Ok(state)
+}
+
+async fn advance(state: &mut RotationState, approval: ApprovalMode) -> Result<RotationOutcome> {
+    loop {
+        let result = match state.phase {
+            RotationPhase::Staged => verify(state).await,
+            RotationPhase::Verified => promote(state, approval).await,
+            RotationPhase::Promoted => {
+                let retire_at = state
+                    .retire_at()
+                    .ok_or_else(|| anyhow::anyhow!("Promoted rotation has no promotion time"))?;
+
+                if Utc::now() < retire_at {
+                    println!(
+                        "⏳ Previous value retained until {}; the daemon or the next rotation retires it afterwards (or run 'birch rotate {} --env {} --resume')",
+                        retire_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                        state.secret_name,
+                        state.env
+                    );
+                    return Ok(state.outcome(
+                        RotationStatus::Applied,
+                        Some(format!("Awaiting retirement at {}", retire_at.to_rfc3339())),
+                    ));
+                }
+
+                retire(state);
+                Ok(())
+            }
+            RotationPhase::Retired => {
+                println!("✅ Rotation complete");
+                return Ok(state.outcome(RotationStatus::Applied, None));
+            }
+            RotationPhase::Aborted => anyhow::bail!("Rotation was aborted"),
+        };
+
+        if let Err(e) = result {
+            state.last_error = Some(e.to_string());
+            state.save()?;
+            return Err(e);
+        }
+
+        state.last_error = None;
+        state.save()?;
+    }
+}
+
+async fn verify(state: &mut RotationState) -> Result<()> {
+    let new_value = decrypt_new_value(state)?;
+    let config = crate::config::Config::load()?;
+
+    let verify_command = config
+        .secrets
+        .get(&state.secret_name)
+        .and_then(|s| s.verify_command.clone());
+
+    match verify_command {
+        Some(command) => {
+            println!("🔎 Verifying new value with: {}", command);
+
+            let mut cmd = if cfg!(windows) {
+                let mut c = tokio::process::Command::new("cmd");
+                c.arg("/C").arg(&command);
+                c
+            } else {
+                let mut c = tokio::process::Command::new("sh");
+                c.arg("-c").arg(&command);
+                c
+            };
+
+            let status = cmd
+                .env("BIRCH_SECRET_NAME", &state.secret_name)
+                .env("BIRCH_SECRET_ENV", &state.env)
+                .env("BIRCH_SECRET_VALUE", &new_value)
+                .status()
+                .await
+                .context("Failed to run verify_command")?;
+
+            if !status.success() {
+                anyhow::bail!("Verification failed: verify_command exited with {}", status);
+            }
+
+            println!("   ✓ New value verified");
+        }
+        None => {
+            println!(
+                "🔎 No verify_command configured for '{}'; skipping verification",
+                state.secret_name
+            );
+        }
+    }
+
+    state.phase = RotationPhase::Verified;
+    Ok(())
+}
+
+async fn promote(state: &mut RotationState, approval: ApprovalMode) -> Result<()> {
+    let new_value = decrypt_new_value(state)?;
+    let masked = crate::connectors::mask_secret(&new_value);
+
+    println!("🚀 Promoting new value {}", masked);
+
+    crate::rotation::apply_value(
+        &state.secret_name,
+        &new_value,
+        &state.env,
+        state.service.as_deref(),
+        state.env_file.as_deref(),
+        state.key_path.as_deref(),
+        state.redeploy,
+        approval,
+    )
+    .await?;
+
+    if let Ok(current) = crate::rotation::get_current_secret_value(
+        &state.secret_name,
+        &state.env,
+        state.service.as_deref(),
+        state.env_file.as_deref(),
+        state.key_path.as_deref(),
+    )
+    .await
+    {
+        if current != new_value {
+            anyhow::bail!("Read-back after promotion does not match the staged value");
+        }
+        println!("   ✓ Read-back matches staged value");
+    }
+
+    crate::rotation::record_rotation(&state.env, &state.secret_name)?;
+    crate::minters::record_promoted_key(
+        &state.env,
+        &state.secret_name,
+        state.new_key_id.as_deref(),
+    )?;
+    crate::versions::record_version(
+        &state.env,
+        &state.secret_name,
+        &new_value,
+        crate::audit::AuditAction::Rotate,
+        None,
+        state.new_key_id.as_deref(),
+    )?;
+
+    let logger = crate::audit::AuditLogger::new()?;
+    logger.log_with_value(crate::audit::LogParams {
+        secret_name: state.secret_name.clone(),
+        env: state.env.clone(),
+        service: state.service.clone(),
+        action: crate::audit::AuditAction::Rotate,
+        success: true,
+        masked_secret_preview: Some(masked),
+        secret_value: Some(new_value),
+    })?;
+
+    state.phase = RotationPhase::Promoted;
+    state.promoted_at = Some(Utc::now());
+    Ok(())
+}
+
+fn retire(state: &mut RotationState) {
+    println!(
+        "🍂 Grace period of the '{}' rotation in env '{}' elapsed; retiring previous value",
+        state.secret_name, state.env
+    );
+
+    state.phase = RotationPhase::Retired;
+    state.encrypted_old_value = None;
+    state.encrypted_new_value = None;
+}
+
+fn decrypt_new_value(state: &RotationState) -> Result<String> {
+    let encrypted = state
+        .encrypted_new_value
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Rotation state has no staged value"))?;
+
+    crate::audit::AuditLogger::new()?.decrypt_secret(encrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(phase: RotationPhase) -> RotationState {
+        let mut state = RotationState::new("API_KEY", "prod", None, None, None, false, 3600);
+        state.phase = phase;
+        state.encrypted_old_value = Some("old".to_string());
+        state.encrypted_new_value = Some("new".to_string());
+        state
+    }
+
+    fn promoted_at(at: DateTime<Utc>) -> RotationState {
+        let mut state = state(RotationPhase::Promoted);
+        state.promoted_at = Some(at);
+        state
+    }
+
+    #[test]
+    fn active_phases() {
+        assert!(RotationPhase::Staged.is_active());
+        assert!(RotationPhase::Verified.is_active());
+        assert!(RotationPhase::Promoted.is_active());
+        assert!(!RotationPhase::Retired.is_active());
+        assert!(!RotationPhase::Aborted.is_active());
+    }
+
+    #[test]
+    fn new_state_starts_staged() {
+        let state = RotationState::new("API_KEY", "prod", None, None, None, true, 60);
+        assert_eq!(state.phase, RotationPhase::Staged);
+        assert_eq!(state.retire_at(), None);
+        assert!(!state.retirement_due(Utc::now()));
+    }
+
+    #[test]
+    fn retirement_is_due_after_grace_period() {
+        let now = Utc::now();
+        let state = promoted_at(now - Duration::seconds(3600));
+
+        assert_eq!(state.retire_at(), Some(now));
+        assert!(state.retirement_due(now));
+        assert!(!state.retirement_due(now - Duration::seconds(1)));
+
+        let mut verified = state.clone();
+        verified.phase = RotationPhase::Verified;
+        assert!(!verified.retirement_due(now));
+    }
+
+    #[test]
+    fn finished_rotations_do_not_block() {
+        for phase in [RotationPhase::Retired, RotationPhase::Aborted] {
+            let mut state = state(phase);
+            assert!(!clear_for_new_rotation(&mut state, Utc::now()).unwrap());
+            assert_eq!(state.phase, phase);
+        }
+    }
+
+    #[test]
+    fn unfinished_rotations_block() {
+        for phase in [RotationPhase::Staged, RotationPhase::Verified] {
+            let mut state = state(phase);
+            let err = clear_for_new_rotation(&mut state, Utc::now()).unwrap_err();
+            assert!(err.to_string().contains("--resume or --abort"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn promoted_rotation_blocks_during_grace_period() {
+        let mut state = promoted_at(Utc::now());
+        assert!(clear_for_new_rotation(&mut state, Utc::now()).is_err());
+        assert_eq!(state.phase, RotationPhase::Promoted);
+        assert!(state.encrypted_old_value.is_some());
+    }
+
+    #[test]
+    fn promoted_rotation_is_retired_after_grace_period() {
+        let now = Utc::now();
+        let mut state = promoted_at(now - Duration::seconds(7200));
+
+        assert!(clear_for_new_rotation(&mut state, now).unwrap());
+        assert_eq!(state.phase, RotationPhase::Retired);
+        assert_eq!(state.encrypted_old_value, None);
+        assert_eq!(state.encrypted_new_value, None);
+    }
+
+    #[test]
+    fn state_round_trips_through_json() {
+        let state = promoted_at(Utc::now());
+        let json = serde_json::to_string(&state).unwrap();
+        let parsed: RotationState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed.id, state.id);
+        assert_eq!(parsed.phase, RotationPhase::Promoted);
+        assert_eq!(parsed.promoted_at, state.promoted_at);
+        assert!(json.contains("\"phase\":\"promoted\""));
+    }
+}
@@ -132,10 +132,10 @@ fn run_app<B: ratatui::backend::Backend>(
                     KeyCode::Char('r') => {
                         state.refresh()?;
                     }
-                    KeyCode::Down => {
-                        if state.scroll_offset < state.recent_audits.len().saturating_sub(1) {
-                            state.scroll_offset += 1;
-                        }
+                    KeyCode::Down
+                        if state.scroll_offset < state.recent_audits.len().saturating_sub(1) =>
+                    {
+                        state.scroll_offset += 1;
                     }
                     KeyCode::Up => {
                         state.scroll_offset = state.scroll_offset.saturating_sub(1);
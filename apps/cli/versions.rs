@@ -0,0 +1,439 @@
+use crate::audit::{AuditAction, AuditLogger};
+use crate::config::Config;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretVersion {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub actor: String,
+    pub action: AuditAction,
+    pub masked_value: String,
+    pub encrypted_value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<u32>,
+    /// Id of the minted key the value belongs to, for secrets with a minter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionHistory {
+    pub secret_name: String,
+    pub env: String,
+    #[serde(default)]
+    pub versions: Vec<SecretVersion>,
+}
+
+impl VersionHistory {
+    pub fn load(env: &str, secret_name: &str) -> Result<Self> {
+        let path = Self::history_path(env, secret_name);
+
+        if !path.exists() {
+            return Self::import_from_audit(env, secret_name);
+        }
+
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read version history {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse version history {}", path.display()))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::history_path(&self.env, &self.secret_name);
+        let temp_path = path.with_extension("json.tmp");
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&temp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&temp_path, &path)?;
+
+        Ok(())
+    }
+
+    pub fn current(&self) -> Option<&SecretVersion> {
+        self.versions.last()
+    }
+
+    pub fn get(&self, version: u32) -> Option<&SecretVersion> {
+        self.versions.iter().find(|v| v.version == version)
+    }
+
+    /// The version that was live before the current one. After a rollback
+    /// this walks further back instead of returning the value that was just
+    /// rolled away from.
+    pub fn previous(&self) -> Option<&SecretVersion> {
+        let current = self.current()?;
+        let effective = current.restored_from.unwrap_or(current.version);
+        let position = self.versions.iter().position(|v| v.version == effective)?;
+
+        position.checked_sub(1).map(|i| &self.versions[i])
+    }
+
+    /// The version `rollback --to` resolves to: `to` if given, otherwise the
+    /// version that preceded the current one.
+    pub fn rollback_target(&self, to: Option<u32>) -> Result<&SecretVersion> {
+        let target = match to {
+            Some(version) => self.get(version).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Version {} of '{}' in env '{}' does not exist (see `birch history`)",
+                    version,
+                    self.secret_name,
+                    self.env
+                )
+            })?,
+            None => self.previous().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No previous version of '{}' in env '{}' (found {} version(s))",
+                    self.secret_name,
+                    self.env,
+                    self.versions.len()
+                )
+            })?,
+        };
+
+        if self.current().is_some_and(|c| c.version == target.version) {
+            anyhow::bail!("Version {} is already the current version", target.version);
+        }
+
+        Ok(target)
+    }
+
+    fn push(
+        &mut self,
+        logger: &AuditLogger,
+        value: &str,
+        action: AuditAction,
+        restored_from: Option<u32>,
+        key_id: Option<&str>,
+    ) -> Result<u32> {
+        let version = self.current().map(|v| v.version + 1).unwrap_or(1);
+
+        self.versions.push(SecretVersion {
+            version,
+            created_at: Utc::now(),
+            actor: crate::audit::current_actor(),
+            action,
+            masked_value: crate::connectors::mask_secret(value),
+            encrypted_value: logger.encrypt_secret(value)?,
+            restored_from,
+            key_id: key_id.map(str::to_string),
+        });
+
+        Ok(version)
+    }
+
+    /// Seeds the history of secrets rotated before the version store existed
+    /// from the values kept in successful `rotate` audit entries.
+    fn import_from_audit(env: &str, secret_name: &str) -> Result<Self> {
+        let logger = AuditLogger::new()?;
+        let entries =
+            logger.read_logs(Some(secret_name.to_string()), Some(env.to_string()), None)?;
+
+        Ok(Self::from_audit_entries(env, secret_name, entries))
+    }
+
+    /// Builds a history from audit entries, newest first as `read_logs`
+    /// returns them.
+    fn from_audit_entries(
+        env: &str,
+        secret_name: &str,
+        entries: Vec<crate::audit::AuditEntry>,
+    ) -> Self {
+        let mut history = Self {
+            secret_name: secret_name.to_string(),
+            env: env.to_string(),
+            versions: Vec::new(),
+        };
+
+        for entry in entries.into_iter().rev() {
+            if !matches!(entry.action, AuditAction::Rotate) || !entry.success {
+                continue;
+            }
+            let Some(encrypted_value) = entry.encrypted_secret_value else {
+                continue;
+            };
+
+            let version = history.current().map(|v| v.version + 1).unwrap_or(1);
+            history.versions.push(SecretVersion {
+                version,
+                created_at: entry.timestamp,
+                actor: entry.actor,
+                action: entry.action,
+                masked_value: entry.masked_secret_preview.unwrap_or_default(),
+                encrypted_value,
+                restored_from: None,
+                key_id: None,
+            });
+        }
+
+        history
+    }
+
+    fn versions_dir() -> PathBuf {
+        Config::birch_dir().join("versions")
+    }
+
+    /// One directory per env, so env and secret names containing `-` cannot
+    /// map two secrets to the same file.
+    fn history_path(env: &str, secret_name: &str) -> PathBuf {
+        Self::versions_dir()
+            .join(env)
+            .join(format!("{}.json", secret_name))
+    }
+}
+
+/// Stores `value` as the newest version of the secret and returns its number.
+/// Call this before writing the matching audit entry.
+pub fn record_version(
+    env: &str,
+    secret_name: &str,
+    value: &str,
+    action: AuditAction,
+    restored_from: Option<u32>,
+    key_id: Option<&str>,
+) -> Result<u32> {
+    let logger = AuditLogger::new()?;
+    let mut history = VersionHistory::load(env, secret_name)?;
+    let version = history.push(&logger, value, action, restored_from, key_id)?;
+    history.save()?;
+
+    Ok(version)
+}
+
+/// Decrypted value of the current version, if the secret has any history.
+pub fn current_value(env: &str, secret_name: &str) -> Result<Option<String>> {
+    let history = VersionHistory::load(env, secret_name)?;
+
+    match history.current() {
+        Some(version) => Ok(Some(
+            AuditLogger::new()?.decrypt_secret(&version.encrypted_value)?,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Finds the newest version holding `value`, if any.
+pub fn find_version(env: &str, secret_name: &str, value: &str) -> Result<Option<u32>> {
+    let logger = AuditLogger::new()?;
+    let history = VersionHistory::load(env, secret_name)?;
+
+    for version in history.versions.iter().rev() {
+        if logger.decrypt_secret(&version.encrypted_value)? == value {
+            return Ok(Some(version.version));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Minted key id stored with `version`, if any.
+pub fn key_id(env: &str, secret_name: &str, version: u32) -> Result<Option<String>> {
+    let history = VersionHistory::load(env, secret_name)?;
+    Ok(history.get(version).and_then(|v| v.key_id.clone()))
+}
+
+/// Returns the version number, decrypted value and minted key id to roll
+/// back to: `to` if given, otherwise the version that preceded the current
+/// one.
+pub fn resolve_rollback_target(
+    env: &str,
+    secret_name: &str,
+    to: Option<u32>,
+) -> Result<(u32, String, Option<String>)> {
+    let history = VersionHistory::load(env, secret_name)?;
+    let target = history.rollback_target(to)?;
+
+    let logger = AuditLogger::new()?;
+    let value = logger.decrypt_secret(&target.encrypted_value)?;
+
+    Ok((target.version, value, target.key_id.clone()))
+}
+
+pub fn show_history(secret_name: &str, env: &str) -> Result<()> {
+    let history = VersionHistory::load(env, secret_name)?;
+
+    if history.versions.is_empty() {
+        println!(
+            "No versions recorded for '{}' in env '{}'",
+            secret_name, env
+        );
+        return Ok(());
+    }
+
+    println!("\n📜 History of '{}' in env '{}'\n", secret_name, env);
+    println!(
+        "  {:<9} {:<22} {:<14} {:<10} VALUE",
+        "VERSION", "CREATED", "ACTOR", "ACTION"
+    );
+
+    let current = history.current().map(|v| v.version);
+
+    for version in history.versions.iter().rev() {
+        let marker = if Some(version.version) == current {
+            "*"
+        } else {
+            " "
+        };
+        let action = serde_json::to_value(&version.action)?
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        let restored = version
+            .restored_from
+            .map(|v| format!("  (restored v{})", v))
+            .unwrap_or_default();
+
+        println!(
+            "{} {:<9} {:<22} {:<14} {:<10} {}{}",
+            marker,
+            version.version,
+            version.created_at.format("%Y-%m-%d %H:%M UTC"),
+            version.actor,
+            action,
+            version.masked_value,
+            restored
+        );
+    }
+
+    println!(
+        "\n💡 Roll back with: birch rollback {} --env {} --to <VERSION>",
+        secret_name, env
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: u32, restored_from: Option<u32>) -> SecretVersion {
+        SecretVersion {
+            version,
+            created_at: Utc::now(),
+            actor: "test".to_string(),
+            action: match restored_from {
+                Some(_) => AuditAction::Rollback,
+                None => AuditAction::Rotate,
+            },
+            masked_value: format!("v{}", version),
+            encrypted_value: format!("enc-v{}", version),
+            restored_from,
+            key_id: None,
+        }
+    }
+
+    fn history(versions: Vec<SecretVersion>) -> VersionHistory {
+        VersionHistory {
+            secret_name: "API_KEY".to_string(),
+            env: "prod".to_string(),
+            versions,
+        }
+    }
+
+    fn entry(action: AuditAction, success: bool, value: Option<&str>) -> crate::audit::AuditEntry {
+        crate::audit::AuditEntry {
+            timestamp: Utc::now(),
+            actor: "test".to_string(),
+            secret_name: "API_KEY".to_string(),
+            env: "prod".to_string(),
+            service: None,
+            action,
+            success,
+            masked_secret_preview: value.map(|v| format!("{}***", &v[..2])),
+            encrypted_secret_value: value.map(str::to_string),
+            signature: String::new(),
+        }
+    }
+
+    #[test]
+    fn previous_is_the_version_before_the_current_one() {
+        let history = history(vec![version(1, None), version(2, None), version(3, None)]);
+        assert_eq!(history.previous().map(|v| v.version), Some(2));
+
+        assert!(self::history(vec![version(1, None)]).previous().is_none());
+        assert!(self::history(Vec::new()).previous().is_none());
+    }
+
+    #[test]
+    fn previous_after_a_rollback_walks_past_the_restored_version() {
+        // v4 rolled back to v2, so "previous" is what preceded v2.
+        let history = history(vec![
+            version(1, None),
+            version(2, None),
+            version(3, None),
+            version(4, Some(2)),
+        ]);
+        assert_eq!(history.previous().map(|v| v.version), Some(1));
+
+        // Rolling back again from there lands before v1: nothing is left.
+        let mut history = history;
+        history.versions.push(version(5, Some(1)));
+        assert!(history.previous().is_none());
+    }
+
+    #[test]
+    fn rollback_target_resolution() {
+        let history = history(vec![version(1, None), version(2, None), version(3, None)]);
+
+        assert_eq!(history.rollback_target(None).unwrap().version, 2);
+        assert_eq!(history.rollback_target(Some(1)).unwrap().version, 1);
+
+        let missing = history.rollback_target(Some(9)).unwrap_err();
+        assert!(
+            missing.to_string().contains("does not exist"),
+            "{}",
+            missing
+        );
+
+        let current = history.rollback_target(Some(3)).unwrap_err();
+        assert!(
+            current.to_string().contains("already the current version"),
+            "{}",
+            current
+        );
+
+        let empty = self::history(vec![version(1, None)]);
+        assert!(empty.rollback_target(None).is_err());
+    }
+
+    #[test]
+    fn audit_import_keeps_successful_rotations_oldest_first() {
+        // Newest first, as read_logs returns them.
+        let entries = vec![
+            entry(AuditAction::Rotate, true, Some("enc-c")),
+            entry(AuditAction::Rotate, false, Some("enc-failed")),
+            entry(AuditAction::Rollback, true, Some("enc-rollback")),
+            entry(AuditAction::Rotate, true, None),
+            entry(AuditAction::Rotate, true, Some("enc-b")),
+            entry(AuditAction::Rotate, true, Some("enc-a")),
+        ];
+
+        let history = VersionHistory::from_audit_entries("prod", "API_KEY", entries);
+        let versions: Vec<(u32, &str)> = history
+            .versions
+            .iter()
+            .map(|v| (v.version, v.encrypted_value.as_str()))
+            .collect();
+
+        assert_eq!(versions, [(1, "enc-a"), (2, "enc-b"), (3, "enc-c")]);
+        assert_eq!(history.versions[0].masked_value, "en***");
+    }
+
+    #[test]
+    fn history_paths_do_not_collide() {
+        assert_ne!(
+            VersionHistory::history_path("prod-eu", "X"),
+            VersionHistory::history_path("prod", "eu-X")
+        );
+        assert!(
+            VersionHistory::history_path("prod", "API_KEY").ends_with("versions/prod/API_KEY.json")
+        );
+    }
+}
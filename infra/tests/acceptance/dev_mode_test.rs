@@ -12,10 +12,11 @@ async fn test_dev_mode_env_update() {
     )
     .unwrap();
     
-    birch::dev::update_env_file(
+    birch::dev::update_local_file(
         "API_KEY",
         "new_value",
         Some(env_path.to_str().unwrap()),
+        None,
     )
     .await
     .unwrap();
@@ -25,7 +26,7 @@ async fn test_dev_mode_env_update() {
     assert!(contents.contains("OTHER_VAR=unchanged"));
     assert!(contents.contains("# Test env file"));
     
-    let rollback_path = temp_dir.path().join(".birch-rollback");
+    let rollback_path = birch::dev::snapshot_path(&env_path).unwrap();
     assert!(rollback_path.exists());
     
     let rollback_contents = fs::read_to_string(&rollback_path).unwrap();
@@ -39,10 +40,11 @@ async fn test_dev_mode_new_secret() {
     
     fs::write(&env_path, "EXISTING_VAR=value\n").unwrap();
     
-    birch::dev::update_env_file(
+    birch::dev::update_local_file(
         "NEW_SECRET",
         "secret_value",
         Some(env_path.to_str().unwrap()),
+        None,
     )
     .await
     .unwrap();
@@ -63,10 +65,11 @@ async fn test_dev_mode_preserves_formatting() {
     )
     .unwrap();
     
-    birch::dev::update_env_file(
+    birch::dev::update_local_file(
         "API_KEY",
         "new",
         Some(env_path.to_str().unwrap()),
+        None,
     )
     .await
     .unwrap();
@@ -77,3 +80,64 @@ async fn test_dev_mode_preserves_formatting() {
     assert!(contents.contains("API_KEY=new"));
 }
 
+
+#[tokio::test]
+async fn test_dev_mode_quoting_and_export() {
+    let temp_dir = TempDir::new().unwrap();
+    let env_path = temp_dir.path().join(".env");
+    
+    fs::write(
+        &env_path,
+        "export API_KEY='old' # rotated by birch\nCERT=\"line1\nline2\"\nOTHER=value\n",
+    )
+    .unwrap();
+    
+    let path = env_path.to_str().unwrap();
+    assert_eq!(
+        birch::dev::get_local_secret("CERT", Some(path), None).unwrap().as_deref(),
+        Some("line1\nline2")
+    );
+    
+    birch::dev::update_local_file("API_KEY", "new value", Some(path), None)
+        .await
+        .unwrap();
+    
+    let contents = fs::read_to_string(&env_path).unwrap();
+    assert!(contents.starts_with("export API_KEY='new value' # rotated by birch\n"));
+    assert!(contents.contains("CERT=\"line1\nline2\"\nOTHER=value\n"));
+    assert_eq!(
+        birch::dev::get_local_secret("API_KEY", Some(path), None).unwrap().as_deref(),
+        Some("new value")
+    );
+}
+
+#[tokio::test]
+async fn test_dev_mode_structured_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("application.yaml");
+    
+    fs::write(
+        &config_path,
+        "# Local settings\ndatabase:\n  host: localhost # dev only\n  password: old\nlogging: debug\n",
+    )
+    .unwrap();
+    
+    birch::dev::update_local_file(
+        "DB_PASSWORD",
+        "new_password",
+        Some(config_path.to_str().unwrap()),
+        Some("database.password"),
+    )
+    .await
+    .unwrap();
+    
+    let contents = fs::read_to_string(&config_path).unwrap();
+    assert_eq!(
+        contents,
+        "# Local settings\ndatabase:\n  host: localhost # dev only\n  password: new_password\nlogging: debug\n"
+    );
+    
+    let rollback_contents =
+        fs::read_to_string(birch::dev::snapshot_path(&config_path).unwrap()).unwrap();
+    assert!(rollback_contents.contains("password: old"));
+}